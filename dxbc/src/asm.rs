@@ -0,0 +1,2480 @@
+//! An assembler for the shader model 4 and 5 listings `fxc` prints, which
+//! turns them back into a container through `dr::Builder`.
+//!
+//! Besides the program, the signature tables in the listing's header comment
+//! are written as `ISGN`, `OSGN` and `PCSG` chunks. The rest of the header,
+//! like the resource bindings, is skipped, so the container has no `RDEF`.
+//!
+//! The listing prints floats with 6 decimals, so a float like `1.0 / 3.0`
+//! comes back as the nearest float to `0.333333` instead. Other than that,
+//! `SHEX` comes back the way fxc wrote it.
+
+use crate::dr::shex::{
+    self, ConstantBufferIndexPattern, InterpolationMode, MinPrecision, Opcode, Primitive,
+    PrimitiveTopology, ResInfoReturnType, ResourceDimension, ResourceReturnType, SamplerMode,
+    SyncFlags, TessellatorDomain, TessellatorOutputPrimitive, TessellatorPartitioning, TestBoolean,
+};
+use crate::dr::{
+    Builder, Chunk, ComponentMode, DxbcModule, GlobalFlags, IOsgnChunk, Immediate,
+    InputOutputElement, Instruction, Modifier, NumComponent, OpcodeEx, Operand, OperandType,
+    RegisterComponentType, Semantic, SemanticName, ShexChunk, SignatureLayout, W, X, Y, Z,
+};
+
+use std::{error, fmt};
+use winapi::um::d3d11tokenizedprogramformat::*;
+
+/// A syntax error in a listing. Lines and columns start at 1; an error that
+/// has no position in the listing, like an instruction that's too long to
+/// encode, is at line 0, column 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl error::Error for Error {}
+
+/// Assembles a listing into a module, including its checksum.
+pub fn assemble(source: &str) -> Result<DxbcModule, Error> {
+    let builder = parse(source)?;
+
+    builder.module().map_err(|error| Error {
+        line: 0,
+        column: 0,
+        message: error.to_string(),
+    })
+}
+
+/// Parses a listing into a builder with its signature and `SHEX` chunks.
+pub fn parse(source: &str) -> Result<Builder<'static>, Error> {
+    let signatures = parse_signatures(source)?;
+    let shex = Parser::new(tokenize(source)).program()?;
+
+    let mut builder = Builder::new();
+    match signature_chunk(signatures.input) {
+        chunk if chunk.layout == SignatureLayout::Basic => builder.add_chunk(Chunk::Isgn(chunk)),
+        chunk => builder.add_chunk(Chunk::Isg1(chunk)),
+    }
+    match signature_chunk(signatures.output) {
+        chunk if chunk.layout == SignatureLayout::Basic => builder.add_chunk(Chunk::Osgn(chunk)),
+        chunk => builder.add_chunk(Chunk::Osg1(chunk)),
+    }
+    if let Some(elements) = signatures.patch_constant {
+        let mut chunk = signature_chunk(elements);
+        chunk.layout = SignatureLayout::Basic;
+        builder.add_chunk(Chunk::Pcsg(chunk));
+    }
+    builder.set_shex(shex);
+
+    Ok(builder)
+}
+
+fn signature_chunk(elements: Vec<InputOutputElement>) -> IOsgnChunk {
+    // fxc only writes the minimum precision of elements to `ISG1` and `OSG1`
+    let layout = if elements
+        .iter()
+        .any(|element| element.min_precision != MinPrecision::Default)
+    {
+        SignatureLayout::StreamMinPrecision
+    } else {
+        SignatureLayout::Basic
+    };
+
+    IOsgnChunk { layout, elements }
+}
+
+#[derive(Default)]
+struct Signatures {
+    input: Vec<InputOutputElement>,
+    output: Vec<InputOutputElement>,
+    patch_constant: Option<Vec<InputOutputElement>>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum SignatureKind {
+    Input,
+    Output,
+    PatchConstant,
+}
+
+/// Reads the signature tables out of the comment that starts a listing:
+///
+/// ```text
+/// // Name                 Index   Mask Register SysValue  Format   Used
+/// // -------------------- ----- ------ -------- -------- ------- ------
+/// // TEXCOORD                 0   xy          1     NONE   float   xy
+/// ```
+fn parse_signatures(source: &str) -> Result<Signatures, Error> {
+    let mut signatures = Signatures::default();
+    let mut table = None;
+    let mut in_rows = false;
+
+    for (line_idx, line) in source.lines().enumerate() {
+        let comment = match line.trim_start().strip_prefix("//") {
+            Some(comment) => comment,
+            None => {
+                table = None;
+                continue;
+            }
+        };
+
+        let kind = match comment.trim() {
+            "Input signature:" => Some(SignatureKind::Input),
+            "Output signature:" => Some(SignatureKind::Output),
+            "Patch Constant signature:" => Some(SignatureKind::PatchConstant),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            if kind == SignatureKind::PatchConstant {
+                signatures.patch_constant = Some(Vec::new());
+            }
+            table = Some(kind);
+            in_rows = false;
+            continue;
+        }
+
+        let kind = match table {
+            Some(kind) => kind,
+            None => continue,
+        };
+
+        let text = comment.trim();
+        if text.starts_with("---") {
+            in_rows = true;
+        } else if text.is_empty() {
+            // tables end with an empty comment line
+            if in_rows {
+                table = None;
+            }
+        } else if in_rows && !text.starts_with("no ") {
+            let element = parse_signature_element(line, line_idx + 1, kind)?;
+            match kind {
+                SignatureKind::Input => signatures.input.push(element),
+                SignatureKind::Output => signatures.output.push(element),
+                SignatureKind::PatchConstant => signatures
+                    .patch_constant
+                    .get_or_insert_with(Vec::new)
+                    .push(element),
+            }
+        }
+    }
+
+    Ok(signatures)
+}
+
+fn parse_signature_element(
+    line: &str,
+    line_number: usize,
+    kind: SignatureKind,
+) -> Result<InputOutputElement, Error> {
+    // split the row on whitespace, but keep the columns for errors
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (column, c) in line.chars().chain(Some(' ')).enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(column),
+            (true, Some(first)) => {
+                let field: String = line.chars().skip(first).take(column - first).collect();
+                fields.push((first + 1, field));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    // skip the `//`
+    let mut fields = fields
+        .into_iter()
+        .skip_while(|(_, field)| field.starts_with("//"));
+    let end_column = line.chars().count() + 1;
+    let mut next = |what: &str| {
+        fields.next().ok_or_else(|| Error {
+            line: line_number,
+            column: end_column,
+            message: format!("expected {} in the signature", what),
+        })
+    };
+    let field_error = |column: usize, message: String| Error {
+        line: line_number,
+        column,
+        message,
+    };
+
+    let (_, name) = next("a semantic name")?;
+
+    let (column, semantic_index) = next("a semantic index")?;
+    let semantic_index = semantic_index.parse().map_err(|_| {
+        field_error(
+            column,
+            format!("expected a semantic index, found `{}`", semantic_index),
+        )
+    })?;
+
+    // masks may be split up by spaces, like `x zw`, and scalar system values
+    // have none
+    let (column, mask) = next("a component mask")?;
+    let mut component_mask = if mask == "N/A" {
+        0x1
+    } else {
+        parse_mask(&mask).ok_or_else(|| {
+            field_error(
+                column,
+                format!("expected a component mask, found `{}`", mask),
+            )
+        })?
+    };
+    let (mut column, mut register) = next("a register")?;
+    while let Some(mask) = parse_mask(&register) {
+        component_mask |= mask;
+        let field = next("a register")?;
+        column = field.0;
+        register = field.1;
+    }
+    // scalar system values like `oDepth` don't have a register
+    let register = if register.bytes().all(|c| c.is_ascii_digit()) {
+        register
+            .parse()
+            .map_err(|_| field_error(column, format!("register `{}` is out of range", register)))?
+    } else {
+        0xffff_ffff
+    };
+
+    let (column, system_value) = next("a system value")?;
+    let semantic_type = parse_system_value(&system_value)
+        .ok_or_else(|| field_error(column, format!("unknown system value `{}`", system_value)))?;
+
+    let (column, format) = next("a format")?;
+    let (component_type, min_precision) = parse_format(&format)
+        .ok_or_else(|| field_error(column, format!("unknown format `{}`", format)))?;
+
+    let mut used = 0;
+    for (column, field) in fields {
+        used |= match field.as_str() {
+            "YES" => 0x1,
+            "NO" => 0,
+            _ => parse_mask(&field).ok_or_else(|| {
+                field_error(
+                    column,
+                    format!("expected a component mask, found `{}`", field),
+                )
+            })?,
+        };
+    }
+
+    // outputs store the components that are never written instead
+    let rw_mask = match kind {
+        SignatureKind::Input => used,
+        SignatureKind::Output | SignatureKind::PatchConstant => !used & 0xf,
+    };
+
+    Ok(InputOutputElement {
+        stream: 0,
+        name,
+        semantic_index,
+        semantic_type,
+        component_type,
+        register,
+        component_mask,
+        rw_mask,
+        min_precision,
+    })
+}
+
+/// Parses the components of a signature mask, like `xy`.
+fn parse_mask(text: &str) -> Option<u8> {
+    text.chars().try_fold(0, |mask, c| match c {
+        'x' => Some(mask | 0x1),
+        'y' => Some(mask | 0x2),
+        'z' => Some(mask | 0x4),
+        'w' => Some(mask | 0x8),
+        _ => None,
+    })
+}
+
+fn parse_system_value(name: &str) -> Option<SemanticName> {
+    Some(match name {
+        "NONE" => SemanticName::Undefined,
+        "POS" => SemanticName::Position,
+        "CLIPDST" => SemanticName::ClipDistance,
+        "CULLDST" => SemanticName::CullDistance,
+        "RTINDEX" => SemanticName::RenderTargetArrayIndex,
+        "VPINDEX" => SemanticName::ViewportArrayIndex,
+        "VERTID" => SemanticName::VertexId,
+        "PRIMID" => SemanticName::PrimitiveId,
+        "INSTID" => SemanticName::InstanceId,
+        "FFACE" => SemanticName::IsFrontFace,
+        "SAMPLE" => SemanticName::SampleIndex,
+        "QUADEDGE" => SemanticName::FinalQuadEdgeTessfactor,
+        "QUADINT" => SemanticName::FinalQuadInsideTessfactor,
+        "TRIEDGE" => SemanticName::FinalTriEdgeTessfactor,
+        "TRIINT" => SemanticName::FinalTriInsideTessfactor,
+        "LINEDET" => SemanticName::FinalLineDetailTessfactor,
+        "LINEDEN" => SemanticName::FinalLineDensityTessfactor,
+        // fxc stores the pixel shader outputs as `NONE` and tells them apart
+        // by their name
+        "TARGET" | "DEPTH" | "COVERAGE" | "DEPTHGE" | "DEPTHLE" => SemanticName::Undefined,
+        _ => return None,
+    })
+}
+
+fn parse_format(name: &str) -> Option<(RegisterComponentType, MinPrecision)> {
+    Some(match name {
+        "unknown" => (RegisterComponentType::Unknown, MinPrecision::Default),
+        "float" => (RegisterComponentType::Float32, MinPrecision::Default),
+        "int" => (RegisterComponentType::Int32, MinPrecision::Default),
+        "uint" => (RegisterComponentType::Uint32, MinPrecision::Default),
+        "min16f" => (RegisterComponentType::Float32, MinPrecision::Float16),
+        "min2_8f" => (RegisterComponentType::Float32, MinPrecision::Float2_8),
+        "min16i" => (RegisterComponentType::Int32, MinPrecision::SInt16),
+        "min16u" => (RegisterComponentType::Uint32, MinPrecision::UInt16),
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    /// The text of a number, which is only interpreted once it's known
+    /// whether it's an integer, a float or a double.
+    Number(String),
+    Punct(char),
+    /// The text between the quotes of a shader message, like `"%0 is fine"`.
+    String(String),
+    Newline,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+    /// Whether whitespace comes before the token, which separates a mnemonic
+    /// from its operands.
+    spaced: bool,
+}
+
+impl Token {
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.kind {
+            TokenKind::Ident(ref text) | TokenKind::Number(ref text) => format!("`{}`", text),
+            TokenKind::Punct(c) => format!("`{}`", c),
+            TokenKind::String(ref text) => format!("`\"{}\"`", text),
+            TokenKind::Newline => String::from("the end of the line"),
+            TokenKind::End => String::from("the end of the listing"),
+        }
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.kind == TokenKind::Punct(c)
+    }
+}
+
+/// Splits a listing into tokens, skipping comments. Line breaks inside
+/// braces are dropped, so immediate constant buffers can span lines.
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut depth = 0u32;
+    let mut line_count = 0;
+
+    for (line_idx, line) in source.lines().enumerate() {
+        let line_number = line_idx + 1;
+        line_count = line_number;
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut idx = 0;
+        let mut spaced = true;
+        while idx < chars.len() {
+            let c = chars[idx];
+            if c.is_whitespace() {
+                spaced = true;
+                idx += 1;
+                continue;
+            }
+            if c == '/' && chars.get(idx + 1) == Some(&'/') {
+                break;
+            }
+
+            let start = idx;
+            idx += 1;
+            let kind = if c.is_ascii_alphabetic() || c == '_' {
+                while idx < chars.len() && (chars[idx].is_ascii_alphanumeric() || chars[idx] == '_')
+                {
+                    idx += 1;
+                }
+                TokenKind::Ident(chars[start..idx].iter().collect())
+            } else if c.is_ascii_digit() {
+                let hex = c == '0' && matches!(chars.get(idx), Some('x') | Some('X'));
+                while idx < chars.len() {
+                    let c = chars[idx];
+                    let exponent_sign =
+                        !hex && (c == '+' || c == '-') && matches!(chars[idx - 1], 'e' | 'E');
+                    if c.is_ascii_alphanumeric() || c == '.' || exponent_sign {
+                        idx += 1;
+                    } else {
+                        break;
+                    }
+                }
+                TokenKind::Number(chars[start..idx].iter().collect())
+            } else if c == '"' {
+                while idx < chars.len() && chars[idx] != '"' {
+                    idx += 1;
+                }
+                let text = chars[start + 1..idx].iter().collect();
+                idx = (idx + 1).min(chars.len());
+                TokenKind::String(text)
+            } else {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                TokenKind::Punct(c)
+            };
+
+            tokens.push(Token {
+                kind,
+                line: line_number,
+                column: start + 1,
+                spaced,
+            });
+            spaced = false;
+        }
+
+        if depth == 0 {
+            tokens.push(Token {
+                kind: TokenKind::Newline,
+                line: line_number,
+                column: chars.len() + 1,
+                spaced: true,
+            });
+        }
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        line: line_count + 1,
+        column: 1,
+        spaced: true,
+    });
+
+    tokens
+}
+
+/// Whether an operand is written to or read from, which decides what its
+/// components mean.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Role {
+    /// The components are a mask, like `o0.xy`.
+    Destination,
+    /// The components are a swizzle, like `r0.xyxx`, or select one
+    /// component, like `r0.x`.
+    Source,
+}
+
+/// The components of a register that is written without any.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Components {
+    None,
+    Scalar,
+    Vector,
+}
+
+/// A mnemonic with its suffixes, like `sample_aoffimmi_indexable(-1,0,0)`.
+struct Mnemonic {
+    token: Token,
+    opcode: Opcode,
+    name: String,
+    /// The words after the mnemonic, like `sat` or `texture2d`.
+    suffixes: Vec<(String, Token)>,
+    /// The parenthesized groups attached to the mnemonic, with the tokens of
+    /// each comma separated item.
+    groups: Vec<(Token, Vec<Vec<Token>>)>,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn peek_second(&self) -> &Token {
+        &self.tokens[(self.pos + 1).min(self.tokens.len() - 1)]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos + 1 < self.tokens.len() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn expected(&self, what: &str) -> Error {
+        let token = self.peek();
+        token.error(format!("expected {}, found {}", what, token.describe()))
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        let found = self.peek().is_punct(c);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<Token, Error> {
+        if self.peek().is_punct(c) {
+            Ok(self.next())
+        } else {
+            Err(self.expected(&format!("`{}`", c)))
+        }
+    }
+
+    fn expect_ident(&mut self, what: &str) -> Result<(String, Token), Error> {
+        match self.peek().kind {
+            TokenKind::Ident(ref name) => {
+                let name = name.clone();
+                Ok((name, self.next()))
+            }
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    /// Parses a decimal or hexadecimal integer.
+    fn expect_u32(&mut self, what: &str) -> Result<u32, Error> {
+        let token = self.peek().clone();
+        let value = match token.kind {
+            TokenKind::Number(ref text) => parse_integer(text),
+            _ => None,
+        };
+
+        match value.map(u32::try_from) {
+            Some(Ok(value)) => {
+                self.next();
+                Ok(value)
+            }
+            Some(Err(_)) => Err(token.error(format!("{} is out of range", token.describe()))),
+            None => Err(self.expected(what)),
+        }
+    }
+
+    fn at_end_of_line(&self) -> bool {
+        matches!(self.peek().kind, TokenKind::Newline | TokenKind::End)
+    }
+
+    fn expect_end_of_line(&mut self) -> Result<(), Error> {
+        if self.at_end_of_line() {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.expected("the end of the line"))
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek().kind == TokenKind::Newline {
+            self.next();
+        }
+    }
+
+    fn program(&mut self) -> Result<ShexChunk, Error> {
+        self.skip_newlines();
+        let (name, token) = self.expect_ident("a shader model like `ps_5_0`")?;
+        let mut shex = parse_shader_model(&name)
+            .ok_or_else(|| token.error(format!("unknown shader model `{}`", name)))?;
+        self.expect_end_of_line()?;
+
+        loop {
+            self.skip_newlines();
+            if self.peek().kind == TokenKind::End {
+                return Ok(shex);
+            }
+
+            let instruction = self.instruction()?;
+            shex.add_instruction(instruction);
+            self.expect_end_of_line()?;
+        }
+    }
+
+    fn instruction(&mut self) -> Result<Instruction, Error> {
+        if matches!(self.peek_second().kind, TokenKind::String(_)) {
+            return self.shader_message();
+        }
+
+        let mnemonic = self.mnemonic()?;
+
+        if mnemonic.opcode.is_declaration() || mnemonic.opcode == Opcode::CustomData {
+            self.declaration(&mnemonic)
+        } else {
+            self.operation(&mnemonic)
+        }
+    }
+
+    fn mnemonic(&mut self) -> Result<Mnemonic, Error> {
+        let (name, token) = self.expect_ident("an instruction")?;
+        let mut words: Vec<(String, Token)> = split_words(&name, &token);
+
+        // suffixes and groups are attached to the mnemonic, like in
+        // `resinfo_indexable(texture2d)(float,float,float,float)_uint`
+        let mut groups = Vec::new();
+        loop {
+            let next = self.peek();
+            if next.spaced {
+                break;
+            }
+
+            match next.kind {
+                TokenKind::Punct('(') => groups.push(self.group()?),
+                TokenKind::Ident(ref suffix) if suffix.starts_with('_') => {
+                    let suffix = suffix.clone();
+                    let token = self.next();
+                    words.extend(split_words(&suffix, &token));
+                }
+                _ => break,
+            }
+        }
+
+        for len in (1..=words.len()).rev() {
+            let base: Vec<&str> = words[..len].iter().map(|(word, _)| &word[..]).collect();
+            let base = base.join("_");
+
+            if let Some(&(name, opcode)) = MNEMONICS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(&base))
+            {
+                return Ok(Mnemonic {
+                    token,
+                    opcode,
+                    name: String::from(name),
+                    suffixes: words.split_off(len),
+                    groups,
+                });
+            }
+        }
+
+        Err(token.error(format!("unknown instruction `{}`", name)))
+    }
+
+    /// Parses a message from `printf` or `errorf`, like
+    /// `printf "%0 is fine!", r0.x`.
+    fn shader_message(&mut self) -> Result<Instruction, Error> {
+        let (name, token) = self.expect_ident("an instruction")?;
+        let (id, format) = match &name[..] {
+            "printf" => (
+                D3D11_SB_SHADER_MESSAGE_ID_MESSAGE,
+                D3D11_SB_SHADER_MESSAGE_FORMAT_ANSI_PRINTF,
+            ),
+            "errorf" => (
+                D3D11_SB_SHADER_MESSAGE_ID_ERROR,
+                D3D11_SB_SHADER_MESSAGE_FORMAT_ANSI_PRINTF,
+            ),
+            "message" => (
+                D3D11_SB_SHADER_MESSAGE_ID_MESSAGE,
+                D3D11_SB_SHADER_MESSAGE_FORMAT_ANSI_TEXT,
+            ),
+            "error" => (
+                D3D11_SB_SHADER_MESSAGE_ID_ERROR,
+                D3D11_SB_SHADER_MESSAGE_FORMAT_ANSI_TEXT,
+            ),
+            _ => return Err(token.error(format!("unknown instruction `{}`", name))),
+        };
+
+        let text = match self.next().kind {
+            TokenKind::String(text) => text,
+            _ => unreachable!("the caller checked for the string"),
+        };
+
+        let mut operands = Vec::new();
+        while self.eat_punct(',') {
+            operands.push(self.operand(Role::Source)?);
+        }
+
+        Ok(Instruction::ShaderMessage {
+            id,
+            format,
+            text,
+            operands,
+        })
+    }
+
+    /// Parses a parenthesized group, like `(float,float,float,float)`.
+    fn group(&mut self) -> Result<(Token, Vec<Vec<Token>>), Error> {
+        let open = self.expect_punct('(')?;
+        let mut items = vec![Vec::new()];
+
+        loop {
+            let token = self.next();
+            match token.kind {
+                TokenKind::Punct(')') => break,
+                TokenKind::Punct(',') => items.push(Vec::new()),
+                TokenKind::Newline | TokenKind::End => {
+                    return Err(token.error("expected `)`, found the end of the line"))
+                }
+                _ => items.last_mut().unwrap().push(token),
+            }
+        }
+
+        Ok((open, items))
+    }
+
+    fn operation(&mut self, mnemonic: &Mnemonic) -> Result<Instruction, Error> {
+        let opcode = mnemonic.opcode;
+
+        let mut saturated = false;
+        let mut test = None;
+        let mut resinfo_return_type = ResInfoReturnType::Float;
+        let mut sync_flags = SyncFlags::empty();
+        for (suffix, token) in &mnemonic.suffixes {
+            match (&suffix.to_ascii_lowercase()[..], opcode) {
+                ("sat", _) => saturated = true,
+                ("z", _) => test = Some(TestBoolean::Zero),
+                ("nz", _) => test = Some(TestBoolean::NonZero),
+                // the offsets and resource are given by the groups
+                ("aoffimmi", _) | ("indexable", _) => {}
+                ("uint", Opcode::ResInfo) | ("uint", Opcode::SampleInfo) => {
+                    resinfo_return_type = ResInfoReturnType::UInt
+                }
+                ("rcpfloat", Opcode::ResInfo) => resinfo_return_type = ResInfoReturnType::RcpFloat,
+                ("uglobal", Opcode::Sync) => {
+                    sync_flags |= SyncFlags::UNORDERED_ACCESS_VIEW_MEMORY_GLOBAL
+                }
+                ("ugroup", Opcode::Sync) => {
+                    sync_flags |= SyncFlags::UNORDERED_ACCESS_VIEW_MEMORY_GROUP
+                }
+                ("g", Opcode::Sync) => sync_flags |= SyncFlags::THREAD_GROUP_SHARED_MEMORY,
+                ("t", Opcode::Sync) => sync_flags |= SyncFlags::THREADS_IN_GROUP,
+                _ => return Err(unknown_suffix(mnemonic, suffix, token)),
+            }
+        }
+
+        let mut extended = Vec::new();
+        for (token, items) in &mnemonic.groups {
+            extended.push(parse_extended_opcode(token, items)?);
+        }
+
+        match opcode {
+            Opcode::Sync => return Ok(Instruction::Sync { flags: sync_flags }),
+            Opcode::InterfaceCall => return self.interface_call(),
+            _ => {}
+        }
+
+        let destinations = match opcode {
+            Opcode::SinCos
+            | Opcode::UDiv
+            | Opcode::IMul
+            | Opcode::UMul
+            | Opcode::UAddC
+            | Opcode::USubB
+            | Opcode::SwapC
+            | Opcode::ImmAtomicAlloc
+            | Opcode::ImmAtomicConsume
+            | Opcode::ImmAtomicIAdd
+            | Opcode::ImmAtomicAnd
+            | Opcode::ImmAtomicOr
+            | Opcode::ImmAtomicXor
+            | Opcode::ImmAtomicExch
+            | Opcode::ImmAtomicCmpExch
+            | Opcode::ImmAtomicIMax
+            | Opcode::ImmAtomicIMin
+            | Opcode::ImmAtomicUMax
+            | Opcode::ImmAtomicUMin => 2,
+            Opcode::Break
+            | Opcode::BreakC
+            | Opcode::Call
+            | Opcode::CallC
+            | Opcode::Case
+            | Opcode::Continue
+            | Opcode::ContinueC
+            | Opcode::Cut
+            | Opcode::Default
+            | Opcode::Discard
+            | Opcode::Else
+            | Opcode::Emit
+            | Opcode::EmitThenCut
+            | Opcode::EndIf
+            | Opcode::EndLoop
+            | Opcode::EndSwitch
+            | Opcode::If
+            | Opcode::Label
+            | Opcode::Loop
+            | Opcode::Nop
+            | Opcode::Ret
+            | Opcode::RetC
+            | Opcode::Switch
+            | Opcode::HsDecls
+            | Opcode::HsControlPointPhase
+            | Opcode::HsForkPhase
+            | Opcode::HsJoinPhase
+            | Opcode::EmitStream
+            | Opcode::CutStream
+            | Opcode::EmitThenCutStream
+            | Opcode::Abort
+            | Opcode::DebugBreak => 0,
+            _ => 1,
+        };
+
+        let mut operands = Vec::new();
+        if !self.at_end_of_line() {
+            loop {
+                let role = if operands.len() < destinations {
+                    Role::Destination
+                } else {
+                    Role::Source
+                };
+                operands.push(self.operand(role)?);
+
+                if !self.eat_punct(',') {
+                    break;
+                }
+            }
+        }
+
+        let operand_count_error = |expected: usize, found: usize| {
+            mnemonic.token.error(format!(
+                "`{}` takes {} operands, found {}",
+                mnemonic.name, expected, found
+            ))
+        };
+
+        Ok(match opcode {
+            Opcode::ResInfo => {
+                let found = operands.len();
+                let [dest, mip_level, resource]: [Operand; 3] = operands
+                    .try_into()
+                    .map_err(|_| operand_count_error(3, found))?;
+
+                Instruction::ResInfo {
+                    return_type: resinfo_return_type,
+                    extended,
+                    dest,
+                    mip_level,
+                    resource,
+                }
+            }
+            Opcode::SampleInfo => {
+                let found = operands.len();
+                let [dest, resource]: [Operand; 2] = operands
+                    .try_into()
+                    .map_err(|_| operand_count_error(2, found))?;
+
+                Instruction::SampleInfo {
+                    uint: resinfo_return_type == ResInfoReturnType::UInt,
+                    extended,
+                    dest,
+                    resource,
+                }
+            }
+            _ => Instruction::Operation {
+                opcode,
+                saturated,
+                test,
+                extended,
+                operands,
+            },
+        })
+    }
+
+    /// Parses the operand of `fcall`, like `fp0[0][1]`, with the index of the
+    /// function to call last.
+    fn interface_call(&mut self) -> Result<Instruction, Error> {
+        let token = self.peek().clone();
+        let (ty, mut indices, _) = self.register()?;
+        if ty != shex::OperandType::Interface {
+            return Err(token.error(format!("expected an interface, found {}", token.describe())));
+        }
+
+        let function_index = if self.eat_punct(',') {
+            self.expect_u32("a function index")?
+        } else if indices.len() == 3 {
+            match indices.pop() {
+                Some(Immediate::U32(index)) => index,
+                _ => return Err(token.error("the function index must be a number")),
+            }
+        } else {
+            return Err(token.error("expected a function index, like `fp0[0][1]`"));
+        };
+
+        Ok(Instruction::InterfaceCall {
+            function_index,
+            interface: Operand::new(
+                OperandType::Other(ty, indices),
+                Modifier::None,
+                NumComponent::D0,
+            ),
+        })
+    }
+
+    fn operand(&mut self, role: Role) -> Result<Operand, Error> {
+        let negated = self.eat_punct('-');
+        let absolute = self.eat_punct('|');
+        let modifier = match (negated, absolute) {
+            (false, false) => Modifier::None,
+            (true, false) => Modifier::Neg,
+            (false, true) => Modifier::Abs,
+            (true, true) => Modifier::AbsNeg,
+        };
+
+        let immediate = match self.peek().kind {
+            TokenKind::Ident(ref name) if name == "l" || name == "d" => {
+                self.peek_second().is_punct('(')
+            }
+            TokenKind::Ident(_) => false,
+            _ => return Err(self.expected("an operand")),
+        };
+
+        let mut operand = if immediate {
+            let (name, _) = self.expect_ident("an immediate")?;
+            let (ty, components) = self.immediate(name == "d")?;
+            Operand::new(ty, modifier, components)
+        } else {
+            let (ty, indices, default) = self.register()?;
+            let components = self.components(role, default)?;
+            Operand::new(OperandType::Other(ty, indices), modifier, components)
+        };
+
+        if absolute {
+            self.expect_punct('|')?;
+        }
+
+        // the minimum precision follows in braces, like `r0.xy {min16f}`
+        if self.peek().is_punct('{') {
+            self.next();
+            let (name, token) = self.expect_ident("a precision like `min16f`")?;
+            let min_precision = match &name[..] {
+                "def32" => MinPrecision::Default,
+                "min16f" => MinPrecision::Float16,
+                "min2_8f" => MinPrecision::Float2_8,
+                "min16i" => MinPrecision::SInt16,
+                "min16u" => MinPrecision::UInt16,
+                _ => return Err(token.error(format!("unknown precision `{}`", name))),
+            };
+            self.expect_punct('}')?;
+            operand = operand.with_min_precision(min_precision);
+        }
+
+        Ok(operand)
+    }
+
+    /// Parses the values of `l(...)` or `d(...)`.
+    fn immediate(&mut self, double: bool) -> Result<(OperandType, NumComponent), Error> {
+        let open = self.expect_punct('(')?;
+
+        let mut values = Vec::new();
+        loop {
+            values.push(self.immediate_value(double)?);
+            if !self.eat_punct(',') {
+                break;
+            }
+        }
+        self.expect_punct(')')?;
+
+        let ty = match (double, &values[..]) {
+            (false, &[x]) => OperandType::Imm32(x as u32),
+            (false, &[x, y]) => OperandType::Imm32x2(x as u32, y as u32),
+            (false, &[x, y, z]) => OperandType::Imm32x3(x as u32, y as u32, z as u32),
+            (false, &[x, y, z, w]) => OperandType::Imm32x4(x as u32, y as u32, z as u32, w as u32),
+            (true, &[x]) => OperandType::Imm64(x),
+            (true, &[x, y]) => OperandType::Imm64x2(x, y),
+            _ => return Err(open.error(format!("an immediate can't have {} values", values.len()))),
+        };
+
+        let components = if values.len() == 1 {
+            NumComponent::D1
+        } else {
+            NumComponent::D4(ComponentMode::Mask(0))
+        };
+
+        Ok((ty, components))
+    }
+
+    /// Parses one value of an immediate. 32-bit values are returned in the
+    /// low bits.
+    fn immediate_value(&mut self, double: bool) -> Result<u64, Error> {
+        let negated = self.eat_punct('-');
+        let token = self.peek().clone();
+
+        let value = match token.kind {
+            TokenKind::Number(ref text) => parse_number(text, negated, double),
+            TokenKind::Ident(ref name) if name == "inf" || name == "nan" => {
+                let value = if name == "inf" {
+                    f64::INFINITY
+                } else {
+                    f64::NAN
+                };
+                let value = if negated { -value } else { value };
+                Some(float_bits(value, double))
+            }
+            _ => return Err(self.expected("a number")),
+        };
+
+        self.next();
+        value.ok_or_else(|| token.error(format!("invalid number {}", token.describe())))
+    }
+
+    /// Parses a register and its indices, like `v1`, `cb0[r0.x + 1]` or
+    /// `vicp[0][1]`.
+    fn register(&mut self) -> Result<(shex::OperandType, Vec<Immediate>, Components), Error> {
+        let (name, token) = self.expect_ident("a register")?;
+
+        let prefix = name.trim_end_matches(|c: char| c.is_ascii_digit());
+        let (ty, components) = REGISTERS
+            .iter()
+            .find(|(register, _, _)| register.eq_ignore_ascii_case(prefix))
+            .map(|&(_, ty, components)| (ty, components))
+            .ok_or_else(|| token.error(format!("unknown register `{}`", name)))?;
+
+        let mut indices = Vec::new();
+        if prefix.len() < name.len() {
+            let index = name[prefix.len()..]
+                .parse()
+                .map_err(|_| token.error(format!("register `{}` is out of range", name)))?;
+            indices.push(Immediate::U32(index));
+        }
+
+        while self.eat_punct('[') {
+            indices.push(self.index()?);
+            self.expect_punct(']')?;
+        }
+
+        if indices.len() > 3 {
+            return Err(token.error(format!("`{}` has more than 3 indices", name)));
+        }
+
+        Ok((ty, indices, components))
+    }
+
+    /// Parses an index, like `1`, `r0.x` or `r0.x + 1`.
+    fn index(&mut self) -> Result<Immediate, Error> {
+        let token = self.peek().clone();
+        if let TokenKind::Number(ref text) = token.kind {
+            self.next();
+            return match parse_integer(text) {
+                Some(index) => Ok(u32::try_from(index)
+                    .map(Immediate::U32)
+                    .unwrap_or(Immediate::U64(index))),
+                None => Err(token.error(format!("invalid index {}", token.describe()))),
+            };
+        }
+
+        let relative = self.operand(Role::Source)?;
+        if !self.eat_punct('+') {
+            return Ok(Immediate::Relative(relative));
+        }
+
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::Number(ref text) => {
+                self.next();
+                match parse_integer(text) {
+                    Some(offset) => Ok(match u32::try_from(offset) {
+                        Ok(offset) => Immediate::U32Relative(offset, relative),
+                        Err(_) => Immediate::U64Relative(offset, relative),
+                    }),
+                    None => Err(token.error(format!("invalid index {}", token.describe()))),
+                }
+            }
+            _ => Err(self.expected("an index")),
+        }
+    }
+
+    fn components(&mut self, role: Role, default: Components) -> Result<NumComponent, Error> {
+        if !self.eat_punct('.') {
+            return Ok(match default {
+                Components::None => NumComponent::D0,
+                Components::Scalar => NumComponent::D1,
+                Components::Vector => match role {
+                    Role::Destination => NumComponent::D4(ComponentMode::Mask(X | Y | Z | W)),
+                    Role::Source => NumComponent::D4(ComponentMode::Swizzle(X, Y, Z, W)),
+                },
+            });
+        }
+
+        let (letters, token) = self.expect_ident("components like `xyzw`")?;
+        let components = letters
+            .chars()
+            .map(|c| match c {
+                'x' => Some(X),
+                'y' => Some(Y),
+                'z' => Some(Z),
+                'w' => Some(W),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .filter(|components| components.len() <= 4)
+            .ok_or_else(|| token.error(format!("invalid components `{}`", letters)))?;
+
+        Ok(NumComponent::D4(match (role, &components[..]) {
+            (Role::Destination, _) => {
+                ComponentMode::Mask(components.iter().fold(0, |mask, &c| mask | c))
+            }
+            (Role::Source, &[x]) => ComponentMode::Select(x),
+            // shorter swizzles repeat their last component
+            (Role::Source, _) => {
+                let component = |idx: usize| components[idx.min(components.len() - 1)];
+                ComponentMode::Swizzle(component(0), component(1), component(2), component(3))
+            }
+        }))
+    }
+
+    fn declaration(&mut self, mnemonic: &Mnemonic) -> Result<Instruction, Error> {
+        let opcode = mnemonic.opcode;
+
+        let mut dimension = None;
+        let mut globally_coherent = false;
+        let mut has_counter = false;
+        let mut dynamically_indexed = false;
+        for (suffix, token) in &mnemonic.suffixes {
+            match (&suffix.to_ascii_lowercase()[..], opcode) {
+                ("glc", Opcode::DclUavTyped)
+                | ("glc", Opcode::DclUavRaw)
+                | ("glc", Opcode::DclUavStructured) => globally_coherent = true,
+                ("opc", Opcode::DclUavStructured) => has_counter = true,
+                ("dynamicindexed", Opcode::DclInterface) => dynamically_indexed = true,
+                (name, Opcode::DclResource) | (name, Opcode::DclUavTyped)
+                    if parse_resource_dimension(name).is_some() =>
+                {
+                    dimension = parse_resource_dimension(name)
+                }
+                _ => return Err(unknown_suffix(mnemonic, suffix, token)),
+            }
+        }
+
+        // only multisampled textures have a group, their sample count
+        let mut sample_count = 0;
+        for (token, items) in &mnemonic.groups {
+            match (&items[..], dimension) {
+                (
+                    [item],
+                    Some(ResourceDimension::Texture2DMS | ResourceDimension::Texture2DMSArray),
+                ) => sample_count = parse_group_integer(token, item)?,
+                _ => return Err(token.error("unexpected `(`")),
+            }
+        }
+
+        let dimension = match opcode {
+            Opcode::DclResource | Opcode::DclUavTyped => dimension.ok_or_else(|| {
+                mnemonic.token.error(format!(
+                    "expected a resource dimension, like `{}_texture2d`",
+                    mnemonic.name
+                ))
+            })?,
+            _ => ResourceDimension::Unknown,
+        };
+
+        Ok(match opcode {
+            Opcode::DclGlobalFlags => {
+                let mut flags = GlobalFlags::empty();
+                loop {
+                    let (name, token) = self.expect_ident("a global flag")?;
+                    flags |= parse_global_flag(&name)
+                        .ok_or_else(|| token.error(format!("unknown global flag `{}`", name)))?;
+
+                    if !self.eat_punct('|') {
+                        break;
+                    }
+                }
+
+                Instruction::DclGlobalFlags { flags }
+            }
+            Opcode::DclTemps => Instruction::DclTemps {
+                count: self.expect_u32("a register count")?,
+            },
+            Opcode::DclIndexableTemp => {
+                let [register, count] =
+                    self.declared_register::<2>(shex::OperandType::IndexableTemp)?;
+                self.expect_punct(',')?;
+
+                Instruction::DclIndexableTemp {
+                    register,
+                    count,
+                    components: self.expect_u32("a component count")?,
+                }
+            }
+            Opcode::DclInput => Instruction::DclInput {
+                register: self.operand(Role::Destination)?,
+            },
+            Opcode::DclInputSgv => Instruction::DclInputSgv {
+                register: self.operand(Role::Destination)?,
+                semantic: self.semantic()?,
+            },
+            Opcode::DclInputSiv => Instruction::DclInputSiv {
+                register: self.operand(Role::Destination)?,
+                semantic: self.semantic()?,
+            },
+            Opcode::DclInputPs => Instruction::DclInputPs {
+                interpolation: self.interpolation()?,
+                register: self.operand(Role::Destination)?,
+            },
+            Opcode::DclInputPsSgv => Instruction::DclInputPsSgv {
+                interpolation: self.interpolation()?,
+                register: self.operand(Role::Destination)?,
+                semantic: self.semantic()?,
+            },
+            Opcode::DclInputPsSiv => Instruction::DclInputPsSiv {
+                interpolation: self.interpolation()?,
+                register: self.operand(Role::Destination)?,
+                semantic: self.semantic()?,
+            },
+            Opcode::DclOutput => Instruction::DclOutput {
+                register: self.operand(Role::Destination)?,
+            },
+            Opcode::DclOutputSgv => Instruction::DclOutputSgv {
+                register: self.operand(Role::Destination)?,
+                semantic: self.semantic()?,
+            },
+            Opcode::DclOutputSiv => Instruction::DclOutputSiv {
+                register: self.operand(Role::Destination)?,
+                semantic: self.semantic()?,
+            },
+            Opcode::DclIndexRange => {
+                let register = self.operand(Role::Destination)?;
+                self.eat_punct(',');
+
+                Instruction::DclIndexRange {
+                    register,
+                    count: self.expect_u32("a register count")?,
+                }
+            }
+            Opcode::DclConstantBuffer => {
+                let register = self.operand(Role::Source)?;
+                let access = if self.eat_punct(',') {
+                    let (name, token) = self.expect_ident("an access pattern")?;
+                    match &name[..] {
+                        "immediateIndexed" => ConstantBufferIndexPattern::Immediate,
+                        "dynamicIndexed" => ConstantBufferIndexPattern::Dynamic,
+                        _ => return Err(token.error(format!("unknown access pattern `{}`", name))),
+                    }
+                } else {
+                    ConstantBufferIndexPattern::Immediate
+                };
+
+                Instruction::DclConstantBuffer { register, access }
+            }
+            Opcode::CustomData => {
+                if !mnemonic
+                    .name
+                    .eq_ignore_ascii_case("dcl_immediateConstantBuffer")
+                {
+                    return Err(mnemonic.token.error("custom data can't be assembled"));
+                }
+
+                Instruction::DclImmediateConstantBuffer(self.immediate_constant_buffer()?)
+            }
+            Opcode::DclSampler => {
+                let register = self.operand(Role::Destination)?;
+                self.expect_punct(',')?;
+                let (name, token) = self.expect_ident("a sampler mode")?;
+                let mode = match &name[..] {
+                    "mode_default" => SamplerMode::Default,
+                    "mode_comparison" => SamplerMode::Comparison,
+                    "mode_mono" => SamplerMode::Mono,
+                    _ => return Err(token.error(format!("unknown sampler mode `{}`", name))),
+                };
+
+                Instruction::DclSampler { register, mode }
+            }
+            Opcode::DclResource => Instruction::DclResource {
+                dimension,
+                return_type: self.return_type()?,
+                register: self.operand(Role::Destination)?,
+                sample_count,
+            },
+            Opcode::DclResourceRaw => Instruction::DclResourceRaw {
+                register: self.operand(Role::Destination)?,
+            },
+            Opcode::DclResourceStructured => {
+                let register = self.operand(Role::Destination)?;
+                self.expect_punct(',')?;
+
+                Instruction::DclResourceStructured {
+                    register,
+                    stride: self.expect_u32("a stride")?,
+                }
+            }
+            Opcode::DclUavTyped => Instruction::DclUavTyped {
+                dimension,
+                return_type: self.return_type()?,
+                register: self.operand(Role::Destination)?,
+                globally_coherent,
+            },
+            Opcode::DclUavRaw => Instruction::DclUavRaw {
+                register: self.operand(Role::Destination)?,
+                globally_coherent,
+            },
+            Opcode::DclUavStructured => {
+                let register = self.operand(Role::Destination)?;
+                self.expect_punct(',')?;
+
+                Instruction::DclUavStructured {
+                    register,
+                    stride: self.expect_u32("a stride")?,
+                    globally_coherent,
+                    has_counter,
+                }
+            }
+            Opcode::DclTgsmRaw => {
+                let register = self.operand(Role::Destination)?;
+                self.expect_punct(',')?;
+
+                Instruction::DclTgsmRaw {
+                    register,
+                    count: self.expect_u32("a byte count")?,
+                }
+            }
+            Opcode::DclTgsmStructured => {
+                let register = self.operand(Role::Destination)?;
+                self.expect_punct(',')?;
+                let stride = self.expect_u32("a stride")?;
+                self.expect_punct(',')?;
+
+                Instruction::DclTgsmStructured {
+                    register,
+                    stride,
+                    count: self.expect_u32("a structure count")?,
+                }
+            }
+            Opcode::DclThreadGroup => {
+                let x = self.expect_u32("a thread count")?;
+                self.expect_punct(',')?;
+                let y = self.expect_u32("a thread count")?;
+                self.expect_punct(',')?;
+
+                Instruction::DclThreadGroup {
+                    x,
+                    y,
+                    z: self.expect_u32("a thread count")?,
+                }
+            }
+            Opcode::DclGsInputPrimitive => {
+                let (name, token) = self.expect_ident("a primitive")?;
+                let primitive = match &name[..] {
+                    "point" => Primitive::Point,
+                    "line" => Primitive::Line,
+                    "triangle" => Primitive::Triangle,
+                    "lineadj" => Primitive::LineAdj,
+                    "triangleadj" => Primitive::TriangleAdj,
+                    _ => match name.strip_prefix("patch").map(str::parse) {
+                        Some(Ok(count @ 1..=32)) => Primitive::ControlPointPatch(count),
+                        _ => return Err(token.error(format!("unknown primitive `{}`", name))),
+                    },
+                };
+
+                Instruction::DclGsInputPrimitive { primitive }
+            }
+            Opcode::DclGsOutputPrimitiveTopology => {
+                let (name, token) = self.expect_ident("a primitive topology")?;
+                let topology = match &name[..] {
+                    "pointlist" => PrimitiveTopology::PointList,
+                    "linelist" => PrimitiveTopology::LineList,
+                    "linestrip" => PrimitiveTopology::LineStrip,
+                    "trianglelist" => PrimitiveTopology::TriangleList,
+                    "trianglestrip" => PrimitiveTopology::TriangleStrip,
+                    "linelistadj" => PrimitiveTopology::LineListAdj,
+                    "linestripadj" => PrimitiveTopology::LineStripAdj,
+                    "trianglelistadj" => PrimitiveTopology::TriangleListAdj,
+                    "trianglestripadj" => PrimitiveTopology::TriangleStripAdj,
+                    _ => return Err(token.error(format!("unknown primitive topology `{}`", name))),
+                };
+
+                Instruction::DclGsOutputPrimitiveTopology { topology }
+            }
+            Opcode::DclMaxOutputVertexCount => Instruction::DclMaxOutputVertexCount {
+                count: self.expect_u32("a vertex count")?,
+            },
+            Opcode::DclGsInstanceCount => Instruction::DclGsInstanceCount {
+                count: self.expect_u32("an instance count")?,
+            },
+            Opcode::DclStream => Instruction::DclStream {
+                register: self.operand(Role::Destination)?,
+            },
+            Opcode::DclInputControlPointCount => Instruction::DclInputControlPointCount {
+                count: self.expect_u32("a control point count")?,
+            },
+            Opcode::DclOutputControlPointCount => Instruction::DclOutputControlPointCount {
+                count: self.expect_u32("a control point count")?,
+            },
+            Opcode::DclTessDomain => {
+                let (name, token) = self.expect_ident("a tessellator domain")?;
+                let domain = match &name[..] {
+                    "domain_isoline" => TessellatorDomain::Isoline,
+                    "domain_tri" => TessellatorDomain::Tri,
+                    "domain_quad" => TessellatorDomain::Quad,
+                    _ => return Err(token.error(format!("unknown domain `{}`", name))),
+                };
+
+                Instruction::DclTessellatorDomain { domain }
+            }
+            Opcode::DclTessPartitioning => {
+                let (name, token) = self.expect_ident("a tessellator partitioning")?;
+                let partitioning = match &name[..] {
+                    "partitioning_integer" => TessellatorPartitioning::Integer,
+                    "partitioning_pow2" => TessellatorPartitioning::Pow2,
+                    "partitioning_fractional_odd" => TessellatorPartitioning::FractionalOdd,
+                    "partitioning_fractional_even" => TessellatorPartitioning::FractionalEven,
+                    _ => return Err(token.error(format!("unknown partitioning `{}`", name))),
+                };
+
+                Instruction::DclTessellatorPartitioning { partitioning }
+            }
+            Opcode::DclTessOutputPrimitive => {
+                let (name, token) = self.expect_ident("a tessellator output primitive")?;
+                let primitive = match &name[..] {
+                    "output_point" => TessellatorOutputPrimitive::Point,
+                    "output_line" => TessellatorOutputPrimitive::Line,
+                    "output_triangle_cw" => TessellatorOutputPrimitive::TriangleCw,
+                    "output_triangle_ccw" => TessellatorOutputPrimitive::TriangleCcw,
+                    _ => return Err(token.error(format!("unknown output primitive `{}`", name))),
+                };
+
+                Instruction::DclTessellatorOutputPrimitive { primitive }
+            }
+            Opcode::DclHsMaxTessFactor => {
+                let (name, token) = self.expect_ident("an immediate like `l(64.000000)`")?;
+                if name != "l" {
+                    return Err(token.error(format!("expected `l`, found `{}`", name)));
+                }
+                let factor = match self.immediate(false)? {
+                    (OperandType::Imm32(factor), _) => f32::from_bits(factor),
+                    _ => return Err(token.error("expected a single tessellation factor")),
+                };
+
+                Instruction::DclHsMaxTessFactor { factor }
+            }
+            Opcode::DclHsForkPhaseInstanceCount => Instruction::DclHsForkPhaseInstanceCount {
+                count: self.expect_u32("an instance count")?,
+            },
+            Opcode::DclHsJoinPhaseInstanceCount => Instruction::DclHsJoinPhaseInstanceCount {
+                count: self.expect_u32("an instance count")?,
+            },
+            Opcode::DclFunctionBody => {
+                let [index] = self.declared_register::<1>(shex::OperandType::FunctionBody)?;
+
+                Instruction::DclFunctionBody { index }
+            }
+            Opcode::DclFunctionTable => {
+                let [index] = self.declared_register::<1>(shex::OperandType::FunctionTable)?;
+                self.expect_punct('=')?;
+
+                Instruction::DclFunctionTable {
+                    index,
+                    bodies: self.register_list(shex::OperandType::FunctionBody)?,
+                }
+            }
+            Opcode::DclInterface => {
+                let [index, array_length, body_count] =
+                    self.declared_register::<3>(shex::OperandType::Interface)?;
+                self.expect_punct('=')?;
+
+                Instruction::DclInterface {
+                    index,
+                    dynamically_indexed,
+                    body_count,
+                    array_length,
+                    tables: self.register_list(shex::OperandType::FunctionTable)?,
+                }
+            }
+            _ => {
+                return Err(mnemonic
+                    .token
+                    .error(format!("`{}` can't be assembled", mnemonic.name)))
+            }
+        })
+    }
+
+    /// Parses a register of the given type with `N` constant indices, like
+    /// `x0[4]`.
+    fn declared_register<const N: usize>(
+        &mut self,
+        ty: shex::OperandType,
+    ) -> Result<[u32; N], Error> {
+        let token = self.peek().clone();
+        let (found, indices, _) = self.register()?;
+        let prefix = REGISTERS
+            .iter()
+            .find(|(_, register, _)| *register == ty)
+            .map_or("", |(name, _, _)| name);
+
+        let indices: Option<Vec<u32>> = indices
+            .into_iter()
+            .map(|index| match index {
+                Immediate::U32(index) => Some(index),
+                _ => None,
+            })
+            .collect();
+
+        match indices.map(|indices| indices.try_into()) {
+            Some(Ok(indices)) if found == ty => Ok(indices),
+            _ => Err(token.error(format!(
+                "expected `{}` with {} constant indices, found {}",
+                prefix,
+                N,
+                token.describe()
+            ))),
+        }
+    }
+
+    /// Parses a list of registers in braces, like `{fb0, fb1}`.
+    fn register_list(&mut self, ty: shex::OperandType) -> Result<Vec<u32>, Error> {
+        self.expect_punct('{')?;
+
+        let mut registers = Vec::new();
+        if self.eat_punct('}') {
+            return Ok(registers);
+        }
+        loop {
+            let [register] = self.declared_register::<1>(ty)?;
+            registers.push(register);
+
+            if !self.eat_punct(',') {
+                break;
+            }
+        }
+        self.expect_punct('}')?;
+
+        Ok(registers)
+    }
+
+    fn semantic(&mut self) -> Result<Semantic, Error> {
+        self.expect_punct(',')?;
+        let (name, token) = self.expect_ident("a system value")?;
+
+        SEMANTICS
+            .iter()
+            .find(|(semantic, _)| semantic.eq_ignore_ascii_case(&name))
+            .map(|&(_, semantic)| semantic)
+            .ok_or_else(|| token.error(format!("unknown system value `{}`", name)))
+    }
+
+    /// Parses an interpolation mode, like `linear noperspective centroid`.
+    fn interpolation(&mut self) -> Result<InterpolationMode, Error> {
+        let mut words = Vec::new();
+        while let TokenKind::Ident(ref word) = self.peek().kind {
+            match &word[..] {
+                "constant" | "linear" | "noperspective" | "centroid" | "sample" => {
+                    words.push(word.clone());
+                    self.next();
+                }
+                _ => break,
+            }
+        }
+
+        Ok(match &words.join(" ")[..] {
+            "" => InterpolationMode::Undefined,
+            "constant" => InterpolationMode::Constant,
+            "linear" => InterpolationMode::Linear,
+            "linear centroid" => InterpolationMode::LinearCentroid,
+            "linear noperspective" => InterpolationMode::LinearNoPerspective,
+            "linear noperspective centroid" => InterpolationMode::LinearNoPerspectiveCentroid,
+            "linear sample" => InterpolationMode::LinearSample,
+            "linear noperspective sample" => InterpolationMode::LinearNoPerspectiveSample,
+            mode => {
+                return Err(self
+                    .peek()
+                    .error(format!("unknown interpolation mode `{}`", mode)))
+            }
+        })
+    }
+
+    /// Parses the return types of a resource, like `(float,float,float,float)`.
+    fn return_type(&mut self) -> Result<[ResourceReturnType; 4], Error> {
+        let (token, items) = self.group()?;
+
+        match parse_extended_opcode(&token, &items)? {
+            OpcodeEx::ResourceReturnType(x, y, z, w) => Ok([x, y, z, w]),
+            _ => Err(token.error("expected 4 return types")),
+        }
+    }
+
+    /// Parses the rows of an immediate constant buffer, like
+    /// `{ { 1.000000, 0, 0, 0}, { 0, 1.000000, 0, 0} }`.
+    fn immediate_constant_buffer(&mut self) -> Result<Vec<[u32; 4]>, Error> {
+        self.expect_punct('{')?;
+
+        let mut rows = Vec::new();
+        loop {
+            let open = self.expect_punct('{')?;
+            let mut row = Vec::new();
+            loop {
+                row.push(self.immediate_value(false)? as u32);
+                if !self.eat_punct(',') {
+                    break;
+                }
+            }
+            self.expect_punct('}')?;
+
+            rows.push(
+                row.try_into()
+                    .map_err(|_| open.error("immediate constant buffer rows have 4 values"))?,
+            );
+
+            if !self.eat_punct(',') {
+                break;
+            }
+        }
+        self.expect_punct('}')?;
+
+        Ok(rows)
+    }
+}
+
+fn split_words(name: &str, token: &Token) -> Vec<(String, Token)> {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| (String::from(word), token.clone()))
+        .collect()
+}
+
+fn unknown_suffix(mnemonic: &Mnemonic, suffix: &str, token: &Token) -> Error {
+    token.error(format!(
+        "unknown suffix `_{}` for `{}`",
+        suffix, mnemonic.name
+    ))
+}
+
+fn parse_shader_model(name: &str) -> Option<ShexChunk> {
+    let mut parts = name.split('_');
+    let program_type = match parts.next()? {
+        "ps" => D3D10_SB_PIXEL_SHADER,
+        "vs" => D3D10_SB_VERTEX_SHADER,
+        "gs" => D3D10_SB_GEOMETRY_SHADER,
+        "hs" => D3D11_SB_HULL_SHADER,
+        "ds" => D3D11_SB_DOMAIN_SHADER,
+        "cs" => D3D11_SB_COMPUTE_SHADER,
+        _ => return None,
+    };
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(ShexChunk::with_version(program_type as u16, major, minor))
+}
+
+fn parse_integer(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+fn float_bits(value: f64, double: bool) -> u64 {
+    if double {
+        value.to_bits()
+    } else {
+        (value as f32).to_bits() as u64
+    }
+}
+
+/// Parses a number of an immediate. Integers are written as is, and floats
+/// are written as `f32` or, if `double` is set, `f64`.
+fn parse_number(text: &str, negated: bool, double: bool) -> Option<u64> {
+    let is_hex = text.starts_with("0x") || text.starts_with("0X");
+    if !is_hex && text.contains(['.', 'e', 'E']) {
+        // doubles end with an `l`, like `1.000000l`
+        let value: f64 = text.strip_suffix('l').unwrap_or(text).parse().ok()?;
+        let value = if negated { -value } else { value };
+        return Some(float_bits(value, double));
+    }
+
+    let value = parse_integer(text)?;
+    let value = if negated { value.wrapping_neg() } else { value };
+    if double {
+        Some(value)
+    } else {
+        // 32-bit values may be written signed or unsigned
+        match i32::try_from(value as i64).map(|value| value as u32) {
+            Ok(value) => Some(value as u64),
+            Err(_) => u32::try_from(value).ok().map(u64::from),
+        }
+    }
+}
+
+fn parse_group_integer(token: &Token, item: &[Token]) -> Result<u32, Error> {
+    let (negated, number) = match item {
+        [number] => (false, number),
+        [sign, number] if sign.is_punct('-') => (true, number),
+        _ => return Err(token.error("expected a number")),
+    };
+
+    let value = match number.kind {
+        TokenKind::Number(ref text) => parse_integer(text)
+            .and_then(|value| i64::try_from(value).ok())
+            .map(|value| if negated { -value } else { value }),
+        _ => None,
+    };
+
+    value
+        .map(|value| value as u32)
+        .ok_or_else(|| number.error(format!("expected a number, found {}", number.describe())))
+}
+
+/// Parses a group after a mnemonic into an extended opcode, like the offsets
+/// `(-1,0,0)`, the resource `(texture2d)` or the return types
+/// `(float,float,float,float)`.
+fn parse_extended_opcode(token: &Token, items: &[Vec<Token>]) -> Result<OpcodeEx, Error> {
+    let first = items
+        .first()
+        .and_then(|item| item.first())
+        .ok_or_else(|| token.error("empty group"))?;
+
+    match first.kind {
+        TokenKind::Number(_) | TokenKind::Punct('-') => {
+            if items.len() > 3 {
+                return Err(token.error("expected at most 3 offsets"));
+            }
+
+            let mut offsets = [0; 3];
+            for (offset, item) in offsets.iter_mut().zip(items) {
+                let value = parse_group_integer(token, item)? as i32;
+                if !(-8..=7).contains(&value) {
+                    return Err(item[0].error(format!("offset {} is out of range", value)));
+                }
+                // the offsets are 4 bit two's complement
+                *offset = value as u32 & 0xf;
+            }
+
+            Ok(OpcodeEx::UvOffset(offsets[0], offsets[1], offsets[2]))
+        }
+        TokenKind::Ident(ref name) => {
+            if let Some(dimension) = parse_resource_dimension(name) {
+                // structured buffers give their stride, like
+                // `(structured_buffer, stride=16)`
+                let stride = match &items[1..] {
+                    [] => 0,
+                    [item] => match &item[..] {
+                        [key, equals, _]
+                            if key.kind == TokenKind::Ident(String::from("stride"))
+                                && equals.is_punct('=') =>
+                        {
+                            parse_group_integer(token, &item[2..])?
+                        }
+                        _ => return Err(item[0].error("expected a stride, like `stride=16`")),
+                    },
+                    _ => return Err(token.error("expected a resource dimension and stride")),
+                };
+
+                return Ok(OpcodeEx::Dimension(dimension, stride));
+            }
+
+            let return_types = items
+                .iter()
+                .map(|item| match &item[..] {
+                    [token] => match token.kind {
+                        TokenKind::Ident(ref name) => parse_return_type(name)
+                            .ok_or_else(|| token.error(format!("unknown return type `{}`", name))),
+                        _ => Err(token.error("expected a return type")),
+                    },
+                    _ => Err(token.error("expected a return type")),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            match return_types[..] {
+                [x, y, z, w] => Ok(OpcodeEx::ResourceReturnType(x, y, z, w)),
+                _ => Err(token.error("expected 4 return types")),
+            }
+        }
+        _ => Err(first.error(format!("unexpected {}", first.describe()))),
+    }
+}
+
+fn parse_resource_dimension(name: &str) -> Option<ResourceDimension> {
+    Some(match name {
+        "buffer" => ResourceDimension::Buffer,
+        "texture1d" => ResourceDimension::Texture1D,
+        "texture2d" => ResourceDimension::Texture2D,
+        "texture2dms" => ResourceDimension::Texture2DMS,
+        "texture3d" => ResourceDimension::Texture3D,
+        "texturecube" => ResourceDimension::TextureCube,
+        "texture1darray" => ResourceDimension::Texture1DArray,
+        "texture2darray" => ResourceDimension::Texture2DArray,
+        "texture2dmsarray" => ResourceDimension::Texture2DMSArray,
+        "texturecubearray" => ResourceDimension::TextureCubeArray,
+        "raw_buffer" => ResourceDimension::RawBuffer,
+        "structured_buffer" => ResourceDimension::StructuredBuffer,
+        _ => return None,
+    })
+}
+
+fn parse_return_type(name: &str) -> Option<ResourceReturnType> {
+    Some(match name {
+        "unorm" => ResourceReturnType::Unorm,
+        "snorm" => ResourceReturnType::Snorm,
+        "sint" => ResourceReturnType::Sint,
+        "uint" => ResourceReturnType::Uint,
+        "float" => ResourceReturnType::Float,
+        "mixed" => ResourceReturnType::Mixed,
+        "double" => ResourceReturnType::Double,
+        "continued" => ResourceReturnType::Continued,
+        "unused" => ResourceReturnType::Unused,
+        _ => return None,
+    })
+}
+
+fn parse_global_flag(name: &str) -> Option<GlobalFlags> {
+    Some(match name {
+        "refactoringAllowed" => GlobalFlags::REFACTORING_ALLOWED,
+        "enableDoublePrecisionFloatOps" => GlobalFlags::ENABLE_DOUBLE_PRECISION_FLOAT_OPS,
+        "forceEarlyDepthStencil" => GlobalFlags::FORCE_EARLY_DEPTH_STENCIL,
+        "enableRawAndStructuredBuffers" => GlobalFlags::ENABLE_RAW_AND_STRUCTURED_BUFFERS,
+        "skipOptimization" => GlobalFlags::SKIP_OPTIMIZATION,
+        "enableMinimumPrecision" => GlobalFlags::ENABLE_MINIMUM_PRECISION,
+        "enable11_1DoubleExtensions" => GlobalFlags::ENABLE_DOUBLE_EXTENSIONS,
+        "enable11_1ShaderExtensions" => GlobalFlags::ENABLE_SHADER_EXTENSIONS,
+        _ => return None,
+    })
+}
+
+/// The registers by their prefix, with the components they have when none
+/// are given.
+pub(crate) const REGISTERS: &[(&str, shex::OperandType, Components)] = &[
+    ("r", shex::OperandType::Temp, Components::Vector),
+    ("v", shex::OperandType::Input, Components::Vector),
+    ("o", shex::OperandType::Output, Components::Vector),
+    ("x", shex::OperandType::IndexableTemp, Components::Vector),
+    ("s", shex::OperandType::Sampler, Components::None),
+    ("t", shex::OperandType::Resource, Components::None),
+    ("cb", shex::OperandType::ConstantBuffer, Components::Vector),
+    (
+        "icb",
+        shex::OperandType::ImmediateConstantBuffer,
+        Components::Vector,
+    ),
+    ("l", shex::OperandType::Label, Components::None),
+    (
+        "vPrim",
+        shex::OperandType::InputPrimitiveId,
+        Components::Scalar,
+    ),
+    ("oDepth", shex::OperandType::OutputDepth, Components::Scalar),
+    ("null", shex::OperandType::Null, Components::None),
+    (
+        "rasterizer",
+        shex::OperandType::Rasterizer,
+        Components::None,
+    ),
+    (
+        "oMask",
+        shex::OperandType::OutputCoverageMask,
+        Components::Scalar,
+    ),
+    ("m", shex::OperandType::Stream, Components::None),
+    ("fb", shex::OperandType::FunctionBody, Components::None),
+    ("ft", shex::OperandType::FunctionTable, Components::None),
+    ("fp", shex::OperandType::Interface, Components::None),
+    (
+        "vOutputControlPointID",
+        shex::OperandType::OutputControlPointId,
+        Components::Scalar,
+    ),
+    (
+        "vForkInstanceID",
+        shex::OperandType::InputForkInstanceId,
+        Components::Scalar,
+    ),
+    (
+        "vJoinInstanceID",
+        shex::OperandType::InputJoinInstanceId,
+        Components::Scalar,
+    ),
+    (
+        "vicp",
+        shex::OperandType::InputControlPoint,
+        Components::Vector,
+    ),
+    (
+        "vocp",
+        shex::OperandType::OutputControlPoint,
+        Components::Vector,
+    ),
+    (
+        "vpc",
+        shex::OperandType::InputPatchConstant,
+        Components::Vector,
+    ),
+    (
+        "vDomain",
+        shex::OperandType::InputDomainPoint,
+        Components::Vector,
+    ),
+    ("this", shex::OperandType::ThisPointer, Components::None),
+    (
+        "u",
+        shex::OperandType::UnorderedAccessView,
+        Components::None,
+    ),
+    (
+        "g",
+        shex::OperandType::ThreadGroupSharedMemory,
+        Components::None,
+    ),
+    (
+        "vThreadID",
+        shex::OperandType::InputThreadId,
+        Components::Vector,
+    ),
+    (
+        "vThreadGroupID",
+        shex::OperandType::InputThreadGroupId,
+        Components::Vector,
+    ),
+    (
+        "vThreadIDInGroup",
+        shex::OperandType::InputThreadIdInGroup,
+        Components::Vector,
+    ),
+    (
+        "vCoverage",
+        shex::OperandType::InputCoverageMask,
+        Components::Scalar,
+    ),
+    (
+        "vThreadIDInGroupFlattened",
+        shex::OperandType::InputThreadIdInGroupFlattened,
+        Components::Scalar,
+    ),
+    (
+        "vGSInstanceID",
+        shex::OperandType::InputGsinstanceid,
+        Components::Scalar,
+    ),
+    (
+        "oDepthGE",
+        shex::OperandType::OutputDepthGreaterEqual,
+        Components::Scalar,
+    ),
+    (
+        "oDepthLE",
+        shex::OperandType::OutputDepthLessEqual,
+        Components::Scalar,
+    ),
+    (
+        "vCycleCounter",
+        shex::OperandType::CycleCounter,
+        Components::Vector,
+    ),
+];
+
+/// The system values of `dcl_*_siv` and `dcl_*_sgv`.
+pub(crate) const SEMANTICS: &[(&str, Semantic)] = &[
+    ("undefined", Semantic::Undefined),
+    ("position", Semantic::Position),
+    ("clip_distance", Semantic::ClipDistance),
+    ("cull_distance", Semantic::CullDistance),
+    ("rendertarget_array_index", Semantic::RenderTargetArrayIndex),
+    ("viewport_array_index", Semantic::ViewportArrayIndex),
+    ("vertex_id", Semantic::VertexId),
+    ("primitive_id", Semantic::PrimitiveId),
+    ("instance_id", Semantic::InstanceId),
+    ("is_front_face", Semantic::IsFrontFace),
+    ("sampleIndex", Semantic::SampleIndex),
+    (
+        "finalQuadUeq0EdgeTessFactor",
+        Semantic::FinalQuadUEq0EdgeTessfactor,
+    ),
+    (
+        "finalQuadVeq0EdgeTessFactor",
+        Semantic::FinalQuadVEq0EdgeTessfactor,
+    ),
+    (
+        "finalQuadUeq1EdgeTessFactor",
+        Semantic::FinalQuadUEq1EdgeTessfactor,
+    ),
+    (
+        "finalQuadVeq1EdgeTessFactor",
+        Semantic::FinalQuadVEq1EdgeTessfactor,
+    ),
+    (
+        "finalQuadUInsideTessFactor",
+        Semantic::FinalQuadUInsideTessfactor,
+    ),
+    (
+        "finalQuadVInsideTessFactor",
+        Semantic::FinalQuadVInsideTessfactor,
+    ),
+    (
+        "finalTriUeq0EdgeTessFactor",
+        Semantic::FinalTriUEq0EdgeTessfactor,
+    ),
+    (
+        "finalTriVeq0EdgeTessFactor",
+        Semantic::FinalTriVEq0EdgeTessfactor,
+    ),
+    (
+        "finalTriWeq0EdgeTessFactor",
+        Semantic::FinalTriWEq0EdgeTessfactor,
+    ),
+    (
+        "finalTriInsideTessFactor",
+        Semantic::FinalTriInsideTessfactor,
+    ),
+    (
+        "finalLineDetailTessFactor",
+        Semantic::FinalLineDetailTessfactor,
+    ),
+    (
+        "finalLineDensityTessFactor",
+        Semantic::FinalLineDensityTessfactor,
+    ),
+];
+
+/// The mnemonics `fxc` prints, without their suffixes.
+pub(crate) const MNEMONICS: &[(&str, Opcode)] = &[
+    ("add", Opcode::Add),
+    ("and", Opcode::And),
+    ("break", Opcode::Break),
+    ("breakc", Opcode::BreakC),
+    ("call", Opcode::Call),
+    ("callc", Opcode::CallC),
+    ("case", Opcode::Case),
+    ("continue", Opcode::Continue),
+    ("continuec", Opcode::ContinueC),
+    ("cut", Opcode::Cut),
+    ("default", Opcode::Default),
+    ("deriv_rtx", Opcode::DerivRtx),
+    ("deriv_rty", Opcode::DerivRty),
+    ("discard", Opcode::Discard),
+    ("div", Opcode::Div),
+    ("dp2", Opcode::Dp2),
+    ("dp3", Opcode::Dp3),
+    ("dp4", Opcode::Dp4),
+    ("else", Opcode::Else),
+    ("emit", Opcode::Emit),
+    ("emit_then_cut", Opcode::EmitThenCut),
+    ("endif", Opcode::EndIf),
+    ("endloop", Opcode::EndLoop),
+    ("endswitch", Opcode::EndSwitch),
+    ("eq", Opcode::Eq),
+    ("exp", Opcode::Exp),
+    ("frc", Opcode::Frc),
+    ("ftoi", Opcode::Ftoi),
+    ("ftou", Opcode::Ftou),
+    ("ge", Opcode::Ge),
+    ("iadd", Opcode::IAdd),
+    ("if", Opcode::If),
+    ("ieq", Opcode::IEq),
+    ("ige", Opcode::IGe),
+    ("ilt", Opcode::ILt),
+    ("imad", Opcode::IMad),
+    ("imax", Opcode::IMax),
+    ("imin", Opcode::IMin),
+    ("imul", Opcode::IMul),
+    ("ine", Opcode::INe),
+    ("ineg", Opcode::INeg),
+    ("ishl", Opcode::IShl),
+    ("ishr", Opcode::IShr),
+    ("itof", Opcode::Itof),
+    ("label", Opcode::Label),
+    ("ld", Opcode::Ld),
+    ("ld_ms", Opcode::LdMs),
+    ("log", Opcode::Log),
+    ("loop", Opcode::Loop),
+    ("lt", Opcode::Lt),
+    ("mad", Opcode::Mad),
+    ("min", Opcode::Min),
+    ("max", Opcode::Max),
+    ("dcl_immediateConstantBuffer", Opcode::CustomData),
+    ("mov", Opcode::Mov),
+    ("movc", Opcode::MovC),
+    ("mul", Opcode::Mul),
+    ("ne", Opcode::Ne),
+    ("nop", Opcode::Nop),
+    ("not", Opcode::Not),
+    ("or", Opcode::Or),
+    ("resinfo", Opcode::ResInfo),
+    ("ret", Opcode::Ret),
+    ("retc", Opcode::RetC),
+    ("round_ne", Opcode::RoundNe),
+    ("round_ni", Opcode::RoundNi),
+    ("round_pi", Opcode::RoundPi),
+    ("round_z", Opcode::RoundZ),
+    ("rsq", Opcode::Rsq),
+    ("sample", Opcode::Sample),
+    ("sample_c", Opcode::SampleC),
+    ("sample_c_lz", Opcode::SampleCLz),
+    ("sample_l", Opcode::SampleL),
+    ("sample_d", Opcode::SampleD),
+    ("sample_b", Opcode::SampleB),
+    ("sqrt", Opcode::Sqrt),
+    ("switch", Opcode::Switch),
+    ("sincos", Opcode::SinCos),
+    ("udiv", Opcode::UDiv),
+    ("ult", Opcode::ULt),
+    ("uge", Opcode::UGe),
+    ("umul", Opcode::UMul),
+    ("umad", Opcode::UMad),
+    ("umax", Opcode::UMax),
+    ("umin", Opcode::UMin),
+    ("ushr", Opcode::UShr),
+    ("utof", Opcode::Utof),
+    ("xor", Opcode::Xor),
+    ("dcl_resource", Opcode::DclResource),
+    ("dcl_constantbuffer", Opcode::DclConstantBuffer),
+    ("dcl_sampler", Opcode::DclSampler),
+    ("dcl_indexrange", Opcode::DclIndexRange),
+    ("dcl_outputtopology", Opcode::DclGsOutputPrimitiveTopology),
+    ("dcl_inputprimitive", Opcode::DclGsInputPrimitive),
+    ("dcl_maxout", Opcode::DclMaxOutputVertexCount),
+    ("dcl_input", Opcode::DclInput),
+    ("dcl_input_sgv", Opcode::DclInputSgv),
+    ("dcl_input_siv", Opcode::DclInputSiv),
+    ("dcl_input_ps", Opcode::DclInputPs),
+    ("dcl_input_ps_sgv", Opcode::DclInputPsSgv),
+    ("dcl_input_ps_siv", Opcode::DclInputPsSiv),
+    ("dcl_output", Opcode::DclOutput),
+    ("dcl_output_sgv", Opcode::DclOutputSgv),
+    ("dcl_output_siv", Opcode::DclOutputSiv),
+    ("dcl_temps", Opcode::DclTemps),
+    ("dcl_indexableTemp", Opcode::DclIndexableTemp),
+    ("dcl_globalFlags", Opcode::DclGlobalFlags),
+    ("lod", Opcode::Lod),
+    ("gather4", Opcode::Gather4),
+    ("samplepos", Opcode::SamplePos),
+    ("sampleinfo", Opcode::SampleInfo),
+    ("hs_decls", Opcode::HsDecls),
+    ("hs_control_point_phase", Opcode::HsControlPointPhase),
+    ("hs_fork_phase", Opcode::HsForkPhase),
+    ("hs_join_phase", Opcode::HsJoinPhase),
+    ("emit_stream", Opcode::EmitStream),
+    ("cut_stream", Opcode::CutStream),
+    ("emit_then_cut_stream", Opcode::EmitThenCutStream),
+    ("fcall", Opcode::InterfaceCall),
+    ("bufinfo", Opcode::BufInfo),
+    ("deriv_rtx_coarse", Opcode::DerivRtxCoarse),
+    ("deriv_rtx_fine", Opcode::DerivRtxFine),
+    ("deriv_rty_coarse", Opcode::DerivRtyCoarse),
+    ("deriv_rty_fine", Opcode::DerivRtyFine),
+    ("gather4_c", Opcode::Gather4C),
+    ("gather4_po", Opcode::Gather4Po),
+    ("gather4_po_c", Opcode::Gather4PoC),
+    ("rcp", Opcode::Rcp),
+    ("f32tof16", Opcode::F32ToF16),
+    ("f16tof32", Opcode::F16ToF32),
+    ("uaddc", Opcode::UAddC),
+    ("usubb", Opcode::USubB),
+    ("countbits", Opcode::CountBits),
+    ("firstbit_hi", Opcode::FirstBitHi),
+    ("firstbit_lo", Opcode::FirstBitLo),
+    ("firstbit_shi", Opcode::FirstBitShi),
+    ("ubfe", Opcode::UBfe),
+    ("ibfe", Opcode::IBfe),
+    ("bfi", Opcode::Bfi),
+    ("bfrev", Opcode::BfRev),
+    ("swapc", Opcode::SwapC),
+    ("dcl_stream", Opcode::DclStream),
+    ("dcl_function_body", Opcode::DclFunctionBody),
+    ("dcl_function_table", Opcode::DclFunctionTable),
+    ("dcl_interface", Opcode::DclInterface),
+    (
+        "dcl_input_control_point_count",
+        Opcode::DclInputControlPointCount,
+    ),
+    (
+        "dcl_output_control_point_count",
+        Opcode::DclOutputControlPointCount,
+    ),
+    ("dcl_tessellator_domain", Opcode::DclTessDomain),
+    ("dcl_tessellator_partitioning", Opcode::DclTessPartitioning),
+    (
+        "dcl_tessellator_output_primitive",
+        Opcode::DclTessOutputPrimitive,
+    ),
+    ("dcl_hs_max_tessfactor", Opcode::DclHsMaxTessFactor),
+    (
+        "dcl_hs_fork_phase_instance_count",
+        Opcode::DclHsForkPhaseInstanceCount,
+    ),
+    (
+        "dcl_hs_join_phase_instance_count",
+        Opcode::DclHsJoinPhaseInstanceCount,
+    ),
+    ("dcl_thread_group", Opcode::DclThreadGroup),
+    ("dcl_uav_typed", Opcode::DclUavTyped),
+    ("dcl_uav_raw", Opcode::DclUavRaw),
+    ("dcl_uav_structured", Opcode::DclUavStructured),
+    ("dcl_tgsm_raw", Opcode::DclTgsmRaw),
+    ("dcl_tgsm_structured", Opcode::DclTgsmStructured),
+    ("dcl_resource_raw", Opcode::DclResourceRaw),
+    ("dcl_resource_structured", Opcode::DclResourceStructured),
+    ("ld_uav_typed", Opcode::LdUavTyped),
+    ("store_uav_typed", Opcode::StoreUavTyped),
+    ("ld_raw", Opcode::LdRaw),
+    ("store_raw", Opcode::StoreRaw),
+    ("ld_structured", Opcode::LdStructured),
+    ("store_structured", Opcode::StoreStructured),
+    ("atomic_and", Opcode::AtomicAnd),
+    ("atomic_or", Opcode::AtomicOr),
+    ("atomic_xor", Opcode::AtomicXor),
+    ("atomic_cmp_store", Opcode::AtomicCmpStore),
+    ("atomic_iadd", Opcode::AtomicIAdd),
+    ("atomic_imax", Opcode::AtomicIMax),
+    ("atomic_imin", Opcode::AtomicIMin),
+    ("atomic_umax", Opcode::AtomicUMax),
+    ("atomic_umin", Opcode::AtomicUMin),
+    ("imm_atomic_alloc", Opcode::ImmAtomicAlloc),
+    ("imm_atomic_consume", Opcode::ImmAtomicConsume),
+    ("imm_atomic_iadd", Opcode::ImmAtomicIAdd),
+    ("imm_atomic_and", Opcode::ImmAtomicAnd),
+    ("imm_atomic_or", Opcode::ImmAtomicOr),
+    ("imm_atomic_xor", Opcode::ImmAtomicXor),
+    ("imm_atomic_exch", Opcode::ImmAtomicExch),
+    ("imm_atomic_cmp_exch", Opcode::ImmAtomicCmpExch),
+    ("imm_atomic_imax", Opcode::ImmAtomicIMax),
+    ("imm_atomic_imin", Opcode::ImmAtomicIMin),
+    ("imm_atomic_umax", Opcode::ImmAtomicUMax),
+    ("imm_atomic_umin", Opcode::ImmAtomicUMin),
+    ("sync", Opcode::Sync),
+    ("dadd", Opcode::DAdd),
+    ("dmax", Opcode::DMax),
+    ("dmin", Opcode::DMin),
+    ("dmul", Opcode::DMul),
+    ("deq", Opcode::DEq),
+    ("dge", Opcode::DGe),
+    ("dlt", Opcode::DLt),
+    ("dne", Opcode::DNe),
+    ("dmov", Opcode::DMov),
+    ("dmovc", Opcode::DMovC),
+    ("dtof", Opcode::Dtof),
+    ("ftod", Opcode::Ftod),
+    ("eval_snapped", Opcode::EvalSnapped),
+    ("eval_sample_index", Opcode::EvalSampleIndex),
+    ("eval_centroid", Opcode::EvalCentroid),
+    ("dcl_gsinstances", Opcode::DclGsInstanceCount),
+    ("abort", Opcode::Abort),
+    ("debug_break", Opcode::DebugBreak),
+    ("ddiv", Opcode::DDiv),
+    ("dfma", Opcode::DFma),
+    ("drcp", Opcode::DRcp),
+    ("msad", Opcode::Msad),
+    ("dtoi", Opcode::Dtoi),
+    ("dtou", Opcode::Dtou),
+    ("itod", Opcode::Itod),
+    ("utod", Opcode::Utod),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::Container;
+
+    #[test]
+    fn assemble_listing() {
+        let listing = "\
+//
+// Input signature:
+//
+// Name                 Index   Mask Register SysValue  Format   Used
+// -------------------- ----- ------ -------- -------- ------- ------
+// COLOR                    0   xyzw        0     NONE   float
+// UV                       0   xy          1     NONE   float   xy
+//
+//
+// Output signature:
+//
+// Name                 Index   Mask Register SysValue  Format   Used
+// -------------------- ----- ------ -------- -------- ------- ------
+// SV_Target                0   xyzw        0   TARGET   float   xyzw
+//
+ps_5_0
+dcl_globalFlags refactoringAllowed
+dcl_constantbuffer CB0[2], dynamicIndexed
+dcl_sampler s0, mode_default
+dcl_resource_texture2d (float,float,float,float) t0
+dcl_input_ps linear v1.xy
+dcl_output o0.xyzw
+dcl_temps 1
+mov_sat o0.xyzw, l(1.000000,1.000000,1.000000,1.000000)
+if_nz r0.x  // a comment
+ret
+// Approximately 3 instruction slots used
+";
+        let module = assemble(listing).unwrap();
+        let container = Container::parse(module.as_bytes()).unwrap();
+        let fourccs: Vec<_> = container.chunks.iter().map(|chunk| chunk.fourcc).collect();
+        assert_eq!(fourccs, [*b"ISGN", *b"OSGN", *b"SHEX"]);
+
+        let shex: Vec<u32> = container
+            .find_chunk(b"SHEX")
+            .unwrap()
+            .chunks_exact(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        let one = 1.0f32.to_bits();
+        #[rustfmt::skip]
+        assert_eq!(
+            shex,
+            [
+                0x00000050, 0x00000022,
+                0x0100086a,
+                0x04000859, 0x00208e46, 0x00000000, 0x00000002,
+                0x0300005a, 0x00106000, 0x00000000,
+                0x04001858, 0x00107000, 0x00000000, 0x00005555,
+                0x03001062, 0x00101032, 0x00000001,
+                0x03000065, 0x001020f2, 0x00000000,
+                0x02000068, 0x00000001,
+                0x08002036, 0x001020f2, 0x00000000, 0x00004002, one, one, one, one,
+                0x0304001f, 0x0010000a, 0x00000000,
+                0x0100003e,
+            ]
+        );
+
+        let builder = Builder::parse(module.as_bytes()).unwrap();
+        match builder.get_chunks() {
+            [Chunk::Isgn(isgn), Chunk::Osgn(osgn), Chunk::Shex(_)] => {
+                let uv = &isgn.elements[1];
+                assert_eq!(uv.name, "UV");
+                assert_eq!(uv.register, 1);
+                assert_eq!(uv.component_mask, 0x3);
+                assert_eq!(uv.rw_mask, 0x3);
+                assert_eq!(isgn.elements[0].rw_mask, 0);
+
+                let target = &osgn.elements[0];
+                assert!(matches!(target.semantic_type, SemanticName::Undefined));
+                assert_eq!(target.component_mask, 0xf);
+                assert_eq!(target.rw_mask, 0);
+            }
+            chunks => panic!("unexpected chunks {:?}", chunks),
+        }
+    }
+
+    #[test]
+    fn assemble_everything() {
+        let listing = "\
+cs_5_0
+dcl_globalFlags refactoringAllowed | enableRawAndStructuredBuffers
+dcl_immediateConstantBuffer { { 1.000000, 0, 0, 0},
+                              { 0, -1, 0x7f800000, 2.5e-1} }
+dcl_constantbuffer CB0[4], immediateIndexed
+dcl_resource_structured t0, 16
+dcl_resource_texture2dms(4) (float,float,float,float) t1
+dcl_uav_typed_texture2d (float,float,float,float) u0
+dcl_uav_structured_glc_opc u1, 4
+dcl_input vThreadID.xy
+dcl_temps 2
+dcl_indexableTemp x0[4], 4
+dcl_tgsm_structured g0, 4, 64
+dcl_function_body fb0
+dcl_function_body fb1
+dcl_function_table ft0 = {fb0, fb1}
+dcl_interface_dynamicindexed fp0[1][2] = {ft0}
+dcl_thread_group 8, 8, 1
+ld_structured_indexable(structured_buffer, stride=16)(mixed,mixed,mixed,mixed) r0.x, vThreadID.x, l(0), t0.xxxx
+sample_l_aoffimmi_indexable(-1,0,7)(texture2d)(float,float,float,float) r1.xyzw, r0.xyxx, t1.xyzw, s0, l(0.000000)
+mov r0.y {min16f}, -|cb0[r0.x + 1].w| {min16f}
+mov x0[r0.x].xy, icb[r0.y + 0].zwzz
+resinfo_indexable(texture2d)(float,float,float,float)_uint r0.xy, l(0), t1.xyzw
+dmov r0.xy, d(1.000000l, -2.5l)
+sincos null, r1.x, r0.x
+store_uav_typed u0.xyzw, vThreadID.xyyy, r1.xyzw
+imm_atomic_iadd r0.z, u1, vThreadID.x, l(1)
+sync_g_t
+fcall fp0[0][1]
+loop
+  breakc_z r0.x
+  call l0
+endloop
+ret
+label l0
+ret
+";
+        let module = assemble(listing).unwrap();
+
+        // the parser reads back everything the assembler wrote
+        let builder = Builder::parse(module.as_bytes()).unwrap();
+        assert_eq!(builder.module().unwrap().dwords, module.dwords);
+
+        assert!(matches!(builder.get_chunks()[2], Chunk::Shex(_)));
+    }
+
+    #[test]
+    fn complex_shader() {
+        let module = assemble(include_str!("../../dxbcd/complex_shader.asm")).unwrap();
+        let assembled = Container::parse(module.as_bytes()).unwrap();
+        let original = Container::parse(include_bytes!("../../dxbcd/complex_shader.dxbc")).unwrap();
+
+        for fourcc in [b"ISGN", b"OSGN"] {
+            assert_eq!(assembled.find_chunk(fourcc), original.find_chunk(fourcc));
+        }
+
+        // the only words that differ are floats that print the same
+        let words = |container: &Container| -> Vec<u32> {
+            container
+                .find_chunk(b"SHEX")
+                .unwrap()
+                .chunks_exact(4)
+                .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+                .collect()
+        };
+        let (assembled, original) = (words(&assembled), words(&original));
+        assert_eq!(assembled.len(), original.len());
+        for (&a, &b) in assembled.iter().zip(&original) {
+            if a != b {
+                assert_eq!(
+                    format!("{:.6}", f32::from_bits(a)),
+                    format!("{:.6}", f32::from_bits(b))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source| assemble(source).err().unwrap();
+
+        assert_eq!(
+            error("ps_5_0\nmov r0.xyzw, q0.x\n"),
+            Error {
+                line: 2,
+                column: 14,
+                message: String::from("unknown register `q0`"),
+            }
+        );
+        assert_eq!(
+            error("ps_5_0\nsample_aoffimmi(8,0,0) r0.xyzw, v0.xyxx, t0.xyzw, s0\n").to_string(),
+            "2:17: offset 8 is out of range"
+        );
+        assert_eq!(
+            error("mov r0.x, l(1)").to_string(),
+            "1:1: unknown shader model `mov`"
+        );
+        assert_eq!(
+            error("vs_4_0\ndcl_temps 1 2").to_string(),
+            "2:13: expected the end of the line, found `2`"
+        );
+        assert_eq!(
+            error("vs_4_0\nmov_sad r0.x, l(1)").to_string(),
+            "2:1: unknown suffix `_sad` for `mov`"
+        );
+        assert_eq!(
+            error(
+                "// Output signature:\n// ----\n// SV_Target 0 xyzw 0 TARGET half xyzw\n//\nps_4_0"
+            )
+            .to_string(),
+            "3:30: unknown format `half`"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use super::decoder::Decoder;
+use super::parser::State;
+use crate::dr;
+
+/// Where a chunk is in its container. `offset` points at the fourcc, the
+/// chunk data starts 8 bytes later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkInfo {
+    pub fourcc: [u8; 4],
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// The chunks of a DXBC container, without parsing any of them.
+#[derive(Debug)]
+pub struct Container<'a> {
+    bytes: &'a [u8],
+    pub header: dr::DxbcHeader,
+    pub chunks: Vec<ChunkInfo>,
+}
+
+impl<'a> Container<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, State> {
+        let mut decoder = Decoder::new(bytes);
+        let header = dr::DxbcHeader::parse(&mut decoder)?;
+        if header.magic != *b"DXBC" {
+            return Err(State::HeaderIncorrect);
+        }
+
+        let chunk_offsets = decoder.words(header.chunk_count as usize)?;
+
+        let mut chunks = Vec::new();
+        for offset in chunk_offsets {
+            decoder.seek_mut(offset as usize)?;
+
+            let mut fourcc = [0; 4];
+            fourcc.copy_from_slice(decoder.bytes(4)?);
+            let size = decoder.read_u32()?;
+
+            // make sure the data can be handed out later
+            decoder.skip(size as usize)?;
+
+            chunks.push(ChunkInfo {
+                fourcc,
+                offset,
+                size,
+            });
+        }
+
+        Ok(Self {
+            bytes,
+            header,
+            chunks,
+        })
+    }
+
+    pub fn get_chunk_data(&self, chunk: &ChunkInfo) -> &'a [u8] {
+        let start = chunk.offset as usize + 8;
+
+        &self.bytes[start..start + chunk.size as usize]
+    }
+
+    /// Returns the data of the first chunk with the given fourcc.
+    pub fn find_chunk(&self, fourcc: &[u8; 4]) -> Option<&'a [u8]> {
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.fourcc == *fourcc)
+            .map(|chunk| self.get_chunk_data(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dr::{Builder, Sfi0Chunk, ShaderFeatureFlags};
+
+    #[test]
+    fn raw_chunks() {
+        let mut builder = Builder::new();
+        builder.set_sfi0(Sfi0Chunk {
+            flags: ShaderFeatureFlags::DOUBLES,
+        });
+        builder.add_raw_chunk(*b"PRIV", b"vendor".to_vec());
+        builder.add_raw_chunk(*b"XYZW", vec![1, 2, 3, 4]);
+        let module = builder.module().unwrap();
+
+        let container = Container::parse(module.as_bytes()).unwrap();
+        let fourccs: Vec<_> = container.chunks.iter().map(|chunk| chunk.fourcc).collect();
+        assert_eq!(fourccs, [*b"SFI0", *b"PRIV", *b"XYZW"]);
+        assert_eq!(container.chunks[1].size, 6);
+
+        assert_eq!(container.find_chunk(b"PRIV"), Some(&b"vendor"[..]));
+        assert_eq!(container.find_chunk(b"XYZW"), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(container.find_chunk(b"SHEX"), None);
+    }
+}
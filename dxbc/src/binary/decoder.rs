@@ -1,6 +1,5 @@
 use super::error::Error;
 
-use std::slice;
 use std::str;
 
 use byteorder::{ByteOrder, LittleEndian};
@@ -22,110 +21,191 @@ impl<'a> Decoder<'a> {
         }
     }
 
-    pub fn seek_mut(&mut self, offset: usize) {
+    /// Restricts all further reads to bytes before `limit`.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = Some(limit);
+    }
+
+    pub fn clear_limit(&mut self) {
+        self.limit = None;
+    }
+
+    pub fn get_limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// Checks that `len` bytes starting at `offset` can be read and returns
+    /// the end of that range.
+    fn check(&self, offset: usize, len: usize) -> DecoderResult<usize> {
+        let end = offset
+            .checked_add(len)
+            .ok_or(Error::StreamExpected(offset))?;
+
+        if let Some(limit) = self.limit {
+            if end > limit {
+                return Err(Error::LimitReached(offset));
+            }
+        }
+
+        if end > self.bytes.len() {
+            return Err(Error::StreamExpected(offset));
+        }
+
+        Ok(end)
+    }
+
+    pub fn seek_mut(&mut self, offset: usize) -> DecoderResult<()> {
+        self.check(offset, 0)?;
         self.offset = offset;
+
+        Ok(())
     }
 
-    pub fn seek(&self, offset: usize) -> Self {
-        Decoder {
+    pub fn seek(&self, offset: usize) -> DecoderResult<Self> {
+        self.check(offset, 0)?;
+
+        Ok(Decoder {
             bytes: self.bytes,
             offset,
             limit: self.limit,
-        }
+        })
     }
 
     pub fn eof(&self) -> bool {
-        self.offset >= self.bytes.len()
+        self.offset >= self.limit.unwrap_or(self.bytes.len()).min(self.bytes.len())
     }
 
-    pub fn scoped_decoder(&self, len: usize) -> Self {
-        Decoder {
-            bytes: &self.bytes[self.offset..(self.offset + len)],
+    pub fn scoped_decoder(&self, len: usize) -> DecoderResult<Self> {
+        let end = self.check(self.offset, len)?;
+
+        Ok(Decoder {
+            bytes: &self.bytes[self.offset..end],
             offset: 0,
             limit: None,
-        }
+        })
     }
 
-    pub fn skip(&mut self, n: usize) {
-        self.offset += n;
+    pub fn skip(&mut self, n: usize) -> DecoderResult<()> {
+        self.offset = self.check(self.offset, n)?;
+
+        Ok(())
     }
 
     pub fn get_offset(&self) -> usize {
         self.offset
     }
 
-    pub fn bytes(&mut self, n: usize) -> &'a [u8] {
-        let slice = &self.bytes[self.offset..(self.offset + n)];
+    pub fn bytes(&mut self, n: usize) -> DecoderResult<&'a [u8]> {
+        let end = self.check(self.offset, n)?;
+        let slice = &self.bytes[self.offset..end];
 
-        self.offset += n;
+        self.offset = end;
 
-        slice
+        Ok(slice)
     }
 
-    pub fn words(&mut self, n: usize) -> &'a [u32] {
-        let byte_len = 4 * n;
-        let slice = &self.bytes[self.offset..(self.offset + byte_len)];
+    pub fn words(&mut self, n: usize) -> DecoderResult<Vec<u32>> {
+        let byte_len = n.checked_mul(4).ok_or(Error::StreamExpected(self.offset))?;
+        let slice = self.bytes(byte_len)?;
 
-        self.offset += byte_len;
-
-        unsafe { slice::from_raw_parts(slice.as_ptr() as _, n) }
-    }
-
-    pub fn read_u64(&mut self) -> u64 {
-        let val = LittleEndian::read_u64(&self.bytes[self.offset..]);
-        self.offset += 8;
-        val
+        Ok(slice.chunks_exact(4).map(LittleEndian::read_u32).collect())
     }
 
-    pub fn read_u32(&mut self) -> u32 {
-        let val = LittleEndian::read_u32(&self.bytes[self.offset..]);
-        self.offset += 4;
-        val
+    pub fn read_u64(&mut self) -> DecoderResult<u64> {
+        Ok(LittleEndian::read_u64(self.bytes(8)?))
     }
 
-    pub fn read_u32_address(&mut self) -> *const u32 {
-        let ptr = self.bytes[self.offset..].as_ptr() as _;
-        self.offset += 4;
-        ptr
+    pub fn read_u32(&mut self) -> DecoderResult<u32> {
+        Ok(LittleEndian::read_u32(self.bytes(4)?))
     }
 
-    pub fn read_u16(&mut self) -> u16 {
-        let val = LittleEndian::read_u16(&self.bytes[self.offset..]);
-        self.offset += 2;
-        val
+    pub fn read_u16(&mut self) -> DecoderResult<u16> {
+        Ok(LittleEndian::read_u16(self.bytes(2)?))
     }
 
-    pub fn read_u8(&mut self) -> u8 {
-        let val = self.bytes[self.offset];
-        self.offset += 1;
-        val
+    pub fn read_u8(&mut self) -> DecoderResult<u8> {
+        Ok(self.bytes(1)?[0])
     }
 
-    pub fn str(&mut self) -> DecoderResult<&'a str> {
-        let null = self.bytes[self.offset..]
-            .iter()
-            .position(|&b| b == 0)
-            .unwrap_or(self.bytes.len());
+    /// Returns the bytes up to (but not including) the next null terminator
+    /// and advances past the terminator.
+    fn null_terminated(&mut self) -> DecoderResult<&'a [u8]> {
+        let end = self.limit.unwrap_or(self.bytes.len()).min(self.bytes.len());
+        let remaining = self.bytes.get(self.offset..end).unwrap_or(&[]);
 
-        let string = str::from_utf8(&self.bytes[self.offset..(self.offset + null)])
-            .map_err(|e| Error::DecodeStrFailed(self.offset, e));
+        let null = match remaining.iter().position(|&b| b == 0) {
+            Some(null) => null,
+            None if self.limit.is_some_and(|limit| limit < self.bytes.len()) => {
+                return Err(Error::LimitReached(end))
+            }
+            None => return Err(Error::StreamExpected(end)),
+        };
 
+        let string = &remaining[..null];
         self.offset += null + 1;
 
-        string
+        Ok(string)
     }
 
-    pub fn string(&mut self) -> DecoderResult<String> {
-        let null = self.bytes[self.offset..]
-            .iter()
-            .position(|&b| b == 0)
-            .unwrap_or(self.bytes.len());
+    pub fn str(&mut self) -> DecoderResult<&'a str> {
+        let offset = self.offset;
+        let bytes = self.null_terminated()?;
 
-        let string = String::from_utf8(self.bytes[self.offset..(self.offset + null)].to_vec())
-            .map_err(|e| Error::DecodeStringFailed(self.offset, e));
+        str::from_utf8(bytes).map_err(|e| Error::DecodeStrFailed(offset, e))
+    }
 
-        self.offset += null + 1;
+    pub fn string(&mut self) -> DecoderResult<String> {
+        let offset = self.offset;
+        let bytes = self.null_terminated()?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|e| Error::DecodeStringFailed(offset, e))
+    }
+}
 
-        string
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncated_reads() {
+        let bytes = [1, 0, 0, 0, 2, 0];
+        let mut decoder = Decoder::new(&bytes);
+
+        assert_eq!(decoder.read_u32().unwrap(), 1);
+        assert!(matches!(decoder.read_u32(), Err(Error::StreamExpected(4))));
+        // a failed read doesn't move the decoder
+        assert_eq!(decoder.read_u16().unwrap(), 2);
+        assert!(decoder.eof());
+        assert!(matches!(decoder.read_u8(), Err(Error::StreamExpected(6))));
+        assert!(matches!(decoder.seek(7), Err(Error::StreamExpected(7))));
+        assert!(matches!(
+            decoder.seek(0).unwrap().scoped_decoder(8),
+            Err(Error::StreamExpected(0))
+        ));
+    }
+
+    #[test]
+    fn limit() {
+        let bytes = [1, 0, 0, 0, 2, 0, 0, 0, b'a', 0];
+        let mut decoder = Decoder::new(&bytes);
+        decoder.set_limit(6);
+
+        assert_eq!(decoder.read_u32().unwrap(), 1);
+        assert!(matches!(decoder.read_u32(), Err(Error::LimitReached(4))));
+        assert!(matches!(decoder.skip(4), Err(Error::LimitReached(4))));
+        assert!(matches!(decoder.seek(8), Err(Error::LimitReached(8))));
+
+        decoder.clear_limit();
+        assert_eq!(decoder.read_u32().unwrap(), 2);
+        assert_eq!(decoder.str().unwrap(), "a");
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let bytes = [b'a', b'b'];
+        let mut decoder = Decoder::new(&bytes);
+
+        assert!(matches!(decoder.str(), Err(Error::StreamExpected(2))));
+        assert_eq!(decoder.get_offset(), 0);
     }
 }
@@ -9,25 +9,44 @@ pub enum Error {
     DecodeStringFailed(usize, FromUtf8Error),
     DecodeStrFailed(usize, Utf8Error),
     DecodeEnumFailed(usize),
+    InstructionLengthMismatch(usize),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
+            Error::StreamExpected(index) => {
+                write!(f, "expected more bytes in the stream at index {}", index)
+            }
+            Error::LimitReached(index) => {
+                write!(f, "reached the decoder limit at index {}", index)
+            }
             Error::DecodeStringFailed(index, ref e) => {
                 write!(f, "cannot decode string at index {}: {}", index, e)
             }
-            _ => write!(f, "unimplemented"),
+            Error::DecodeStrFailed(index, ref e) => {
+                write!(f, "cannot decode string at index {}: {}", index, e)
+            }
+            Error::DecodeEnumFailed(index) => {
+                write!(f, "unknown enumerant value at index {}", index)
+            }
+            Error::InstructionLengthMismatch(index) => {
+                write!(
+                    f,
+                    "instruction length doesn't match its tokens at index {}",
+                    index
+                )
+            }
         }
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        // TODO: add descriptions for other errors
-        #[allow(clippy::match_single_binding)]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
-            _ => "unknown operand value for the given kind",
+            Error::DecodeStringFailed(_, ref e) => Some(e),
+            Error::DecodeStrFailed(_, ref e) => Some(e),
+            _ => None,
         }
     }
 }
@@ -1,7 +1,9 @@
+pub mod container;
 pub mod decoder;
 pub mod error;
 pub mod parser;
 
+pub use self::container::*;
 pub use self::decoder::*;
 pub use self::error::*;
 pub use self::parser::*;
@@ -1,9 +1,9 @@
+use super::container::Container;
 use super::decoder::Decoder;
 use super::error;
 use crate::dr;
 
-use std::mem;
-use std::str;
+use std::fmt;
 
 #[derive(Debug)]
 pub enum State {
@@ -18,15 +18,35 @@ pub enum State {
     DecoderError(error::Error),
 }
 
+impl From<error::Error> for State {
+    fn from(err: error::Error) -> Self {
+        State::DecoderError(err)
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            State::Complete => write!(f, "parsing completed"),
+            State::ConsumerStopRequested => write!(f, "the consumer stopped the parse"),
+            State::ConsumerError(ref e) => write!(f, "the consumer failed: {}", e),
+            State::HeaderIncorrect => write!(f, "incorrect container header"),
+            State::ChunkIncorrect => write!(f, "incorrect chunk"),
+            State::DecoderError(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
 macro_rules! read_enum {
     ($enum:ty, $decoder:ident, $ty:ty) => {
-        paste! {
-            match <$enum>::from_int($decoder.[<read_ $ty>]().into()) {
+        paste! {{
+            let offset = $decoder.get_offset();
+            match <$enum>::from_int($decoder.[<read_ $ty>]()?.into()) {
                 Ok(v) => v,
-                Err(_) => return Err(State::DecoderError(Error::DecodeEnumFailed($decoder.get_offset()))),
+                Err(_) => return Err(State::DecoderError(Error::DecodeEnumFailed(offset))),
             }
-        }
-    }
+        }}
+    };
 }
 
 pub(crate) use read_enum;
@@ -54,12 +74,67 @@ pub trait Consumer {
     fn consume_osgn(&mut self, osgn: &dr::IOsgnChunk) -> Action {
         Action::Continue
     }
+    fn consume_isg1(&mut self, isg1: &dr::IOsgnChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_osg1(&mut self, osg1: &dr::IOsgnChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_osg5(&mut self, osg5: &dr::IOsgnChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_pcsg(&mut self, pcsg: &dr::IOsgnChunk) -> Action {
+        Action::Continue
+    }
     fn consume_shex(&mut self, shex: &dr::ShexHeader) -> Action {
         Action::Continue
     }
     fn consume_stat(&mut self, stat: &dr::IStatChunk) -> Action {
         Action::Continue
     }
+    fn consume_sfi0(&mut self, sfi0: &dr::Sfi0Chunk) -> Action {
+        Action::Continue
+    }
+    fn consume_rts0(&mut self, rts0: &dr::Rts0Chunk) -> Action {
+        Action::Continue
+    }
+    fn consume_spdb(&mut self, spdb: &dr::SpdbChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_dxil(&mut self, dxil: &dr::DxilChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_ildb(&mut self, ildb: &dr::DxilChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_psv0(&mut self, psv0: &dr::Psv0Chunk) -> Action {
+        Action::Continue
+    }
+    fn consume_hash(&mut self, hash: &dr::HashChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_ildn(&mut self, ildn: &dr::IldnChunk) -> Action {
+        Action::Continue
+    }
+    fn consume_priv(&mut self, data: &[u8]) -> Action {
+        Action::Continue
+    }
+    fn consume_aon9(&mut self, aon9: &dr::Aon9Chunk) -> Action {
+        Action::Continue
+    }
+    fn consume_xnas(&mut self, xnas: &dr::Aon9Chunk) -> Action {
+        Action::Continue
+    }
+    fn consume_xnap(&mut self, xnap: &dr::Aon9Chunk) -> Action {
+        Action::Continue
+    }
+    /// Called for every chunk the parser doesn't know, with its raw data.
+    fn consume_unknown_chunk(&mut self, fourcc: &[u8; 4], data: &[u8]) -> Action {
+        Action::Continue
+    }
+    fn consume_ifce(&mut self, ifce: &dr::IfceChunk) -> Action {
+        Action::Continue
+    }
     fn consume_instruction(&mut self, offset: u32, instruction: dr::SparseInstruction) -> Action {
         Action::Continue
     }
@@ -74,55 +149,73 @@ fn try_consume(action: Action) -> Result<(), State> {
 }
 
 pub struct Parser<'c, 'd> {
-    decoder: Decoder<'d>,
+    binary: &'d [u8],
     consumer: &'c mut dyn Consumer,
 }
 
 impl<'c, 'd> Parser<'c, 'd> {
     pub fn new(binary: &'d [u8], consumer: &'c mut dyn Consumer) -> Self {
-        Parser {
-            decoder: Decoder::new(binary),
-            consumer,
-        }
+        Parser { binary, consumer }
     }
 
     pub fn parse(&mut self) -> Result<(), State> {
         try_consume(self.consumer.initialize())?;
 
-        let header = self.parse_header()?;
-        try_consume(self.consumer.consume_header(header))?;
-
-        let chunk_offsets = self.decoder.words(header.chunk_count as usize);
-
-        for &chunk_offset in chunk_offsets {
-            self.decoder.seek_mut(chunk_offset as usize);
-            let fourcc = self.decoder.bytes(4);
-            let chunk_length = self.decoder.read_u32();
+        let container = Container::parse(self.binary)?;
+        try_consume(self.consumer.consume_header(&container.header))?;
 
-            let mut decoder = self.decoder.scoped_decoder(chunk_length as usize);
+        for chunk in &container.chunks {
+            let data = container.get_chunk_data(chunk);
+            let mut decoder = Decoder::new(data);
 
-            match fourcc {
+            match &chunk.fourcc {
                 b"RDEF" => {
                     let rdef = dr::RdefChunk::parse(&mut decoder)?;
                     try_consume(self.consumer.consume_rdef(&rdef))?;
                 }
                 b"ISGN" => {
-                    let isgn = dr::IOsgnChunk::parse(&mut decoder)?;
+                    let isgn = dr::IOsgnChunk::parse(&mut decoder, dr::SignatureLayout::Basic)?;
                     try_consume(self.consumer.consume_isgn(&isgn))?;
                 }
                 b"OSGN" => {
-                    let osgn = dr::IOsgnChunk::parse(&mut decoder)?;
+                    let osgn = dr::IOsgnChunk::parse(&mut decoder, dr::SignatureLayout::Basic)?;
                     try_consume(self.consumer.consume_osgn(&osgn))?;
                 }
+                b"ISG1" => {
+                    let isg1 = dr::IOsgnChunk::parse(
+                        &mut decoder,
+                        dr::SignatureLayout::StreamMinPrecision,
+                    )?;
+                    try_consume(self.consumer.consume_isg1(&isg1))?;
+                }
+                b"OSG1" => {
+                    let osg1 = dr::IOsgnChunk::parse(
+                        &mut decoder,
+                        dr::SignatureLayout::StreamMinPrecision,
+                    )?;
+                    try_consume(self.consumer.consume_osg1(&osg1))?;
+                }
+                b"OSG5" => {
+                    let osg5 = dr::IOsgnChunk::parse(&mut decoder, dr::SignatureLayout::Stream)?;
+                    try_consume(self.consumer.consume_osg5(&osg5))?;
+                }
+                b"PCSG" => {
+                    let pcsg = dr::IOsgnChunk::parse(&mut decoder, dr::SignatureLayout::Basic)?;
+                    try_consume(self.consumer.consume_pcsg(&pcsg))?;
+                }
                 b"SHEX" | b"SHDR" => {
                     let shex = dr::ShexHeader::parse(&mut decoder)?;
                     try_consume(self.consumer.consume_shex(&shex))?;
 
-                    let mut decoder = decoder.scoped_decoder(shex.instruction_length as usize * 4);
+                    let mut decoder =
+                        decoder.scoped_decoder(shex.instruction_length as usize * 4)?;
+
+                    let mut phase = dr::Phase::Global;
 
                     while !decoder.eof() {
                         let offset = decoder.get_offset();
-                        let instruction = dr::SparseInstruction::parse(&mut decoder);
+                        let instruction = dr::SparseInstruction::parse(&mut decoder, phase)?;
+                        phase = instruction.phase;
 
                         try_consume(
                             self.consumer
@@ -134,12 +227,59 @@ impl<'c, 'd> Parser<'c, 'd> {
                     let stat = dr::IStatChunk::parse(&mut decoder)?;
                     try_consume(self.consumer.consume_stat(&stat))?;
                 }
+                b"SFI0" => {
+                    let sfi0 = dr::Sfi0Chunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_sfi0(&sfi0))?;
+                }
+                b"RTS0" => {
+                    let rts0 = dr::Rts0Chunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_rts0(&rts0))?;
+                }
+                b"SPDB" => {
+                    let spdb = dr::SpdbChunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_spdb(&spdb))?;
+                }
+                b"IFCE" => {
+                    let ifce = dr::IfceChunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_ifce(&ifce))?;
+                }
+                b"DXIL" => {
+                    let dxil = dr::DxilChunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_dxil(&dxil))?;
+                }
+                b"ILDB" => {
+                    let ildb = dr::DxilChunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_ildb(&ildb))?;
+                }
+                b"PSV0" => {
+                    let psv0 = dr::Psv0Chunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_psv0(&psv0))?;
+                }
+                b"HASH" => {
+                    let hash = dr::HashChunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_hash(&hash))?;
+                }
+                b"ILDN" => {
+                    let ildn = dr::IldnChunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_ildn(&ildn))?;
+                }
+                b"Aon9" => {
+                    let aon9 = dr::Aon9Chunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_aon9(&aon9))?;
+                }
+                b"XNAS" => {
+                    let xnas = dr::Aon9Chunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_xnas(&xnas))?;
+                }
+                b"XNAP" => {
+                    let xnap = dr::Aon9Chunk::parse(&mut decoder)?;
+                    try_consume(self.consumer.consume_xnap(&xnap))?;
+                }
+                b"PRIV" => {
+                    try_consume(self.consumer.consume_priv(data))?;
+                }
                 _ => {
-                    eprintln!(
-                        "{}: Incorrect or unimplemented chunk type '{}'",
-                        chunk_offset,
-                        unsafe { str::from_utf8_unchecked(fourcc) },
-                    );
+                    try_consume(self.consumer.consume_unknown_chunk(&chunk.fourcc, data))?;
                 }
             }
         }
@@ -148,18 +288,155 @@ impl<'c, 'd> Parser<'c, 'd> {
 
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct InstructionCollector {
+        instructions: Vec<String>,
+        owned: Vec<dr::shex::Instruction>,
+    }
 
-    fn parse_header(&mut self) -> Result<&'d dr::DxbcHeader, State> {
-        let bytes = self.decoder.bytes(mem::size_of::<dr::DxbcHeader>());
+    impl Consumer for InstructionCollector {
+        fn initialize(&mut self) -> Action {
+            Action::Continue
+        }
 
-        // mcarton says raw pointer casts have additional sanity checking
-        // compared to transmute: https://github.com/rust-lang/rust-clippy/issues/864#issuecomment-211926815
-        let header: &'d dr::DxbcHeader = unsafe { &*(bytes.as_ptr() as *const dr::DxbcHeader) };
+        fn finalize(&mut self) -> Action {
+            Action::Continue
+        }
 
-        if header.magic == *b"DXBC" {
-            Ok(header)
-        } else {
-            Err(State::HeaderIncorrect)
+        fn consume_instruction(
+            &mut self,
+            offset: u32,
+            instruction: dr::SparseInstruction,
+        ) -> Action {
+            self.instructions
+                .push(format!("{}: {:?}", offset, instruction));
+
+            self.owned.push(instruction.to_instruction().unwrap());
+
+            Action::Continue
+        }
+    }
+
+    fn parse_instructions(bytes: &[u8]) -> Vec<String> {
+        let mut collector = InstructionCollector::default();
+        Parser::new(bytes, &mut collector).parse().unwrap();
+
+        collector.instructions
+    }
+
+    #[test]
+    fn unaligned_buffer() {
+        let shader = include_bytes!("../../../dxbcd/shader.dxbc");
+
+        // embed the shader at an odd offset, like it would be inside a larger file
+        let mut buffer = vec![0u8; shader.len() + 1];
+        buffer[1..].copy_from_slice(shader);
+
+        let aligned = parse_instructions(shader);
+        assert!(!aligned.is_empty());
+        assert_eq!(parse_instructions(&buffer[1..]), aligned);
+    }
+
+    #[test]
+    fn owned_instructions() {
+        use crate::dr::shex::{
+            ComponentMask, ComponentName, ConstantBufferIndexPattern, Declaration, Instruction,
+            InterpolationMode, Opcode, OperandComponents, OperandIndex, OperandType,
+            ResourceDimension, ResourceReturnType,
+        };
+        use crate::dr::GlobalFlags;
+
+        let mut collector = InstructionCollector::default();
+        Parser::new(include_bytes!("../../../dxbcd/shader.dxbc"), &mut collector)
+            .parse()
+            .unwrap();
+
+        let owned = collector.owned;
+        assert_eq!(owned.len(), collector.instructions.len());
+        assert_eq!(owned.last().unwrap().opcode(), Opcode::Ret);
+
+        assert_eq!(
+            owned[0],
+            Instruction::Declaration(Declaration::GlobalFlags {
+                flags: GlobalFlags::REFACTORING_ALLOWED,
+            })
+        );
+        match owned[1] {
+            Instruction::Declaration(Declaration::ConstantBuffer {
+                ref register,
+                access,
+                size: None,
+                space: None,
+            }) => {
+                assert_eq!(register.ty, OperandType::ConstantBuffer);
+                assert_eq!(
+                    register.indices,
+                    [OperandIndex::Immediate32(0), OperandIndex::Immediate32(9)]
+                );
+                assert_eq!(access, ConstantBufferIndexPattern::Dynamic);
+            }
+            ref other => panic!("expected dcl_constantbuffer, got {:?}", other),
+        }
+        match owned[3] {
+            Instruction::Declaration(Declaration::Resource {
+                ref register,
+                dimension,
+                return_type,
+                sample_count,
+                space: None,
+            }) => {
+                assert_eq!(register.indices, [OperandIndex::Immediate32(0)]);
+                assert_eq!(dimension, ResourceDimension::Texture2D);
+                assert_eq!(return_type, [ResourceReturnType::Float; 4]);
+                assert_eq!(sample_count, 0);
+            }
+            ref other => panic!("expected dcl_resource, got {:?}", other),
+        }
+        match owned[4] {
+            Instruction::Declaration(Declaration::InputPs {
+                ref register,
+                interpolation,
+            }) => {
+                assert_eq!(register.ty, OperandType::Input);
+                assert_eq!(
+                    register.components,
+                    OperandComponents::Mask(
+                        ComponentMask::COMPONENT_MASK_R | ComponentMask::COMPONENT_MASK_G
+                    )
+                );
+                assert_eq!(interpolation, InterpolationMode::Linear);
+            }
+            ref other => panic!("expected dcl_input_ps, got {:?}", other),
+        }
+        assert_eq!(
+            owned[6],
+            Instruction::Declaration(Declaration::Temps { count: 1 })
+        );
+
+        // mul r0.x, r0.x, v1.y
+        match owned[8] {
+            Instruction::Operation {
+                opcode: Opcode::Mul,
+                controls: 0,
+                ref extended,
+                ref operands,
+            } => {
+                assert!(extended.is_empty());
+                assert_eq!(operands.len(), 3);
+                assert_eq!(operands[2].ty, OperandType::Input);
+                assert_eq!(operands[2].indices, [OperandIndex::Immediate32(1)]);
+                assert_eq!(
+                    operands[2].components,
+                    OperandComponents::Select1(ComponentName::Y)
+                );
+            }
+            ref other => panic!("expected mul, got {:?}", other),
         }
     }
 }
@@ -0,0 +1,1481 @@
+//! A disassembler that prints containers the way `fxc /dumpbin` does, the
+//! counterpart of `asm`.
+//!
+//! The listing has the same sections as fxc's: the `RDEF` buffer definitions
+//! and resource bindings, the signature tables, the features `SFI0` asks for,
+//! the program and the instruction count of `STAT`.
+
+use crate::asm::{MNEMONICS, REGISTERS, SEMANTICS};
+use crate::binary::*;
+use crate::dr::shex::{
+    self, ComponentMask, ComponentName, ComponentSwizzle, ConstantBufferIndexPattern,
+    CustomDataClass, InterpolationMode, MinPrecision, Opcode, OpcodeExtension, OperandComponents,
+    OperandIndex, OperandModifier, Primitive, PrimitiveTopology, ResourceDimension,
+    ResourceReturnType, SamplerMode, ShaderMessage, ShexHeader, TessellatorDomain,
+    TessellatorOutputPrimitive, TessellatorPartitioning,
+};
+use crate::dr::{
+    ConstantBuffer, ConstantBufferType, GlobalFlags, IOsgnChunk, IStatChunk, InputOutputElement,
+    RdefChunk, RegisterComponentType, ResourceBinding, Semantic, SemanticName, Sfi0Chunk,
+    ShaderFeatureFlags, ShaderInputFlags, ShaderInputType, ShaderType, ShaderVariableClass,
+    ShaderVariableFlags, ShaderVariableType, SignatureLayout, ViewDimension,
+};
+
+use std::fmt::{self, Write};
+use winapi::um::d3d11tokenizedprogramformat::*;
+
+/// The names fxc prints for the features of `SFI0`.
+pub const FEATURES: &[(ShaderFeatureFlags, &str)] = &[
+    (
+        ShaderFeatureFlags::DOUBLES,
+        "Double-precision floating point",
+    ),
+    (
+        ShaderFeatureFlags::COMPUTE_SHADERS_PLUS_RAW_AND_STRUCTURED_BUFFERS_VIA_SHADER_4_X,
+        "Raw and Structured buffers",
+    ),
+    (
+        ShaderFeatureFlags::UAVS_AT_EVERY_STAGE,
+        "UAVs at every shader stage",
+    ),
+    (ShaderFeatureFlags::UAVS_64, "64 UAV slots"),
+    (
+        ShaderFeatureFlags::MINIMUM_PRECISION,
+        "Minimum-precision data types",
+    ),
+    (
+        ShaderFeatureFlags::DOUBLE_EXTENSIONS_11_1,
+        "Double-precision extensions for 11.1",
+    ),
+    (
+        ShaderFeatureFlags::SHADER_EXTENSIONS_11_1,
+        "Shader extensions for 11.1",
+    ),
+    (
+        ShaderFeatureFlags::LEVEL_9_COMPARISON_FILTERING,
+        "Comparison filtering for feature level 9",
+    ),
+    (ShaderFeatureFlags::TILED_RESOURCES, "Tiled resources"),
+    (ShaderFeatureFlags::STENCIL_REF, "PS Output Stencil Ref"),
+    (ShaderFeatureFlags::INNER_COVERAGE, "PS Inner Coverage"),
+    (
+        ShaderFeatureFlags::TYPED_UAV_LOAD_ADDITIONAL_FORMATS,
+        "Typed UAV Load Additional Formats",
+    ),
+    (ShaderFeatureFlags::ROVS, "Raster Ordered UAVs"),
+    (
+        ShaderFeatureFlags::VIEWPORT_AND_RT_ARRAY_INDEX_FROM_ANY_SHADER_FEEDING_RASTERIZER,
+        "SV_RenderTargetArrayIndex or SV_ViewportArrayIndex from any shader feeding rasterizer",
+    ),
+];
+
+/// Disassembles a container into the listing `fxc /dumpbin` prints for it.
+pub fn disassemble(bytes: &[u8]) -> Result<String, State> {
+    let listing = Listing::parse(bytes)?;
+
+    let mut out = String::new();
+    listing
+        .write(&mut out)
+        .expect("formatting into a string doesn't fail");
+
+    Ok(out)
+}
+
+/// The chunks of a container that end up in its listing.
+struct Listing<'a> {
+    rdef: Option<RdefChunk<'a>>,
+    inputs: Option<IOsgnChunk>,
+    outputs: Option<IOsgnChunk>,
+    patch_constants: Option<IOsgnChunk>,
+    sfi0: Option<Sfi0Chunk>,
+    stat: Option<IStatChunk>,
+    program: Option<(ShexHeader, Vec<shex::Instruction>)>,
+}
+
+impl<'a> Listing<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Self, State> {
+        let container = Container::parse(bytes)?;
+
+        let mut listing = Listing {
+            rdef: None,
+            inputs: None,
+            outputs: None,
+            patch_constants: None,
+            sfi0: None,
+            stat: None,
+            program: None,
+        };
+
+        for chunk in &container.chunks {
+            let mut decoder = Decoder::new(container.get_chunk_data(chunk));
+
+            let layout = match &chunk.fourcc {
+                b"ISG1" | b"OSG1" => SignatureLayout::StreamMinPrecision,
+                b"OSG5" => SignatureLayout::Stream,
+                _ => SignatureLayout::Basic,
+            };
+
+            match &chunk.fourcc {
+                b"RDEF" => listing.rdef = Some(RdefChunk::parse(&mut decoder)?),
+                b"ISGN" | b"ISG1" => {
+                    listing.inputs = Some(IOsgnChunk::parse(&mut decoder, layout)?)
+                }
+                b"OSGN" | b"OSG1" | b"OSG5" => {
+                    listing.outputs = Some(IOsgnChunk::parse(&mut decoder, layout)?)
+                }
+                b"PCSG" => listing.patch_constants = Some(IOsgnChunk::parse(&mut decoder, layout)?),
+                b"SFI0" => listing.sfi0 = Some(Sfi0Chunk::parse(&mut decoder)?),
+                b"STAT" => listing.stat = Some(IStatChunk::parse(&mut decoder)?),
+                b"SHEX" | b"SHDR" => {
+                    let header = ShexHeader::parse(&mut decoder)?;
+                    let mut decoder =
+                        decoder.scoped_decoder(header.instruction_length as usize * 4)?;
+
+                    let mut instructions = Vec::new();
+                    while !decoder.eof() {
+                        instructions.push(shex::Instruction::parse(&mut decoder)?);
+                    }
+
+                    listing.program = Some((header, instructions));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(listing)
+    }
+
+    fn write(&self, out: &mut String) -> fmt::Result {
+        if let Some(rdef) = &self.rdef {
+            writeln!(out, "//")?;
+            writeln!(out, "// Generated by {}", rdef.author)?;
+            writeln!(out, "//")?;
+
+            write_constant_buffers(out, &rdef.constant_buffers)?;
+            write_resource_bindings(out, &rdef.resource_bindings)?;
+        }
+
+        for (title, signature) in [
+            ("Input", &self.inputs),
+            ("Output", &self.outputs),
+            ("Patch Constant", &self.patch_constants),
+        ] {
+            if let Some(signature) = signature {
+                write_signature(out, title, &signature.elements)?;
+            }
+        }
+
+        if let Some(sfi0) = self.sfi0.as_ref().filter(|sfi0| !sfi0.flags.is_empty()) {
+            writeln!(out, "// Note: shader requires additional functionality:")?;
+            for (flag, name) in FEATURES {
+                if sfi0.flags.contains(*flag) {
+                    writeln!(out, "//       {}", name)?;
+                }
+            }
+            writeln!(out, "//")?;
+            writeln!(out, "//")?;
+        }
+
+        if let Some((header, instructions)) = &self.program {
+            write_program(out, header, instructions)?;
+        }
+
+        if let Some(stat) = &self.stat {
+            writeln!(
+                out,
+                "// Approximately {} instruction slots used",
+                stat.instruction_count
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_constant_buffers(out: &mut String, buffers: &[ConstantBuffer]) -> fmt::Result {
+    if buffers.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "//")?;
+    writeln!(out, "// Buffer Definitions: ")?;
+    writeln!(out, "//")?;
+
+    for buffer in buffers {
+        match buffer.ty {
+            ConstantBufferType::ConstantBuffer => writeln!(out, "// cbuffer {}", buffer.name)?,
+            ConstantBufferType::TextureBuffer => writeln!(out, "// tbuffer {}", buffer.name)?,
+            ConstantBufferType::ResourceBindInformation => {
+                writeln!(out, "// Resource bind info for {}", buffer.name)?
+            }
+            ConstantBufferType::InterfacePointers => continue,
+        }
+        writeln!(out, "// {{")?;
+        writeln!(out, "//")?;
+
+        for variable in &buffer.variables {
+            let mut location = format!(
+                "// Offset: {:>4} Size: {:>5}",
+                variable.offset, variable.size
+            );
+            if !variable.flags.contains(ShaderVariableFlags::USED) {
+                location.push_str(" [unused]");
+            }
+
+            write_variable(
+                out,
+                3,
+                variable.name,
+                &variable.ty,
+                variable.offset,
+                &location,
+            )?;
+
+            if let Some(value) = variable.default_value {
+                write_default_value(out, value)?;
+            }
+        }
+
+        writeln!(out, "//")?;
+        writeln!(out, "// }}")?;
+        writeln!(out, "//")?;
+    }
+
+    Ok(())
+}
+
+/// Writes the default value of a variable as the little endian dwords it is
+/// made of, four to a line like fxc does.
+fn write_default_value(out: &mut String, value: &[u8]) -> fmt::Result {
+    for (i, dwords) in value.chunks(16).enumerate() {
+        out.push_str(if i == 0 { "//      = " } else { "//        " });
+        for dword in dwords.chunks(4) {
+            let mut bytes = [0u8; 4];
+            bytes[..dword.len()].copy_from_slice(dword);
+            write!(out, "0x{:08x} ", u32::from_le_bytes(bytes))?;
+        }
+        out.push('\n');
+    }
+
+    Ok(())
+}
+
+/// Writes a variable or struct member `indent` columns after the `//`, with
+/// its `location` lined up with the other variables. Struct members are
+/// written with their offset from the start of the buffer.
+fn write_variable(
+    out: &mut String,
+    indent: usize,
+    name: &str,
+    ty: &ShaderType,
+    offset: u32,
+    location: &str,
+) -> fmt::Result {
+    let width = 37 - indent.min(37);
+    let array = match ty.count {
+        0 => String::new(),
+        count => format!("[{}]", count),
+    };
+
+    if ty.class != ShaderVariableClass::Struct {
+        let declaration = format!("{} {}{};", type_name(ty), name, array);
+        return writeln!(
+            out,
+            "//{:indent$}{:<width$} {}",
+            "",
+            declaration,
+            location,
+            indent = indent,
+            width = width
+        );
+    }
+
+    match ty.name {
+        Some(struct_name) => writeln!(
+            out,
+            "//{:indent$}struct {}",
+            "",
+            struct_name,
+            indent = indent
+        )?,
+        None => writeln!(out, "//{:indent$}struct", "", indent = indent)?,
+    }
+    writeln!(out, "//{:indent$}{{", "", indent = indent)?;
+    writeln!(out, "//{:indent$}", "", indent = indent + 4)?;
+
+    for member in &ty.members {
+        let member_offset = offset + member.offset;
+        write_variable(
+            out,
+            indent + 4,
+            member.name,
+            &member.ty,
+            member_offset,
+            &format!("// Offset: {:>4}", member_offset),
+        )?;
+    }
+
+    writeln!(out, "//")?;
+    writeln!(
+        out,
+        "//{:indent$}{:<width$} {}",
+        "",
+        format!("}} {}{};", name, array),
+        location,
+        indent = indent,
+        width = width
+    )
+}
+
+/// The HLSL name of a type, like `float4` or `row_major float4x4`.
+fn type_name(ty: &ShaderType) -> String {
+    let name = match ty.name {
+        Some(name) => String::from(name),
+        None => {
+            let base = match ty.ty {
+                ShaderVariableType::Bool => "bool",
+                ShaderVariableType::Int_ => "int",
+                ShaderVariableType::Float => "float",
+                ShaderVariableType::UInt => "uint",
+                ShaderVariableType::UInt8 => "uint8",
+                ShaderVariableType::Double => "double",
+                _ => "void",
+            };
+
+            match ty.class {
+                ShaderVariableClass::Vector => format!("{}{}", base, ty.columns),
+                ShaderVariableClass::MatrixRows | ShaderVariableClass::MatrixColumns => {
+                    format!("{}{}x{}", base, ty.rows, ty.columns)
+                }
+                _ => String::from(base),
+            }
+        }
+    };
+
+    match ty.class {
+        ShaderVariableClass::MatrixRows => format!("row_major {}", name),
+        _ => name,
+    }
+}
+
+fn write_resource_bindings(out: &mut String, bindings: &[ResourceBinding]) -> fmt::Result {
+    if bindings.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "//")?;
+    writeln!(out, "// Resource Bindings:")?;
+    writeln!(out, "//")?;
+    writeln!(
+        out,
+        "// Name                                 Type  Format         Dim      HLSL Bind  Count"
+    )?;
+    writeln!(
+        out,
+        "// ------------------------------ ---------- ------- ----------- -------------- ------"
+    )?;
+
+    for binding in bindings {
+        let (ty, prefix) = match binding.input_type {
+            ShaderInputType::CBuffer => ("cbuffer", "cb"),
+            ShaderInputType::TBuffer => ("tbuffer", "t"),
+            ShaderInputType::Texture
+            | ShaderInputType::Structured
+            | ShaderInputType::ByteAddress => ("texture", "t"),
+            ShaderInputType::Sampler
+                if binding
+                    .input_flags
+                    .contains(ShaderInputFlags::COMPARISON_SAMPLER) =>
+            {
+                ("sampler_c", "s")
+            }
+            ShaderInputType::Sampler => ("sampler", "s"),
+            _ => ("UAV", "u"),
+        };
+
+        let format = match binding.input_type {
+            ShaderInputType::CBuffer | ShaderInputType::TBuffer | ShaderInputType::Sampler => {
+                String::from("NA")
+            }
+            ShaderInputType::Structured
+            | ShaderInputType::UavRwStructured
+            | ShaderInputType::UavAppendStructured
+            | ShaderInputType::UavConsumeStructured
+            | ShaderInputType::UavRwStructuredWithCounter => String::from("struct"),
+            ShaderInputType::ByteAddress | ShaderInputType::UavRwByteAddress => {
+                String::from("byte")
+            }
+            ShaderInputType::Texture | ShaderInputType::UavRwTyped => {
+                let components =
+                    (binding.input_flags & ShaderInputFlags::TEXTURE_COMPONENTS).bits() >> 2;
+                let name = match binding.return_type {
+                    crate::dr::ResourceReturnType::NotApplicable => "NA",
+                    crate::dr::ResourceReturnType::UNorm => "unorm",
+                    crate::dr::ResourceReturnType::SNorm => "snorm",
+                    crate::dr::ResourceReturnType::SInt => "sint",
+                    crate::dr::ResourceReturnType::UInt => "uint",
+                    crate::dr::ResourceReturnType::Float => "float",
+                    crate::dr::ResourceReturnType::Mixed => "mixed",
+                    crate::dr::ResourceReturnType::Double => "double",
+                    crate::dr::ResourceReturnType::Continued => "continued",
+                };
+
+                match components {
+                    0 => String::from(name),
+                    components => format!("{}{}", name, components + 1),
+                }
+            }
+        };
+
+        let dimension = match binding.input_type {
+            ShaderInputType::CBuffer | ShaderInputType::TBuffer | ShaderInputType::Sampler => "NA",
+            ShaderInputType::Structured | ShaderInputType::ByteAddress => "r/o",
+            ShaderInputType::UavRwStructured | ShaderInputType::UavRwByteAddress => "r/w",
+            ShaderInputType::UavAppendStructured
+            | ShaderInputType::UavConsumeStructured
+            | ShaderInputType::UavRwStructuredWithCounter => "r/w+cnt",
+            ShaderInputType::Texture | ShaderInputType::UavRwTyped => {
+                match binding.view_dimension {
+                    ViewDimension::Unknown => "NA",
+                    ViewDimension::Buffer | ViewDimension::ExtendedBuffer => "buf",
+                    ViewDimension::Texture1D => "1d",
+                    ViewDimension::Texture1DArray => "1darray",
+                    ViewDimension::Texture2D => "2d",
+                    ViewDimension::Texture2DArray => "2darray",
+                    ViewDimension::Texture2DMultiSampled => "2dMS",
+                    ViewDimension::Texture2DMultiSampledArray => "2dMSarray",
+                    ViewDimension::Texture3D => "3d",
+                    ViewDimension::TextureCube => "cube",
+                    ViewDimension::TextureCubeArray => "cubearray",
+                }
+            }
+        };
+
+        let bind = match binding.space {
+            Some(space) if space != 0 => format!("{}{},space{}", prefix, binding.bind_point, space),
+            _ => format!("{}{}", prefix, binding.bind_point),
+        };
+
+        writeln!(
+            out,
+            "// {:<30} {:>10} {:>7} {:>11} {:>14} {:>6} ",
+            binding.name, ty, format, dimension, bind, binding.bind_count
+        )?;
+    }
+
+    writeln!(out, "//")?;
+    writeln!(out, "//")
+}
+
+fn write_signature(out: &mut String, title: &str, elements: &[InputOutputElement]) -> fmt::Result {
+    writeln!(out, "//")?;
+    writeln!(out, "// {} signature:", title)?;
+    writeln!(out, "//")?;
+    writeln!(
+        out,
+        "// Name                 Index   Mask Register SysValue  Format   Used"
+    )?;
+    writeln!(
+        out,
+        "// -------------------- ----- ------ -------- -------- ------- ------"
+    )?;
+
+    if elements.is_empty() {
+        writeln!(out, "// no {}", title)?;
+    }
+
+    for element in elements {
+        // outputs store the components that are never written instead
+        let used = match title {
+            "Input" => element.rw_mask,
+            _ => !element.rw_mask & element.component_mask,
+        };
+
+        // scalar system values like `SV_Depth` don't have a register
+        let (mask, register, used) = if element.register == 0xffff_ffff {
+            let register = match system_value(element) {
+                SemanticName::Depth => "oDepth",
+                SemanticName::DepthGreaterEqual => "oDepthGE",
+                SemanticName::DepthLessEqual => "oDepthLE",
+                SemanticName::Coverage if title == "Input" => "vCoverage",
+                SemanticName::Coverage => "oMask",
+                SemanticName::PrimitiveId => "primID",
+                _ => "???",
+            };
+            let used = if used != 0 { "YES" } else { "NO" };
+
+            (
+                String::from("N/A"),
+                String::from(register),
+                String::from(used),
+            )
+        } else {
+            (
+                signature_mask(element.component_mask),
+                element.register.to_string(),
+                signature_mask(used),
+            )
+        };
+
+        writeln!(
+            out,
+            "// {:<20} {:>5} {:>6} {:>8} {:>8} {:>7} {:>6}",
+            element.name,
+            element.semantic_index,
+            mask,
+            register,
+            system_value_name(system_value(element)),
+            format_name(element.component_type, element.min_precision),
+            used
+        )?;
+    }
+
+    writeln!(out, "//")
+}
+
+/// The system value of a signature element. fxc stores the pixel shader
+/// outputs, like `SV_Target`, as `NONE` and prints them by their name.
+fn system_value(element: &InputOutputElement) -> SemanticName {
+    if !matches!(element.semantic_type, SemanticName::Undefined) {
+        return element.semantic_type;
+    }
+
+    match element.name.to_ascii_uppercase().as_str() {
+        "SV_TARGET" => SemanticName::Target,
+        "SV_DEPTH" => SemanticName::Depth,
+        "SV_DEPTHGREATEREQUAL" => SemanticName::DepthGreaterEqual,
+        "SV_DEPTHLESSEQUAL" => SemanticName::DepthLessEqual,
+        "SV_COVERAGE" => SemanticName::Coverage,
+        _ => SemanticName::Undefined,
+    }
+}
+
+/// The components of a signature mask, each in its own column, like `x zw`.
+fn signature_mask(mask: u8) -> String {
+    "xyzw"
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if mask & (1 << i) != 0 { c } else { ' ' })
+        .collect()
+}
+
+fn system_value_name(name: SemanticName) -> &'static str {
+    match name {
+        SemanticName::Undefined => "NONE",
+        SemanticName::Position => "POS",
+        SemanticName::ClipDistance => "CLIPDST",
+        SemanticName::CullDistance => "CULLDST",
+        SemanticName::RenderTargetArrayIndex => "RTINDEX",
+        SemanticName::ViewportArrayIndex => "VPINDEX",
+        SemanticName::VertexId => "VERTID",
+        SemanticName::PrimitiveId => "PRIMID",
+        SemanticName::InstanceId => "INSTID",
+        SemanticName::IsFrontFace => "FFACE",
+        SemanticName::SampleIndex => "SAMPLE",
+        SemanticName::FinalQuadEdgeTessfactor => "QUADEDGE",
+        SemanticName::FinalQuadInsideTessfactor => "QUADINT",
+        SemanticName::FinalTriEdgeTessfactor => "TRIEDGE",
+        SemanticName::FinalTriInsideTessfactor => "TRIINT",
+        SemanticName::FinalLineDetailTessfactor => "LINEDET",
+        SemanticName::FinalLineDensityTessfactor => "LINEDEN",
+        SemanticName::Target => "TARGET",
+        SemanticName::Depth => "DEPTH",
+        SemanticName::Coverage => "COVERAGE",
+        SemanticName::DepthGreaterEqual => "DEPTHGE",
+        SemanticName::DepthLessEqual => "DEPTHLE",
+    }
+}
+
+fn format_name(ty: RegisterComponentType, min_precision: MinPrecision) -> &'static str {
+    match (ty, min_precision) {
+        (_, MinPrecision::Float16) => "min16f",
+        (_, MinPrecision::Float2_8) => "min2_8f",
+        (_, MinPrecision::SInt16) => "min16i",
+        (_, MinPrecision::UInt16) => "min16u",
+        (RegisterComponentType::Unknown, _) => "unknown",
+        (RegisterComponentType::Uint32, _) => "uint",
+        (RegisterComponentType::Int32, _) => "int",
+        (RegisterComponentType::Float32, _) => "float",
+    }
+}
+
+fn write_program(
+    out: &mut String,
+    header: &ShexHeader,
+    instructions: &[shex::Instruction],
+) -> fmt::Result {
+    let program = match header.program_type as u32 {
+        D3D10_SB_PIXEL_SHADER => "ps",
+        D3D10_SB_VERTEX_SHADER => "vs",
+        D3D10_SB_GEOMETRY_SHADER => "gs",
+        D3D11_SB_HULL_SHADER => "hs",
+        D3D11_SB_DOMAIN_SHADER => "ds",
+        _ => "cs",
+    };
+    writeln!(out, "{}_{}_{}", program, header.major, header.minor)?;
+
+    let mut indent = 0;
+    for instruction in instructions {
+        let text = match instruction {
+            shex::Instruction::Declaration(declaration_) => declaration(declaration_),
+            shex::Instruction::Operation {
+                opcode,
+                controls,
+                extended,
+                operands,
+            } => operation(*opcode, *controls, extended, operands),
+            shex::Instruction::InterfaceCall {
+                function_index,
+                interface,
+                ..
+            } => format!(
+                "fcall {}[{}]",
+                operand(interface, Number::UInt, false),
+                function_index
+            ),
+            shex::Instruction::CustomData { class, data }
+                if *class == CustomDataClass::ShaderMessage as u32 =>
+            {
+                let bytes: Vec<u8> = data.iter().flat_map(|word| word.to_le_bytes()).collect();
+                match ShaderMessage::parse(&mut Decoder::new(&bytes)) {
+                    Ok(message) => shader_message(&message),
+                    Err(_) => custom_data_comment(*class, data),
+                }
+            }
+            shex::Instruction::CustomData { class, data }
+                if *class != CustomDataClass::ImmediateConstantBuffer as u32 =>
+            {
+                custom_data_comment(*class, data)
+            }
+            shex::Instruction::CustomData { data, .. } => {
+                let rows: Vec<String> = data
+                    .chunks(4)
+                    .map(|row| {
+                        let values: Vec<String> = row
+                            .iter()
+                            .map(|&value| number(value, Number::Any))
+                            .collect();
+                        format!("{{ {}}}", values.join(", "))
+                    })
+                    .collect();
+                format!(
+                    "dcl_immediateConstantBuffer {{ {} }}",
+                    rows.join(&format!(",\n{:30}", ""))
+                )
+            }
+        };
+
+        let opcode = instruction.opcode();
+        if matches!(
+            opcode,
+            Opcode::Else | Opcode::EndIf | Opcode::EndLoop | Opcode::EndSwitch
+        ) {
+            indent = usize::saturating_sub(indent, 1);
+        }
+
+        writeln!(out, "{:indent$}{}", "", text, indent = 2 * indent)?;
+
+        if matches!(
+            opcode,
+            Opcode::If | Opcode::Else | Opcode::Loop | Opcode::Switch
+        ) {
+            indent += 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints a message like `printf "%0 is fine!", r0.x`.
+fn shader_message(message: &ShaderMessage) -> String {
+    let mnemonic = match (message.id, message.format) {
+        (D3D11_SB_SHADER_MESSAGE_ID_ERROR, D3D11_SB_SHADER_MESSAGE_FORMAT_ANSI_PRINTF) => "errorf",
+        (D3D11_SB_SHADER_MESSAGE_ID_ERROR, _) => "error",
+        (_, D3D11_SB_SHADER_MESSAGE_FORMAT_ANSI_PRINTF) => "printf",
+        _ => "message",
+    };
+
+    let mut text = format!("{} \"{}\"", mnemonic, message.text);
+    for operand_ in &message.operands {
+        let _ = write!(text, ", {}", operand(operand_, Number::Any, false));
+    }
+
+    text
+}
+
+/// Custom data that has no listing of its own, like comments and debug
+/// info, only shows up as a comment.
+fn custom_data_comment(class: u32, data: &[u32]) -> String {
+    format!("// customdata class {}, {} dwords", class, data.len())
+}
+
+fn mnemonic(opcode: Opcode) -> &'static str {
+    MNEMONICS
+        .iter()
+        .find(|(_, mnemonic)| *mnemonic == opcode)
+        .map_or("unknown", |(name, _)| name)
+}
+
+fn operation(
+    opcode: Opcode,
+    controls: u32,
+    extended: &[OpcodeExtension],
+    operands: &[shex::Operand],
+) -> String {
+    let token = controls << 11;
+    let mut text = String::from(mnemonic(opcode));
+
+    if matches!(
+        opcode,
+        Opcode::If
+            | Opcode::BreakC
+            | Opcode::ContinueC
+            | Opcode::RetC
+            | Opcode::Discard
+            | Opcode::CallC
+    ) {
+        match DECODE_D3D10_SB_INSTRUCTION_TEST_BOOLEAN(token) {
+            D3D10_SB_INSTRUCTION_TEST_ZERO => text.push_str("_z"),
+            _ => text.push_str("_nz"),
+        }
+    }
+    if DECODE_IS_D3D10_SB_INSTRUCTION_SATURATE_ENABLED(token) != 0 {
+        text.push_str("_sat");
+    }
+
+    if extended
+        .iter()
+        .any(|extension| matches!(extension, OpcodeExtension::SampleControls(_)))
+    {
+        text.push_str("_aoffimmi");
+    }
+    if extended
+        .iter()
+        .any(|extension| matches!(extension, OpcodeExtension::ResourceDim { .. }))
+    {
+        text.push_str("_indexable");
+    }
+    for extension in extended {
+        match extension {
+            OpcodeExtension::SampleControls([u, v, w]) => {
+                let _ = write!(text, "({},{},{})", u, v, w);
+            }
+            OpcodeExtension::ResourceDim { dimension, stride } => {
+                let _ = match dimension {
+                    ResourceDimension::StructuredBuffer => write!(
+                        text,
+                        "({}, stride={})",
+                        resource_dimension_name(*dimension),
+                        stride
+                    ),
+                    _ => write!(text, "({})", resource_dimension_name(*dimension)),
+                };
+            }
+            OpcodeExtension::ResourceReturnType(return_type) => {
+                text.push_str(&return_type_names(return_type));
+            }
+        }
+    }
+
+    match opcode {
+        Opcode::ResInfo => match DECODE_D3D10_SB_RESINFO_INSTRUCTION_RETURN_TYPE(token) {
+            D3D10_SB_RESINFO_INSTRUCTION_RETURN_UINT => text.push_str("_uint"),
+            D3D10_SB_RESINFO_INSTRUCTION_RETURN_RCPFLOAT => text.push_str("_rcpFloat"),
+            _ => {}
+        },
+        Opcode::SampleInfo
+            if DECODE_D3D10_SB_INSTRUCTION_RETURN_TYPE(token)
+                == D3D10_SB_INSTRUCTION_RETURN_UINT =>
+        {
+            text.push_str("_uint")
+        }
+        Opcode::Sync => {
+            let flags = DECODE_D3D11_SB_SYNC_FLAGS(token);
+            for (flag, suffix) in [
+                (
+                    D3D11_SB_SYNC_UNORDERED_ACCESS_VIEW_MEMORY_GLOBAL,
+                    "_uglobal",
+                ),
+                (D3D11_SB_SYNC_UNORDERED_ACCESS_VIEW_MEMORY_GROUP, "_ugroup"),
+                (D3D11_SB_SYNC_THREAD_GROUP_SHARED_MEMORY, "_g"),
+                (D3D11_SB_SYNC_THREADS_IN_GROUP, "_t"),
+            ] {
+                if flags & flag != 0 {
+                    text.push_str(suffix);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let number = number_type(opcode);
+    let operands: Vec<String> = operands
+        .iter()
+        .map(|operand_| operand(operand_, number, false))
+        .collect();
+
+    // fxc leaves the space even if there are no operands, like in `ret `
+    format!("{} {}", text, operands.join(", "))
+}
+
+fn declaration(declaration: &shex::Declaration) -> String {
+    let name = mnemonic(declaration.opcode());
+    let register = |register| operand(register, Number::UInt, true);
+    let semantic = |semantic: Semantic| {
+        SEMANTICS
+            .iter()
+            .find(|(_, candidate)| *candidate == semantic)
+            .map_or("undefined", |(name, _)| name)
+    };
+    let interpolation = |interpolation| match interpolation {
+        InterpolationMode::Undefined => "",
+        InterpolationMode::Constant => "constant ",
+        InterpolationMode::Linear => "linear ",
+        InterpolationMode::LinearCentroid => "linear centroid ",
+        InterpolationMode::LinearNoPerspective => "linear noperspective ",
+        InterpolationMode::LinearNoPerspectiveCentroid => "linear noperspective centroid ",
+        InterpolationMode::LinearSample => "linear sample ",
+        InterpolationMode::LinearNoPerspectiveSample => "linear noperspective sample ",
+    };
+    let typed = |dimension, return_type, globally_coherent, sample_count| {
+        let mut text = format!("{}_{}", name, resource_dimension_name(dimension));
+        if matches!(
+            dimension,
+            ResourceDimension::Texture2DMS | ResourceDimension::Texture2DMSArray
+        ) {
+            let _ = write!(text, "({})", sample_count);
+        }
+        if globally_coherent {
+            text.push_str("_glc");
+        }
+        let _ = write!(text, " {}", return_type_names(return_type));
+
+        text
+    };
+
+    match declaration {
+        shex::Declaration::GlobalFlags { flags } => {
+            let names: Vec<&str> = [
+                (GlobalFlags::REFACTORING_ALLOWED, "refactoringAllowed"),
+                (
+                    GlobalFlags::ENABLE_DOUBLE_PRECISION_FLOAT_OPS,
+                    "enableDoublePrecisionFloatOps",
+                ),
+                (
+                    GlobalFlags::FORCE_EARLY_DEPTH_STENCIL,
+                    "forceEarlyDepthStencil",
+                ),
+                (
+                    GlobalFlags::ENABLE_RAW_AND_STRUCTURED_BUFFERS,
+                    "enableRawAndStructuredBuffers",
+                ),
+                (GlobalFlags::SKIP_OPTIMIZATION, "skipOptimization"),
+                (
+                    GlobalFlags::ENABLE_MINIMUM_PRECISION,
+                    "enableMinimumPrecision",
+                ),
+                (
+                    GlobalFlags::ENABLE_DOUBLE_EXTENSIONS,
+                    "enable11_1DoubleExtensions",
+                ),
+                (
+                    GlobalFlags::ENABLE_SHADER_EXTENSIONS,
+                    "enable11_1ShaderExtensions",
+                ),
+            ]
+            .into_iter()
+            .filter(|(flag, _)| flags.contains(*flag))
+            .map(|(_, name)| name)
+            .collect();
+
+            format!("{} {}", name, names.join(" | "))
+        }
+        shex::Declaration::ConstantBuffer {
+            register: register_,
+            access,
+            ..
+        } => {
+            let access = match access {
+                ConstantBufferIndexPattern::Immediate => "immediateIndexed",
+                ConstantBufferIndexPattern::Dynamic => "dynamicIndexed",
+            };
+
+            format!(
+                "{} {}, {}",
+                name,
+                register(register_).replacen("cb", "CB", 1),
+                access
+            )
+        }
+        shex::Declaration::Sampler {
+            register: register_,
+            mode,
+            ..
+        } => {
+            let mode = match mode {
+                SamplerMode::Default => "mode_default",
+                SamplerMode::Comparison => "mode_comparison",
+                SamplerMode::Mono => "mode_mono",
+            };
+
+            format!("{} {}, {}", name, register(register_), mode)
+        }
+        shex::Declaration::Resource {
+            register: register_,
+            dimension,
+            return_type,
+            sample_count,
+            ..
+        } => format!(
+            "{} {}",
+            typed(*dimension, return_type, false, *sample_count),
+            register(register_)
+        ),
+        shex::Declaration::UavTyped {
+            register: register_,
+            dimension,
+            return_type,
+            globally_coherent,
+            ..
+        } => format!(
+            "{} {}",
+            typed(*dimension, return_type, *globally_coherent, 0),
+            register(register_)
+        ),
+        shex::Declaration::UavRaw {
+            register: register_,
+            globally_coherent,
+            ..
+        } => {
+            let glc = if *globally_coherent { "_glc" } else { "" };
+
+            format!("{}{} {}", name, glc, register(register_))
+        }
+        shex::Declaration::UavStructured {
+            register: register_,
+            stride,
+            globally_coherent,
+            has_counter,
+            ..
+        } => {
+            let glc = if *globally_coherent { "_glc" } else { "" };
+            let opc = if *has_counter { "_opc" } else { "" };
+
+            format!("{}{}{} {}, {}", name, glc, opc, register(register_), stride)
+        }
+        shex::Declaration::InputPs {
+            register: register_,
+            interpolation: mode,
+        } => format!("{} {}{}", name, interpolation(*mode), register(register_)),
+        shex::Declaration::InputPsSgv {
+            register: register_,
+            interpolation: mode,
+            semantic: semantic_,
+        }
+        | shex::Declaration::InputPsSiv {
+            register: register_,
+            interpolation: mode,
+            semantic: semantic_,
+        } => format!(
+            "{} {}{}, {}",
+            name,
+            interpolation(*mode),
+            register(register_),
+            semantic(*semantic_)
+        ),
+        shex::Declaration::InputSgv {
+            register: register_,
+            semantic: semantic_,
+        }
+        | shex::Declaration::InputSiv {
+            register: register_,
+            semantic: semantic_,
+        }
+        | shex::Declaration::OutputSgv {
+            register: register_,
+            semantic: semantic_,
+        }
+        | shex::Declaration::OutputSiv {
+            register: register_,
+            semantic: semantic_,
+        } => format!("{} {}, {}", name, register(register_), semantic(*semantic_)),
+        shex::Declaration::IndexRange {
+            register: register_,
+            count,
+        } => format!("{} {} {}", name, register(register_), count),
+        shex::Declaration::IndexableTemp {
+            register,
+            count,
+            components,
+        } => format!("{} x{}[{}], {}", name, register, count, components),
+        shex::Declaration::ResourceStructured {
+            register: register_,
+            stride: count,
+            ..
+        }
+        | shex::Declaration::TgsmRaw {
+            register: register_,
+            count,
+        } => format!("{} {}, {}", name, register(register_), count),
+        shex::Declaration::TgsmStructured {
+            register: register_,
+            stride,
+            count,
+        } => format!("{} {}, {}, {}", name, register(register_), stride, count),
+        shex::Declaration::ThreadGroup { x, y, z } => format!("{} {}, {}, {}", name, x, y, z),
+        shex::Declaration::GsInputPrimitive { primitive } => {
+            let primitive = match primitive {
+                Primitive::Undefined => String::from("undefined"),
+                Primitive::Point => String::from("point"),
+                Primitive::Line => String::from("line"),
+                Primitive::Triangle => String::from("triangle"),
+                Primitive::LineAdj => String::from("lineadj"),
+                Primitive::TriangleAdj => String::from("triangleadj"),
+                Primitive::ControlPointPatch(count) => format!("patch{}", count),
+            };
+
+            format!("{} {}", name, primitive)
+        }
+        shex::Declaration::GsOutputPrimitiveTopology { topology } => {
+            let topology = match topology {
+                PrimitiveTopology::Undefined => "undefined",
+                PrimitiveTopology::PointList => "pointlist",
+                PrimitiveTopology::LineList => "linelist",
+                PrimitiveTopology::LineStrip => "linestrip",
+                PrimitiveTopology::TriangleList => "trianglelist",
+                PrimitiveTopology::TriangleStrip => "trianglestrip",
+                PrimitiveTopology::LineListAdj => "linelistadj",
+                PrimitiveTopology::LineStripAdj => "linestripadj",
+                PrimitiveTopology::TriangleListAdj => "trianglelistadj",
+                PrimitiveTopology::TriangleStripAdj => "trianglestripadj",
+            };
+
+            format!("{} {}", name, topology)
+        }
+        shex::Declaration::TessellatorDomain { domain } => {
+            let domain = match domain {
+                TessellatorDomain::Undefined => "domain_undefined",
+                TessellatorDomain::Isoline => "domain_isoline",
+                TessellatorDomain::Tri => "domain_tri",
+                TessellatorDomain::Quad => "domain_quad",
+            };
+
+            format!("{} {}", name, domain)
+        }
+        shex::Declaration::TessellatorPartitioning { partitioning } => {
+            let partitioning = match partitioning {
+                TessellatorPartitioning::Undefined => "partitioning_undefined",
+                TessellatorPartitioning::Integer => "partitioning_integer",
+                TessellatorPartitioning::Pow2 => "partitioning_pow2",
+                TessellatorPartitioning::FractionalOdd => "partitioning_fractional_odd",
+                TessellatorPartitioning::FractionalEven => "partitioning_fractional_even",
+            };
+
+            format!("{} {}", name, partitioning)
+        }
+        shex::Declaration::TessellatorOutputPrimitive { primitive } => {
+            let primitive = match primitive {
+                TessellatorOutputPrimitive::Undefined => "output_undefined",
+                TessellatorOutputPrimitive::Point => "output_point",
+                TessellatorOutputPrimitive::Line => "output_line",
+                TessellatorOutputPrimitive::TriangleCw => "output_triangle_cw",
+                TessellatorOutputPrimitive::TriangleCcw => "output_triangle_ccw",
+            };
+
+            format!("{} {}", name, primitive)
+        }
+        shex::Declaration::HsMaxTessFactor { factor } => {
+            format!("{} l({})", name, float(*factor))
+        }
+        shex::Declaration::FunctionBody { index } => format!("{} fb{}", name, index),
+        shex::Declaration::FunctionTable { index, bodies } => {
+            let bodies: Vec<String> = bodies.iter().map(|body| format!("fb{}", body)).collect();
+
+            format!("{} ft{} = {{{}}}", name, index, bodies.join(", "))
+        }
+        shex::Declaration::Interface {
+            index,
+            dynamically_indexed,
+            body_count,
+            array_length,
+            tables,
+        } => {
+            let indexed = if *dynamically_indexed {
+                "_dynamicindexed"
+            } else {
+                ""
+            };
+            let tables: Vec<String> = tables.iter().map(|table| format!("ft{}", table)).collect();
+
+            format!(
+                "{}{} fp{}[{}][{}] = {{{}}}",
+                name,
+                indexed,
+                index,
+                array_length,
+                body_count,
+                tables.join(", ")
+            )
+        }
+        shex::Declaration::Temps { count }
+        | shex::Declaration::MaxOutputVertexCount { count }
+        | shex::Declaration::GsInstanceCount { count }
+        | shex::Declaration::InputControlPointCount { count }
+        | shex::Declaration::OutputControlPointCount { count }
+        | shex::Declaration::HsForkPhaseInstanceCount { count }
+        | shex::Declaration::HsJoinPhaseInstanceCount { count } => {
+            format!("{} {}", name, count)
+        }
+        shex::Declaration::Input {
+            register: register_,
+        }
+        | shex::Declaration::ResourceRaw {
+            register: register_,
+            ..
+        }
+        | shex::Declaration::Output {
+            register: register_,
+        }
+        | shex::Declaration::Stream {
+            register: register_,
+        } => format!("{} {}", name, register(register_)),
+    }
+}
+
+/// Names a resource dimension the way the `_indexable` group and
+/// `dcl_resource_*` do.
+fn resource_dimension_name(dimension: ResourceDimension) -> &'static str {
+    match dimension {
+        ResourceDimension::Unknown => "unknown",
+        ResourceDimension::Buffer => "buffer",
+        ResourceDimension::Texture1D => "texture1d",
+        ResourceDimension::Texture2D => "texture2d",
+        ResourceDimension::Texture2DMS => "texture2dms",
+        ResourceDimension::Texture3D => "texture3d",
+        ResourceDimension::TextureCube => "texturecube",
+        ResourceDimension::Texture1DArray => "texture1darray",
+        ResourceDimension::Texture2DArray => "texture2darray",
+        ResourceDimension::Texture2DMSArray => "texture2dmsarray",
+        ResourceDimension::TextureCubeArray => "texturecubearray",
+        ResourceDimension::RawBuffer => "raw_buffer",
+        ResourceDimension::StructuredBuffer => "structured_buffer",
+    }
+}
+
+/// Writes return types like `(float,float,float,float)`.
+fn return_type_names(return_type: &[ResourceReturnType; 4]) -> String {
+    let names: Vec<&str> = return_type
+        .iter()
+        .map(|ty| match ty {
+            ResourceReturnType::Unorm => "unorm",
+            ResourceReturnType::Snorm => "snorm",
+            ResourceReturnType::Sint => "sint",
+            ResourceReturnType::Uint => "uint",
+            ResourceReturnType::Float => "float",
+            ResourceReturnType::Mixed => "mixed",
+            ResourceReturnType::Double => "double",
+            ResourceReturnType::Continued => "continued",
+            ResourceReturnType::Unused => "unused",
+        })
+        .collect();
+
+    format!("({})", names.join(","))
+}
+
+/// How the 32-bit immediates of an instruction are printed.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Number {
+    Float,
+    Int,
+    UInt,
+    /// The instruction doesn't care, like `mov`, so the value is printed as
+    /// whatever it looks like.
+    Any,
+}
+
+fn number_type(opcode: Opcode) -> Number {
+    use Opcode::*;
+
+    match opcode {
+        Add | Div | Dp2 | Dp3 | Dp4 | Eq | Exp | Frc | Ftoi | Ftou | Ftod | Ge | Log | Lt | Mad
+        | Min | Max | Mul | Ne | RoundNe | RoundNi | RoundPi | RoundZ | Rsq | Sqrt | SinCos
+        | Rcp | F32ToF16 | DerivRtx | DerivRty | DerivRtxCoarse | DerivRtxFine | DerivRtyCoarse
+        | DerivRtyFine | Sample | SampleC | SampleCLz | SampleL | SampleD | SampleB | Lod
+        | Gather4C | Gather4PoC => Number::Float,
+        IAdd | IEq | IGe | ILt | IMad | IMax | IMin | IMul | INe | INeg | IShl | IShr | Itof
+        | Itod | IBfe | Switch | Case | Ld | LdMs | Gather4Po | EvalSnapped | EvalSampleIndex
+        | AtomicIAdd | AtomicIMax | AtomicIMin | ImmAtomicIAdd | ImmAtomicIMax | ImmAtomicIMin => {
+            Number::Int
+        }
+        UDiv | ULt | UGe | UMul | UMad | UMax | UMin | UShr | Utof | Utod | UBfe | Bfi | UAddC
+        | USubB | AtomicUMax | AtomicUMin | ImmAtomicUMax | ImmAtomicUMin | LdRaw
+        | LdStructured | StoreRaw | StoreStructured | LdUavTyped | StoreUavTyped | CountBits
+        | FirstBitHi | FirstBitLo | FirstBitShi | BfRev | F16ToF32 | Msad => Number::UInt,
+        _ => Number::Any,
+    }
+}
+
+fn number(value: u32, ty: Number) -> String {
+    match ty {
+        Number::Float => float(f32::from_bits(value)),
+        Number::Int => (value as i32).to_string(),
+        Number::UInt => value.to_string(),
+        Number::Any => {
+            let exponent = (value >> 23) & 0xff;
+            if exponent != 0 && exponent != 0xff {
+                float(f32::from_bits(value))
+            } else if value <= 10000 {
+                value.to_string()
+            } else {
+                format!("0x{:08x}", value)
+            }
+        }
+    }
+}
+
+fn float(value: f32) -> String {
+    if value.is_nan() {
+        String::from("nan")
+    } else if value.is_infinite() {
+        String::from(if value < 0.0 { "-inf" } else { "inf" })
+    } else {
+        format!("{:.6}", value)
+    }
+}
+
+/// Prints an operand, like `-|cb0[r0.x + 1].xyzw|`. Declarations only print
+/// the components of masks, like `dcl_input v0.xy`.
+fn operand(operand_: &shex::Operand, number_ty: Number, declaration: bool) -> String {
+    let mut text = match operand_.ty {
+        shex::OperandType::Immediate32 => {
+            let values: Vec<String> = operand_
+                .immediates
+                .iter()
+                .map(|&value| number(value, number_ty))
+                .collect();
+
+            format!("l({})", values.join(","))
+        }
+        shex::OperandType::Immediate64 => {
+            let values: Vec<String> = operand_
+                .immediates
+                .chunks_exact(2)
+                .map(|value| {
+                    let value = f64::from_bits(value[0] as u64 | (value[1] as u64) << 32);
+                    format!("{:.6}l", value)
+                })
+                .collect();
+
+            format!("d({})", values.join(", "))
+        }
+        ty => {
+            let prefix = REGISTERS
+                .iter()
+                .find(|(_, register, _)| *register == ty)
+                .map_or_else(|| format!("{:?}", ty), |(name, _, _)| String::from(*name));
+            let mut text = prefix;
+
+            // the register number is part of the name, unless the indices
+            // pick a vertex first, like `v[0][1]`
+            let bracketed = ty == shex::OperandType::ImmediateConstantBuffer
+                || (operand_.indices.len() == 2
+                    && matches!(
+                        ty,
+                        shex::OperandType::Input
+                            | shex::OperandType::Output
+                            | shex::OperandType::InputControlPoint
+                            | shex::OperandType::OutputControlPoint
+                    ));
+            for (i, index) in operand_.indices.iter().enumerate() {
+                match index {
+                    OperandIndex::Immediate32(index) if i == 0 && !bracketed => {
+                        let _ = write!(text, "{}", index);
+                    }
+                    index => {
+                        let _ = write!(text, "[{}]", operand_index(index));
+                    }
+                }
+            }
+
+            match operand_.components {
+                OperandComponents::Mask(mask) => text.push_str(&mask_name(mask)),
+                OperandComponents::Swizzle(ComponentSwizzle(x, y, z, w)) if !declaration => {
+                    text.push('.');
+                    for component in [x, y, z, w] {
+                        text.push(component_name(component));
+                    }
+                }
+                OperandComponents::Select1(component) if !declaration => {
+                    text.push('.');
+                    text.push(component_name(component));
+                }
+                _ => {}
+            }
+
+            text
+        }
+    };
+
+    text = match operand_.modifier {
+        OperandModifier::None => text,
+        OperandModifier::Neg => format!("-{}", text),
+        OperandModifier::Abs => format!("|{}|", text),
+        OperandModifier::AbsNeg => format!("-|{}|", text),
+    };
+
+    match operand_.min_precision {
+        MinPrecision::Default => {}
+        MinPrecision::Float16 => text.push_str(" {min16f}"),
+        MinPrecision::Float2_8 => text.push_str(" {min2_8f}"),
+        MinPrecision::SInt16 => text.push_str(" {min16i}"),
+        MinPrecision::UInt16 => text.push_str(" {min16u}"),
+    }
+
+    text
+}
+
+fn operand_index(index: &OperandIndex) -> String {
+    match index {
+        OperandIndex::Immediate32(index) => index.to_string(),
+        OperandIndex::Immediate64(index) => index.to_string(),
+        OperandIndex::Relative(relative) => operand(relative, Number::UInt, false),
+        OperandIndex::Immediate32PlusRelative(index, relative) => {
+            format!("{} + {}", operand(relative, Number::UInt, false), index)
+        }
+        OperandIndex::Immediate64PlusRelative(index, relative) => {
+            format!("{} + {}", operand(relative, Number::UInt, false), index)
+        }
+    }
+}
+
+fn mask_name(mask: ComponentMask) -> String {
+    let mut text = String::new();
+    for (component, letter) in [
+        (ComponentMask::COMPONENT_MASK_R, 'x'),
+        (ComponentMask::COMPONENT_MASK_G, 'y'),
+        (ComponentMask::COMPONENT_MASK_B, 'z'),
+        (ComponentMask::COMPONENT_MASK_A, 'w'),
+    ] {
+        if mask.contains(component) {
+            text.push(letter);
+        }
+    }
+
+    if text.is_empty() {
+        text
+    } else {
+        format!(".{}", text)
+    }
+}
+
+fn component_name(component: ComponentName) -> char {
+    match component {
+        ComponentName::X => 'x',
+        ComponentName::Y => 'y',
+        ComponentName::Z => 'z',
+        ComponentName::W => 'w',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble;
+
+    #[test]
+    fn round_trip_listing() {
+        let listing = "\
+//
+// Input signature:
+//
+// Name                 Index   Mask Register SysValue  Format   Used
+// -------------------- ----- ------ -------- -------- ------- ------
+// COLOR                    0   xyzw        0     NONE   float
+// UV                       0   xy          1     NONE   float   xy
+//
+//
+// Output signature:
+//
+// Name                 Index   Mask Register SysValue  Format   Used
+// -------------------- ----- ------ -------- -------- ------- ------
+// SV_Target                0   xyzw        0   TARGET   float   xyzw
+// SV_Depth                 0    N/A   oDepth    DEPTH   float    YES
+//
+ps_5_0
+dcl_globalFlags refactoringAllowed | enableRawAndStructuredBuffers
+dcl_immediateConstantBuffer { { 1.000000, 0, 0, 0},
+                              { 0, 0xffffffff, 0x7f800000, 0.250000} }
+dcl_constantbuffer CB0[4], dynamicIndexed
+dcl_sampler s0, mode_comparison
+dcl_resource_texture2dms(4) (float,float,float,float) t1
+dcl_resource_structured t2, 16
+dcl_uav_typed_texture2d (float,float,float,float) u0
+dcl_uav_structured_glc_opc u1, 4
+dcl_input_ps linear centroid v1.xy
+dcl_input_ps_siv constant v2.x, primitive_id
+dcl_output o0.xyzw
+dcl_output oDepth
+dcl_temps 2
+dcl_indexableTemp x0[4], 4
+mov_sat o0.xyzw, l(1.000000,-1.000000,0.500000,0)
+if_nz r0.x
+  ld_structured_indexable(structured_buffer, stride=16)(mixed,mixed,mixed,mixed) r0.x, v2.x, l(0), t2.xxxx
+  sample_c_lz_aoffimmi_indexable(-1,0,7)(texture2d)(float,float,float,float) r1.x, r0.xyxx, t1.xxxx, s0, l(0.500000)
+else
+  mov r0.y {min16f}, -|cb0[r0.x + 1].w| {min16f}
+  mov x0[r0.x + 0].xy, icb[r0.y + 0].zwzz
+  iadd r0.z, r0.z, l(-1)
+endif
+resinfo_indexable(texture2d)(float,float,float,float)_uint r0.xy, l(0), t1.xyzw
+dmov r0.xy, d(1.000000l, -2.500000l)
+sync_g_t
+loop
+  breakc_z r0.x
+endloop
+ret
+";
+        let module = assemble(listing).unwrap();
+        let text = disassemble(module.as_bytes()).unwrap();
+
+        // fxc pads the columns of the tables and leaves a space after
+        // instructions without operands
+        assert!(text.contains("\nret \n"));
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        assert_eq!(lines, listing.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reference_shader() {
+        let listing = disassemble(include_bytes!("../../dxbcd/reference.dxbc")).unwrap();
+
+        assert!(listing.starts_with("//\n// Generated by "));
+        assert!(listing.ends_with(" instruction slots used\n"));
+        assert!(listing
+            .lines()
+            .any(|line| line.starts_with("ps_") || line.starts_with("vs_")));
+    }
+
+    // complex_shader.asm is written by this disassembler, not by `fxc /Fc`,
+    // so it only catches changes to the listing, not differences from fxc's
+    #[test]
+    fn complex_shader_listing() {
+        let listing = disassemble(include_bytes!("../../dxbcd/complex_shader.dxbc")).unwrap();
+
+        assert_eq!(listing, include_str!("../../dxbcd/complex_shader.asm"));
+    }
+
+    #[test]
+    fn default_values() {
+        let mut out = String::new();
+        let value: Vec<u8> = (0..5u32)
+            .flat_map(|i| (i as f32).to_bits().to_le_bytes())
+            .collect();
+        write_default_value(&mut out, &value).unwrap();
+
+        assert_eq!(
+            out,
+            "//      = 0x00000000 0x3f800000 0x40000000 0x40400000 \n\
+             //        0x40800000 \n"
+        );
+    }
+}
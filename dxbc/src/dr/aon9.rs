@@ -0,0 +1,630 @@
+use crate::binary::*;
+
+use int_enum::IntEnum;
+
+const LEVEL9_HEADER_SIZE: usize = 12;
+
+pub const SM2_END_TOKEN: u32 = 0x0000_ffff;
+
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum Sm2Opcode {
+    Nop = 0,
+    Mov = 1,
+    Add = 2,
+    Sub = 3,
+    Mad = 4,
+    Mul = 5,
+    Rcp = 6,
+    Rsq = 7,
+    Dp3 = 8,
+    Dp4 = 9,
+    Min = 10,
+    Max = 11,
+    Slt = 12,
+    Sge = 13,
+    Exp = 14,
+    Log = 15,
+    Lit = 16,
+    Dst = 17,
+    Lrp = 18,
+    Frc = 19,
+    M4x4 = 20,
+    M4x3 = 21,
+    M3x4 = 22,
+    M3x3 = 23,
+    M3x2 = 24,
+    Call = 25,
+    CallNz = 26,
+    Loop = 27,
+    Ret = 28,
+    EndLoop = 29,
+    Label = 30,
+    Dcl = 31,
+    Pow = 32,
+    Crs = 33,
+    Sgn = 34,
+    Abs = 35,
+    Nrm = 36,
+    SinCos = 37,
+    Rep = 38,
+    EndRep = 39,
+    If = 40,
+    Ifc = 41,
+    Else = 42,
+    EndIf = 43,
+    Break = 44,
+    Breakc = 45,
+    Mova = 46,
+    DefB = 47,
+    DefI = 48,
+    TexCoord = 64,
+    TexKill = 65,
+    Tex = 66,
+    TexBem = 67,
+    TexBemL = 68,
+    TexReg2Ar = 69,
+    TexReg2Gb = 70,
+    TexM3x2Pad = 71,
+    TexM3x2Tex = 72,
+    TexM3x3Pad = 73,
+    TexM3x3Tex = 74,
+    Reserved0 = 75,
+    TexM3x3Spec = 76,
+    TexM3x3VSpec = 77,
+    ExpP = 78,
+    LogP = 79,
+    Cnd = 80,
+    Def = 81,
+    TexReg2Rgb = 82,
+    TexDp3Tex = 83,
+    TexM3x2Depth = 84,
+    TexDp3 = 85,
+    TexM3x3 = 86,
+    TexDepth = 87,
+    Cmp = 88,
+    Bem = 89,
+    Dp2Add = 90,
+    Dsx = 91,
+    Dsy = 92,
+    TexLdd = 93,
+    Setp = 94,
+    TexLdl = 95,
+    BreakP = 96,
+    Phase = 0xfffd,
+    Comment = 0xfffe,
+    End = 0xffff,
+}
+
+impl Sm2Opcode {
+    pub fn is_texture(self) -> bool {
+        matches!(self as u16, 64..=77 | 82..=87 | 93 | 95)
+    }
+
+    /// Flow control instructions only have source parameters.
+    pub fn is_flow_control(self) -> bool {
+        matches!(
+            self,
+            Sm2Opcode::Call
+                | Sm2Opcode::CallNz
+                | Sm2Opcode::Loop
+                | Sm2Opcode::Ret
+                | Sm2Opcode::EndLoop
+                | Sm2Opcode::Label
+                | Sm2Opcode::Rep
+                | Sm2Opcode::EndRep
+                | Sm2Opcode::If
+                | Sm2Opcode::Ifc
+                | Sm2Opcode::Else
+                | Sm2Opcode::EndIf
+                | Sm2Opcode::Break
+                | Sm2Opcode::Breakc
+                | Sm2Opcode::BreakP
+        )
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum Sm2RegisterType {
+    Temp = 0,
+    Input = 1,
+    Const = 2,
+    /// `a#` in vertex shaders, `t#` in pixel shaders.
+    AddrOrTexture = 3,
+    RastOut = 4,
+    AttrOut = 5,
+    /// `oT#` before shader model 3, `o#` after.
+    TexCrdOutOrOutput = 6,
+    ConstInt = 7,
+    ColorOut = 8,
+    DepthOut = 9,
+    Sampler = 10,
+    Const2 = 11,
+    Const3 = 12,
+    Const4 = 13,
+    ConstBool = 14,
+    Loop = 15,
+    TempFloat16 = 16,
+    MiscType = 17,
+    Label = 18,
+    Predicate = 19,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum Sm2SourceModifier {
+    None = 0,
+    Neg = 1,
+    Bias = 2,
+    BiasNeg = 3,
+    Sign = 4,
+    SignNeg = 5,
+    Comp = 6,
+    X2 = 7,
+    X2Neg = 8,
+    Dz = 9,
+    Dw = 10,
+    Abs = 11,
+    AbsNeg = 12,
+    Not = 13,
+}
+
+bitflags! {
+    pub struct Sm2ResultModifier: u8 {
+        const SATURATE = 0x1;
+        const PARTIAL_PRECISION = 0x2;
+        const CENTROID = 0x4;
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum Sm2DeclUsage {
+    Position = 0,
+    BlendWeight = 1,
+    BlendIndices = 2,
+    Normal = 3,
+    PSize = 4,
+    TexCoord = 5,
+    Tangent = 6,
+    Binormal = 7,
+    TessFactor = 8,
+    PositionT = 9,
+    Color = 10,
+    Fog = 11,
+    Depth = 12,
+    Sample = 13,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum Sm2TextureType {
+    Unknown = 0,
+    Texture2D = 2,
+    Cube = 3,
+    Volume = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sm2ShaderType {
+    Vertex,
+    Pixel,
+}
+
+/// The version token starting a shader model 2 token stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sm2Version {
+    pub ty: Sm2ShaderType,
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl Sm2Version {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        let token = decoder.read_u32()?;
+        let ty = match token >> 16 {
+            0xfffe => Sm2ShaderType::Vertex,
+            0xffff => Sm2ShaderType::Pixel,
+            _ => return Err(State::ChunkIncorrect),
+        };
+
+        Ok(Self {
+            ty,
+            major: (token >> 8) as u8,
+            minor: token as u8,
+        })
+    }
+
+    pub fn token(&self) -> u32 {
+        let ty = match self.ty {
+            Sm2ShaderType::Vertex => 0xfffe,
+            Sm2ShaderType::Pixel => 0xffff,
+        };
+
+        (ty << 16) | ((self.major as u32) << 8) | self.minor as u32
+    }
+}
+
+fn decode_register_type(decoder: &Decoder, token: u32) -> Result<Sm2RegisterType, State> {
+    let ty = ((token >> 28) & 0x7) | ((token >> 8) & 0x18);
+    Sm2RegisterType::from_int(ty as u8)
+        .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(decoder.get_offset() - 4)))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sm2SourceParameter {
+    pub ty: Sm2RegisterType,
+    pub register: u16,
+    /// The source component of each destination component.
+    pub swizzle: [u8; 4],
+    pub modifier: Sm2SourceModifier,
+    /// The register, like `a0.x` or `aL`, added to `register`.
+    pub relative: Option<Box<Sm2SourceParameter>>,
+}
+
+impl Sm2SourceParameter {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        let token = decoder.read_u32()?;
+        let ty = decode_register_type(decoder, token)?;
+        let modifier = Sm2SourceModifier::from_int(((token >> 24) & 0xf) as u8)
+            .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(decoder.get_offset() - 4)))?;
+
+        let mut swizzle = [0; 4];
+        for (idx, component) in swizzle.iter_mut().enumerate() {
+            *component = ((token >> (16 + 2 * idx)) & 0x3) as u8;
+        }
+
+        // shader model 2 always follows relative parameters with the address
+        // register token
+        let relative = if token & 0x2000 != 0 {
+            Some(Box::new(Sm2SourceParameter::parse(decoder)?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            ty,
+            register: (token & 0x7ff) as u16,
+            swizzle,
+            modifier,
+            relative,
+        })
+    }
+
+    pub fn is_identity_swizzle(&self) -> bool {
+        self.swizzle == [0, 1, 2, 3]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sm2DestinationParameter {
+    pub ty: Sm2RegisterType,
+    pub register: u16,
+    pub write_mask: u8,
+    pub result_modifier: Sm2ResultModifier,
+    /// The result is multiplied by `2^shift`.
+    pub shift: i8,
+    pub relative: Option<Box<Sm2SourceParameter>>,
+}
+
+impl Sm2DestinationParameter {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        let token = decoder.read_u32()?;
+        let ty = decode_register_type(decoder, token)?;
+
+        let relative = if token & 0x2000 != 0 {
+            Some(Box::new(Sm2SourceParameter::parse(decoder)?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            ty,
+            register: (token & 0x7ff) as u16,
+            write_mask: ((token >> 16) & 0xf) as u8,
+            result_modifier: Sm2ResultModifier::from_bits_truncate((token >> 20) as u8 & 0xf),
+            // sign extend the 4 bit shift
+            shift: (((token >> 24) as u8) << 4) as i8 >> 4,
+            relative,
+        })
+    }
+}
+
+/// A `dcl`, whose usage applies to input and output registers and whose
+/// texture type applies to samplers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sm2Dcl {
+    pub semantic: u32,
+    pub dst: Sm2DestinationParameter,
+}
+
+impl Sm2Dcl {
+    pub fn get_usage(&self) -> Option<Sm2DeclUsage> {
+        Sm2DeclUsage::from_int((self.semantic & 0x1f) as u8).ok()
+    }
+
+    pub fn get_usage_index(&self) -> u32 {
+        (self.semantic >> 16) & 0xf
+    }
+
+    pub fn get_texture_type(&self) -> Option<Sm2TextureType> {
+        Sm2TextureType::from_int(((self.semantic >> 27) & 0xf) as u8).ok()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sm2Operation {
+    pub opcode: Sm2Opcode,
+    /// Opcode specific controls, like the comparison of `ifc` or the
+    /// projection of `texld`.
+    pub controls: u8,
+    pub coissue: bool,
+    pub dst: Option<Sm2DestinationParameter>,
+    pub predicate: Option<Sm2SourceParameter>,
+    pub src: Vec<Sm2SourceParameter>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sm2Instruction {
+    Dcl(Sm2Dcl),
+    Def {
+        dst: Sm2DestinationParameter,
+        values: [f32; 4],
+    },
+    DefI {
+        dst: Sm2DestinationParameter,
+        values: [i32; 4],
+    },
+    DefB {
+        dst: Sm2DestinationParameter,
+        value: bool,
+    },
+    Texture(Sm2Operation),
+    Arithmetic(Sm2Operation),
+    FlowControl(Sm2Operation),
+    Comment(Vec<u32>),
+    Phase,
+}
+
+impl Sm2Instruction {
+    /// Parses the instruction started by `token`, which must not be the end
+    /// token.
+    pub fn parse(decoder: &mut Decoder, token: u32) -> Result<Self, State> {
+        let offset = decoder.get_offset() - 4;
+        let opcode = Sm2Opcode::from_int(token as u16)
+            .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(offset)))?;
+
+        if opcode == Sm2Opcode::Comment {
+            let len = (token >> 16) & 0x7fff;
+            return Ok(Sm2Instruction::Comment(decoder.words(len as usize)?));
+        }
+
+        let len = ((token >> 24) & 0xf) as usize;
+        let mut params = decoder.scoped_decoder(len * 4)?;
+        decoder.skip(len * 4)?;
+
+        let instruction = match opcode {
+            Sm2Opcode::Phase => Sm2Instruction::Phase,
+            Sm2Opcode::Dcl => {
+                let semantic = params.read_u32()?;
+                let dst = Sm2DestinationParameter::parse(&mut params)?;
+                Sm2Instruction::Dcl(Sm2Dcl { semantic, dst })
+            }
+            Sm2Opcode::Def => {
+                let dst = Sm2DestinationParameter::parse(&mut params)?;
+                let mut values = [0.0; 4];
+                for value in &mut values {
+                    *value = f32::from_bits(params.read_u32()?);
+                }
+                Sm2Instruction::Def { dst, values }
+            }
+            Sm2Opcode::DefI => {
+                let dst = Sm2DestinationParameter::parse(&mut params)?;
+                let mut values = [0; 4];
+                for value in &mut values {
+                    *value = params.read_u32()? as i32;
+                }
+                Sm2Instruction::DefI { dst, values }
+            }
+            Sm2Opcode::DefB => {
+                let dst = Sm2DestinationParameter::parse(&mut params)?;
+                let value = params.read_u32()? != 0;
+                Sm2Instruction::DefB { dst, value }
+            }
+            _ => {
+                let dst = if !opcode.is_flow_control() && !params.eof() {
+                    Some(Sm2DestinationParameter::parse(&mut params)?)
+                } else {
+                    None
+                };
+                let predicate = if token & 0x1000_0000 != 0 {
+                    Some(Sm2SourceParameter::parse(&mut params)?)
+                } else {
+                    None
+                };
+                let mut src = Vec::new();
+                while !params.eof() {
+                    src.push(Sm2SourceParameter::parse(&mut params)?);
+                }
+
+                let operation = Sm2Operation {
+                    opcode,
+                    controls: (token >> 16) as u8,
+                    coissue: token & 0x4000_0000 != 0,
+                    dst,
+                    predicate,
+                    src,
+                };
+
+                if opcode.is_texture() {
+                    Sm2Instruction::Texture(operation)
+                } else if opcode.is_flow_control() {
+                    Sm2Instruction::FlowControl(operation)
+                } else {
+                    Sm2Instruction::Arithmetic(operation)
+                }
+            }
+        };
+
+        if !params.eof() {
+            return Err(State::ChunkIncorrect);
+        }
+
+        Ok(instruction)
+    }
+
+    pub fn get_opcode(&self) -> Sm2Opcode {
+        match *self {
+            Sm2Instruction::Dcl(_) => Sm2Opcode::Dcl,
+            Sm2Instruction::Def { .. } => Sm2Opcode::Def,
+            Sm2Instruction::DefI { .. } => Sm2Opcode::DefI,
+            Sm2Instruction::DefB { .. } => Sm2Opcode::DefB,
+            Sm2Instruction::Texture(ref operation)
+            | Sm2Instruction::Arithmetic(ref operation)
+            | Sm2Instruction::FlowControl(ref operation) => operation.opcode,
+            Sm2Instruction::Comment(_) => Sm2Opcode::Comment,
+            Sm2Instruction::Phase => Sm2Opcode::Phase,
+        }
+    }
+}
+
+/// The shader model 2 fallback of a `ps_4_0_level_9_x` or `vs_4_0_level_9_x`
+/// shader, stored in `Aon9` chunks and, for the Xbox, in `XNAS` and `XNAP`
+/// chunks.
+#[derive(Debug)]
+pub struct Aon9Chunk {
+    /// The first dword of the chunk, which isn't understood yet and is kept
+    /// as is when writing.
+    pub unknown: u32,
+    /// The constant buffer, loop register, sampler and runtime constant
+    /// mappings between the header and the shader, kept as is.
+    pub mappings: Vec<u32>,
+    pub version: Sm2Version,
+    pub instructions: Vec<Sm2Instruction>,
+}
+
+impl Aon9Chunk {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        let unknown = decoder.read_u32()?;
+        let shader_size = decoder.read_u32()? as usize;
+        let shader_offset = decoder.read_u32()? as usize;
+        if shader_offset < LEVEL9_HEADER_SIZE || !shader_offset.is_multiple_of(4) {
+            return Err(State::ChunkIncorrect);
+        }
+
+        let mappings = decoder.words((shader_offset - LEVEL9_HEADER_SIZE) / 4)?;
+
+        let mut decoder = decoder.seek(shader_offset)?.scoped_decoder(shader_size)?;
+        let version = Sm2Version::parse(&mut decoder)?;
+
+        let mut instructions = Vec::new();
+        while !decoder.eof() {
+            let token = decoder.read_u32()?;
+            if token == SM2_END_TOKEN {
+                break;
+            }
+
+            instructions.push(Sm2Instruction::parse(&mut decoder, token)?);
+        }
+
+        Ok(Self {
+            unknown,
+            mappings,
+            version,
+            instructions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dr::DxbcModule;
+
+    #[test]
+    fn round_trip() {
+        let dst = |ty, register, write_mask| Sm2DestinationParameter {
+            ty,
+            register,
+            write_mask,
+            result_modifier: Sm2ResultModifier::empty(),
+            shift: 0,
+            relative: None,
+        };
+        let src = |ty, register| Sm2SourceParameter {
+            ty,
+            register,
+            swizzle: [0, 1, 2, 3],
+            modifier: Sm2SourceModifier::None,
+            relative: None,
+        };
+
+        let aon9 = Aon9Chunk {
+            unknown: 0x200,
+            mappings: vec![0, 0, 0, 0, 0],
+            version: Sm2Version {
+                ty: Sm2ShaderType::Pixel,
+                major: 2,
+                minor: 0,
+            },
+            instructions: vec![
+                Sm2Instruction::Comment(vec![0x4242_4242]),
+                Sm2Instruction::Def {
+                    dst: dst(Sm2RegisterType::Const, 1, 0xf),
+                    values: [1.0, 0.5, -2.0, 0.0],
+                },
+                Sm2Instruction::Dcl(Sm2Dcl {
+                    semantic: 0x8000_0000,
+                    dst: dst(Sm2RegisterType::AddrOrTexture, 0, 0x3),
+                }),
+                Sm2Instruction::Dcl(Sm2Dcl {
+                    semantic: 0x9000_0000,
+                    dst: dst(Sm2RegisterType::Sampler, 0, 0xf),
+                }),
+                Sm2Instruction::Texture(Sm2Operation {
+                    opcode: Sm2Opcode::Tex,
+                    controls: 0,
+                    coissue: false,
+                    dst: Some(dst(Sm2RegisterType::Temp, 0, 0xf)),
+                    predicate: None,
+                    src: vec![
+                        src(Sm2RegisterType::AddrOrTexture, 0),
+                        src(Sm2RegisterType::Sampler, 0),
+                    ],
+                }),
+                Sm2Instruction::Arithmetic(Sm2Operation {
+                    opcode: Sm2Opcode::Mul,
+                    controls: 0,
+                    coissue: false,
+                    dst: Some(Sm2DestinationParameter {
+                        result_modifier: Sm2ResultModifier::SATURATE,
+                        shift: -1,
+                        ..dst(Sm2RegisterType::ColorOut, 0, 0xf)
+                    }),
+                    predicate: None,
+                    src: vec![
+                        Sm2SourceParameter {
+                            swizzle: [3, 3, 3, 3],
+                            modifier: Sm2SourceModifier::Neg,
+                            ..src(Sm2RegisterType::Temp, 0)
+                        },
+                        src(Sm2RegisterType::Const, 1),
+                    ],
+                }),
+            ],
+        };
+
+        let mut module = DxbcModule::new();
+        module.write_aon9(&aon9);
+        let bytes = module.as_bytes();
+        assert_eq!(&bytes[..4], b"Aon9");
+
+        let parsed = Aon9Chunk::parse(&mut Decoder::new(&bytes[8..])).unwrap();
+        assert_eq!(parsed.unknown, aon9.unknown);
+        assert_eq!(parsed.mappings, aon9.mappings);
+        assert_eq!(parsed.version, aon9.version);
+        assert_eq!(parsed.instructions, aon9.instructions);
+        assert_eq!(parsed.instructions[5].get_opcode(), Sm2Opcode::Mul);
+    }
+}
@@ -1,26 +1,113 @@
-use super::isgn::IOsgnChunk;
-use super::rdef::RdefChunk;
-use super::shex::{ResourceDimension, ResourceReturnType};
+use super::aon9::{
+    Aon9Chunk, Sm2DestinationParameter, Sm2Instruction, Sm2Operation, Sm2RegisterType,
+    Sm2SourceParameter, SM2_END_TOKEN,
+};
+use super::dxil::{DxilChunk, HashChunk, IldnChunk};
+use super::isgn::{IOsgnChunk, SignatureLayout};
+use super::rdef::{RdefChunk, ShaderType};
+use super::rts0::{RootParameterData, RootSignatureVersion, Rts0Chunk};
+use super::sfi0::Sfi0Chunk;
+use super::shex::{
+    self, ConstantBufferIndexPattern, CustomDataClass, InterpolationMode, MinPrecision, Opcode,
+    Primitive, PrimitiveTopology, ResInfoReturnType, ResourceDimension, ResourceReturnType,
+    SamplerMode, SyncFlags, TessellatorDomain, TessellatorOutputPrimitive, TessellatorPartitioning,
+    TestBoolean,
+};
 use super::stat::IStatChunk;
+use crate::binary::{Container, Decoder, State};
 use crate::checksum;
 
-use std::{mem, slice};
+use int_enum::IntEnum;
+use std::collections::HashMap;
+use std::{error, fmt, mem, slice};
 use winapi::um::d3d11tokenizedprogramformat::*;
 
 const DXBC_MAGIC: u32 = 0x43425844;
 const RDEF_MAGIC: u32 = 0x46454452;
-const RD11_MAGIC: u32 = 0x31314452;
 const ISGN_MAGIC: u32 = 0x4e475349;
 const OSGN_MAGIC: u32 = 0x4e47534f;
+const ISG1_MAGIC: u32 = 0x31475349;
+const OSG1_MAGIC: u32 = 0x3147534f;
+const OSG5_MAGIC: u32 = 0x3547534f;
+const PCSG_MAGIC: u32 = 0x47534350;
 const SHEX_MAGIC: u32 = 0x58454853;
+const SHDR_MAGIC: u32 = 0x52444853;
 const STAT_MAGIC: u32 = 0x54415453;
+const SFI0_MAGIC: u32 = 0x30494653;
+const RTS0_MAGIC: u32 = 0x30535452;
+const AON9_MAGIC: u32 = 0x396e6f41;
+const XNAS_MAGIC: u32 = 0x53414e58;
+const XNAP_MAGIC: u32 = 0x50414e58;
+const DXIL_MAGIC: u32 = 0x4c495844;
+const ILDB_MAGIC: u32 = 0x42444c49;
+const HASH_MAGIC: u32 = 0x48534148;
+const ILDN_MAGIC: u32 = 0x4e444c49;
+
+/// A chunk of a module. Chunks are written in the order they are added to
+/// the `Builder`.
+#[derive(Debug)]
+pub enum Chunk<'a> {
+    Rdef(RdefChunk<'a>),
+    Isgn(IOsgnChunk),
+    Osgn(IOsgnChunk),
+    Isg1(IOsgnChunk),
+    Osg1(IOsgnChunk),
+    Osg5(IOsgnChunk),
+    Pcsg(IOsgnChunk),
+    Shex(ShexChunk),
+    Stat(IStatChunk),
+    Sfi0(Sfi0Chunk),
+    Rts0(Rts0Chunk),
+    Aon9(Aon9Chunk),
+    Xnas(Aon9Chunk),
+    Xnap(Aon9Chunk),
+    Dxil(DxilChunk<'a>),
+    Ildb(DxilChunk<'a>),
+    Hash(HashChunk),
+    Ildn(IldnChunk<'a>),
+    /// A chunk that is written as is, like one this crate can't write.
+    Raw([u8; 4], Vec<u8>),
+}
+
+/// Why a `Builder` couldn't write its module.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// An operand has more indices than the three its token has room for.
+    TooManyIndices,
+    /// An instruction other than `customdata` is longer than the length
+    /// field of its opcode token allows. Holds the dword offset of the
+    /// instruction in the module.
+    InstructionTooLong(usize),
+    /// A swizzle or select component isn't exactly one of `X`, `Y`, `Z` or
+    /// `W`.
+    InvalidComponent(u8),
+    /// A control point patch has a count outside of 1 to 32.
+    InvalidControlPointCount(u32),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::TooManyIndices => write!(f, "operand has more than 3 indices"),
+            BuildError::InstructionTooLong(offset) => write!(
+                f,
+                "instruction at dword {} is longer than {} dwords",
+                offset, MAX_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH
+            ),
+            BuildError::InvalidComponent(component) => {
+                write!(f, "invalid swizzle component {:#x}", component)
+            }
+            BuildError::InvalidControlPointCount(count) => {
+                write!(f, "invalid control point count {}", count)
+            }
+        }
+    }
+}
+
+impl error::Error for BuildError {}
 
 pub struct Builder<'a> {
-    rdef: Option<RdefChunk<'a>>,
-    isgn: Option<IOsgnChunk>,
-    osgn: Option<IOsgnChunk>,
-    stat: Option<IStatChunk>,
-    shex: Option<ShexChunk>,
+    chunks: Vec<Chunk<'a>>,
     _code: Vec<u32>,
 }
 
@@ -45,6 +132,12 @@ impl DxbcModule {
         self.dwords.push(val);
     }
 
+    /// Writes the low dword first, like the parser reads it.
+    pub fn write_u64(&mut self, val: u64) {
+        self.write_u32(val as u32);
+        self.write_u32((val >> 32) as u32);
+    }
+
     pub fn set_u32_slice(&mut self, offset: usize, val: &[u32]) {
         self.dwords[offset..].copy_from_slice(val);
     }
@@ -109,105 +202,366 @@ impl DxbcModule {
         self.write_u32(stat.texture_comp_instructions);
         self.write_u32(stat.texture_bias_instructions);
         self.write_u32(stat.texture_gradient_instructions);
-
-        for _ in 0..18 {
-            self.write_u32(0);
+        self.write_u32(stat.mov_instruction_count);
+        self.write_u32(stat.movc_instruction_count);
+        self.write_u32(stat.conversion_instruction_count);
+        self.write_u32(stat.bitwise_instruction_count);
+        self.write_u32(stat.gs_input_primitive);
+        self.write_u32(stat.gs_output_topology);
+        self.write_u32(stat.gs_max_output_vertex_count);
+        self.write_u32(stat.unknown[0]);
+        self.write_u32(stat.unknown[1]);
+        self.write_u32(stat.is_sample_frequency);
+
+        if let Some(ref sm5) = stat.sm5 {
+            self.write_u32(sm5.gs_instance_count);
+            self.write_u32(sm5.control_points);
+            self.write_u32(sm5.hs_output_primitive);
+            self.write_u32(sm5.hs_partitioning);
+            self.write_u32(sm5.tessellator_domain);
+            self.write_u32(sm5.barrier_instructions);
+            self.write_u32(sm5.interlocked_instructions);
+            self.write_u32(sm5.texture_store_instructions);
         }
 
         let end_pos = self.position();
         self.set_u32(stat_size_pos, 4 * (end_pos - chunk_start) as u32);
     }
 
-    pub fn write_rdef(&mut self, rdef: &RdefChunk) {
-        self.write_u32(RDEF_MAGIC);
-        let rdef_size_pos = self.position();
+    pub fn write_sfi0(&mut self, sfi0: &Sfi0Chunk) {
+        self.write_u32(SFI0_MAGIC);
+        self.write_u32(8);
+
+        let flags = sfi0.flags.bits();
+        self.write_u32(flags as u32);
+        self.write_u32((flags >> 32) as u32);
+    }
+
+    pub fn write_rts0(&mut self, rts0: &Rts0Chunk) {
+        self.write_u32(RTS0_MAGIC);
+        let rts0_size_pos = self.position();
         self.write_u32(0);
         let chunk_start = self.position();
-        self.write_u32(rdef.constant_buffers.len() as u32);
-        let constant_buffers_pos = self.position();
-        self.write_u32(0);
-        self.write_u32(rdef.resource_bindings.len() as u32);
-        let resource_bindings_pos = self.position();
-        self.write_u32(0);
 
-        let version_tok = (((rdef.program_ty as u32) << 16) & 0xffff0000)
-            | (((rdef.major as u32) << 4) & 0x000000f0)
-            | (rdef.minor as u32 & 0x0000000f);
-        self.write_u32(version_tok);
-        self.write_u32(rdef.flags);
-        let author_pos = self.position();
+        let v1_1 = rts0.version == RootSignatureVersion::V1_1;
+
+        self.write_u32(rts0.version as u32);
+        self.write_u32(rts0.parameters.len() as u32);
+        let parameter_offset_pos = self.position();
+        self.write_u32(0);
+        self.write_u32(rts0.static_samplers.len() as u32);
+        let static_sampler_offset_pos = self.position();
         self.write_u32(0);
+        self.write_u32(rts0.flags.bits());
 
-        // TODO: figure out the deal with RD11
-        if let Some(_rd11) = rdef.rd11 {
-            self.write_u32(RD11_MAGIC);
-            self.write_u32(60);
-            self.write_u32(24);
-            self.write_u32(32);
-            self.write_u32(40);
-            self.write_u32(36);
-            self.write_u32(12);
+        self.set_u32(
+            parameter_offset_pos,
+            4 * (self.position() - chunk_start) as u32,
+        );
+        let mut payload_offset_positions = Vec::new();
+        for parameter in &rts0.parameters {
+            self.write_u32(parameter.get_type() as u32);
+            self.write_u32(parameter.visibility as u32);
+            payload_offset_positions.push(self.position());
             self.write_u32(0);
-            // self.write_u32(rd11[0]);
-            // self.write_u32(rd11[1]);
-            // self.write_u32(rd11[2]);
-            // self.write_u32(rd11[3]);
-            // self.write_u32(rd11[4]);
-            // self.write_u32(rd11[5]);
-            // self.write_u32(rd11[6]);
         }
 
-        let constant_buffers_loc = (self.position() - chunk_start) as u32;
-        self.set_u32(constant_buffers_pos, constant_buffers_loc);
-        for _constant_buffer in &rdef.constant_buffers {
-            // TODO: write constant buffers
+        for (parameter, pos) in rts0.parameters.iter().zip(payload_offset_positions) {
+            self.set_u32(pos, 4 * (self.position() - chunk_start) as u32);
+
+            match parameter.data {
+                RootParameterData::DescriptorTable(ref ranges) => {
+                    self.write_u32(ranges.len() as u32);
+                    // the ranges directly follow the table
+                    self.write_u32(4 * (self.position() + 1 - chunk_start) as u32);
+
+                    for range in ranges {
+                        self.write_u32(range.ty as u32);
+                        self.write_u32(range.count);
+                        self.write_u32(range.base_register);
+                        self.write_u32(range.space);
+                        if v1_1 {
+                            self.write_u32(range.flags.bits());
+                        }
+                        self.write_u32(range.offset);
+                    }
+                }
+                RootParameterData::Constants32Bit(ref constants) => {
+                    self.write_u32(constants.register);
+                    self.write_u32(constants.space);
+                    self.write_u32(constants.count);
+                }
+                RootParameterData::Cbv(ref descriptor)
+                | RootParameterData::Srv(ref descriptor)
+                | RootParameterData::Uav(ref descriptor) => {
+                    self.write_u32(descriptor.register);
+                    self.write_u32(descriptor.space);
+                    if v1_1 {
+                        self.write_u32(descriptor.flags.bits());
+                    }
+                }
+            }
         }
 
-        let resource_bindings_loc = (self.position() - chunk_start) as u32;
-        self.set_u32(resource_bindings_pos, resource_bindings_loc);
-        for _resource_binding in &rdef.resource_bindings {
-            // TODO: write resource bindings
+        self.set_u32(
+            static_sampler_offset_pos,
+            4 * (self.position() - chunk_start) as u32,
+        );
+        for sampler in &rts0.static_samplers {
+            self.write_u32(sampler.filter);
+            self.write_u32(sampler.address_u as u32);
+            self.write_u32(sampler.address_v as u32);
+            self.write_u32(sampler.address_w as u32);
+            self.write_u32(sampler.mip_lod_bias.to_bits());
+            self.write_u32(sampler.max_anisotropy);
+            self.write_u32(sampler.comparison_func as u32);
+            self.write_u32(sampler.border_color as u32);
+            self.write_u32(sampler.min_lod.to_bits());
+            self.write_u32(sampler.max_lod.to_bits());
+            self.write_u32(sampler.register);
+            self.write_u32(sampler.space);
+            self.write_u32(sampler.visibility as u32);
         }
 
-        let author_loc = 4 * (self.position() - chunk_start) as u32;
-        self.set_u32(author_pos, author_loc);
-        self.write_str(rdef.author);
-
         let end_pos = self.position();
-        self.set_u32(rdef_size_pos, 4 * (end_pos - chunk_start) as u32);
+        self.set_u32(rts0_size_pos, 4 * (end_pos - chunk_start) as u32);
     }
 
-    pub fn write_iosgn(&mut self, chunk: &IOsgnChunk, magic: u32) {
+    pub fn write_level9(&mut self, chunk: &Aon9Chunk, magic: u32) {
         self.write_u32(magic);
         let chunk_sz_pos = self.position();
         self.write_u32(0);
         let chunk_start = self.position();
-        self.write_u32(chunk.elements.len() as u32);
-        // TODO: unknown
-        self.write_u32(0x8);
 
-        let mut string_positions = Vec::new();
-        for element in &chunk.elements {
-            string_positions.push(self.position());
-            self.write_u32(0);
-            self.write_u32(element.semantic_index);
-            self.write_u32(element.semantic_type as u32);
-            self.write_u32(element.component_type as u32);
-            self.write_u32(element.register);
-            let mask_tok = ((element.rw_mask as u32) << 8) | (element.component_mask as u32);
-            self.write_u32(mask_tok);
+        self.write_u32(chunk.unknown);
+        let shader_size_pos = self.position();
+        self.write_u32(0);
+        let shader_offset_pos = self.position();
+        self.write_u32(0);
+        for &mapping in &chunk.mappings {
+            self.write_u32(mapping);
         }
 
-        for (element, pos) in chunk.elements.iter().zip(string_positions) {
-            let name_offset = self.position() - chunk_start;
-            self.set_u32(pos, 4 * name_offset as u32);
-            self.write_str(&element.name);
+        let shader_start = self.position();
+        self.set_u32(shader_offset_pos, 4 * (shader_start - chunk_start) as u32);
+        self.write_u32(chunk.version.token());
+        for instruction in &chunk.instructions {
+            self.write_sm2_instruction(instruction);
         }
+        self.write_u32(SM2_END_TOKEN);
+        self.set_u32(shader_size_pos, 4 * (self.position() - shader_start) as u32);
 
         let chunk_sz = self.position() - chunk_start;
         self.set_u32(chunk_sz_pos, 4 * chunk_sz as u32);
     }
 
+    pub fn write_aon9(&mut self, chunk: &Aon9Chunk) {
+        self.write_level9(chunk, AON9_MAGIC);
+    }
+
+    pub fn write_xnas(&mut self, chunk: &Aon9Chunk) {
+        self.write_level9(chunk, XNAS_MAGIC);
+    }
+
+    pub fn write_xnap(&mut self, chunk: &Aon9Chunk) {
+        self.write_level9(chunk, XNAP_MAGIC);
+    }
+
+    pub fn write_sm2_instruction(&mut self, instruction: &Sm2Instruction) {
+        let opcode = instruction.get_opcode() as u32;
+
+        if let Sm2Instruction::Comment(ref data) = *instruction {
+            self.write_u32(opcode | ((data.len() as u32) << 16));
+            for &word in data {
+                self.write_u32(word);
+            }
+            return;
+        }
+
+        let token_pos = self.position();
+        self.write_u32(opcode);
+
+        let mut token = opcode;
+        match *instruction {
+            Sm2Instruction::Dcl(ref dcl) => {
+                self.write_u32(dcl.semantic);
+                self.write_sm2_destination(&dcl.dst);
+            }
+            Sm2Instruction::Def { ref dst, values } => {
+                self.write_sm2_destination(dst);
+                for value in values {
+                    self.write_u32(value.to_bits());
+                }
+            }
+            Sm2Instruction::DefI { ref dst, values } => {
+                self.write_sm2_destination(dst);
+                for value in values {
+                    self.write_u32(value as u32);
+                }
+            }
+            Sm2Instruction::DefB { ref dst, value } => {
+                self.write_sm2_destination(dst);
+                self.write_u32(value as u32);
+            }
+            Sm2Instruction::Texture(ref operation)
+            | Sm2Instruction::Arithmetic(ref operation)
+            | Sm2Instruction::FlowControl(ref operation) => {
+                token |= self.write_sm2_operation(operation);
+            }
+            Sm2Instruction::Comment(_) | Sm2Instruction::Phase => {}
+        }
+
+        let len = (self.position() - token_pos - 1) as u32;
+        self.set_u32(token_pos, token | (len << 24));
+    }
+
+    /// Writes the parameters of `operation` and returns its instruction
+    /// token bits.
+    fn write_sm2_operation(&mut self, operation: &Sm2Operation) -> u32 {
+        if let Some(ref dst) = operation.dst {
+            self.write_sm2_destination(dst);
+        }
+        if let Some(ref predicate) = operation.predicate {
+            self.write_sm2_source(predicate);
+        }
+        for src in &operation.src {
+            self.write_sm2_source(src);
+        }
+
+        ((operation.controls as u32) << 16)
+            | ((operation.predicate.is_some() as u32) << 28)
+            | ((operation.coissue as u32) << 30)
+    }
+
+    fn sm2_register_token(ty: Sm2RegisterType, register: u16, relative: bool) -> u32 {
+        let ty = ty as u32;
+
+        0x8000_0000
+            | ((ty & 0x7) << 28)
+            | ((ty & 0x18) << 8)
+            | ((relative as u32) << 13)
+            | (register as u32 & 0x7ff)
+    }
+
+    pub fn write_sm2_destination(&mut self, dst: &Sm2DestinationParameter) {
+        let token = Self::sm2_register_token(dst.ty, dst.register, dst.relative.is_some())
+            | ((dst.write_mask as u32 & 0xf) << 16)
+            | ((dst.result_modifier.bits() as u32) << 20)
+            | ((dst.shift as u32 & 0xf) << 24);
+        self.write_u32(token);
+
+        if let Some(ref relative) = dst.relative {
+            self.write_sm2_source(relative);
+        }
+    }
+
+    pub fn write_sm2_source(&mut self, src: &Sm2SourceParameter) {
+        let mut token = Self::sm2_register_token(src.ty, src.register, src.relative.is_some())
+            | ((src.modifier as u32) << 24);
+        for (idx, &component) in src.swizzle.iter().enumerate() {
+            token |= (component as u32 & 0x3) << (16 + 2 * idx);
+        }
+        self.write_u32(token);
+
+        if let Some(ref relative) = src.relative {
+            self.write_sm2_source(relative);
+        }
+    }
+
+    pub fn write_chunk(&mut self, chunk: &Chunk) -> Result<(), BuildError> {
+        match chunk {
+            Chunk::Rdef(rdef) => self.write_rdef(rdef),
+            Chunk::Isgn(isgn) => self.write_isgn(isgn),
+            Chunk::Osgn(osgn) => self.write_osgn(osgn),
+            Chunk::Isg1(isg1) => self.write_iosgn(isg1, ISG1_MAGIC),
+            Chunk::Osg1(osg1) => self.write_iosgn(osg1, OSG1_MAGIC),
+            Chunk::Osg5(osg5) => self.write_iosgn(osg5, OSG5_MAGIC),
+            Chunk::Pcsg(pcsg) => self.write_iosgn(pcsg, PCSG_MAGIC),
+            Chunk::Shex(shex) => self.write_shex(shex)?,
+            Chunk::Stat(stat) => self.write_stat(stat),
+            Chunk::Sfi0(sfi0) => self.write_sfi0(sfi0),
+            Chunk::Rts0(rts0) => self.write_rts0(rts0),
+            Chunk::Aon9(aon9) => self.write_aon9(aon9),
+            Chunk::Xnas(xnas) => self.write_xnas(xnas),
+            Chunk::Xnap(xnap) => self.write_xnap(xnap),
+            Chunk::Dxil(dxil) => self.write_dxil(dxil),
+            Chunk::Ildb(ildb) => self.write_ildb(ildb),
+            Chunk::Hash(hash) => self.write_hash(hash),
+            Chunk::Ildn(ildn) => self.write_ildn(ildn),
+            Chunk::Raw(fourcc, data) => self.write_raw_chunk(*fourcc, data),
+        }
+
+        Ok(())
+    }
+
+    pub fn write_program(&mut self, chunk: &DxilChunk, magic: u32) {
+        let mut data = Vec::new();
+        let version =
+            ((chunk.kind as u32) << 16) | ((chunk.major as u32) << 4) | chunk.minor as u32;
+        data.extend_from_slice(&version.to_le_bytes());
+        data.extend_from_slice(&chunk.size.to_le_bytes());
+        data.extend_from_slice(&DXIL_MAGIC.to_le_bytes());
+        let dxil_version = ((chunk.dxil_major as u32) << 8) | chunk.dxil_minor as u32;
+        data.extend_from_slice(&dxil_version.to_le_bytes());
+        // the bitcode directly follows the bitcode header
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&(chunk.bitcode.len() as u32).to_le_bytes());
+        data.extend_from_slice(chunk.bitcode);
+
+        self.write_raw_chunk(magic.to_le_bytes(), &data);
+    }
+
+    pub fn write_dxil(&mut self, chunk: &DxilChunk) {
+        self.write_program(chunk, DXIL_MAGIC);
+    }
+
+    pub fn write_ildb(&mut self, chunk: &DxilChunk) {
+        self.write_program(chunk, ILDB_MAGIC);
+    }
+
+    pub fn write_hash(&mut self, chunk: &HashChunk) {
+        let mut data = chunk.flags.bits().to_le_bytes().to_vec();
+        data.extend_from_slice(&chunk.digest);
+
+        self.write_raw_chunk(HASH_MAGIC.to_le_bytes(), &data);
+    }
+
+    pub fn write_ildn(&mut self, chunk: &IldnChunk) {
+        let mut data = chunk.flags.to_le_bytes().to_vec();
+        data.extend_from_slice(&(chunk.name.len() as u16).to_le_bytes());
+        data.extend_from_slice(chunk.name.as_bytes());
+        data.push(0);
+        data.resize((data.len() + 3) & !3, 0);
+
+        self.write_raw_chunk(ILDN_MAGIC.to_le_bytes(), &data);
+    }
+
+    /// Writes a chunk verbatim, padding its data to a whole number of dwords.
+    pub fn write_raw_chunk(&mut self, fourcc: [u8; 4], data: &[u8]) {
+        self.write_u32(u32::from_le_bytes(fourcc));
+        self.write_u32(data.len() as u32);
+
+        for word in data.chunks(4) {
+            let mut bytes = [0; 4];
+            bytes[..word.len()].copy_from_slice(word);
+            self.write_u32(u32::from_le_bytes(bytes));
+        }
+    }
+
+    pub fn write_rdef(&mut self, rdef: &RdefChunk) {
+        let mut writer = ChunkWriter::default();
+        writer.write_rdef(rdef);
+
+        self.write_raw_chunk(RDEF_MAGIC.to_le_bytes(), &writer.bytes);
+    }
+
+    pub fn write_iosgn(&mut self, chunk: &IOsgnChunk, magic: u32) {
+        let mut writer = ChunkWriter::default();
+        writer.write_signature(chunk);
+
+        self.write_raw_chunk(magic.to_le_bytes(), &writer.bytes);
+    }
+
     pub fn write_isgn(&mut self, chunk: &IOsgnChunk) {
         self.write_iosgn(chunk, ISGN_MAGIC);
     }
@@ -216,28 +570,35 @@ impl DxbcModule {
         self.write_iosgn(chunk, OSGN_MAGIC);
     }
 
-    pub fn write_shex(&mut self, chunk: &ShexChunk) {
-        self.write_u32(SHEX_MAGIC);
+    pub fn write_shex(&mut self, chunk: &ShexChunk) -> Result<(), BuildError> {
+        // shader model 4 programs are stored in `SHDR` chunks
+        self.write_u32(if chunk.major >= 5 {
+            SHEX_MAGIC
+        } else {
+            SHDR_MAGIC
+        });
         let chunk_sz_pos = self.position();
         self.write_u32(0);
         let chunk_start = self.position();
 
         self.write_u32(ENCODE_D3D10_SB_TOKENIZED_PROGRAM_VERSION_TOKEN(
-            D3D10_SB_VERTEX_SHADER,
-            5,
-            0,
+            chunk.program_type as u32,
+            chunk.major as u32,
+            chunk.minor as u32,
         ));
 
         let word_sz_pos = self.position();
         self.write_u32(0);
 
         for instr in &chunk.instructions {
-            instr.encode(self);
+            instr.encode(self)?;
         }
 
         let chunk_sz = self.position() - chunk_start;
         self.set_u32(word_sz_pos, chunk_sz as u32);
         self.set_u32(chunk_sz_pos, 4 * chunk_sz as u32);
+
+        Ok(())
     }
 
     pub fn write_opcode(
@@ -257,7 +618,7 @@ impl DxbcModule {
         }
 
         if let Some(test) = test {
-            opcode |= ENCODE_D3D10_SB_INSTRUCTION_TEST_BOOLEAN(test as u32);
+            opcode |= ENCODE_D3D10_SB_INSTRUCTION_TEST_BOOLEAN(test);
         }
 
         self.write_u32(opcode);
@@ -321,19 +682,334 @@ impl DxbcModule {
         }
     }
 
+    /// Writes an instruction decoded by the parser back token for token.
+    pub fn write_instruction(&mut self, instruction: &shex::Instruction) -> Result<(), BuildError> {
+        let start = self.position();
+
+        let (opcode, controls, extended) = match instruction {
+            shex::Instruction::CustomData { class, data } => {
+                self.write_custom_data(*class, data);
+                return Ok(());
+            }
+            shex::Instruction::Declaration(declaration) => {
+                return self.write_declaration(declaration);
+            }
+            shex::Instruction::Operation {
+                opcode,
+                controls,
+                extended,
+                ..
+            } => (*opcode, *controls, &extended[..]),
+            shex::Instruction::InterfaceCall { controls, .. } => {
+                (shex::Opcode::InterfaceCall, *controls, &[][..])
+            }
+        };
+
+        let mut token = ENCODE_D3D10_SB_OPCODE_TYPE(opcode as u32) | (controls << 11);
+        if !extended.is_empty() {
+            token |= ENCODE_D3D10_SB_OPCODE_EXTENDED(1);
+        }
+        self.write_u32(token);
+
+        for (idx, extension) in extended.iter().enumerate() {
+            let opcode_ex = match *extension {
+                shex::OpcodeExtension::SampleControls([u, v, w]) => {
+                    OpcodeEx::UvOffset(u as u32, v as u32, w as u32)
+                }
+                shex::OpcodeExtension::ResourceDim { dimension, stride } => {
+                    OpcodeEx::Dimension(dimension, stride)
+                }
+                shex::OpcodeExtension::ResourceReturnType([x, y, z, w]) => {
+                    OpcodeEx::ResourceReturnType(x, y, z, w)
+                }
+            };
+
+            self.write_opcode_ex(opcode_ex, idx != extended.len() - 1);
+        }
+
+        match instruction {
+            shex::Instruction::Operation { operands, .. } => {
+                for operand in operands {
+                    self.write_decoded_operand(operand)?;
+                }
+            }
+            shex::Instruction::InterfaceCall {
+                function_index,
+                interface,
+                ..
+            } => {
+                self.write_u32(*function_index);
+                self.write_decoded_operand(interface)?;
+            }
+            shex::Instruction::Declaration(..) | shex::Instruction::CustomData { .. } => {
+                unreachable!()
+            }
+        }
+
+        self.set_instruction_length(start)
+    }
+
+    /// Writes a declaration decoded by the parser back token for token.
+    pub fn write_declaration(&mut self, declaration: &shex::Declaration) -> Result<(), BuildError> {
+        let start = self.position();
+
+        self.write_u32(
+            ENCODE_D3D10_SB_OPCODE_TYPE(declaration.opcode() as u32) | declaration.controls(),
+        );
+
+        match declaration {
+            shex::Declaration::GlobalFlags { .. }
+            | shex::Declaration::GsInputPrimitive { .. }
+            | shex::Declaration::GsOutputPrimitiveTopology { .. }
+            | shex::Declaration::InputControlPointCount { .. }
+            | shex::Declaration::OutputControlPointCount { .. }
+            | shex::Declaration::TessellatorDomain { .. }
+            | shex::Declaration::TessellatorPartitioning { .. }
+            | shex::Declaration::TessellatorOutputPrimitive { .. } => {}
+            &shex::Declaration::Temps { count }
+            | &shex::Declaration::MaxOutputVertexCount { count }
+            | &shex::Declaration::GsInstanceCount { count }
+            | &shex::Declaration::HsForkPhaseInstanceCount { count }
+            | &shex::Declaration::HsJoinPhaseInstanceCount { count }
+            | &shex::Declaration::FunctionBody { index: count } => self.write_u32(count),
+            &shex::Declaration::IndexableTemp {
+                register,
+                count,
+                components,
+            } => {
+                self.write_u32(register);
+                self.write_u32(count);
+                self.write_u32(components);
+            }
+            shex::Declaration::Input { register }
+            | shex::Declaration::InputPs { register, .. }
+            | shex::Declaration::Output { register }
+            | shex::Declaration::Stream { register } => self.write_decoded_operand(register)?,
+            shex::Declaration::InputSgv { register, semantic }
+            | shex::Declaration::InputSiv { register, semantic }
+            | shex::Declaration::InputPsSgv {
+                register, semantic, ..
+            }
+            | shex::Declaration::InputPsSiv {
+                register, semantic, ..
+            }
+            | shex::Declaration::OutputSgv { register, semantic }
+            | shex::Declaration::OutputSiv { register, semantic } => {
+                self.write_decoded_operand(register)?;
+                self.write_u32(ENCODE_D3D10_SB_NAME(*semantic as u32));
+            }
+            shex::Declaration::IndexRange { register, count }
+            | shex::Declaration::TgsmRaw { register, count } => {
+                self.write_decoded_operand(register)?;
+                self.write_u32(*count);
+            }
+            shex::Declaration::TgsmStructured {
+                register,
+                stride,
+                count,
+            } => {
+                self.write_decoded_operand(register)?;
+                self.write_u32(*stride);
+                self.write_u32(*count);
+            }
+            shex::Declaration::ConstantBuffer {
+                register,
+                size,
+                space,
+                ..
+            } => {
+                self.write_decoded_operand(register)?;
+                self.dwords.extend(size);
+                self.dwords.extend(space);
+            }
+            shex::Declaration::Sampler {
+                register, space, ..
+            }
+            | shex::Declaration::ResourceRaw { register, space }
+            | shex::Declaration::UavRaw {
+                register, space, ..
+            } => {
+                self.write_decoded_operand(register)?;
+                self.dwords.extend(space);
+            }
+            shex::Declaration::Resource {
+                register,
+                return_type,
+                space,
+                ..
+            }
+            | shex::Declaration::UavTyped {
+                register,
+                return_type,
+                space,
+                ..
+            } => {
+                self.write_decoded_operand(register)?;
+                self.write_u32(return_type_token(return_type));
+                self.dwords.extend(space);
+            }
+            shex::Declaration::ResourceStructured {
+                register,
+                stride,
+                space,
+            }
+            | shex::Declaration::UavStructured {
+                register,
+                stride,
+                space,
+                ..
+            } => {
+                self.write_decoded_operand(register)?;
+                self.write_u32(*stride);
+                self.dwords.extend(space);
+            }
+            &shex::Declaration::ThreadGroup { x, y, z } => {
+                self.write_u32(x);
+                self.write_u32(y);
+                self.write_u32(z);
+            }
+            shex::Declaration::HsMaxTessFactor { factor } => self.write_u32(factor.to_bits()),
+            shex::Declaration::FunctionTable { index, bodies } => {
+                self.write_u32(*index);
+                self.write_u32(bodies.len() as u32);
+                self.dwords.extend_from_slice(bodies);
+            }
+            shex::Declaration::Interface {
+                index,
+                body_count,
+                array_length,
+                tables,
+                ..
+            } => {
+                self.write_u32(*index);
+                self.write_u32(*body_count);
+                self.write_u32(
+                    ENCODE_D3D11_SB_INTERFACE_TABLE_LENGTH(tables.len() as u32)
+                        | ENCODE_D3D11_SB_INTERFACE_ARRAY_LENGTH(*array_length),
+                );
+                self.dwords.extend_from_slice(tables);
+            }
+        }
+
+        self.set_instruction_length(start)
+    }
+
+    /// Writes an operand decoded by the parser, including its relative
+    /// addresses.
+    pub fn write_decoded_operand(&mut self, operand: &shex::Operand) -> Result<(), BuildError> {
+        if operand.indices.len() > 3 {
+            return Err(BuildError::TooManyIndices);
+        }
+
+        let mut token = ENCODE_D3D10_SB_OPERAND_TYPE(operand.ty as u32)
+            | ENCODE_D3D10_SB_OPERAND_INDEX_DIMENSION(operand.indices.len() as u32);
+
+        token |= match operand.components {
+            shex::OperandComponents::Zero => {
+                ENCODE_D3D10_SB_OPERAND_NUM_COMPONENTS(D3D10_SB_OPERAND_0_COMPONENT)
+            }
+            shex::OperandComponents::One => {
+                ENCODE_D3D10_SB_OPERAND_NUM_COMPONENTS(D3D10_SB_OPERAND_1_COMPONENT)
+            }
+            shex::OperandComponents::Mask(mask) => {
+                ENCODE_D3D10_SB_OPERAND_NUM_COMPONENTS(D3D10_SB_OPERAND_4_COMPONENT)
+                    | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
+                        D3D10_SB_OPERAND_4_COMPONENT_MASK_MODE,
+                    )
+                    | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_MASK(mask.bits())
+            }
+            shex::OperandComponents::Swizzle(shex::ComponentSwizzle(x, y, z, w)) => {
+                ENCODE_D3D10_SB_OPERAND_NUM_COMPONENTS(D3D10_SB_OPERAND_4_COMPONENT)
+                    | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
+                        D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_MODE,
+                    )
+                    | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE(
+                        x as u32, y as u32, z as u32, w as u32,
+                    )
+            }
+            shex::OperandComponents::Select1(component) => {
+                ENCODE_D3D10_SB_OPERAND_NUM_COMPONENTS(D3D10_SB_OPERAND_4_COMPONENT)
+                    | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
+                        D3D10_SB_OPERAND_4_COMPONENT_SELECT_1_MODE,
+                    )
+                    | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECT_1(component as u32)
+            }
+            shex::OperandComponents::N(count) => {
+                ENCODE_D3D10_SB_OPERAND_NUM_COMPONENTS(D3D10_SB_OPERAND_N_COMPONENT)
+                    | ((count & 0xff) << 4)
+            }
+        };
+
+        for (idx, index) in operand.indices.iter().enumerate() {
+            let repr = match index {
+                shex::OperandIndex::Immediate32(..) => D3D10_SB_OPERAND_INDEX_IMMEDIATE32,
+                shex::OperandIndex::Immediate64(..) => D3D10_SB_OPERAND_INDEX_IMMEDIATE64,
+                shex::OperandIndex::Relative(..) => D3D10_SB_OPERAND_INDEX_RELATIVE,
+                shex::OperandIndex::Immediate32PlusRelative(..) => {
+                    D3D10_SB_OPERAND_INDEX_IMMEDIATE32_PLUS_RELATIVE
+                }
+                shex::OperandIndex::Immediate64PlusRelative(..) => {
+                    D3D10_SB_OPERAND_INDEX_IMMEDIATE64_PLUS_RELATIVE
+                }
+            };
+
+            token |= ENCODE_D3D10_SB_OPERAND_INDEX_REPRESENTATION(idx as u32, repr);
+        }
+
+        let extended = operand.modifier != shex::OperandModifier::None
+            || operand.min_precision != shex::MinPrecision::Default
+            || operand.non_uniform;
+        if extended {
+            token |= ENCODE_D3D10_SB_OPERAND_EXTENDED(1);
+        }
+
+        self.write_u32(token);
+
+        if extended {
+            let mut extended_token =
+                ENCODE_D3D10_SB_EXTENDED_OPERAND_MODIFIER(operand.modifier as u32)
+                    | ENCODE_D3D11_SB_OPERAND_MIN_PRECISION(operand.min_precision as u32);
+            if operand.non_uniform {
+                extended_token |= shex::OPERAND_NON_UNIFORM_MASK;
+            }
+
+            self.write_u32(extended_token);
+        }
+
+        self.dwords.extend_from_slice(&operand.immediates);
+
+        for index in &operand.indices {
+            match index {
+                shex::OperandIndex::Immediate32(val) => self.write_u32(*val),
+                shex::OperandIndex::Immediate64(val) => self.write_u64(*val),
+                shex::OperandIndex::Relative(relative) => self.write_decoded_operand(relative)?,
+                shex::OperandIndex::Immediate32PlusRelative(val, relative) => {
+                    self.write_u32(*val);
+                    self.write_decoded_operand(relative)?;
+                }
+                shex::OperandIndex::Immediate64PlusRelative(val, relative) => {
+                    self.write_u64(*val);
+                    self.write_decoded_operand(relative)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn write_operand(
         &mut self,
         op: u32,
         modifier: Modifier,
+        min_precision: MinPrecision,
         component_mode: NumComponent,
         immediates: &[Immediate],
-    ) {
-        debug_assert!(immediates.len() < 4);
-
+    ) -> Result<(), BuildError> {
         let mut operand = ENCODE_D3D10_SB_OPERAND_TYPE(op);
 
-        if let Modifier::None = modifier {
-        } else {
+        let extended =
+            !matches!(modifier, Modifier::None) || min_precision != MinPrecision::Default;
+        if extended {
             operand |= ENCODE_D3D10_SB_OPERAND_EXTENDED(1);
         }
 
@@ -362,79 +1038,101 @@ impl DxbcModule {
             1 => D3D10_SB_OPERAND_INDEX_1D,
             2 => D3D10_SB_OPERAND_INDEX_2D,
             3 => D3D10_SB_OPERAND_INDEX_3D,
-            _ => unreachable!(),
+            _ => return Err(BuildError::TooManyIndices),
         };
 
         operand |= ENCODE_D3D10_SB_OPERAND_INDEX_DIMENSION(immediate_arity);
 
+        let component_index = |component: u8| match component {
+            X => Ok(D3D10_SB_4_COMPONENT_X),
+            Y => Ok(D3D10_SB_4_COMPONENT_Y),
+            Z => Ok(D3D10_SB_4_COMPONENT_Z),
+            W => Ok(D3D10_SB_4_COMPONENT_W),
+            _ => Err(BuildError::InvalidComponent(component)),
+        };
+
         match component_mode {
             NumComponent::D0 | NumComponent::D1 => {}
             NumComponent::D4(mode) => match mode {
                 ComponentMode::Mask(mask) => {
                     operand |= ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
                         D3D10_SB_OPERAND_4_COMPONENT_MASK_MODE,
-                    ) | mask as u32;
+                    ) | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_MASK(mask as u32);
                 }
                 ComponentMode::Swizzle(x, y, z, w) => {
                     operand |= ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
                         D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_MODE,
                     ) | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE(
-                        x as u32 >> 5,
-                        y as u32 >> 5,
-                        z as u32 >> 5,
-                        w as u32 >> 5,
+                        component_index(x)?,
+                        component_index(y)?,
+                        component_index(z)?,
+                        component_index(w)?,
                     );
                 }
                 ComponentMode::Select(comp) => {
-                    operand |= ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
-                        D3D10_SB_OPERAND_4_COMPONENT_SELECT_1_MODE,
-                    ) | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECT_1(comp as u32 >> 5);
+                    operand |=
+                        ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(
+                            D3D10_SB_OPERAND_4_COMPONENT_SELECT_1_MODE,
+                        ) | ENCODE_D3D10_SB_OPERAND_4_COMPONENT_SELECT_1(component_index(comp)?);
                 }
             },
         }
 
         self.write_u32(operand);
 
-        let operand_modifier = match modifier {
-            Modifier::None => None,
-            Modifier::Neg => Some(D3D10_SB_OPERAND_MODIFIER_NEG),
-            Modifier::Abs => Some(D3D10_SB_OPERAND_MODIFIER_ABS),
-            Modifier::AbsNeg => Some(D3D10_SB_OPERAND_MODIFIER_ABSNEG),
-        };
+        if extended {
+            let operand_modifier = match modifier {
+                Modifier::None => D3D10_SB_OPERAND_MODIFIER_NONE,
+                Modifier::Neg => D3D10_SB_OPERAND_MODIFIER_NEG,
+                Modifier::Abs => D3D10_SB_OPERAND_MODIFIER_ABS,
+                Modifier::AbsNeg => D3D10_SB_OPERAND_MODIFIER_ABSNEG,
+            };
 
-        if let Some(modifier) = operand_modifier {
-            self.write_u32(ENCODE_D3D10_SB_EXTENDED_OPERAND_MODIFIER(modifier));
+            self.write_u32(
+                ENCODE_D3D10_SB_EXTENDED_OPERAND_MODIFIER(operand_modifier)
+                    | ENCODE_D3D11_SB_OPERAND_MIN_PRECISION(min_precision as u32),
+            );
         }
 
         for imm in immediates {
-            let relative = match *imm {
-                Immediate::U32(val) => {
-                    self.write_u32(val);
-                    None
-                }
-                Immediate::U64(val) => {
-                    // TODO: u64
-                    self.write_u32(val as u32);
-                    None
-                }
-                Immediate::Relative(ref rel) => Some(rel),
+            match *imm {
+                Immediate::U32(val) => self.write_u32(val),
+                Immediate::U64(val) => self.write_u64(val),
+                Immediate::Relative(ref rel) => rel.encode(self)?,
                 Immediate::U32Relative(val, ref rel) => {
                     self.write_u32(val);
-                    Some(rel)
+                    rel.encode(self)?;
                 }
                 Immediate::U64Relative(val, ref rel) => {
-                    // TODO: u64
-                    self.write_u32(val as u32);
-                    Some(rel)
+                    self.write_u64(val);
+                    rel.encode(self)?;
                 }
-            };
+            }
+        }
 
-            if let Some(relative) = relative {
-                let operand_ty = relative.get_type();
+        Ok(())
+    }
 
-                self.write_operand(operand_ty, relative.modifiers, relative.component_mode, &[]);
-            }
+    /// Writes a `customdata` block, which stores its length in the token
+    /// after the opcode token.
+    pub fn write_custom_data(&mut self, class: u32, data: &[u32]) {
+        self.write_u32(ENCODE_D3D10_SB_CUSTOMDATA_CLASS(class));
+        self.write_u32(data.len() as u32 + 2);
+        self.dwords.extend_from_slice(data);
+    }
+
+    /// Patches the length of the instruction starting at `start` into its
+    /// opcode token. Only `customdata` can be longer than the length field
+    /// allows, and it's written by `write_custom_data` instead.
+    fn set_instruction_length(&mut self, start: usize) -> Result<(), BuildError> {
+        let len = (self.position() - start) as u32;
+        if len > MAX_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH {
+            return Err(BuildError::InstructionTooLong(start));
         }
+
+        self.dwords[start] |= ENCODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(len);
+
+        Ok(())
     }
 
     pub fn as_bytes(&self) -> &[u8] {
@@ -447,92 +1145,430 @@ impl DxbcModule {
     }
 }
 
+/// Packs the per component return types of `dcl_resource` and
+/// `dcl_uav_typed` into their token.
+fn return_type_token(return_type: &[ResourceReturnType; 4]) -> u32 {
+    let mut token = 0;
+    for (component, &ty) in return_type.iter().enumerate() {
+        token |= ENCODE_D3D10_SB_RESOURCE_RETURN_TYPE(ty as u32, component as u32);
+    }
+
+    token
+}
+
 impl Default for DxbcModule {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<'a> Builder<'a> {
-    pub fn new() -> Self {
-        Builder {
-            rdef: None,
-            isgn: None,
-            osgn: None,
-            shex: None,
-            stat: None,
-            _code: Vec::new(),
-        }
-    }
+/// Lays out chunks with a string table the way fxc does: every string is only
+/// written once and the table is padded with `0xab`. `RDEF` chunks start with
+/// the resource bindings and their names, followed by the constant buffers,
+/// each with its variables, their names and their types, and end with the
+/// creator string. Types are only written once as well.
+#[derive(Default)]
+struct ChunkWriter<'a> {
+    bytes: Vec<u8>,
+    strings: HashMap<&'a str, u32>,
+    types: Vec<(&'a ShaderType<'a>, u32)>,
+}
 
-    pub fn set_rdef(&mut self, rdef: RdefChunk<'a>) {
-        self.rdef = Some(rdef);
+impl<'a> ChunkWriter<'a> {
+    fn position(&self) -> u32 {
+        self.bytes.len() as u32
     }
 
-    pub fn set_isgn(&mut self, isgn: IOsgnChunk) {
-        self.isgn = Some(isgn);
+    fn write_u32(&mut self, val: u32) {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
     }
 
-    pub fn set_osgn(&mut self, osgn: IOsgnChunk) {
-        self.osgn = Some(osgn);
+    fn write_u16(&mut self, val: u16) {
+        self.bytes.extend_from_slice(&val.to_le_bytes());
     }
 
-    pub fn set_shex(&mut self, shex: ShexChunk) {
-        self.shex = Some(shex);
+    fn set_u32(&mut self, pos: u32, val: u32) {
+        let pos = pos as usize;
+        self.bytes[pos..pos + 4].copy_from_slice(&val.to_le_bytes());
     }
 
-    pub fn set_stat(&mut self, stat: IStatChunk) {
-        self.stat = Some(stat);
+    /// Pads to the next dword with the `0xab` bytes fxc uses.
+    fn align(&mut self) {
+        while !self.bytes.len().is_multiple_of(4) {
+            self.bytes.push(0xab);
+        }
     }
 
-    pub fn set_profile(&mut self) {}
+    fn write_string(&mut self, text: &'a str) -> u32 {
+        if let Some(&offset) = self.strings.get(text) {
+            return offset;
+        }
 
-    // TODO: determine fallibility
-    #[allow(clippy::result_unit_err)]
-    pub fn module(&self) -> Result<DxbcModule, ()> {
-        let mut module = DxbcModule::new();
+        let offset = self.position();
+        self.bytes.extend_from_slice(text.as_bytes());
+        self.bytes.push(0);
+        self.strings.insert(text, offset);
 
-        module.write_u32(DXBC_MAGIC);
-        let checksum_pos = module.position();
-        module.write_u32(0);
-        module.write_u32(0);
-        module.write_u32(0);
-        module.write_u32(0);
+        offset
+    }
 
-        module.write_u32(1);
+    fn write_signature(&mut self, chunk: &'a IOsgnChunk) {
+        self.write_u32(chunk.elements.len() as u32);
+        // the elements directly follow the header
+        self.write_u32(8);
 
-        let size_pos = module.position();
-        module.write_u32(0);
+        let mut name_positions = Vec::new();
+        for element in &chunk.elements {
+            if chunk.layout != SignatureLayout::Basic {
+                self.write_u32(element.stream);
+            }
+            name_positions.push(self.position());
+            self.write_u32(0);
+            self.write_u32(element.semantic_index);
+            self.write_u32(element.semantic_type as u32);
+            self.write_u32(element.component_type as u32);
+            self.write_u32(element.register);
+            self.write_u32(((element.rw_mask as u32) << 8) | (element.component_mask as u32));
+            if chunk.layout == SignatureLayout::StreamMinPrecision {
+                self.write_u32(element.min_precision as u32);
+            }
+        }
 
-        let chunk_count = 4;
-        module.write_u32(chunk_count);
-        let chunk_count_pos = module.position();
-        for _ in 0..chunk_count {
-            module.write_u32(0);
+        for (element, pos) in chunk.elements.iter().zip(name_positions) {
+            let name = self.write_string(&element.name);
+            self.set_u32(pos, name);
+        }
+        self.align();
+    }
+
+    fn write_rdef(&mut self, rdef: &'a RdefChunk<'a>) {
+        let sm5 = rdef.major >= 5;
+
+        self.write_u32(rdef.constant_buffers.len() as u32);
+        let constant_buffers_pos = self.position();
+        self.write_u32(0);
+        self.write_u32(rdef.resource_bindings.len() as u32);
+        let resource_bindings_pos = self.position();
+        self.write_u32(0);
+        self.bytes.push(rdef.minor);
+        self.bytes.push(rdef.major);
+        self.write_u16(rdef.program_ty as u16);
+        self.write_u32(rdef.flags);
+        let author_pos = self.position();
+        self.write_u32(0);
+
+        if let Some(rd11) = rdef.rd11 {
+            self.write_u32(rd11.magic);
+            self.write_u32(rd11.header_size);
+            self.write_u32(rd11.constant_buffer_size);
+            self.write_u32(rd11.resource_binding_size);
+            self.write_u32(rd11.variable_size);
+            self.write_u32(rd11.type_size);
+            self.write_u32(rd11.member_size);
+            self.write_u32(rd11.interface_slot_count);
+        }
+
+        self.set_u32(resource_bindings_pos, self.position());
+        let mut name_positions = Vec::new();
+        for binding in &rdef.resource_bindings {
+            name_positions.push(self.position());
+            self.write_u32(0);
+            self.write_u32(binding.input_type as u32);
+            self.write_u32(binding.return_type as u32);
+            self.write_u32(binding.view_dimension as u32);
+            self.write_u32(binding.sample_count);
+            self.write_u32(binding.bind_point);
+            self.write_u32(binding.bind_count);
+            self.write_u32(binding.input_flags.bits());
+            if let (Some(space), Some(id)) = (binding.space, binding.id) {
+                self.write_u32(space);
+                self.write_u32(id);
+            }
         }
+        for (binding, pos) in rdef.resource_bindings.iter().zip(name_positions) {
+            let name = self.write_string(binding.name);
+            self.set_u32(pos, name);
+        }
+        self.align();
 
-        if let Some(ref rdef) = self.rdef {
-            let pos = module.position() * 4;
-            module.set_u32(chunk_count_pos, pos as u32);
-            module.write_rdef(rdef);
+        // unlike the binding offset, fxc leaves this at 0 without any buffers
+        if !rdef.constant_buffers.is_empty() {
+            self.set_u32(constant_buffers_pos, self.position());
+        }
+        let mut cb_positions = Vec::new();
+        for cb in &rdef.constant_buffers {
+            cb_positions.push(self.position());
+            self.write_u32(0);
+            self.write_u32(cb.variables.len() as u32);
+            self.write_u32(0);
+            self.write_u32(cb.size);
+            self.write_u32(cb.flags.bits());
+            self.write_u32(cb.ty as u32);
+        }
+        for (cb, &pos) in rdef.constant_buffers.iter().zip(&cb_positions) {
+            let name = self.write_string(cb.name);
+            self.set_u32(pos, name);
         }
 
-        if let Some(ref isgn) = self.isgn {
-            let pos = module.position() * 4;
-            module.set_u32(chunk_count_pos + 1, pos as u32);
-            module.write_isgn(isgn);
+        for (cb, pos) in rdef.constant_buffers.iter().zip(cb_positions) {
+            self.align();
+            self.set_u32(pos + 8, self.position());
+
+            let mut variable_positions = Vec::new();
+            for variable in &cb.variables {
+                variable_positions.push(self.position());
+                self.write_u32(0);
+                self.write_u32(variable.offset);
+                self.write_u32(variable.size);
+                self.write_u32(variable.flags.bits());
+                self.write_u32(0);
+                self.write_u32(0);
+                if sm5 {
+                    self.write_u32(variable.start_texture.unwrap_or(u32::MAX));
+                    self.write_u32(variable.texture_size.unwrap_or(0));
+                    self.write_u32(variable.start_sampler.unwrap_or(u32::MAX));
+                    self.write_u32(variable.sampler_size.unwrap_or(0));
+                }
+            }
+
+            for (variable, pos) in cb.variables.iter().zip(variable_positions) {
+                let name = self.write_string(variable.name);
+                self.set_u32(pos, name);
+
+                if let Some(default_value) = variable.default_value {
+                    self.align();
+                    self.set_u32(pos + 20, self.position());
+                    self.bytes.extend_from_slice(default_value);
+                }
+
+                let ty = self.write_type(&variable.ty, sm5);
+                self.set_u32(pos + 16, ty);
+            }
         }
 
-        if let Some(ref osgn) = self.osgn {
-            let pos = module.position() * 4;
-            module.set_u32(chunk_count_pos + 2, pos as u32);
-            module.write_osgn(osgn);
+        self.align();
+        let author = self.write_string(rdef.author);
+        self.set_u32(author_pos, author);
+        self.align();
+    }
+
+    /// Writes `ty` after everything it refers to and returns its offset.
+    fn write_type(&mut self, ty: &'a ShaderType<'a>, sm5: bool) -> u32 {
+        if let Some(&(_, offset)) = self.types.iter().find(|&&(written, _)| written == ty) {
+            return offset;
+        }
+
+        let name = ty.name.map_or(0, |name| self.write_string(name));
+
+        let mut members = Vec::new();
+        for member in &ty.members {
+            let name = self.write_string(member.name);
+            members.push((name, self.write_type(&member.ty, sm5), member.offset));
+        }
+
+        let sub_type = ty
+            .sub_type
+            .as_ref()
+            .map_or(0, |sub_type| self.write_type(sub_type, sm5));
+        let base_class = ty
+            .base_class
+            .as_ref()
+            .map_or(0, |base_class| self.write_type(base_class, sm5));
+        let interfaces: Vec<_> = ty
+            .interfaces
+            .iter()
+            .map(|interface| self.write_type(interface, sm5))
+            .collect();
+
+        self.align();
+
+        let members_offset = if members.is_empty() {
+            0
+        } else {
+            self.position()
+        };
+        for (name, ty, offset) in members {
+            self.write_u32(name);
+            self.write_u32(ty);
+            self.write_u32(offset);
+        }
+
+        let interfaces_offset = if interfaces.is_empty() {
+            0
+        } else {
+            self.position()
+        };
+        for &interface in &interfaces {
+            self.write_u32(interface);
+        }
+
+        let offset = self.position();
+        self.write_u16(ty.class as u16);
+        self.write_u16(ty.ty as u16);
+        self.write_u16(ty.rows);
+        self.write_u16(ty.columns);
+        self.write_u16(ty.count);
+        self.write_u16(ty.members.len() as u16);
+        self.write_u32(members_offset);
+        if sm5 {
+            self.write_u32(sub_type);
+            self.write_u32(base_class);
+            self.write_u32(interfaces.len() as u32);
+            self.write_u32(interfaces_offset);
+            self.write_u32(name);
+        }
+
+        self.types.push((ty, offset));
+
+        offset
+    }
+}
+
+impl<'a> Builder<'a> {
+    pub fn new() -> Self {
+        Builder {
+            chunks: Vec::new(),
+            _code: Vec::new(),
+        }
+    }
+
+    /// Parses every chunk of a module, so that `module` writes it back
+    /// unchanged. Chunks that can't be written from their parsed form, like
+    /// `SPDB`, `IFCE` and `PSV0`, and unknown chunks are kept as raw chunks.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, State> {
+        let container = Container::parse(bytes)?;
+        let mut builder = Builder::new();
+
+        for chunk in &container.chunks {
+            let data = container.get_chunk_data(chunk);
+            let mut decoder = Decoder::new(data);
+
+            let chunk = match &chunk.fourcc {
+                b"RDEF" => Chunk::Rdef(RdefChunk::parse(&mut decoder)?),
+                b"ISGN" => Chunk::Isgn(IOsgnChunk::parse(&mut decoder, SignatureLayout::Basic)?),
+                b"OSGN" => Chunk::Osgn(IOsgnChunk::parse(&mut decoder, SignatureLayout::Basic)?),
+                b"ISG1" => Chunk::Isg1(IOsgnChunk::parse(
+                    &mut decoder,
+                    SignatureLayout::StreamMinPrecision,
+                )?),
+                b"OSG1" => Chunk::Osg1(IOsgnChunk::parse(
+                    &mut decoder,
+                    SignatureLayout::StreamMinPrecision,
+                )?),
+                b"OSG5" => Chunk::Osg5(IOsgnChunk::parse(&mut decoder, SignatureLayout::Stream)?),
+                b"PCSG" => Chunk::Pcsg(IOsgnChunk::parse(&mut decoder, SignatureLayout::Basic)?),
+                b"SHEX" | b"SHDR" => {
+                    let header = shex::ShexHeader::parse(&mut decoder)?;
+                    let mut shex =
+                        ShexChunk::with_version(header.program_type, header.major, header.minor);
+
+                    let mut decoder =
+                        decoder.scoped_decoder(header.instruction_length as usize * 4)?;
+                    while !decoder.eof() {
+                        shex.add_instruction(Instruction::Decoded(shex::Instruction::parse(
+                            &mut decoder,
+                        )?));
+                    }
+
+                    Chunk::Shex(shex)
+                }
+                b"STAT" => Chunk::Stat(IStatChunk::parse(&mut decoder)?),
+                b"SFI0" => Chunk::Sfi0(Sfi0Chunk::parse(&mut decoder)?),
+                b"RTS0" => Chunk::Rts0(Rts0Chunk::parse(&mut decoder)?),
+                b"Aon9" => Chunk::Aon9(Aon9Chunk::parse(&mut decoder)?),
+                b"XNAS" => Chunk::Xnas(Aon9Chunk::parse(&mut decoder)?),
+                b"XNAP" => Chunk::Xnap(Aon9Chunk::parse(&mut decoder)?),
+                b"DXIL" => Chunk::Dxil(DxilChunk::parse(&mut decoder)?),
+                b"ILDB" => Chunk::Ildb(DxilChunk::parse(&mut decoder)?),
+                b"HASH" => Chunk::Hash(HashChunk::parse(&mut decoder)?),
+                b"ILDN" => Chunk::Ildn(IldnChunk::parse(&mut decoder)?),
+                _ => Chunk::Raw(chunk.fourcc, data.to_vec()),
+            };
+
+            builder.add_chunk(chunk);
+        }
+
+        Ok(builder)
+    }
+
+    pub fn get_chunks(&self) -> &[Chunk<'a>] {
+        &self.chunks
+    }
+
+    /// Adds a chunk after the ones added so far, even if the module already
+    /// has a chunk of the same type.
+    pub fn add_chunk(&mut self, chunk: Chunk<'a>) {
+        self.chunks.push(chunk);
+    }
+
+    /// Replaces the first chunk of the same type, or adds the chunk if there
+    /// is none.
+    fn set_chunk(&mut self, chunk: Chunk<'a>) {
+        let existing = self
+            .chunks
+            .iter_mut()
+            .find(|existing| mem::discriminant(*existing) == mem::discriminant(&chunk));
+
+        match existing {
+            Some(existing) => *existing = chunk,
+            None => self.chunks.push(chunk),
+        }
+    }
+
+    pub fn set_rdef(&mut self, rdef: RdefChunk<'a>) {
+        self.set_chunk(Chunk::Rdef(rdef));
+    }
+
+    pub fn set_isgn(&mut self, isgn: IOsgnChunk) {
+        self.set_chunk(Chunk::Isgn(isgn));
+    }
+
+    pub fn set_osgn(&mut self, osgn: IOsgnChunk) {
+        self.set_chunk(Chunk::Osgn(osgn));
+    }
+
+    pub fn set_shex(&mut self, shex: ShexChunk) {
+        self.set_chunk(Chunk::Shex(shex));
+    }
+
+    pub fn set_stat(&mut self, stat: IStatChunk) {
+        self.set_chunk(Chunk::Stat(stat));
+    }
+
+    pub fn set_sfi0(&mut self, sfi0: Sfi0Chunk) {
+        self.set_chunk(Chunk::Sfi0(sfi0));
+    }
+
+    /// Adds a chunk that is written as is, like one kept from `Container` to
+    /// preserve data this crate can't write.
+    pub fn add_raw_chunk(&mut self, fourcc: [u8; 4], data: Vec<u8>) {
+        self.add_chunk(Chunk::Raw(fourcc, data));
+    }
+
+    pub fn module(&self) -> Result<DxbcModule, BuildError> {
+        let mut module = DxbcModule::new();
+
+        module.write_u32(DXBC_MAGIC);
+        let checksum_pos = module.position();
+        module.write_u32(0);
+        module.write_u32(0);
+        module.write_u32(0);
+        module.write_u32(0);
+
+        module.write_u32(1);
+
+        let size_pos = module.position();
+        module.write_u32(0);
+
+        module.write_u32(self.chunks.len() as u32);
+        let chunk_offset_pos = module.position();
+        for _ in &self.chunks {
+            module.write_u32(0);
         }
 
-        if let Some(ref shex) = self.shex {
-            let pos = module.position() * 4;
-            module.set_u32(chunk_count_pos + 3, pos as u32);
-            module.write_shex(shex);
+        for (idx, chunk) in self.chunks.iter().enumerate() {
+            module.set_u32(chunk_offset_pos + idx, 4 * module.position() as u32);
+            module.write_chunk(chunk)?;
         }
 
         // finally, patch in size and checksum
@@ -567,11 +1603,33 @@ bitflags! {
     }
 }
 
+/// A `D3D10_SB_NAME`, the system value of a `dcl_*_siv` or `dcl_*_sgv`.
 #[repr(u32)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum Semantic {
     Undefined = 0,
     Position = 1,
+    ClipDistance = 2,
+    CullDistance = 3,
+    RenderTargetArrayIndex = 4,
+    ViewportArrayIndex = 5,
+    VertexId = 6,
+    PrimitiveId = 7,
+    InstanceId = 8,
+    IsFrontFace = 9,
+    SampleIndex = 10,
+    FinalQuadUEq0EdgeTessfactor = 11,
+    FinalQuadVEq0EdgeTessfactor = 12,
+    FinalQuadUEq1EdgeTessfactor = 13,
+    FinalQuadVEq1EdgeTessfactor = 14,
+    FinalQuadUInsideTessfactor = 15,
+    FinalQuadVInsideTessfactor = 16,
+    FinalTriUEq0EdgeTessfactor = 17,
+    FinalTriVEq0EdgeTessfactor = 18,
+    FinalTriWEq0EdgeTessfactor = 19,
+    FinalTriInsideTessfactor = 20,
+    FinalLineDetailTessfactor = 21,
+    FinalLineDensityTessfactor = 22,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -610,13 +1668,40 @@ pub enum NumComponent {
     D4(ComponentMode),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Address {
     Constant(u32),
+    /// Indexed by the x component of a register, like `x0[r1.x]`.
     Relative(IndexOperandType),
+    /// Any other index, like `cb0[r1.y + 4]`.
+    Index(Box<Immediate>),
 }
 
-#[derive(Debug)]
+impl Address {
+    fn to_immediate(&self) -> Immediate {
+        let select_x = NumComponent::D4(ComponentMode::Select(X));
+
+        match *self {
+            Address::Constant(val) => Immediate::U32(val),
+            Address::Relative(IndexOperandType::Register(reg)) => {
+                Immediate::Relative(Operand::register(reg, Modifier::None, select_x))
+            }
+            Address::Relative(IndexOperandType::Input(reg)) => {
+                Immediate::Relative(Operand::input(reg, Modifier::None, select_x))
+            }
+            Address::Relative(IndexOperandType::Output(reg)) => {
+                Immediate::Relative(Operand::output(reg, Modifier::None, select_x))
+            }
+            Address::Index(ref index) => (**index).clone(),
+        }
+    }
+}
+
+/// The register an operand refers to. Immediates are written with as many
+/// values as their component count says, so `Imm32` and `Imm64` take
+/// `NumComponent::D1` and the others `NumComponent::D4`, with the unused
+/// values of `Imm32x2` and `Imm32x3` written as 0.
+#[derive(Debug, Clone)]
 pub enum OperandType {
     Register(u32),
     Input(u32),
@@ -625,14 +1710,23 @@ pub enum OperandType {
     Imm32x2(u32, u32),
     Imm32x3(u32, u32, u32),
     Imm32x4(u32, u32, u32, u32),
+    Imm64(u64),
+    Imm64x2(u64, u64),
     Resource(u32),
     Sampler(u32),
+    UnorderedAccessView(u32),
+    ThreadGroupSharedMemory(u32),
     IndexableRegister(u32, Address),
     ConstantBuffer(u32, Address),
+    ImmediateConstantBuffer(Address),
+    /// Any other register with its indices, like `vThreadID` without any or
+    /// `vicp[0][1]` with two.
+    Other(shex::OperandType, Vec<Immediate>),
+    /// Operand tokens that are written as is, in place of the operand.
     CustomData(Vec<u32>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Immediate {
     U32(u32),
     U64(u64),
@@ -641,7 +1735,7 @@ pub enum Immediate {
     U64Relative(u64, Operand),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub enum IndexOperandType {
     Register(u32),
     Input(u32),
@@ -656,12 +1750,158 @@ pub enum Instruction {
     DclTemps {
         count: u32,
     },
+    DclIndexableTemp {
+        register: u32,
+        count: u32,
+        components: u32,
+    },
+    DclInput {
+        register: Operand,
+    },
+    DclInputSgv {
+        register: Operand,
+        semantic: Semantic,
+    },
+    DclInputSiv {
+        register: Operand,
+        semantic: Semantic,
+    },
+    DclInputPs {
+        register: Operand,
+        interpolation: InterpolationMode,
+    },
+    DclInputPsSgv {
+        register: Operand,
+        interpolation: InterpolationMode,
+        semantic: Semantic,
+    },
+    DclInputPsSiv {
+        register: Operand,
+        interpolation: InterpolationMode,
+        semantic: Semantic,
+    },
+    DclOutput {
+        register: Operand,
+    },
+    DclOutputSgv {
+        register: Operand,
+        semantic: Semantic,
+    },
     DclOutputSiv {
         register: Operand,
         semantic: Semantic,
     },
-    DclInput {
+    DclIndexRange {
+        register: Operand,
+        count: u32,
+    },
+    DclConstantBuffer {
+        register: Operand,
+        access: ConstantBufferIndexPattern,
+    },
+    /// `dcl_immediateConstantBuffer`, which is stored as `customdata`.
+    DclImmediateConstantBuffer(Vec<[u32; 4]>),
+    DclSampler {
+        register: Operand,
+        mode: SamplerMode,
+    },
+    DclResource {
         register: Operand,
+        dimension: ResourceDimension,
+        return_type: [ResourceReturnType; 4],
+        /// Only used by multisampled textures.
+        sample_count: u32,
+    },
+    DclResourceRaw {
+        register: Operand,
+    },
+    DclResourceStructured {
+        register: Operand,
+        stride: u32,
+    },
+    DclUavTyped {
+        register: Operand,
+        dimension: ResourceDimension,
+        return_type: [ResourceReturnType; 4],
+        globally_coherent: bool,
+    },
+    DclUavRaw {
+        register: Operand,
+        globally_coherent: bool,
+    },
+    DclUavStructured {
+        register: Operand,
+        stride: u32,
+        globally_coherent: bool,
+        has_counter: bool,
+    },
+    DclTgsmRaw {
+        register: Operand,
+        /// The size in bytes.
+        count: u32,
+    },
+    DclTgsmStructured {
+        register: Operand,
+        stride: u32,
+        count: u32,
+    },
+    DclThreadGroup {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    DclGsInputPrimitive {
+        primitive: Primitive,
+    },
+    DclGsOutputPrimitiveTopology {
+        topology: PrimitiveTopology,
+    },
+    DclMaxOutputVertexCount {
+        count: u32,
+    },
+    DclGsInstanceCount {
+        count: u32,
+    },
+    DclStream {
+        register: Operand,
+    },
+    DclInputControlPointCount {
+        count: u32,
+    },
+    DclOutputControlPointCount {
+        count: u32,
+    },
+    DclTessellatorDomain {
+        domain: TessellatorDomain,
+    },
+    DclTessellatorPartitioning {
+        partitioning: TessellatorPartitioning,
+    },
+    DclTessellatorOutputPrimitive {
+        primitive: TessellatorOutputPrimitive,
+    },
+    DclHsMaxTessFactor {
+        factor: f32,
+    },
+    DclHsForkPhaseInstanceCount {
+        count: u32,
+    },
+    DclHsJoinPhaseInstanceCount {
+        count: u32,
+    },
+    DclFunctionBody {
+        index: u32,
+    },
+    DclFunctionTable {
+        index: u32,
+        bodies: Vec<u32>,
+    },
+    DclInterface {
+        index: u32,
+        dynamically_indexed: bool,
+        body_count: u32,
+        array_length: u32,
+        tables: Vec<u32>,
     },
     Add {
         dest: Operand,
@@ -675,10 +1915,57 @@ pub enum Instruction {
         b: Operand,
         saturated: bool,
     },
+    /// Any instruction without opcode specific controls, like `mov`,
+    /// `sample`, `if` or `hs_fork_phase`. The destinations come first, like
+    /// in the disassembly.
+    Operation {
+        opcode: Opcode,
+        saturated: bool,
+        /// Whether conditional instructions like `if` and `breakc` test for
+        /// zero or non-zero.
+        test: Option<TestBoolean>,
+        extended: Vec<OpcodeEx>,
+        operands: Vec<Operand>,
+    },
+    ResInfo {
+        return_type: ResInfoReturnType,
+        /// The resource dimension and return type of `resinfo_indexable`.
+        extended: Vec<OpcodeEx>,
+        dest: Operand,
+        mip_level: Operand,
+        resource: Operand,
+    },
+    SampleInfo {
+        uint: bool,
+        extended: Vec<OpcodeEx>,
+        dest: Operand,
+        resource: Operand,
+    },
+    Sync {
+        flags: SyncFlags,
+    },
+    /// `fcall`
+    InterfaceCall {
+        function_index: u32,
+        interface: Operand,
+    },
     Ret,
+    CustomData {
+        class: CustomDataClass,
+        data: Vec<u32>,
+    },
+    /// `printf` and `errorf`, which are stored as `customdata`.
+    ShaderMessage {
+        id: u32,
+        format: u32,
+        text: String,
+        operands: Vec<Operand>,
+    },
+    /// An instruction decoded by the parser, which is written back as is.
+    Decoded(shex::Instruction),
 }
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub enum OpcodeEx {
     UvOffset(u32, u32, u32),
     Dimension(ResourceDimension, u32),
@@ -696,81 +1983,440 @@ impl Instruction {
             Instruction::Add { .. } => D3D10_SB_OPCODE_ADD,
             Instruction::Mul { .. } => D3D10_SB_OPCODE_MUL,
             Instruction::DclGlobalFlags { .. } => D3D10_SB_OPCODE_DCL_GLOBAL_FLAGS,
-            Instruction::DclOutputSiv { .. } => D3D10_SB_OPCODE_DCL_OUTPUT_SIV,
-            Instruction::DclInput { .. } => D3D10_SB_OPCODE_DCL_INPUT,
             Instruction::DclTemps { .. } => D3D10_SB_OPCODE_DCL_TEMPS,
+            Instruction::DclIndexableTemp { .. } => D3D10_SB_OPCODE_DCL_INDEXABLE_TEMP,
+            Instruction::DclInput { .. } => D3D10_SB_OPCODE_DCL_INPUT,
+            Instruction::DclInputSgv { .. } => D3D10_SB_OPCODE_DCL_INPUT_SGV,
+            Instruction::DclInputSiv { .. } => D3D10_SB_OPCODE_DCL_INPUT_SIV,
+            Instruction::DclInputPs { .. } => D3D10_SB_OPCODE_DCL_INPUT_PS,
+            Instruction::DclInputPsSgv { .. } => D3D10_SB_OPCODE_DCL_INPUT_PS_SGV,
+            Instruction::DclInputPsSiv { .. } => D3D10_SB_OPCODE_DCL_INPUT_PS_SIV,
+            Instruction::DclOutput { .. } => D3D10_SB_OPCODE_DCL_OUTPUT,
+            Instruction::DclOutputSgv { .. } => D3D10_SB_OPCODE_DCL_OUTPUT_SGV,
+            Instruction::DclOutputSiv { .. } => D3D10_SB_OPCODE_DCL_OUTPUT_SIV,
+            Instruction::DclIndexRange { .. } => D3D10_SB_OPCODE_DCL_INDEX_RANGE,
+            Instruction::DclConstantBuffer { .. } => D3D10_SB_OPCODE_DCL_CONSTANT_BUFFER,
+            Instruction::DclSampler { .. } => D3D10_SB_OPCODE_DCL_SAMPLER,
+            Instruction::DclResource { .. } => D3D10_SB_OPCODE_DCL_RESOURCE,
+            Instruction::DclResourceRaw { .. } => D3D11_SB_OPCODE_DCL_RESOURCE_RAW,
+            Instruction::DclResourceStructured { .. } => D3D11_SB_OPCODE_DCL_RESOURCE_STRUCTURED,
+            Instruction::DclUavTyped { .. } => D3D11_SB_OPCODE_DCL_UNORDERED_ACCESS_VIEW_TYPED,
+            Instruction::DclUavRaw { .. } => D3D11_SB_OPCODE_DCL_UNORDERED_ACCESS_VIEW_RAW,
+            Instruction::DclUavStructured { .. } => {
+                D3D11_SB_OPCODE_DCL_UNORDERED_ACCESS_VIEW_STRUCTURED
+            }
+            Instruction::DclTgsmRaw { .. } => D3D11_SB_OPCODE_DCL_THREAD_GROUP_SHARED_MEMORY_RAW,
+            Instruction::DclTgsmStructured { .. } => {
+                D3D11_SB_OPCODE_DCL_THREAD_GROUP_SHARED_MEMORY_STRUCTURED
+            }
+            Instruction::DclThreadGroup { .. } => D3D11_SB_OPCODE_DCL_THREAD_GROUP,
+            Instruction::DclGsInputPrimitive { .. } => D3D10_SB_OPCODE_DCL_GS_INPUT_PRIMITIVE,
+            Instruction::DclGsOutputPrimitiveTopology { .. } => {
+                D3D10_SB_OPCODE_DCL_GS_OUTPUT_PRIMITIVE_TOPOLOGY
+            }
+            Instruction::DclMaxOutputVertexCount { .. } => {
+                D3D10_SB_OPCODE_DCL_MAX_OUTPUT_VERTEX_COUNT
+            }
+            Instruction::DclGsInstanceCount { .. } => D3D11_SB_OPCODE_DCL_GS_INSTANCE_COUNT,
+            Instruction::DclStream { .. } => D3D11_SB_OPCODE_DCL_STREAM,
+            Instruction::DclInputControlPointCount { .. } => {
+                D3D11_SB_OPCODE_DCL_INPUT_CONTROL_POINT_COUNT
+            }
+            Instruction::DclOutputControlPointCount { .. } => {
+                D3D11_SB_OPCODE_DCL_OUTPUT_CONTROL_POINT_COUNT
+            }
+            Instruction::DclTessellatorDomain { .. } => D3D11_SB_OPCODE_DCL_TESS_DOMAIN,
+            Instruction::DclTessellatorPartitioning { .. } => D3D11_SB_OPCODE_DCL_TESS_PARTITIONING,
+            Instruction::DclTessellatorOutputPrimitive { .. } => {
+                D3D11_SB_OPCODE_DCL_TESS_OUTPUT_PRIMITIVE
+            }
+            Instruction::DclHsMaxTessFactor { .. } => D3D11_SB_OPCODE_DCL_HS_MAX_TESSFACTOR,
+            Instruction::DclHsForkPhaseInstanceCount { .. } => {
+                D3D11_SB_OPCODE_DCL_HS_FORK_PHASE_INSTANCE_COUNT
+            }
+            Instruction::DclHsJoinPhaseInstanceCount { .. } => {
+                D3D11_SB_OPCODE_DCL_HS_JOIN_PHASE_INSTANCE_COUNT
+            }
+            Instruction::DclFunctionBody { .. } => D3D11_SB_OPCODE_DCL_FUNCTION_BODY,
+            Instruction::DclFunctionTable { .. } => D3D11_SB_OPCODE_DCL_FUNCTION_TABLE,
+            Instruction::DclInterface { .. } => D3D11_SB_OPCODE_DCL_INTERFACE,
+            Instruction::Operation { opcode, .. } => *opcode as u32,
+            Instruction::ResInfo { .. } => D3D10_SB_OPCODE_RESINFO,
+            Instruction::SampleInfo { .. } => D3D10_1_SB_OPCODE_SAMPLE_INFO,
+            Instruction::Sync { .. } => D3D11_SB_OPCODE_SYNC,
+            Instruction::InterfaceCall { .. } => D3D11_SB_OPCODE_INTERFACE_CALL,
             Instruction::Ret => D3D10_SB_OPCODE_RET,
+            Instruction::DclImmediateConstantBuffer(..)
+            | Instruction::CustomData { .. }
+            | Instruction::ShaderMessage { .. } => D3D10_SB_OPCODE_CUSTOMDATA,
+            Instruction::Decoded(instruction) => instruction.opcode() as u32,
+        }
+    }
+
+    /// Returns the opcode specific bits of the opcode token, other than the
+    /// saturate and test flags.
+    fn get_controls(&self) -> u32 {
+        let coherency = |globally_coherent: bool| {
+            if globally_coherent {
+                ENCODE_D3D11_SB_ACCESS_COHERENCY_FLAGS(D3D11_SB_GLOBALLY_COHERENT_ACCESS)
+            } else {
+                0
+            }
+        };
+
+        match *self {
+            Instruction::DclGlobalFlags { flags } => ENCODE_D3D10_SB_GLOBAL_FLAGS(flags.bits()),
+            Instruction::DclInputPs { interpolation, .. }
+            | Instruction::DclInputPsSgv { interpolation, .. }
+            | Instruction::DclInputPsSiv { interpolation, .. } => {
+                ENCODE_D3D10_SB_INPUT_INTERPOLATION_MODE(interpolation as u32)
+            }
+            Instruction::DclConstantBuffer { access, .. } => {
+                ENCODE_D3D10_SB_D3D10_SB_CONSTANT_BUFFER_ACCESS_PATTERN(access as u32)
+            }
+            Instruction::DclSampler { mode, .. } => ENCODE_D3D10_SB_SAMPLER_MODE(mode as u32),
+            Instruction::DclResource {
+                dimension,
+                sample_count,
+                ..
+            } => {
+                ENCODE_D3D10_SB_RESOURCE_DIMENSION(dimension as u32)
+                    | ENCODE_D3D10_SB_RESOURCE_SAMPLE_COUNT(sample_count)
+            }
+            Instruction::DclUavTyped {
+                dimension,
+                globally_coherent,
+                ..
+            } => {
+                ENCODE_D3D10_SB_RESOURCE_DIMENSION(dimension as u32) | coherency(globally_coherent)
+            }
+            Instruction::DclUavRaw {
+                globally_coherent, ..
+            } => coherency(globally_coherent),
+            Instruction::DclUavStructured {
+                globally_coherent,
+                has_counter,
+                ..
+            } => {
+                let counter = if has_counter {
+                    ENCODE_D3D11_SB_UAV_FLAGS(D3D11_SB_UAV_HAS_ORDER_PRESERVING_COUNTER)
+                } else {
+                    0
+                };
+
+                coherency(globally_coherent) | counter
+            }
+            Instruction::DclGsInputPrimitive { primitive } => {
+                let primitive = match primitive {
+                    Primitive::Undefined => D3D10_SB_PRIMITIVE_UNDEFINED,
+                    Primitive::Point => D3D10_SB_PRIMITIVE_POINT,
+                    Primitive::Line => D3D10_SB_PRIMITIVE_LINE,
+                    Primitive::Triangle => D3D10_SB_PRIMITIVE_TRIANGLE,
+                    Primitive::LineAdj => D3D10_SB_PRIMITIVE_LINE_ADJ,
+                    Primitive::TriangleAdj => D3D10_SB_PRIMITIVE_TRIANGLE_ADJ,
+                    Primitive::ControlPointPatch(count) => {
+                        D3D11_SB_PRIMITIVE_1_CONTROL_POINT_PATCH + count - 1
+                    }
+                };
+
+                ENCODE_D3D10_SB_GS_INPUT_PRIMITIVE(primitive)
+            }
+            Instruction::DclGsOutputPrimitiveTopology { topology } => {
+                ENCODE_D3D10_SB_GS_OUTPUT_PRIMITIVE_TOPOLOGY(topology as u32)
+            }
+            Instruction::DclInputControlPointCount { count } => {
+                ENCODE_D3D11_SB_INPUT_CONTROL_POINT_COUNT(count)
+            }
+            Instruction::DclOutputControlPointCount { count } => {
+                ENCODE_D3D11_SB_OUTPUT_CONTROL_POINT_COUNT(count)
+            }
+            Instruction::DclTessellatorDomain { domain } => {
+                ENCODE_D3D11_SB_TESS_DOMAIN(domain as u32)
+            }
+            Instruction::DclTessellatorPartitioning { partitioning } => {
+                ENCODE_D3D11_SB_TESS_PARTITIONING(partitioning as u32)
+            }
+            Instruction::DclTessellatorOutputPrimitive { primitive } => {
+                ENCODE_D3D11_SB_TESS_OUTPUT_PRIMITIVE(primitive as u32)
+            }
+            Instruction::DclInterface {
+                dynamically_indexed,
+                ..
+            } => ENCODE_D3D11_SB_INTERFACE_INDEXED_BIT(dynamically_indexed as u32),
+            Instruction::ResInfo { return_type, .. } => {
+                ENCODE_D3D10_SB_RESINFO_INSTRUCTION_RETURN_TYPE(return_type as u32)
+            }
+            Instruction::SampleInfo { uint, .. } => {
+                ENCODE_D3D10_SB_INSTRUCTION_RETURN_TYPE(if uint {
+                    D3D10_SB_INSTRUCTION_RETURN_UINT
+                } else {
+                    D3D10_SB_INSTRUCTION_RETURN_FLOAT
+                })
+            }
+            Instruction::Sync { flags } => ENCODE_D3D11_SB_SYNC_FLAGS(flags.bits()),
+            _ => 0,
         }
     }
 
     fn encode_opcode(&self, module: &mut DxbcModule) {
         let opcode = self.get_opcode();
 
-        // TODO: encode other instructions
-        #[allow(unreachable_patterns)]
+        let opcode_pos = module.position();
         match self {
             Instruction::Add { saturated, .. } | Instruction::Mul { saturated, .. } => {
                 module.write_opcode(opcode, 0, None, *saturated, &[]);
             }
+            Instruction::Operation {
+                saturated,
+                test,
+                extended,
+                ..
+            } => {
+                module.write_opcode(
+                    opcode,
+                    0,
+                    test.map(|test| test as u32),
+                    *saturated,
+                    extended,
+                );
+            }
+            Instruction::ResInfo { extended, .. } | Instruction::SampleInfo { extended, .. } => {
+                module.write_opcode(opcode, 0, None, false, extended);
+            }
+            _ => module.write_opcode(opcode, 0, None, false, &[]),
+        }
 
-            Instruction::DclGlobalFlags { flags } => {
-                let opcode_pos = module.position();
-                module.write_opcode(opcode, 0, None, false, &[]);
+        let token = module.get_u32(opcode_pos);
+        module.set_u32(opcode_pos, token | self.get_controls());
+    }
 
-                let opcode = module.get_u32(opcode_pos);
-                module.set_u32(opcode_pos, opcode | (flags.bits() & 0x00fff800));
+    fn encode(&self, module: &mut DxbcModule) -> Result<(), BuildError> {
+        match self {
+            Instruction::Decoded(instruction) => {
+                return module.write_instruction(instruction);
             }
-            Instruction::DclOutputSiv { .. }
-            | Instruction::DclInput { .. }
-            | Instruction::DclTemps { .. }
-            | Instruction::Ret => {
-                module.write_opcode(opcode, 0, None, false, &[]);
+            Instruction::DclImmediateConstantBuffer(values) => {
+                let data: Vec<u32> = values.iter().flatten().copied().collect();
+                module.write_custom_data(D3D10_SB_CUSTOMDATA_DCL_IMMEDIATE_CONSTANT_BUFFER, &data);
+                return Ok(());
+            }
+            Instruction::CustomData { class, data } => {
+                module.write_custom_data(*class as u32, data);
+                return Ok(());
+            }
+            Instruction::ShaderMessage {
+                id,
+                format,
+                text,
+                operands,
+            } => {
+                let start = module.position();
+                module.write_u32(ENCODE_D3D10_SB_CUSTOMDATA_CLASS(
+                    D3D11_SB_CUSTOMDATA_SHADER_MESSAGE,
+                ));
+                module.write_u32(0);
+                module.write_u32(*id);
+                module.write_u32(*format);
+                module.write_u32(text.len() as u32);
+                module.write_u32(operands.len() as u32);
+
+                let operands_start = module.position() + 1;
+                module.write_u32(0);
+                for operand in operands {
+                    operand.encode(module)?;
+                }
+                let operands_len = module.position() - operands_start;
+                module.set_u32(operands_start - 1, operands_len as u32);
+
+                module.write_str(text);
+                module.set_u32(start + 1, (module.position() - start) as u32);
+                return Ok(());
+            }
+            &Instruction::DclGsInputPrimitive {
+                primitive: Primitive::ControlPointPatch(count),
+            } if !(1..=32).contains(&count) => {
+                return Err(BuildError::InvalidControlPointCount(count));
             }
             _ => {}
         }
-    }
 
-    fn encode(&self, module: &mut DxbcModule) {
         let start = module.position();
 
         self.encode_opcode(module);
 
         match self {
             Instruction::Add { dest, a, b, .. } | Instruction::Mul { dest, a, b, .. } => {
-                dest.encode(module);
-                a.encode(module);
-                b.encode(module);
+                dest.encode(module)?;
+                a.encode(module)?;
+                b.encode(module)?;
             }
             &Instruction::DclTemps { count: val } => module.write_u32(val),
-            &Instruction::DclOutputSiv {
+            &Instruction::DclIndexableTemp {
+                register,
+                count,
+                components,
+            } => {
+                module.write_u32(register);
+                module.write_u32(count);
+                module.write_u32(components);
+            }
+            &Instruction::DclInputSgv {
+                ref register,
+                semantic,
+            }
+            | &Instruction::DclInputSiv {
+                ref register,
+                semantic,
+            }
+            | &Instruction::DclInputPsSgv {
+                ref register,
+                semantic,
+                ..
+            }
+            | &Instruction::DclInputPsSiv {
+                ref register,
+                semantic,
+                ..
+            }
+            | &Instruction::DclOutputSgv {
+                ref register,
+                semantic,
+            }
+            | &Instruction::DclOutputSiv {
                 ref register,
                 semantic,
             } => {
-                register.encode(module);
-                module.write_u32(semantic as u32)
+                register.encode(module)?;
+                module.write_u32(ENCODE_D3D10_SB_NAME(semantic as u32));
             }
-            &Instruction::DclInput { ref register } => {
-                register.encode(module);
+            Instruction::DclInput { register }
+            | Instruction::DclInputPs { register, .. }
+            | Instruction::DclOutput { register }
+            | Instruction::DclConstantBuffer { register, .. }
+            | Instruction::DclSampler { register, .. }
+            | Instruction::DclResourceRaw { register }
+            | Instruction::DclUavRaw { register, .. }
+            | Instruction::DclStream { register } => {
+                register.encode(module)?;
             }
-            _ => {}
+            Instruction::DclIndexRange { register, count }
+            | Instruction::DclResourceStructured {
+                register,
+                stride: count,
+            }
+            | Instruction::DclUavStructured {
+                register,
+                stride: count,
+                ..
+            }
+            | Instruction::DclTgsmRaw { register, count } => {
+                register.encode(module)?;
+                module.write_u32(*count);
+            }
+            Instruction::DclTgsmStructured {
+                register,
+                stride,
+                count,
+            } => {
+                register.encode(module)?;
+                module.write_u32(*stride);
+                module.write_u32(*count);
+            }
+            Instruction::DclResource {
+                register,
+                return_type,
+                ..
+            }
+            | Instruction::DclUavTyped {
+                register,
+                return_type,
+                ..
+            } => {
+                register.encode(module)?;
+                module.write_u32(return_type_token(return_type));
+            }
+            &Instruction::DclThreadGroup { x, y, z } => {
+                module.write_u32(x);
+                module.write_u32(y);
+                module.write_u32(z);
+            }
+            &Instruction::DclMaxOutputVertexCount { count }
+            | &Instruction::DclGsInstanceCount { count }
+            | &Instruction::DclHsForkPhaseInstanceCount { count }
+            | &Instruction::DclHsJoinPhaseInstanceCount { count }
+            | &Instruction::DclFunctionBody { index: count } => module.write_u32(count),
+            &Instruction::DclHsMaxTessFactor { factor } => module.write_u32(factor.to_bits()),
+            Instruction::DclFunctionTable { index, bodies } => {
+                module.write_u32(*index);
+                module.write_u32(bodies.len() as u32);
+                module.dwords.extend_from_slice(bodies);
+            }
+            Instruction::DclInterface {
+                index,
+                body_count,
+                array_length,
+                tables,
+                ..
+            } => {
+                module.write_u32(*index);
+                module.write_u32(*body_count);
+                module.write_u32(
+                    ENCODE_D3D11_SB_INTERFACE_TABLE_LENGTH(tables.len() as u32)
+                        | ENCODE_D3D11_SB_INTERFACE_ARRAY_LENGTH(*array_length),
+                );
+                module.dwords.extend_from_slice(tables);
+            }
+            Instruction::Operation { operands, .. } => {
+                for operand in operands {
+                    operand.encode(module)?;
+                }
+            }
+            Instruction::ResInfo {
+                dest,
+                mip_level,
+                resource,
+                ..
+            } => {
+                dest.encode(module)?;
+                mip_level.encode(module)?;
+                resource.encode(module)?;
+            }
+            Instruction::SampleInfo { dest, resource, .. } => {
+                dest.encode(module)?;
+                resource.encode(module)?;
+            }
+            Instruction::InterfaceCall {
+                function_index,
+                interface,
+            } => {
+                module.write_u32(*function_index);
+                interface.encode(module)?;
+            }
+            Instruction::DclGlobalFlags { .. }
+            | Instruction::DclGsInputPrimitive { .. }
+            | Instruction::DclGsOutputPrimitiveTopology { .. }
+            | Instruction::DclInputControlPointCount { .. }
+            | Instruction::DclOutputControlPointCount { .. }
+            | Instruction::DclTessellatorDomain { .. }
+            | Instruction::DclTessellatorPartitioning { .. }
+            | Instruction::DclTessellatorOutputPrimitive { .. }
+            | Instruction::Sync { .. }
+            | Instruction::Ret => {}
+            Instruction::DclImmediateConstantBuffer(..)
+            | Instruction::CustomData { .. }
+            | Instruction::ShaderMessage { .. }
+            | Instruction::Decoded(..) => unreachable!(),
         }
 
-        // patch in instruction length
-        let end = module.position();
-        let sz = end - start;
-        let opcode = module.get_u32(start);
-
-        module.set_u32(
-            start,
-            opcode | ENCODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(sz as u32),
-        );
+        module.set_instruction_length(start)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Operand {
     ty: OperandType,
     modifiers: Modifier,
+    min_precision: MinPrecision,
     component_mode: NumComponent,
 }
 
@@ -779,6 +2425,7 @@ impl Operand {
         Operand {
             ty,
             modifiers,
+            min_precision: MinPrecision::Default,
             component_mode,
         }
     }
@@ -795,38 +2442,62 @@ impl Operand {
         Self::new(OperandType::Output(reg), modifiers, component_mode)
     }
 
-    fn encode(&self, module: &mut DxbcModule) {
-        // TODO: encode other operand types
-        #[allow(unused_variables)]
-        match &self.ty {
-            &OperandType::Register(reg) => module.write_operand(
-                D3D10_SB_OPERAND_TYPE_TEMP,
-                self.modifiers,
-                self.component_mode,
-                &[Immediate::U32(reg)],
-            ),
-            &OperandType::Input(reg) => module.write_operand(
-                D3D10_SB_OPERAND_TYPE_INPUT,
-                self.modifiers,
-                self.component_mode,
-                &[Immediate::U32(reg)],
-            ),
-            &OperandType::Output(reg) => module.write_operand(
-                D3D10_SB_OPERAND_TYPE_OUTPUT,
-                self.modifiers,
-                self.component_mode,
-                &[Immediate::U32(reg)],
-            ),
-            OperandType::Imm32(imm) => {}
-            OperandType::Imm32x2(imm0, imm1) => {}
-            OperandType::Imm32x3(imm0, imm1, imm2) => {}
-            OperandType::Imm32x4(imm0, imm1, imm2, imm3) => {}
-            OperandType::Resource(reg) => {}
-            OperandType::Sampler(reg) => {}
-            OperandType::IndexableRegister(reg, index) => {}
-            OperandType::ConstantBuffer(reg, index) => {}
-            OperandType::CustomData(data) => {}
+    /// Sets the minimum precision the operand is read or written with, like
+    /// `min16f`.
+    pub fn with_min_precision(mut self, min_precision: MinPrecision) -> Self {
+        self.min_precision = min_precision;
+        self
+    }
+
+    fn encode(&self, module: &mut DxbcModule) -> Result<(), BuildError> {
+        let indices = match self.ty {
+            OperandType::Register(reg)
+            | OperandType::Input(reg)
+            | OperandType::Output(reg)
+            | OperandType::Resource(reg)
+            | OperandType::Sampler(reg)
+            | OperandType::UnorderedAccessView(reg)
+            | OperandType::ThreadGroupSharedMemory(reg) => vec![Immediate::U32(reg)],
+            OperandType::IndexableRegister(reg, ref index)
+            | OperandType::ConstantBuffer(reg, ref index) => {
+                vec![Immediate::U32(reg), index.to_immediate()]
+            }
+            OperandType::ImmediateConstantBuffer(ref index) => vec![index.to_immediate()],
+            OperandType::Other(_, ref indices) => indices.clone(),
+            OperandType::Imm32(..)
+            | OperandType::Imm32x2(..)
+            | OperandType::Imm32x3(..)
+            | OperandType::Imm32x4(..)
+            | OperandType::Imm64(..)
+            | OperandType::Imm64x2(..) => Vec::new(),
+            OperandType::CustomData(ref data) => {
+                module.dwords.extend_from_slice(data);
+                return Ok(());
+            }
+        };
+
+        module.write_operand(
+            self.get_type(),
+            self.modifiers,
+            self.min_precision,
+            self.component_mode,
+            &indices,
+        )?;
+
+        match self.ty {
+            OperandType::Imm32(val) => module.write_u32(val),
+            OperandType::Imm32x2(x, y) => module.dwords.extend_from_slice(&[x, y, 0, 0]),
+            OperandType::Imm32x3(x, y, z) => module.dwords.extend_from_slice(&[x, y, z, 0]),
+            OperandType::Imm32x4(x, y, z, w) => module.dwords.extend_from_slice(&[x, y, z, w]),
+            OperandType::Imm64(val) => module.write_u64(val),
+            OperandType::Imm64x2(x, y) => {
+                module.write_u64(x);
+                module.write_u64(y);
+            }
+            _ => {}
         }
+
+        Ok(())
     }
 
     fn get_type(&self) -> u32 {
@@ -834,18 +2505,49 @@ impl Operand {
             OperandType::Register(..) => D3D10_SB_OPERAND_TYPE_TEMP,
             OperandType::Input(..) => D3D10_SB_OPERAND_TYPE_INPUT,
             OperandType::Output(..) => D3D10_SB_OPERAND_TYPE_OUTPUT,
-            _ => 100000,
+            OperandType::Imm32(..)
+            | OperandType::Imm32x2(..)
+            | OperandType::Imm32x3(..)
+            | OperandType::Imm32x4(..) => D3D10_SB_OPERAND_TYPE_IMMEDIATE32,
+            OperandType::Imm64(..) | OperandType::Imm64x2(..) => D3D10_SB_OPERAND_TYPE_IMMEDIATE64,
+            OperandType::Resource(..) => D3D10_SB_OPERAND_TYPE_RESOURCE,
+            OperandType::Sampler(..) => D3D10_SB_OPERAND_TYPE_SAMPLER,
+            OperandType::UnorderedAccessView(..) => D3D11_SB_OPERAND_TYPE_UNORDERED_ACCESS_VIEW,
+            OperandType::ThreadGroupSharedMemory(..) => {
+                D3D11_SB_OPERAND_TYPE_THREAD_GROUP_SHARED_MEMORY
+            }
+            OperandType::IndexableRegister(..) => D3D10_SB_OPERAND_TYPE_INDEXABLE_TEMP,
+            OperandType::ConstantBuffer(..) => D3D10_SB_OPERAND_TYPE_CONSTANT_BUFFER,
+            OperandType::ImmediateConstantBuffer(..) => {
+                D3D10_SB_OPERAND_TYPE_IMMEDIATE_CONSTANT_BUFFER
+            }
+            OperandType::Other(ty, _) => *ty as u32,
+            OperandType::CustomData(..) => unreachable!(),
         }
     }
 }
 
+#[derive(Debug)]
 pub struct ShexChunk {
+    program_type: u16,
+    major: u8,
+    minor: u8,
     instructions: Vec<Instruction>,
 }
 
 impl ShexChunk {
+    /// Creates the program of a shader model 5.0 vertex shader.
     pub fn new() -> Self {
+        Self::with_version(D3D10_SB_VERTEX_SHADER as u16, 5, 0)
+    }
+
+    /// `program_type` is a `D3D10_SB_TOKENIZED_PROGRAM_TYPE`, like in
+    /// `ShexHeader`.
+    pub fn with_version(program_type: u16, major: u8, minor: u8) -> Self {
         ShexChunk {
+            program_type,
+            major,
+            minor,
             instructions: Vec::new(),
         }
     }
@@ -860,3 +2562,402 @@ impl Default for ShexChunk {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dr::{DxilProgramKind, ShaderFeatureFlags, ShaderHashFlags};
+
+    #[test]
+    fn round_trip() {
+        for shader in [
+            &include_bytes!("../../../dxbcd/shader.dxbc")[..],
+            &include_bytes!("../../../dxbcd/complex_shader.dxbc")[..],
+            &include_bytes!("../../../dxbcd/shader_abort.dxbc")[..],
+            &include_bytes!("../../../dxbcd/reference.dxbc")[..],
+            &include_bytes!("../../../dxbcd/assembled.dxbc")[..],
+        ] {
+            let module = Builder::parse(shader).unwrap().module().unwrap();
+            assert_eq!(module.as_bytes(), shader);
+        }
+    }
+
+    #[test]
+    fn encode_instructions() {
+        let xyzw = NumComponent::D4(ComponentMode::Swizzle(X, Y, Z, W));
+        let mask = |mask| NumComponent::D4(ComponentMode::Mask(mask));
+        let select = |component| NumComponent::D4(ComponentMode::Select(component));
+        let one = 1.0f32.to_bits();
+
+        let mut shex = ShexChunk::with_version(D3D10_SB_PIXEL_SHADER as u16, 5, 0);
+        shex.add_instruction(Instruction::DclGlobalFlags {
+            flags: GlobalFlags::REFACTORING_ALLOWED,
+        });
+        shex.add_instruction(Instruction::DclConstantBuffer {
+            register: Operand::new(
+                OperandType::ConstantBuffer(0, Address::Constant(2)),
+                Modifier::None,
+                xyzw,
+            ),
+            access: ConstantBufferIndexPattern::Dynamic,
+        });
+        shex.add_instruction(Instruction::DclSampler {
+            register: Operand::new(OperandType::Sampler(0), Modifier::None, NumComponent::D0),
+            mode: SamplerMode::Default,
+        });
+        shex.add_instruction(Instruction::DclResource {
+            register: Operand::new(OperandType::Resource(0), Modifier::None, NumComponent::D0),
+            dimension: ResourceDimension::Texture2D,
+            return_type: [ResourceReturnType::Float; 4],
+            sample_count: 0,
+        });
+        shex.add_instruction(Instruction::DclInputPs {
+            register: Operand::input(1, Modifier::None, mask(X | Y)),
+            interpolation: InterpolationMode::Linear,
+        });
+        shex.add_instruction(Instruction::DclOutput {
+            register: Operand::output(0, Modifier::None, mask(X | Y | Z | W)),
+        });
+        shex.add_instruction(Instruction::DclTemps { count: 1 });
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::Mov,
+            saturated: true,
+            test: None,
+            extended: Vec::new(),
+            operands: vec![
+                Operand::output(0, Modifier::None, mask(X | Y | Z | W)),
+                Operand::new(
+                    OperandType::Imm32x4(one, one, one, one),
+                    Modifier::None,
+                    mask(0),
+                ),
+            ],
+        });
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::If,
+            saturated: false,
+            test: Some(TestBoolean::NonZero),
+            extended: Vec::new(),
+            operands: vec![Operand::register(0, Modifier::None, select(X))],
+        });
+        shex.add_instruction(Instruction::Ret);
+
+        let mut module = DxbcModule::new();
+        module.write_shex(&shex).unwrap();
+        #[rustfmt::skip]
+        assert_eq!(
+            module.dwords[4..],
+            [
+                0x0100086a,
+                0x04000859, 0x00208e46, 0x00000000, 0x00000002,
+                0x0300005a, 0x00106000, 0x00000000,
+                0x04001858, 0x00107000, 0x00000000, 0x00005555,
+                0x03001062, 0x00101032, 0x00000001,
+                0x03000065, 0x001020f2, 0x00000000,
+                0x02000068, 0x00000001,
+                0x08002036, 0x001020f2, 0x00000000, 0x00004002, one, one, one, one,
+                0x0304001f, 0x0010000a, 0x00000000,
+                0x0100003e,
+            ]
+        );
+
+        // the rest is checked against the parser
+        shex.add_instruction(Instruction::DclImmediateConstantBuffer(vec![[1, 2, 3, 4]]));
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::Sample,
+            saturated: false,
+            test: None,
+            extended: vec![OpcodeEx::UvOffset(0xf, 1, 0)],
+            operands: vec![
+                Operand::register(0, Modifier::None, mask(X | Y | Z | W)),
+                Operand::input(1, Modifier::Neg, xyzw),
+                Operand::new(OperandType::Resource(0), Modifier::None, xyzw),
+                Operand::new(OperandType::Sampler(0), Modifier::None, NumComponent::D0),
+            ],
+        });
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::Mov,
+            saturated: false,
+            test: None,
+            extended: Vec::new(),
+            operands: vec![
+                Operand::register(0, Modifier::None, mask(X)),
+                Operand::new(
+                    OperandType::ConstantBuffer(
+                        0,
+                        Address::Index(Box::new(Immediate::U32Relative(
+                            1,
+                            Operand::register(0, Modifier::None, select(W)),
+                        ))),
+                    ),
+                    Modifier::Abs,
+                    select(Y),
+                )
+                .with_min_precision(MinPrecision::Float16),
+            ],
+        });
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::Mov,
+            saturated: false,
+            test: None,
+            extended: Vec::new(),
+            operands: vec![
+                Operand::new(
+                    OperandType::Other(
+                        shex::OperandType::Temp,
+                        vec![Immediate::U64(0x1_0000_0002)],
+                    ),
+                    Modifier::None,
+                    mask(X),
+                ),
+                Operand::new(OperandType::Imm64(1), Modifier::None, NumComponent::D1),
+            ],
+        });
+        shex.add_instruction(Instruction::ResInfo {
+            return_type: ResInfoReturnType::UInt,
+            extended: Vec::new(),
+            dest: Operand::register(0, Modifier::None, mask(X | Y)),
+            mip_level: Operand::new(OperandType::Imm32(0), Modifier::None, NumComponent::D1),
+            resource: Operand::new(OperandType::Resource(0), Modifier::None, xyzw),
+        });
+        shex.add_instruction(Instruction::DclFunctionTable {
+            index: 0,
+            bodies: (0..100).collect(),
+        });
+
+        let mut builder = Builder::new();
+        builder.set_shex(shex);
+        let module = builder.module().unwrap();
+
+        let parsed = Builder::parse(module.as_bytes()).unwrap();
+        let instructions: Vec<_> = match parsed.get_chunks() {
+            [Chunk::Shex(shex)] => shex
+                .instructions
+                .iter()
+                .map(|instruction| match instruction {
+                    Instruction::Decoded(instruction) => instruction,
+                    _ => unreachable!(),
+                })
+                .collect(),
+            chunks => panic!("unexpected chunks {:?}", chunks),
+        };
+        assert_eq!(instructions.len(), 16);
+
+        match instructions[10] {
+            shex::Instruction::CustomData { class, data } => {
+                assert_eq!(*class, CustomDataClass::ImmediateConstantBuffer as u32);
+                assert_eq!(data, &[1, 2, 3, 4]);
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+        match instructions[11] {
+            shex::Instruction::Operation {
+                extended, operands, ..
+            } => {
+                assert_eq!(
+                    extended,
+                    &[shex::OpcodeExtension::SampleControls([-1, 1, 0])]
+                );
+                assert_eq!(operands[1].modifier, shex::OperandModifier::Neg);
+                assert_eq!(operands[3].ty, shex::OperandType::Sampler);
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+        match instructions[12] {
+            shex::Instruction::Operation { operands, .. } => {
+                let cb = &operands[1];
+                assert_eq!(cb.modifier, shex::OperandModifier::Abs);
+                assert_eq!(cb.min_precision, MinPrecision::Float16);
+                match &cb.indices[1] {
+                    shex::OperandIndex::Immediate32PlusRelative(1, relative) => {
+                        assert_eq!(relative.ty, shex::OperandType::Temp);
+                        assert_eq!(
+                            relative.components,
+                            shex::OperandComponents::Select1(shex::ComponentName::W)
+                        );
+                        assert_eq!(relative.indices, [shex::OperandIndex::Immediate32(0)]);
+                    }
+                    index => panic!("unexpected index {:?}", index),
+                }
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+        match instructions[13] {
+            shex::Instruction::Operation { operands, .. } => {
+                assert_eq!(
+                    operands[0].indices,
+                    [shex::OperandIndex::Immediate64(0x1_0000_0002)]
+                );
+                assert_eq!(operands[1].immediates, [1, 0]);
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+        match instructions[14] {
+            shex::Instruction::Operation { controls, .. } => {
+                assert_eq!(*controls, ResInfoReturnType::UInt as u32);
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+        match instructions[15] {
+            shex::Instruction::Declaration(shex::Declaration::FunctionTable { index, bodies }) => {
+                assert_eq!(*index, 0);
+                assert_eq!(bodies.len(), 100);
+                assert_eq!(bodies[99], 99);
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+
+        assert_eq!(parsed.module().unwrap().as_bytes(), module.as_bytes());
+    }
+
+    #[test]
+    fn encode_errors() {
+        let mut shex = ShexChunk::with_version(D3D10_SB_PIXEL_SHADER as u16, 5, 0);
+        shex.add_instruction(Instruction::DclFunctionTable {
+            index: 0,
+            bodies: (0..200).collect(),
+        });
+        let mut builder = Builder::new();
+        builder.set_shex(shex);
+        assert!(matches!(
+            builder.module(),
+            Err(BuildError::InstructionTooLong(_))
+        ));
+
+        let mut shex = ShexChunk::with_version(D3D10_SB_PIXEL_SHADER as u16, 5, 0);
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::Mov,
+            saturated: false,
+            test: None,
+            extended: Vec::new(),
+            operands: vec![
+                Operand::new(
+                    OperandType::Other(shex::OperandType::Temp, vec![Immediate::U32(0); 4]),
+                    Modifier::None,
+                    NumComponent::D4(ComponentMode::Mask(X)),
+                ),
+                Operand::new(OperandType::Imm32(0), Modifier::None, NumComponent::D1),
+            ],
+        });
+        let mut builder = Builder::new();
+        builder.set_shex(shex);
+        assert_eq!(builder.module().err(), Some(BuildError::TooManyIndices));
+
+        let mut shex = ShexChunk::with_version(D3D10_SB_PIXEL_SHADER as u16, 5, 0);
+        shex.add_instruction(Instruction::Operation {
+            opcode: Opcode::Mov,
+            saturated: false,
+            test: None,
+            extended: Vec::new(),
+            operands: vec![
+                Operand::new(
+                    OperandType::Other(shex::OperandType::Temp, vec![Immediate::U32(0)]),
+                    Modifier::None,
+                    NumComponent::D4(ComponentMode::Mask(X)),
+                ),
+                Operand::new(
+                    OperandType::Other(shex::OperandType::Temp, vec![Immediate::U32(1)]),
+                    Modifier::None,
+                    NumComponent::D4(ComponentMode::Select(0)),
+                ),
+            ],
+        });
+        let mut builder = Builder::new();
+        builder.set_shex(shex);
+        assert_eq!(
+            builder.module().err(),
+            Some(BuildError::InvalidComponent(0))
+        );
+
+        let mut shex = ShexChunk::with_version(D3D10_SB_GEOMETRY_SHADER as u16, 5, 0);
+        shex.add_instruction(Instruction::DclGsInputPrimitive {
+            primitive: Primitive::ControlPointPatch(0),
+        });
+        let mut builder = Builder::new();
+        builder.set_shex(shex);
+        assert_eq!(
+            builder.module().err(),
+            Some(BuildError::InvalidControlPointCount(0))
+        );
+    }
+
+    #[test]
+    fn dxil_chunks() {
+        let bitcode = [0x42, 0x43, 0xc0, 0xde, 0x21, 0x0c, 0x00, 0x00];
+
+        let mut builder = Builder::new();
+        builder.add_chunk(Chunk::Dxil(DxilChunk {
+            kind: DxilProgramKind::Pixel,
+            major: 6,
+            minor: 0,
+            size: 8,
+            dxil_major: 1,
+            dxil_minor: 0,
+            bitcode: &bitcode,
+        }));
+        builder.add_chunk(Chunk::Hash(HashChunk {
+            flags: ShaderHashFlags::INCLUDES_SOURCE,
+            digest: [7; 16],
+        }));
+        builder.add_chunk(Chunk::Ildn(IldnChunk {
+            flags: 0,
+            name: "abc.pdb",
+        }));
+        let module = builder.module().unwrap();
+
+        let parsed = Builder::parse(module.as_bytes()).unwrap();
+        match parsed.get_chunks() {
+            [Chunk::Dxil(dxil), Chunk::Hash(hash), Chunk::Ildn(ildn)] => {
+                assert_eq!((dxil.major, dxil.size), (6, 8));
+                assert_eq!(dxil.bitcode, bitcode);
+                assert_eq!(hash.digest, [7; 16]);
+                assert_eq!(ildn.name, "abc.pdb");
+            }
+            chunks => panic!("unexpected chunks {:?}", chunks),
+        }
+        assert_eq!(parsed.module().unwrap().as_bytes(), module.as_bytes());
+    }
+
+    #[test]
+    fn sfi0_chunk() {
+        let flags = ShaderFeatureFlags::DOUBLES
+            | ShaderFeatureFlags::UAVS_AT_EVERY_STAGE
+            | ShaderFeatureFlags::MINIMUM_PRECISION;
+
+        let mut builder = Builder::new();
+        builder.set_sfi0(Sfi0Chunk { flags });
+        let module = builder.module().unwrap();
+
+        // the chunk follows the header and its one offset
+        assert_eq!(module.dwords[9..], [SFI0_MAGIC, 8, 0x15, 0]);
+
+        let parsed = Builder::parse(module.as_bytes()).unwrap();
+        match parsed.get_chunks() {
+            [Chunk::Sfi0(sfi0)] => assert_eq!(sfi0.flags, flags),
+            chunks => panic!("unexpected chunks {:?}", chunks),
+        }
+    }
+
+    #[test]
+    fn unknown_flags() {
+        let mut builder = Builder::new();
+        builder.set_sfi0(Sfi0Chunk {
+            flags: unsafe { ShaderFeatureFlags::from_bits_unchecked(0x1_0000_0001) },
+        });
+        builder.add_chunk(Chunk::Hash(HashChunk {
+            flags: unsafe { ShaderHashFlags::from_bits_unchecked(0x8000_0001) },
+            digest: [7; 16],
+        }));
+        let module = builder.module().unwrap();
+
+        let parsed = Builder::parse(module.as_bytes()).unwrap();
+        match parsed.get_chunks() {
+            [Chunk::Sfi0(sfi0), Chunk::Hash(hash)] => {
+                assert_eq!(sfi0.flags.bits(), 0x1_0000_0001);
+                assert_eq!(hash.flags.bits(), 0x8000_0001);
+            }
+            chunks => panic!("unexpected chunks {:?}", chunks),
+        }
+        assert_eq!(parsed.module().unwrap().as_bytes(), module.as_bytes());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::binary::*;
+
+use int_enum::IntEnum;
+
+const DXIL_MAGIC: u32 = 0x4c49_5844;
+
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum DxilProgramKind {
+    Pixel = 0,
+    Vertex = 1,
+    Geometry = 2,
+    Hull = 3,
+    Domain = 4,
+    Compute = 5,
+    Library = 6,
+    RayGeneration = 7,
+    Intersection = 8,
+    AnyHit = 9,
+    ClosestHit = 10,
+    Miss = 11,
+    Callable = 12,
+    Mesh = 13,
+    Amplification = 14,
+    Node = 15,
+}
+
+/// The program header of a `DXIL` chunk, or of an `ILDB` chunk, which holds
+/// the same program with its debug info left in.
+#[derive(Debug)]
+pub struct DxilChunk<'a> {
+    pub kind: DxilProgramKind,
+    pub major: u8,
+    pub minor: u8,
+    /// The size of the program in dwords, including this header.
+    pub size: u32,
+    pub dxil_major: u16,
+    pub dxil_minor: u16,
+    /// The LLVM bitcode module.
+    pub bitcode: &'a [u8],
+}
+
+impl<'a> DxilChunk<'a> {
+    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
+        let offset = decoder.get_offset();
+        let version = decoder.read_u32()?;
+        let kind = DxilProgramKind::from_int((version >> 16) as u16)
+            .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(offset)))?;
+        let size = decoder.read_u32()?;
+
+        // the bitcode offset is relative to the start of the bitcode header
+        let bitcode_header = decoder.get_offset();
+        if decoder.read_u32()? != DXIL_MAGIC {
+            return Err(State::ChunkIncorrect);
+        }
+        let dxil_version = decoder.read_u32()?;
+        let bitcode_offset = decoder.read_u32()? as usize;
+        let bitcode_size = decoder.read_u32()? as usize;
+
+        let bitcode = decoder
+            .seek(bitcode_header + bitcode_offset)?
+            .bytes(bitcode_size)?;
+
+        Ok(Self {
+            kind,
+            major: ((version >> 4) & 0xf) as u8,
+            minor: (version & 0xf) as u8,
+            size,
+            dxil_major: (dxil_version >> 8) as u16,
+            dxil_minor: (dxil_version & 0xff) as u16,
+            bitcode,
+        })
+    }
+}
+
+bitflags! {
+    pub struct ShaderHashFlags: u32 {
+        /// The digest covers the source as well as the program.
+        const INCLUDES_SOURCE = 0x1;
+    }
+}
+
+#[derive(Debug)]
+pub struct HashChunk {
+    pub flags: ShaderHashFlags,
+    pub digest: [u8; 16],
+}
+
+impl HashChunk {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        // keep unknown bits so the chunk is written back unchanged
+        let flags = unsafe { ShaderHashFlags::from_bits_unchecked(decoder.read_u32()?) };
+
+        let mut digest = [0; 16];
+        digest.copy_from_slice(decoder.bytes(16)?);
+
+        Ok(Self { flags, digest })
+    }
+}
+
+/// The name of the PDB holding the debug info of a shader compiled with
+/// `/Qstrip_debug`.
+#[derive(Debug)]
+pub struct IldnChunk<'a> {
+    pub flags: u16,
+    pub name: &'a str,
+}
+
+impl<'a> IldnChunk<'a> {
+    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
+        let flags = decoder.read_u16()?;
+        let _name_length = decoder.read_u16()?;
+        let name = decoder.str()?;
+
+        Ok(Self { flags, name })
+    }
+}
@@ -0,0 +1,206 @@
+use crate::binary::*;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct ClassType<'a> {
+    pub name: &'a str,
+    pub id: u16,
+    pub constant_buffer_stride: u16,
+    pub texture: u16,
+    pub sampler: u16,
+}
+
+impl<'a> ClassType<'a> {
+    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
+        let name_offset = decoder.read_u32()? as usize;
+        let id = decoder.read_u16()?;
+        let constant_buffer_stride = decoder.read_u16()?;
+        let texture = decoder.read_u16()?;
+        let sampler = decoder.read_u16()?;
+
+        let name = decoder.seek(name_offset)?.str()?;
+
+        Ok(Self {
+            name,
+            id,
+            constant_buffer_stride,
+            texture,
+            sampler,
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct ClassInstance<'a> {
+    pub name: &'a str,
+    /// Index into `IfceChunk::class_types`.
+    pub ty: u16,
+    _unknown: u16,
+    pub constant_buffer: u16,
+    pub constant_buffer_offset: u16,
+    pub texture: u16,
+    pub sampler: u16,
+}
+
+impl<'a> ClassInstance<'a> {
+    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
+        let name_offset = decoder.read_u32()? as usize;
+        let ty = decoder.read_u16()?;
+        let _unknown = decoder.read_u16()?;
+        let constant_buffer = decoder.read_u16()?;
+        let constant_buffer_offset = decoder.read_u16()?;
+        let texture = decoder.read_u16()?;
+        let sampler = decoder.read_u16()?;
+
+        let name = decoder.seek(name_offset)?.str()?;
+
+        Ok(Self {
+            name,
+            ty,
+            _unknown,
+            constant_buffer,
+            constant_buffer_offset,
+            texture,
+            sampler,
+        })
+    }
+}
+
+/// An interface slot (or an array of them) and the class types that can be
+/// bound to it.
+#[repr(C)]
+#[derive(Debug)]
+pub struct InterfaceSlot {
+    pub start_slot: u32,
+    pub slot_span: u32,
+    pub type_ids: Vec<u16>,
+    /// The function table used by each entry of `type_ids`, as declared by
+    /// `dcl_function_table`.
+    pub table_ids: Vec<u32>,
+}
+
+impl InterfaceSlot {
+    pub fn parse(decoder: &mut Decoder, start_slot: u32) -> Result<Self, State> {
+        let slot_span = decoder.read_u32()?;
+        let count = decoder.read_u32()?;
+        let type_ids_offset = decoder.read_u32()? as usize;
+        let table_ids_offset = decoder.read_u32()? as usize;
+
+        let mut type_ids = Vec::new();
+        let mut type_ids_decoder = decoder.seek(type_ids_offset)?;
+        for _ in 0..count {
+            type_ids.push(type_ids_decoder.read_u16()?);
+        }
+
+        let table_ids = decoder.seek(table_ids_offset)?.words(count as usize)?;
+
+        Ok(Self {
+            start_slot,
+            slot_span,
+            type_ids,
+            table_ids,
+        })
+    }
+
+    /// Returns the function table for class type `type_id` in this slot.
+    pub fn get_table_id(&self, type_id: u16) -> Option<u32> {
+        self.type_ids
+            .iter()
+            .position(|&id| id == type_id)
+            .map(|i| self.table_ids[i])
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct IfceChunk<'a> {
+    pub class_types: Vec<ClassType<'a>>,
+    pub class_instances: Vec<ClassInstance<'a>>,
+    pub interface_slots: Vec<InterfaceSlot>,
+    /// The total number of slots, which is larger than the number of
+    /// `interface_slots` when there are interface arrays.
+    pub interface_slot_count: u32,
+}
+
+impl<'a> IfceChunk<'a> {
+    pub fn parse<'b>(decoder: &'b mut Decoder) -> Result<IfceChunk<'b>, State> {
+        let class_instance_count = decoder.read_u32()?;
+        let class_type_count = decoder.read_u32()?;
+        let interface_slot_record_count = decoder.read_u32()?;
+        let interface_slot_count = decoder.read_u32()?;
+        let _unknown = decoder.read_u32()?;
+        let class_type_offset = decoder.read_u32()? as usize;
+        let interface_slot_offset = decoder.read_u32()? as usize;
+
+        // class instances directly follow the class types
+        let mut class_decoder = decoder.seek(class_type_offset)?;
+
+        let mut class_types = Vec::new();
+        for _ in 0..class_type_count {
+            class_types.push(ClassType::parse(&mut class_decoder)?);
+        }
+
+        let mut class_instances = Vec::new();
+        for _ in 0..class_instance_count {
+            class_instances.push(ClassInstance::parse(&mut class_decoder)?);
+        }
+
+        let mut interface_slots = Vec::new();
+        let mut slot_decoder = decoder.seek(interface_slot_offset)?;
+        let mut start_slot = 0;
+        for _ in 0..interface_slot_record_count {
+            let slot = InterfaceSlot::parse(&mut slot_decoder, start_slot)?;
+            start_slot = start_slot
+                .checked_add(slot.slot_span)
+                .ok_or(State::ChunkIncorrect)?;
+
+            interface_slots.push(slot);
+        }
+
+        Ok(IfceChunk {
+            class_types,
+            class_instances,
+            interface_slots,
+            interface_slot_count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        #[rustfmt::skip]
+        let words: [u32; 20] = [
+            // 1 instance, 1 type, 1 slot record spanning 2 slots
+            1, 1, 1, 2, 9, 0x1c, 0x38,
+            // class type `Light`
+            0x50, 0x0004_0000, 0x0000_0000,
+            // class instance `g_light`
+            0x58, 0x0000_0000, 0x0000_0000, 0x0000_0000,
+            // interface slot
+            2, 1, 0x48, 0x4c,
+            // type ids and table ids
+            0, 3,
+        ];
+        let mut bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        bytes.extend_from_slice(b"Light\0\0\0g_light\0");
+
+        let mut decoder = Decoder::new(&bytes);
+        let ifce = IfceChunk::parse(&mut decoder).unwrap();
+
+        assert_eq!(ifce.class_types.len(), 1);
+        assert_eq!(ifce.class_types[0].name, "Light");
+        assert_eq!(ifce.class_types[0].constant_buffer_stride, 4);
+        assert_eq!(ifce.class_instances.len(), 1);
+        assert_eq!(ifce.class_instances[0].name, "g_light");
+        assert_eq!(ifce.interface_slot_count, 2);
+        assert_eq!(ifce.interface_slots.len(), 1);
+        assert_eq!(ifce.interface_slots[0].slot_span, 2);
+        assert_eq!(ifce.interface_slots[0].get_table_id(0), Some(3));
+        assert_eq!(ifce.interface_slots[0].get_table_id(1), None);
+    }
+}
@@ -1,9 +1,10 @@
+use super::shex::MinPrecision;
 use crate::binary::*;
 
-use std::mem;
+use int_enum::IntEnum;
 
 #[repr(u32)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, IntEnum)]
 pub enum RegisterComponentType {
     Unknown = 0,
     Uint32 = 1,
@@ -11,17 +12,8 @@ pub enum RegisterComponentType {
     Float32 = 3,
 }
 
-impl RegisterComponentType {
-    pub fn from_word(word: u32) -> Self {
-        match word {
-            0..=3 => unsafe { mem::transmute(word) },
-            _ => unreachable!(),
-        }
-    }
-}
-
 #[repr(u32)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, IntEnum)]
 pub enum SemanticName {
     Undefined = 0,
     Position = 1,
@@ -47,18 +39,23 @@ pub enum SemanticName {
     DepthLessEqual = 68,
 }
 
-impl SemanticName {
-    pub fn from_word(word: u32) -> Self {
-        match word {
-            0..=16 | 64..=68 => unsafe { mem::transmute(word) },
-            _ => unreachable!(),
-        }
-    }
+/// The element layout of a signature chunk, which depends on its fourcc.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SignatureLayout {
+    /// `ISGN`, `OSGN` and `PCSG`
+    Basic,
+    /// `OSG5`, which prefixes each element with its geometry shader stream
+    Stream,
+    /// `ISG1` and `OSG1`, which add the stream and the minimum precision
+    StreamMinPrecision,
 }
 
 #[repr(C)]
 #[derive(Debug)]
 pub struct InputOutputElement {
+    /// The geometry shader stream the element is written to. Always 0 for
+    /// the `Basic` layout.
+    pub stream: u32,
     pub name: String,
     pub semantic_index: u32,
     pub semantic_type: SemanticName,
@@ -66,25 +63,34 @@ pub struct InputOutputElement {
     pub register: u32,
     pub component_mask: u8,
     pub rw_mask: u8,
+    /// Always `MinPrecision::Default` unless the layout is
+    /// `StreamMinPrecision`.
+    pub min_precision: MinPrecision,
 }
 
 impl InputOutputElement {
-    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Self, State> {
-        let name_offset = decoder.read_u32();
-        let semantic_index = decoder.read_u32();
-        let semantic_type = SemanticName::from_word(decoder.read_u32());
-        let component_type = RegisterComponentType::from_word(decoder.read_u32());
-        let register = decoder.read_u32();
-        let component_mask = decoder.read_u8();
-        let rw_mask = decoder.read_u8();
-        decoder.skip(2);
-
-        let name = decoder
-            .seek(name_offset as usize)
-            .string()
-            .map_err(State::DecoderError)?;
+    pub fn parse(decoder: &mut decoder::Decoder, layout: SignatureLayout) -> Result<Self, State> {
+        let stream = match layout {
+            SignatureLayout::Basic => 0,
+            SignatureLayout::Stream | SignatureLayout::StreamMinPrecision => decoder.read_u32()?,
+        };
+        let name_offset = decoder.read_u32()?;
+        let semantic_index = decoder.read_u32()?;
+        let semantic_type = read_enum!(SemanticName, decoder, u32);
+        let component_type = read_enum!(RegisterComponentType, decoder, u32);
+        let register = decoder.read_u32()?;
+        let component_mask = decoder.read_u8()?;
+        let rw_mask = decoder.read_u8()?;
+        decoder.skip(2)?;
+        let min_precision = match layout {
+            SignatureLayout::StreamMinPrecision => read_enum!(MinPrecision, decoder, u32),
+            SignatureLayout::Basic | SignatureLayout::Stream => MinPrecision::Default,
+        };
+
+        let name = decoder.seek(name_offset as usize)?.string()?;
 
         Ok(Self {
+            stream,
             name,
             semantic_index,
             semantic_type,
@@ -92,6 +98,7 @@ impl InputOutputElement {
             register,
             component_mask,
             rw_mask,
+            min_precision,
         })
     }
 }
@@ -99,19 +106,63 @@ impl InputOutputElement {
 #[repr(C)]
 #[derive(Debug)]
 pub struct IOsgnChunk {
+    pub layout: SignatureLayout,
     pub elements: Vec<InputOutputElement>,
 }
 
 impl IOsgnChunk {
-    pub fn parse(decoder: &mut decoder::Decoder) -> Result<IOsgnChunk, State> {
-        let element_count = decoder.read_u32();
-        let _unknown = decoder.read_u32();
+    pub fn parse(
+        decoder: &mut decoder::Decoder,
+        layout: SignatureLayout,
+    ) -> Result<IOsgnChunk, State> {
+        let element_count = decoder.read_u32()?;
+        let _unknown = decoder.read_u32()?;
 
         let mut elements = Vec::new();
         for _ in 0..element_count {
-            elements.push(InputOutputElement::parse(decoder)?);
+            elements.push(InputOutputElement::parse(decoder, layout)?);
         }
 
-        Ok(IOsgnChunk { elements })
+        Ok(IOsgnChunk { layout, elements })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dr::DxbcModule;
+
+    #[test]
+    fn stream_min_precision_layout() {
+        let chunk = IOsgnChunk {
+            layout: SignatureLayout::StreamMinPrecision,
+            elements: vec![InputOutputElement {
+                stream: 1,
+                name: String::from("TEXCOORD"),
+                semantic_index: 2,
+                semantic_type: SemanticName::Undefined,
+                component_type: RegisterComponentType::Float32,
+                register: 3,
+                component_mask: 0xf,
+                rw_mask: 0x3,
+                min_precision: MinPrecision::Float16,
+            }],
+        };
+
+        let mut module = DxbcModule::new();
+        module.write_iosgn(&chunk, 0);
+
+        // skip the fourcc and chunk size
+        let bytes = &module.as_bytes()[8..];
+        let parsed = IOsgnChunk::parse(&mut decoder::Decoder::new(bytes), chunk.layout).unwrap();
+
+        let element = &parsed.elements[0];
+        assert_eq!(element.stream, 1);
+        assert_eq!(element.name, "TEXCOORD");
+        assert_eq!(element.semantic_index, 2);
+        assert_eq!(element.register, 3);
+        assert_eq!(element.component_mask, 0xf);
+        assert_eq!(element.rw_mask, 0x3);
+        assert_eq!(element.min_precision, MinPrecision::Float16);
     }
 }
@@ -1,3 +1,5 @@
+use crate::binary::*;
+
 use int_enum::IntEnum;
 
 #[repr(u32)]
@@ -14,19 +16,36 @@ pub enum ResourceReturnType {
     Continued = 8,
 }
 
+pub mod aon9;
 pub mod builder;
+pub mod dxil;
+pub mod ifce;
 pub mod isgn;
+pub mod psv0;
 pub mod rdef;
+pub mod rts0;
+pub mod sfi0;
 pub mod shex;
+pub mod spdb;
 pub mod stat;
 
+pub use self::aon9::*;
 pub use self::builder::*;
+pub use self::dxil::*;
+pub use self::ifce::*;
 pub use self::isgn::*;
+pub use self::psv0::*;
 pub use self::rdef::*;
+pub use self::rts0::*;
+pub use self::sfi0::*;
 pub use self::shex::*;
+pub use self::spdb::*;
 pub use self::stat::*;
 
-#[repr(C)]
+// the builder and shex both define these, `dr::` gets the ones used to write
+// modules and the decoded ones stay at `dr::shex::`
+pub use self::builder::{Immediate, Instruction, Operand, OperandType};
+
 #[derive(Debug)]
 pub struct DxbcHeader {
     pub magic: [u8; 4],
@@ -35,3 +54,23 @@ pub struct DxbcHeader {
     pub size: u32,
     pub chunk_count: u32,
 }
+
+impl DxbcHeader {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Self, State> {
+        let mut magic = [0; 4];
+        magic.copy_from_slice(decoder.bytes(4)?);
+
+        let mut checksum = [0; 4];
+        for word in &mut checksum {
+            *word = decoder.read_u32()?;
+        }
+
+        Ok(DxbcHeader {
+            magic,
+            checksum,
+            _unknown: decoder.read_u32()?,
+            size: decoder.read_u32()?,
+            chunk_count: decoder.read_u32()?,
+        })
+    }
+}
@@ -0,0 +1,310 @@
+use crate::binary::*;
+
+use int_enum::IntEnum;
+
+const RUNTIME_INFO_0_SIZE: usize = 24;
+const RUNTIME_INFO_1_SIZE: usize = 36;
+const RUNTIME_INFO_2_SIZE: usize = 48;
+const RUNTIME_INFO_3_SIZE: usize = 52;
+
+const RESOURCE_BINDING_0_SIZE: usize = 16;
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum PsvResourceType {
+    Invalid = 0,
+    Sampler = 1,
+    Cbv = 2,
+    SrvTyped = 3,
+    SrvRaw = 4,
+    SrvStructured = 5,
+    UavTyped = 6,
+    UavRaw = 7,
+    UavStructured = 8,
+    UavStructuredWithCounter = 9,
+}
+
+#[derive(Debug)]
+pub struct PsvResourceBinding {
+    pub ty: PsvResourceType,
+    pub space: u32,
+    pub lower_bound: u32,
+    pub upper_bound: u32,
+    /// The `DXIL::ResourceKind`, or 0 before validator 1.6.
+    pub kind: u32,
+    pub flags: u32,
+}
+
+impl PsvResourceBinding {
+    pub fn parse(decoder: &mut Decoder, size: usize) -> Result<Self, State> {
+        let start = decoder.get_offset();
+
+        let ty = read_enum!(PsvResourceType, decoder, u32);
+        let space = decoder.read_u32()?;
+        let lower_bound = decoder.read_u32()?;
+        let upper_bound = decoder.read_u32()?;
+        let (kind, flags) = if size > RESOURCE_BINDING_0_SIZE {
+            (decoder.read_u32()?, decoder.read_u32()?)
+        } else {
+            (0, 0)
+        };
+        decoder.seek_mut(start + size)?;
+
+        Ok(Self {
+            ty,
+            space,
+            lower_bound,
+            upper_bound,
+            kind,
+            flags,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PsvSignatureElement<'a> {
+    pub name: &'a str,
+    /// One semantic index per row.
+    pub semantic_indices: Vec<u32>,
+    pub rows: u8,
+    pub start_row: u8,
+    pub columns: u8,
+    pub start_column: u8,
+    pub allocated: bool,
+    /// A `DXIL::SemanticKind`.
+    pub semantic_kind: u8,
+    /// A `DXIL::ComponentType`.
+    pub component_type: u8,
+    /// A `DXIL::InterpolationMode`.
+    pub interpolation_mode: u8,
+    pub dynamic_index_mask: u8,
+    pub stream: u8,
+}
+
+impl<'a> PsvSignatureElement<'a> {
+    pub fn parse(
+        decoder: &mut Decoder<'a>,
+        size: usize,
+        strings: &Decoder<'a>,
+        semantic_indices: &[u32],
+    ) -> Result<Self, State> {
+        let start = decoder.get_offset();
+
+        let name_offset = decoder.read_u32()? as usize;
+        let indices_offset = decoder.read_u32()? as usize;
+        let rows = decoder.read_u8()?;
+        let start_row = decoder.read_u8()?;
+        let columns_and_start = decoder.read_u8()?;
+        let semantic_kind = decoder.read_u8()?;
+        let component_type = decoder.read_u8()?;
+        let interpolation_mode = decoder.read_u8()?;
+        let dynamic_mask_and_stream = decoder.read_u8()?;
+        decoder.seek_mut(start + size)?;
+
+        let name = strings.seek(name_offset)?.str()?;
+        let semantic_indices = semantic_indices
+            .get(indices_offset..indices_offset + rows as usize)
+            .ok_or(State::ChunkIncorrect)?
+            .to_vec();
+
+        Ok(Self {
+            name,
+            semantic_indices,
+            rows,
+            start_row,
+            columns: columns_and_start & 0xf,
+            start_column: (columns_and_start >> 4) & 0x3,
+            allocated: columns_and_start & 0x40 != 0,
+            semantic_kind,
+            component_type,
+            interpolation_mode,
+            dynamic_index_mask: dynamic_mask_and_stream & 0xf,
+            stream: (dynamic_mask_and_stream >> 4) & 0x3,
+        })
+    }
+}
+
+/// Pipeline state validation data, which the runtime uses to validate a DXIL
+/// shader against the rest of the pipeline without parsing its bitcode.
+/// Fields added by later versions of the runtime info are 0 when the chunk
+/// predates them.
+#[derive(Debug)]
+pub struct Psv0Chunk<'a> {
+    pub runtime_info_size: u32,
+    /// The stage specific info, like the tessellator domain of hull shaders or
+    /// the depth output of pixel shaders.
+    pub stage_info: [u32; 4],
+    pub min_wave_lane_count: u32,
+    pub max_wave_lane_count: u32,
+    /// A `DxilProgramKind`.
+    pub shader_stage: u8,
+    pub uses_view_id: bool,
+    /// The max vertex count of geometry shaders, or the patch constant or
+    /// primitive vector count of hull, domain and mesh shaders.
+    pub stage_count: u16,
+    pub input_vectors: u8,
+    pub output_vectors: [u8; 4],
+    pub num_threads: [u32; 3],
+    pub entry_function_name: Option<&'a str>,
+    pub resource_bindings: Vec<PsvResourceBinding>,
+    pub inputs: Vec<PsvSignatureElement<'a>>,
+    pub outputs: Vec<PsvSignatureElement<'a>>,
+    pub patch_constants_or_primitives: Vec<PsvSignatureElement<'a>>,
+}
+
+impl<'a> Psv0Chunk<'a> {
+    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
+        let runtime_info_size = decoder.read_u32()?;
+        let size = runtime_info_size as usize;
+        if size < RUNTIME_INFO_0_SIZE {
+            return Err(State::ChunkIncorrect);
+        }
+
+        let start = decoder.get_offset();
+        let stage_info = [
+            decoder.read_u32()?,
+            decoder.read_u32()?,
+            decoder.read_u32()?,
+            decoder.read_u32()?,
+        ];
+        let min_wave_lane_count = decoder.read_u32()?;
+        let max_wave_lane_count = decoder.read_u32()?;
+
+        let mut chunk = Psv0Chunk {
+            runtime_info_size,
+            stage_info,
+            min_wave_lane_count,
+            max_wave_lane_count,
+            shader_stage: 0,
+            uses_view_id: false,
+            stage_count: 0,
+            input_vectors: 0,
+            output_vectors: [0; 4],
+            num_threads: [0; 3],
+            entry_function_name: None,
+            resource_bindings: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            patch_constants_or_primitives: Vec::new(),
+        };
+
+        let mut element_counts = [0; 3];
+        if size >= RUNTIME_INFO_1_SIZE {
+            chunk.shader_stage = decoder.read_u8()?;
+            chunk.uses_view_id = decoder.read_u8()? != 0;
+            chunk.stage_count = decoder.read_u16()?;
+            for count in &mut element_counts {
+                *count = decoder.read_u8()?;
+            }
+            chunk.input_vectors = decoder.read_u8()?;
+            for vectors in &mut chunk.output_vectors {
+                *vectors = decoder.read_u8()?;
+            }
+        }
+        if size >= RUNTIME_INFO_2_SIZE {
+            for threads in &mut chunk.num_threads {
+                *threads = decoder.read_u32()?;
+            }
+        }
+        let entry_function_name = if size >= RUNTIME_INFO_3_SIZE {
+            Some(decoder.read_u32()? as usize)
+        } else {
+            None
+        };
+        decoder.seek_mut(start + size)?;
+
+        let resource_count = decoder.read_u32()?;
+        if resource_count > 0 {
+            let resource_size = decoder.read_u32()? as usize;
+            for _ in 0..resource_count {
+                chunk
+                    .resource_bindings
+                    .push(PsvResourceBinding::parse(decoder, resource_size)?);
+            }
+        }
+
+        if size < RUNTIME_INFO_1_SIZE {
+            return Ok(chunk);
+        }
+
+        let strings_size = decoder.read_u32()? as usize;
+        let strings = decoder.scoped_decoder(strings_size)?;
+        decoder.skip(strings_size)?;
+
+        let semantic_index_count = decoder.read_u32()? as usize;
+        let semantic_indices = decoder.words(semantic_index_count)?;
+
+        if let Some(offset) = entry_function_name {
+            chunk.entry_function_name = Some(strings.seek(offset)?.str()?);
+        }
+
+        if element_counts.iter().any(|&count| count > 0) {
+            let element_size = decoder.read_u32()? as usize;
+
+            let elements = [
+                &mut chunk.inputs,
+                &mut chunk.outputs,
+                &mut chunk.patch_constants_or_primitives,
+            ];
+            for (elements, count) in elements.into_iter().zip(element_counts) {
+                for _ in 0..count {
+                    elements.push(PsvSignatureElement::parse(
+                        decoder,
+                        element_size,
+                        &strings,
+                        &semantic_indices,
+                    )?);
+                }
+            }
+        }
+
+        Ok(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_info_1() {
+        let mut bytes = Vec::new();
+        for word in [36, 0, 0, 0, 0, 0, 0] {
+            bytes.extend_from_slice(&u32::to_le_bytes(word));
+        }
+        // pixel shader with a single input element taking a single vector
+        bytes.extend_from_slice(&[0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0]);
+        // cb1
+        for word in [1, 24, 2, 0, 1, 1, 13, 0] {
+            bytes.extend_from_slice(&u32::to_le_bytes(word));
+        }
+        bytes.extend_from_slice(&u32::to_le_bytes(12));
+        bytes.extend_from_slice(b"\0TEXCOORD\0\0\0");
+        for word in [1, 3, 16, 1, 0] {
+            bytes.extend_from_slice(&u32::to_le_bytes(word));
+        }
+        bytes.extend_from_slice(&[1, 0, 0x42, 0, 9, 2, 0, 0]);
+
+        let psv0 = Psv0Chunk::parse(&mut Decoder::new(&bytes)).unwrap();
+
+        assert_eq!(psv0.runtime_info_size, 36);
+        assert_eq!(psv0.input_vectors, 1);
+        assert_eq!(psv0.entry_function_name, None);
+
+        assert_eq!(psv0.resource_bindings.len(), 1);
+        let binding = &psv0.resource_bindings[0];
+        assert_eq!(binding.ty, PsvResourceType::Cbv);
+        assert_eq!((binding.lower_bound, binding.upper_bound), (1, 1));
+        assert_eq!(binding.kind, 13);
+
+        assert_eq!(psv0.inputs.len(), 1);
+        assert!(psv0.outputs.is_empty());
+        let input = &psv0.inputs[0];
+        assert_eq!(input.name, "TEXCOORD");
+        assert_eq!(input.semantic_indices, [3]);
+        assert_eq!((input.columns, input.start_column), (2, 0));
+        assert!(input.allocated);
+        assert_eq!(input.component_type, 9);
+        assert_eq!(input.interpolation_mode, 2);
+    }
+}
@@ -27,16 +27,16 @@ bitflags! {
 }
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
 pub enum ConstantBufferType {
-    ConstantBuffer,
-    TextureBuffer,
-    InterfacePointers,
-    ResourceBindInformation,
+    ConstantBuffer = 0,
+    TextureBuffer = 1,
+    InterfacePointers = 2,
+    ResourceBindInformation = 3,
 }
 
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, IntEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
 pub enum ShaderInputType {
     CBuffer = 0,
     TBuffer = 1,
@@ -53,7 +53,7 @@ pub enum ShaderInputType {
 }
 
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, IntEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
 pub enum ShaderVariableClass {
     Scalar = 0,
     Vector = 1,
@@ -66,7 +66,7 @@ pub enum ShaderVariableClass {
 }
 
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, IntEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
 pub enum ShaderVariableType {
     Void = 0,
     Bool = 1,
@@ -117,7 +117,7 @@ pub enum ShaderVariableType {
 }
 
 #[repr(u32)]
-#[derive(Clone, Copy, Debug, IntEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
 pub enum ViewDimension {
     Unknown = 0,
     Buffer = 1,
@@ -133,99 +133,163 @@ pub enum ViewDimension {
     ExtendedBuffer = 11,
 }
 
-#[repr(u32)]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ShaderModel {
+    V4_0,
+    V4_1,
     V5_0,
+    V5_1,
 }
 
+const RD11_MAGIC: u32 = 0x3131_4452;
+const RD11_5_1_MAGIC: u32 = 0x2544_1313;
+
+const RD11_HEADER_SIZE: u32 = 60;
+const CONSTANT_BUFFER_SIZE: u32 = 24;
+const RESOURCE_BINDING_SIZE: u32 = 32;
+const RESOURCE_BINDING_5_1_SIZE: u32 = 40;
+const SHADER_VARIABLE_5_0_SIZE: u32 = 40;
+const SHADER_TYPE_5_0_SIZE: u32 = 36;
+const SHADER_TYPE_MEMBER_SIZE: u32 = 12;
+
+/// Deeper type trees can only come from offsets pointing back at a parent.
+const MAX_TYPE_DEPTH: u32 = 64;
+/// The most types a chunk can expand to. Types are shared by offset, so a
+/// few bytes of chunk can otherwise describe an exponentially large tree.
+const MAX_TYPE_NODES: u32 = 1 << 16;
+
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct ShaderTypeMember<'a> {
-    name: &'a str,
-    ty: ShaderType<'a>,
-    offset: u32,
+    pub name: &'a str,
+    pub ty: ShaderType<'a>,
+    /// The offset of the member in bytes from the start of its parent.
+    pub offset: u32,
 }
 
 impl<'a> ShaderTypeMember<'a> {
-    pub fn parse(decoder: &mut Decoder<'a>, major: u8) -> Result<Self, State> {
-        let name_offset = decoder.read_u32() as usize;
-        let ty_offset = decoder.read_u32() as usize;
-        let offset = decoder.read_u32();
-
-        let name = decoder
-            .seek(name_offset)
-            .str()
-            .map_err(State::DecoderError)?;
-        let ty = ShaderType::parse(&mut decoder.seek(ty_offset), major)?;
+    pub fn parse(
+        decoder: &mut Decoder<'a>,
+        major: u8,
+        depth: u32,
+        nodes: &mut u32,
+    ) -> Result<Self, State> {
+        let name_offset = decoder.read_u32()? as usize;
+        let ty_offset = decoder.read_u32()? as usize;
+        let offset = decoder.read_u32()?;
+
+        let name = decoder.seek(name_offset)?.str()?;
+        let ty = ShaderType::parse(&mut decoder.seek(ty_offset)?, major, depth, nodes)?;
 
         Ok(Self { name, ty, offset })
     }
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct ShaderType<'a> {
     pub class: ShaderVariableClass,
     pub ty: ShaderVariableType,
     pub rows: u16,
     pub columns: u16,
+    /// The number of array elements, or 0 if the type isn't an array.
     pub count: u16,
     pub members: Vec<ShaderTypeMember<'a>>,
-    pub parent_ty_class: Option<ShaderVariableClass>,
-    pub parent_name: Option<&'a str>,
-    pub unknowns: [Option<u32>; 4],
+    /// The sub type of classes and interfaces, as returned by
+    /// `ID3D11ShaderReflectionType::GetSubType`.
+    pub sub_type: Option<Box<ShaderType<'a>>>,
+    /// The class this class is derived from.
+    pub base_class: Option<Box<ShaderType<'a>>>,
+    /// The interfaces this class implements.
+    pub interfaces: Vec<ShaderType<'a>>,
+    /// The HLSL name, like `float4x4`, since shader model 5.0.
+    pub name: Option<&'a str>,
 }
 
 impl<'a> ShaderType<'a> {
-    pub fn parse(decoder: &mut Decoder<'a>, major: u8) -> Result<Self, State> {
+    /// Parses the type tree at the decoder's offset. `nodes` is the number
+    /// of types that can still be parsed, shared by every type in the chunk.
+    pub fn parse(
+        decoder: &mut Decoder<'a>,
+        major: u8,
+        depth: u32,
+        nodes: &mut u32,
+    ) -> Result<Self, State> {
+        if depth >= MAX_TYPE_DEPTH {
+            return Err(State::ChunkIncorrect);
+        }
+        let depth = depth + 1;
+        *nodes = nodes.checked_sub(1).ok_or(State::ChunkIncorrect)?;
+
         let class = read_enum!(ShaderVariableClass, decoder, u16);
         let ty = read_enum!(ShaderVariableType, decoder, u16);
-        let rows = decoder.read_u16();
-        let columns = decoder.read_u16();
-        let count = decoder.read_u16();
-        let member_count = decoder.read_u16();
-        let member_offset = decoder.read_u32() as usize;
+        let rows = decoder.read_u16()?;
+        let columns = decoder.read_u16()?;
+        let count = decoder.read_u16()?;
+        let member_count = decoder.read_u16()?;
+        let member_offset = decoder.read_u32()? as usize;
 
-        let mut parent_ty_class = None;
-        let mut parent_name = None;
-        let mut unknowns = [None; 4];
+        let mut sub_type = None;
+        let mut base_class = None;
+        let mut interfaces = Vec::new();
+        let mut name = None;
 
         if major >= 5 {
-            let parent_ty_offset = decoder.read_u32() as usize;
-            if parent_ty_offset != 0 {
-                let mut parent_ty_decoder = decoder.seek(parent_ty_offset);
-                parent_ty_class = Some(read_enum!(ShaderVariableClass, parent_ty_decoder, u16));
-                unknowns[0] = Some(parent_ty_decoder.read_u16() as u32);
+            let sub_type_offset = decoder.read_u32()? as usize;
+            let base_class_offset = decoder.read_u32()? as usize;
+            let interface_count = decoder.read_u32()?;
+            let interfaces_offset = decoder.read_u32()? as usize;
+            let name_offset = decoder.read_u32()? as usize;
+
+            if sub_type_offset != 0 {
+                let mut sub_type_decoder = decoder.seek(sub_type_offset)?;
+                sub_type = Some(Box::new(ShaderType::parse(
+                    &mut sub_type_decoder,
+                    major,
+                    depth,
+                    nodes,
+                )?));
             }
 
-            let unknown_2_offset = decoder.read_u32();
-            if unknown_2_offset != 0 {
-                unknowns[1] = Some(decoder.seek(unknown_2_offset as usize).read_u32());
+            if base_class_offset != 0 {
+                let mut base_class_decoder = decoder.seek(base_class_offset)?;
+                base_class = Some(Box::new(ShaderType::parse(
+                    &mut base_class_decoder,
+                    major,
+                    depth,
+                    nodes,
+                )?));
             }
 
-            unknowns[2] = Some(decoder.read_u32());
-
-            let unknown_5_offset = decoder.read_u32();
-            if unknown_5_offset != 0 {
-                unknowns[3] = Some(decoder.seek(unknown_5_offset as usize).read_u32());
+            // the offset isn't meaningful without interfaces
+            if interface_count != 0 {
+                let mut interfaces_decoder = decoder.seek(interfaces_offset)?;
+                for _ in 0..interface_count {
+                    let offset = interfaces_decoder.read_u32()? as usize;
+                    let mut interface_decoder = decoder.seek(offset)?;
+                    interfaces.push(ShaderType::parse(
+                        &mut interface_decoder,
+                        major,
+                        depth,
+                        nodes,
+                    )?);
+                }
             }
 
-            let parent_name_offset = decoder.read_u32() as usize;
-            if parent_name_offset != 0 {
-                parent_name = Some(
-                    decoder
-                        .seek(parent_name_offset)
-                        .str()
-                        .map_err(State::DecoderError)?,
-                );
+            if name_offset != 0 {
+                name = Some(decoder.seek(name_offset)?.str()?);
             }
         }
 
-        let mut members = Vec::with_capacity(member_count.into());
-        decoder.seek_mut(member_offset);
+        let mut members = Vec::new();
+        let mut member_decoder = decoder.seek(member_offset)?;
         for _ in 0..member_count {
-            members.push(ShaderTypeMember::parse(decoder, major)?);
+            members.push(ShaderTypeMember::parse(
+                &mut member_decoder,
+                major,
+                depth,
+                nodes,
+            )?);
         }
 
         Ok(Self {
@@ -235,22 +299,29 @@ impl<'a> ShaderType<'a> {
             columns,
             count,
             members,
-            parent_ty_class,
-            parent_name,
-            unknowns,
+            sub_type,
+            base_class,
+            interfaces,
+            name,
         })
     }
+
+    /// Returns the member called `name`.
+    pub fn get_member(&self, name: &str) -> Option<&ShaderTypeMember<'a>> {
+        self.members.iter().find(|member| member.name == name)
+    }
 }
 
 #[repr(C)]
 #[derive(Debug)]
 pub struct ShaderVariable<'a> {
     pub name: &'a str,
+    /// The offset of the variable in bytes from the start of its buffer.
     pub offset: u32,
     pub size: u32,
     pub flags: ShaderVariableFlags,
     pub ty: ShaderType<'a>,
-    pub default_value: Vec<&'a [u8]>,
+    pub default_value: Option<&'a [u8]>,
     pub start_texture: Option<u32>,
     pub texture_size: Option<u32>,
     pub start_sampler: Option<u32>,
@@ -258,34 +329,28 @@ pub struct ShaderVariable<'a> {
 }
 
 impl<'a> ShaderVariable<'a> {
-    pub fn parse(decoder: &mut Decoder<'a>, major: u8) -> Result<Self, State> {
-        let name_offset = decoder.read_u32() as usize;
-        let offset = decoder.read_u32();
-        let size = decoder.read_u32();
-        let flags = ShaderVariableFlags::from_bits_truncate(decoder.read_u32());
-        let ty_offset = decoder.read_u32() as usize;
-        let default_offset = decoder.read_u32() as usize;
-
-        let name = decoder
-            .seek(name_offset)
-            .str()
-            .map_err(State::DecoderError)?;
-        let ty = ShaderType::parse(&mut decoder.seek(ty_offset), major)?;
-        // TODO: figure out scenarios that could cause non-4-divisible default values
-        let values_count = (size / 4) as usize;
-        let mut default_value = Vec::with_capacity(values_count);
-        if default_offset != 0 {
-            for _ in 0..values_count {
-                default_value.push(decoder.bytes(4));
-            }
-        }
+    pub fn parse(decoder: &mut Decoder<'a>, major: u8, nodes: &mut u32) -> Result<Self, State> {
+        let name_offset = decoder.read_u32()? as usize;
+        let offset = decoder.read_u32()?;
+        let size = decoder.read_u32()?;
+        let flags = ShaderVariableFlags::from_bits_truncate(decoder.read_u32()?);
+        let ty_offset = decoder.read_u32()? as usize;
+        let default_offset = decoder.read_u32()? as usize;
+
+        let name = decoder.seek(name_offset)?.str()?;
+        let ty = ShaderType::parse(&mut decoder.seek(ty_offset)?, major, 0, nodes)?;
+        let default_value = if default_offset != 0 {
+            Some(decoder.seek(default_offset)?.bytes(size as usize)?)
+        } else {
+            None
+        };
 
         let (start_texture, texture_size, start_sampler, sampler_size) = if major >= 5 {
             (
-                Some(decoder.read_u32()),
-                Some(decoder.read_u32()),
-                Some(decoder.read_u32()),
-                Some(decoder.read_u32()),
+                Some(decoder.read_u32()?),
+                Some(decoder.read_u32()?),
+                Some(decoder.read_u32()?),
+                Some(decoder.read_u32()?),
             )
         } else {
             (None, None, None, None)
@@ -312,28 +377,25 @@ pub struct ConstantBuffer<'a> {
     pub name: &'a str,
     pub variables: Vec<ShaderVariable<'a>>,
     pub size: u32,
-    pub flags: u32,
-    pub ty: u32,
+    pub flags: ConstantBufferFlags,
+    pub ty: ConstantBufferType,
 }
 
 impl<'a> ConstantBuffer<'a> {
-    pub fn parse(decoder: &mut Decoder<'a>, major: u8) -> Result<Self, State> {
-        let name_offset = decoder.read_u32() as usize;
-        let var_count = decoder.read_u32() as usize;
-        let var_offset = decoder.read_u32() as usize;
-        let size = decoder.read_u32();
-        let flags = decoder.read_u32();
-        let ty = decoder.read_u32();
-
-        let name = decoder
-            .seek(name_offset)
-            .str()
-            .map_err(State::DecoderError)?;
-        
-        let mut variables = Vec::with_capacity(var_count);
-        let mut var_decoder = decoder.seek(var_offset);
+    pub fn parse(decoder: &mut Decoder<'a>, major: u8, nodes: &mut u32) -> Result<Self, State> {
+        let name_offset = decoder.read_u32()? as usize;
+        let var_count = decoder.read_u32()? as usize;
+        let var_offset = decoder.read_u32()? as usize;
+        let size = decoder.read_u32()?;
+        let flags = ConstantBufferFlags::from_bits_truncate(decoder.read_u32()?);
+        let ty = read_enum!(ConstantBufferType, decoder, u32);
+
+        let name = decoder.seek(name_offset)?.str()?;
+
+        let mut variables = Vec::new();
+        let mut var_decoder = decoder.seek(var_offset)?;
         for _ in 0..var_count {
-            variables.push(ShaderVariable::parse(&mut var_decoder, major)?);
+            variables.push(ShaderVariable::parse(&mut var_decoder, major, nodes)?);
         }
 
         Ok(Self {
@@ -344,6 +406,11 @@ impl<'a> ConstantBuffer<'a> {
             ty,
         })
     }
+
+    /// Returns the variable called `name`.
+    pub fn get_variable(&self, name: &str) -> Option<&ShaderVariable<'a>> {
+        self.variables.iter().find(|variable| variable.name == name)
+    }
 }
 
 #[repr(C)]
@@ -357,23 +424,29 @@ pub struct ResourceBinding<'a> {
     pub bind_point: u32,
     pub bind_count: u32,
     pub input_flags: ShaderInputFlags,
+    /// The register space, since shader model 5.1.
+    pub space: Option<u32>,
+    /// The range ID used by the SHEX declarations, since shader model 5.1.
+    pub id: Option<u32>,
 }
 
 impl<'a> ResourceBinding<'a> {
-    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
-        let name_offset = decoder.read_u32();
+    pub fn parse(decoder: &mut Decoder<'a>, size: u32) -> Result<Self, State> {
+        let name_offset = decoder.read_u32()?;
         let input_type = read_enum!(ShaderInputType, decoder, u32);
         let return_type = read_enum!(ResourceReturnType, decoder, u32);
         let view_dimension = read_enum!(ViewDimension, decoder, u32);
-        let sample_count = decoder.read_u32();
-        let bind_point = decoder.read_u32();
-        let bind_count = decoder.read_u32();
-        let input_flags = ShaderInputFlags::from_bits_truncate(decoder.read_u32());
+        let sample_count = decoder.read_u32()?;
+        let bind_point = decoder.read_u32()?;
+        let bind_count = decoder.read_u32()?;
+        let input_flags = ShaderInputFlags::from_bits_truncate(decoder.read_u32()?);
+        let (space, id) = if size >= RESOURCE_BINDING_5_1_SIZE {
+            (Some(decoder.read_u32()?), Some(decoder.read_u32()?))
+        } else {
+            (None, None)
+        };
 
-        let name = decoder
-            .seek(name_offset as usize)
-            .str()
-            .map_err(State::DecoderError)?;
+        let name = decoder.seek(name_offset as usize)?.str()?;
 
         Ok(Self {
             name,
@@ -384,12 +457,14 @@ impl<'a> ResourceBinding<'a> {
             bind_point,
             bind_count,
             input_flags,
+            space,
+            id,
         })
     }
 }
 
 #[repr(u16)]
-#[derive(Clone, Copy, Debug, IntEnum)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
 pub enum ProgramType {
     Pixel = 0xFFFF,
     Vertex = 0xFFFE,
@@ -399,6 +474,56 @@ pub enum ProgramType {
     Compute = 0x4353,
 }
 
+/// The sizes of the structures in the chunk, which follow the header since
+/// shader model 5.0.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rd11Header {
+    /// `RD11`, or another tag for shader model 5.1.
+    pub magic: u32,
+    pub header_size: u32,
+    pub constant_buffer_size: u32,
+    pub resource_binding_size: u32,
+    pub variable_size: u32,
+    pub type_size: u32,
+    pub member_size: u32,
+    pub interface_slot_count: u32,
+}
+
+impl Rd11Header {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        Ok(Self {
+            magic: decoder.read_u32()?,
+            header_size: decoder.read_u32()?,
+            constant_buffer_size: decoder.read_u32()?,
+            resource_binding_size: decoder.read_u32()?,
+            variable_size: decoder.read_u32()?,
+            type_size: decoder.read_u32()?,
+            member_size: decoder.read_u32()?,
+            interface_slot_count: decoder.read_u32()?,
+        })
+    }
+
+    /// Returns the header fxc writes for `model`.
+    pub fn new(model: ShaderModel, interface_slot_count: u32) -> Self {
+        let (magic, resource_binding_size) = match model {
+            ShaderModel::V5_1 => (RD11_5_1_MAGIC, RESOURCE_BINDING_5_1_SIZE),
+            _ => (RD11_MAGIC, RESOURCE_BINDING_SIZE),
+        };
+
+        Self {
+            magic,
+            header_size: RD11_HEADER_SIZE,
+            constant_buffer_size: CONSTANT_BUFFER_SIZE,
+            resource_binding_size,
+            variable_size: SHADER_VARIABLE_5_0_SIZE,
+            type_size: SHADER_TYPE_5_0_SIZE,
+            member_size: SHADER_TYPE_MEMBER_SIZE,
+            interface_slot_count,
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct RdefChunk<'a> {
@@ -407,59 +532,54 @@ pub struct RdefChunk<'a> {
     pub program_ty: ProgramType,
     pub minor: u8,
     pub major: u8,
+    /// The compile flags, like `D3DCOMPILE_DEBUG`.
     pub flags: u32,
     pub author: &'a str,
-    pub rd11: Option<[u32; 7]>,
+    pub rd11: Option<Rd11Header>,
 }
 
 impl<'a> RdefChunk<'a> {
-    pub fn parse<'b>(decoder: &'b mut Decoder) -> Result<RdefChunk<'b>, State> {
-        let cb_count = decoder.read_u32() as usize;
-        let cb_offset = decoder.read_u32() as usize;
+    pub fn parse(decoder: &mut Decoder<'a>) -> Result<Self, State> {
+        let cb_count = decoder.read_u32()? as usize;
+        let cb_offset = decoder.read_u32()? as usize;
 
-        let bind_count = decoder.read_u32() as usize;
-        let bind_offset = decoder.read_u32() as usize;
+        let bind_count = decoder.read_u32()? as usize;
+        let bind_offset = decoder.read_u32()? as usize;
 
-        let minor = decoder.read_u8();
-        let major = decoder.read_u8();
+        let minor = decoder.read_u8()?;
+        let major = decoder.read_u8()?;
 
         let program_ty = read_enum!(ProgramType, decoder, u16);
-        let flags = decoder.read_u32();
-        let author_offset = decoder.read_u32();
+        let flags = decoder.read_u32()?;
+        let author_offset = decoder.read_u32()?;
 
         let rd11 = if major >= 5 {
-            let _magic = decoder.read_u32();
-            // assert_eq!(magic, b"RD11");
-
-            Some([
-                decoder.read_u32(),
-                decoder.read_u32(),
-                decoder.read_u32(),
-                decoder.read_u32(),
-                decoder.read_u32(),
-                decoder.read_u32(),
-                decoder.read_u32(),
-            ])
+            Some(Rd11Header::parse(decoder)?)
         } else {
             None
         };
+        let bind_size = rd11.map_or(RESOURCE_BINDING_SIZE, |rd11| rd11.resource_binding_size);
+        if bind_size < RESOURCE_BINDING_SIZE {
+            return Err(State::ChunkIncorrect);
+        }
 
-        decoder.seek_mut(cb_offset);
-        let mut constant_buffers = Vec::with_capacity(cb_count);
+        decoder.seek_mut(cb_offset)?;
+        let mut constant_buffers = Vec::new();
+        let mut nodes = MAX_TYPE_NODES;
         for _ in 0..cb_count {
-            constant_buffers.push(ConstantBuffer::parse(decoder, major)?);
+            constant_buffers.push(ConstantBuffer::parse(decoder, major, &mut nodes)?);
         }
 
-        decoder.seek_mut(bind_offset);
-        let mut resource_bindings = Vec::with_capacity(bind_count);
-        for _ in 0..bind_count {
-            resource_bindings.push(ResourceBinding::parse(decoder)?);
+        let mut resource_bindings = Vec::new();
+        for idx in 0..bind_count {
+            let offset = bind_offset + idx * bind_size as usize;
+            resource_bindings.push(ResourceBinding::parse(
+                &mut decoder.seek(offset)?,
+                bind_size,
+            )?);
         }
 
-        let author = decoder
-            .seek(author_offset as usize)
-            .str()
-            .map_err(State::DecoderError)?;
+        let author = decoder.seek(author_offset as usize)?.str()?;
 
         Ok(RdefChunk {
             constant_buffers,
@@ -472,4 +592,106 @@ impl<'a> RdefChunk<'a> {
             rd11,
         })
     }
+
+    pub fn get_shader_model(&self) -> Option<ShaderModel> {
+        match (self.major, self.minor) {
+            (4, 0) => Some(ShaderModel::V4_0),
+            (4, 1) => Some(ShaderModel::V4_1),
+            (5, 0) => Some(ShaderModel::V5_0),
+            (5, 1) => Some(ShaderModel::V5_1),
+            _ => None,
+        }
+    }
+
+    pub fn get_interface_slot_count(&self) -> u32 {
+        self.rd11.map_or(0, |rd11| rd11.interface_slot_count)
+    }
+
+    /// Returns the constant buffer called `name`.
+    pub fn get_constant_buffer(&self, name: &str) -> Option<&ConstantBuffer<'a>> {
+        self.constant_buffers.iter().find(|cb| cb.name == name)
+    }
+
+    /// Returns the resource binding called `name`.
+    pub fn get_resource_binding(&self, name: &str) -> Option<&ResourceBinding<'a>> {
+        self.resource_bindings
+            .iter()
+            .find(|binding| binding.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complex_shader() {
+        let shader = include_bytes!("../../../dxbcd/complex_shader.dxbc");
+        let container = Container::parse(shader).unwrap();
+        let mut decoder = Decoder::new(container.find_chunk(b"RDEF").unwrap());
+        let rdef = RdefChunk::parse(&mut decoder).unwrap();
+
+        assert_eq!(rdef.get_shader_model(), Some(ShaderModel::V5_0));
+        assert_eq!(rdef.rd11, Some(Rd11Header::new(ShaderModel::V5_0, 0)));
+
+        let cb = rdef.get_constant_buffer("cbuf0").unwrap();
+        assert_eq!(cb.ty, ConstantBufferType::ConstantBuffer);
+        assert_eq!(cb.size, 2064);
+
+        let arr = cb.get_variable("arr").unwrap();
+        assert_eq!((arr.offset, arr.size), (32, 2024));
+        assert_eq!(arr.ty.name, Some("int2"));
+        assert!(arr.default_value.is_none());
+
+        assert_eq!(rdef.resource_bindings.len(), 9);
+        let tex3 = rdef.get_resource_binding("tex3").unwrap();
+        assert_eq!(tex3.bind_point, 3);
+        assert_eq!(tex3.space, None);
+    }
+
+    #[test]
+    fn write_matches_fxc() {
+        for shader in [
+            &include_bytes!("../../../dxbcd/shader.dxbc")[..],
+            &include_bytes!("../../../dxbcd/complex_shader.dxbc")[..],
+            &include_bytes!("../../../dxbcd/reference.dxbc")[..],
+        ] {
+            let original = Container::parse(shader)
+                .unwrap()
+                .find_chunk(b"RDEF")
+                .unwrap();
+            let rdef = RdefChunk::parse(&mut Decoder::new(original)).unwrap();
+
+            let mut builder = crate::dr::Builder::new();
+            builder.set_rdef(rdef);
+            let module = builder.module().unwrap();
+
+            let container = Container::parse(module.as_bytes()).unwrap();
+            assert_eq!(container.find_chunk(b"RDEF").unwrap(), original);
+        }
+    }
+
+    #[test]
+    fn shared_types_limit() {
+        // each struct has two members of the next struct, so the 40 levels
+        // describe 2^40 types in 1.6KB
+        let depth: u32 = 40;
+        let name = 40 * depth + 16;
+
+        let mut words = Vec::new();
+        for level in 0..depth {
+            let offset = 40 * level;
+            words.extend_from_slice(&[5, 0, 2 << 16, offset + 16]);
+            words.extend_from_slice(&[name, offset + 40, 0, name, offset + 40, 0]);
+        }
+        // the last struct has no members, and is followed by an empty name
+        words.extend_from_slice(&[5, 0, 0, 0, 0]);
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+
+        let mut nodes = MAX_TYPE_NODES;
+        assert!(matches!(
+            ShaderType::parse(&mut Decoder::new(&bytes), 4, 0, &mut nodes),
+            Err(State::ChunkIncorrect)
+        ));
+    }
 }
@@ -0,0 +1,415 @@
+use crate::binary::*;
+
+use int_enum::IntEnum;
+
+bitflags! {
+    pub struct RootSignatureFlags: u32 {
+        const ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT = 0x1;
+        const DENY_VERTEX_SHADER_ROOT_ACCESS = 0x2;
+        const DENY_HULL_SHADER_ROOT_ACCESS = 0x4;
+        const DENY_DOMAIN_SHADER_ROOT_ACCESS = 0x8;
+        const DENY_GEOMETRY_SHADER_ROOT_ACCESS = 0x10;
+        const DENY_PIXEL_SHADER_ROOT_ACCESS = 0x20;
+        const ALLOW_STREAM_OUTPUT = 0x40;
+        const LOCAL_ROOT_SIGNATURE = 0x80;
+        const DENY_AMPLIFICATION_SHADER_ROOT_ACCESS = 0x100;
+        const DENY_MESH_SHADER_ROOT_ACCESS = 0x200;
+        const CBV_SRV_UAV_HEAP_DIRECTLY_INDEXED = 0x400;
+        const SAMPLER_HEAP_DIRECTLY_INDEXED = 0x800;
+    }
+
+    pub struct RootDescriptorFlags: u32 {
+        const DATA_VOLATILE = 0x2;
+        const DATA_STATIC_WHILE_SET_AT_EXECUTE = 0x4;
+        const DATA_STATIC = 0x8;
+    }
+
+    pub struct DescriptorRangeFlags: u32 {
+        const DESCRIPTORS_VOLATILE = 0x1;
+        const DATA_VOLATILE = 0x2;
+        const DATA_STATIC_WHILE_SET_AT_EXECUTE = 0x4;
+        const DATA_STATIC = 0x8;
+        const DESCRIPTORS_STATIC_KEEPING_BUFFER_BOUNDS_CHECKS = 0x10000;
+    }
+}
+
+/// `DescriptorRange::count` of a range that extends to the end of the heap.
+pub const DESCRIPTOR_COUNT_UNBOUNDED: u32 = 0xffff_ffff;
+
+/// `DescriptorRange::offset` of a range that directly follows the previous
+/// one.
+pub const DESCRIPTOR_RANGE_OFFSET_APPEND: u32 = 0xffff_ffff;
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum RootSignatureVersion {
+    V1_0 = 1,
+    V1_1 = 2,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum ShaderVisibility {
+    All = 0,
+    Vertex = 1,
+    Hull = 2,
+    Domain = 3,
+    Geometry = 4,
+    Pixel = 5,
+    Amplification = 6,
+    Mesh = 7,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum RootParameterType {
+    DescriptorTable = 0,
+    Constants32Bit = 1,
+    Cbv = 2,
+    Srv = 3,
+    Uav = 4,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum DescriptorRangeType {
+    Srv = 0,
+    Uav = 1,
+    Cbv = 2,
+    Sampler = 3,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum TextureAddressMode {
+    Wrap = 1,
+    Mirror = 2,
+    Clamp = 3,
+    Border = 4,
+    MirrorOnce = 5,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum ComparisonFunc {
+    None = 0,
+    Never = 1,
+    Less = 2,
+    Equal = 3,
+    LessEqual = 4,
+    Greater = 5,
+    NotEqual = 6,
+    GreaterEqual = 7,
+    Always = 8,
+}
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, IntEnum)]
+pub enum StaticBorderColor {
+    TransparentBlack = 0,
+    OpaqueBlack = 1,
+    OpaqueWhite = 2,
+    OpaqueBlackUint = 3,
+    OpaqueWhiteUint = 4,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptorRange {
+    pub ty: DescriptorRangeType,
+    /// The number of descriptors, or `DESCRIPTOR_COUNT_UNBOUNDED`.
+    pub count: u32,
+    pub base_register: u32,
+    pub space: u32,
+    /// Always empty in version 1.0.
+    pub flags: DescriptorRangeFlags,
+    /// The offset from the start of the table in descriptors, or
+    /// `DESCRIPTOR_RANGE_OFFSET_APPEND`.
+    pub offset: u32,
+}
+
+impl DescriptorRange {
+    pub fn parse(decoder: &mut Decoder, version: RootSignatureVersion) -> Result<Self, State> {
+        let ty = read_enum!(DescriptorRangeType, decoder, u32);
+        let count = decoder.read_u32()?;
+        let base_register = decoder.read_u32()?;
+        let space = decoder.read_u32()?;
+        let flags = match version {
+            RootSignatureVersion::V1_0 => DescriptorRangeFlags::empty(),
+            RootSignatureVersion::V1_1 => {
+                DescriptorRangeFlags::from_bits_truncate(decoder.read_u32()?)
+            }
+        };
+        let offset = decoder.read_u32()?;
+
+        Ok(Self {
+            ty,
+            count,
+            base_register,
+            space,
+            flags,
+            offset,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootConstants {
+    pub register: u32,
+    pub space: u32,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootDescriptor {
+    pub register: u32,
+    pub space: u32,
+    /// Always empty in version 1.0.
+    pub flags: RootDescriptorFlags,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RootParameterData {
+    DescriptorTable(Vec<DescriptorRange>),
+    Constants32Bit(RootConstants),
+    Cbv(RootDescriptor),
+    Srv(RootDescriptor),
+    Uav(RootDescriptor),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootParameter {
+    pub visibility: ShaderVisibility,
+    pub data: RootParameterData,
+}
+
+impl RootParameter {
+    pub fn parse(decoder: &mut Decoder, version: RootSignatureVersion) -> Result<Self, State> {
+        let ty = read_enum!(RootParameterType, decoder, u32);
+        let visibility = read_enum!(ShaderVisibility, decoder, u32);
+        let payload_offset = decoder.read_u32()? as usize;
+
+        let mut payload = decoder.seek(payload_offset)?;
+        let root_descriptor = |payload: &mut Decoder| -> Result<RootDescriptor, State> {
+            let register = payload.read_u32()?;
+            let space = payload.read_u32()?;
+            let flags = match version {
+                RootSignatureVersion::V1_0 => RootDescriptorFlags::empty(),
+                RootSignatureVersion::V1_1 => {
+                    RootDescriptorFlags::from_bits_truncate(payload.read_u32()?)
+                }
+            };
+
+            Ok(RootDescriptor {
+                register,
+                space,
+                flags,
+            })
+        };
+
+        let data = match ty {
+            RootParameterType::DescriptorTable => {
+                let range_count = payload.read_u32()?;
+                let range_offset = payload.read_u32()? as usize;
+
+                let mut ranges = Vec::new();
+                let mut range_decoder = decoder.seek(range_offset)?;
+                for _ in 0..range_count {
+                    ranges.push(DescriptorRange::parse(&mut range_decoder, version)?);
+                }
+
+                RootParameterData::DescriptorTable(ranges)
+            }
+            RootParameterType::Constants32Bit => RootParameterData::Constants32Bit(RootConstants {
+                register: payload.read_u32()?,
+                space: payload.read_u32()?,
+                count: payload.read_u32()?,
+            }),
+            RootParameterType::Cbv => RootParameterData::Cbv(root_descriptor(&mut payload)?),
+            RootParameterType::Srv => RootParameterData::Srv(root_descriptor(&mut payload)?),
+            RootParameterType::Uav => RootParameterData::Uav(root_descriptor(&mut payload)?),
+        };
+
+        Ok(Self { visibility, data })
+    }
+
+    pub fn get_type(&self) -> RootParameterType {
+        match self.data {
+            RootParameterData::DescriptorTable(_) => RootParameterType::DescriptorTable,
+            RootParameterData::Constants32Bit(_) => RootParameterType::Constants32Bit,
+            RootParameterData::Cbv(_) => RootParameterType::Cbv,
+            RootParameterData::Srv(_) => RootParameterType::Srv,
+            RootParameterData::Uav(_) => RootParameterType::Uav,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaticSampler {
+    /// A `D3D12_FILTER` value.
+    pub filter: u32,
+    pub address_u: TextureAddressMode,
+    pub address_v: TextureAddressMode,
+    pub address_w: TextureAddressMode,
+    pub mip_lod_bias: f32,
+    pub max_anisotropy: u32,
+    pub comparison_func: ComparisonFunc,
+    pub border_color: StaticBorderColor,
+    pub min_lod: f32,
+    pub max_lod: f32,
+    pub register: u32,
+    pub space: u32,
+    pub visibility: ShaderVisibility,
+}
+
+impl StaticSampler {
+    pub fn parse(decoder: &mut Decoder) -> Result<Self, State> {
+        Ok(Self {
+            filter: decoder.read_u32()?,
+            address_u: read_enum!(TextureAddressMode, decoder, u32),
+            address_v: read_enum!(TextureAddressMode, decoder, u32),
+            address_w: read_enum!(TextureAddressMode, decoder, u32),
+            mip_lod_bias: f32::from_bits(decoder.read_u32()?),
+            max_anisotropy: decoder.read_u32()?,
+            comparison_func: read_enum!(ComparisonFunc, decoder, u32),
+            border_color: read_enum!(StaticBorderColor, decoder, u32),
+            min_lod: f32::from_bits(decoder.read_u32()?),
+            max_lod: f32::from_bits(decoder.read_u32()?),
+            register: decoder.read_u32()?,
+            space: decoder.read_u32()?,
+            visibility: read_enum!(ShaderVisibility, decoder, u32),
+        })
+    }
+}
+
+/// A serialized D3D12 root signature. Standalone root signature blobs are
+/// DXBC containers with this as their only chunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rts0Chunk {
+    pub version: RootSignatureVersion,
+    pub flags: RootSignatureFlags,
+    pub parameters: Vec<RootParameter>,
+    pub static_samplers: Vec<StaticSampler>,
+}
+
+impl Rts0Chunk {
+    pub fn parse(decoder: &mut Decoder) -> Result<Rts0Chunk, State> {
+        let version = read_enum!(RootSignatureVersion, decoder, u32);
+        let parameter_count = decoder.read_u32()?;
+        let parameter_offset = decoder.read_u32()? as usize;
+        let static_sampler_count = decoder.read_u32()?;
+        let static_sampler_offset = decoder.read_u32()? as usize;
+        let flags = RootSignatureFlags::from_bits_truncate(decoder.read_u32()?);
+
+        decoder.seek_mut(parameter_offset)?;
+        let mut parameters = Vec::new();
+        for _ in 0..parameter_count {
+            parameters.push(RootParameter::parse(decoder, version)?);
+        }
+
+        decoder.seek_mut(static_sampler_offset)?;
+        let mut static_samplers = Vec::new();
+        for _ in 0..static_sampler_count {
+            static_samplers.push(StaticSampler::parse(decoder)?);
+        }
+
+        Ok(Rts0Chunk {
+            version,
+            flags,
+            parameters,
+            static_samplers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dr::DxbcModule;
+
+    fn root_signature(version: RootSignatureVersion) -> Rts0Chunk {
+        let v1_1 = version == RootSignatureVersion::V1_1;
+
+        Rts0Chunk {
+            version,
+            flags: RootSignatureFlags::ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT,
+            parameters: vec![
+                RootParameter {
+                    visibility: ShaderVisibility::Pixel,
+                    data: RootParameterData::DescriptorTable(vec![
+                        DescriptorRange {
+                            ty: DescriptorRangeType::Srv,
+                            count: 2,
+                            base_register: 1,
+                            space: 0,
+                            flags: if v1_1 {
+                                DescriptorRangeFlags::DATA_STATIC
+                            } else {
+                                DescriptorRangeFlags::empty()
+                            },
+                            offset: DESCRIPTOR_RANGE_OFFSET_APPEND,
+                        },
+                        DescriptorRange {
+                            ty: DescriptorRangeType::Uav,
+                            count: DESCRIPTOR_COUNT_UNBOUNDED,
+                            base_register: 0,
+                            space: 1,
+                            flags: DescriptorRangeFlags::empty(),
+                            offset: 2,
+                        },
+                    ]),
+                },
+                RootParameter {
+                    visibility: ShaderVisibility::All,
+                    data: RootParameterData::Constants32Bit(RootConstants {
+                        register: 0,
+                        space: 0,
+                        count: 4,
+                    }),
+                },
+                RootParameter {
+                    visibility: ShaderVisibility::Vertex,
+                    data: RootParameterData::Cbv(RootDescriptor {
+                        register: 1,
+                        space: 2,
+                        flags: if v1_1 {
+                            RootDescriptorFlags::DATA_VOLATILE
+                        } else {
+                            RootDescriptorFlags::empty()
+                        },
+                    }),
+                },
+            ],
+            static_samplers: vec![StaticSampler {
+                filter: 0x15,
+                address_u: TextureAddressMode::Wrap,
+                address_v: TextureAddressMode::Clamp,
+                address_w: TextureAddressMode::Border,
+                mip_lod_bias: 0.5,
+                max_anisotropy: 16,
+                comparison_func: ComparisonFunc::LessEqual,
+                border_color: StaticBorderColor::OpaqueWhite,
+                min_lod: 0.0,
+                max_lod: f32::MAX,
+                register: 0,
+                space: 0,
+                visibility: ShaderVisibility::Pixel,
+            }],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for version in [RootSignatureVersion::V1_0, RootSignatureVersion::V1_1] {
+            let rts0 = root_signature(version);
+
+            let mut module = DxbcModule::new();
+            module.write_rts0(&rts0);
+
+            // skip the fourcc and chunk size
+            let bytes = &module.as_bytes()[8..];
+            let parsed = Rts0Chunk::parse(&mut Decoder::new(bytes)).unwrap();
+
+            assert_eq!(parsed, rts0);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::binary::*;
+
+bitflags! {
+    /// The optional hardware features a shader requires.
+    pub struct ShaderFeatureFlags: u64 {
+        const DOUBLES = 0x1;
+        const COMPUTE_SHADERS_PLUS_RAW_AND_STRUCTURED_BUFFERS_VIA_SHADER_4_X = 0x2;
+        const UAVS_AT_EVERY_STAGE = 0x4;
+        const UAVS_64 = 0x8;
+        const MINIMUM_PRECISION = 0x10;
+        const DOUBLE_EXTENSIONS_11_1 = 0x20;
+        const SHADER_EXTENSIONS_11_1 = 0x40;
+        const LEVEL_9_COMPARISON_FILTERING = 0x80;
+        const TILED_RESOURCES = 0x100;
+        const STENCIL_REF = 0x200;
+        const INNER_COVERAGE = 0x400;
+        const TYPED_UAV_LOAD_ADDITIONAL_FORMATS = 0x800;
+        const ROVS = 0x1000;
+        const VIEWPORT_AND_RT_ARRAY_INDEX_FROM_ANY_SHADER_FEEDING_RASTERIZER = 0x2000;
+    }
+}
+
+#[derive(Debug)]
+pub struct Sfi0Chunk {
+    pub flags: ShaderFeatureFlags,
+}
+
+impl Sfi0Chunk {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Sfi0Chunk, State> {
+        // newer compilers set bits we don't know about yet, keep them so the
+        // chunk is written back unchanged
+        let flags = unsafe { ShaderFeatureFlags::from_bits_unchecked(decoder.read_u64()?) };
+
+        Ok(Sfi0Chunk { flags })
+    }
+}
@@ -1,18 +1,24 @@
+use super::builder::{GlobalFlags, Semantic};
 use crate::binary::*;
 
-use std::marker::PhantomData;
-use std::mem;
+use byteorder::{ByteOrder, LittleEndian};
+use int_enum::IntEnum;
 use winapi::um::d3d11tokenizedprogramformat::*;
 
+/// Reads the `index`th dword of a token stream.
+fn read_token(tokens: &[u8], index: usize) -> u32 {
+    LittleEndian::read_u32(&tokens[4 * index..])
+}
+
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum ConstantBufferIndexPattern {
     Immediate = 0,
     Dynamic = 1,
 }
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum OperandType {
     Temp = 0,
     Input = 1,
@@ -67,7 +73,7 @@ pub enum NumComponents {
 }
 
 #[repr(u8)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ComponentName {
     X = 0,
     Y = 1,
@@ -87,7 +93,7 @@ impl ComponentName {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ComponentSwizzle(
     pub ComponentName,
     pub ComponentName,
@@ -140,6 +146,15 @@ bitflags! {
     }
 }
 
+bitflags! {
+    pub struct SyncFlags: u32 {
+        const THREADS_IN_GROUP = D3D11_SB_SYNC_THREADS_IN_GROUP;
+        const THREAD_GROUP_SHARED_MEMORY = D3D11_SB_SYNC_THREAD_GROUP_SHARED_MEMORY;
+        const UNORDERED_ACCESS_VIEW_MEMORY_GROUP = D3D11_SB_SYNC_UNORDERED_ACCESS_VIEW_MEMORY_GROUP;
+        const UNORDERED_ACCESS_VIEW_MEMORY_GLOBAL = D3D11_SB_SYNC_UNORDERED_ACCESS_VIEW_MEMORY_GLOBAL;
+    }
+}
+
 #[repr(u32)]
 #[derive(Debug)]
 pub enum NameToken {
@@ -169,8 +184,8 @@ pub enum NameToken {
 }
 
 impl NameToken {
-    pub fn from_word(word: u32) -> Self {
-        match word {
+    pub fn from_word(word: u32) -> Option<Self> {
+        Some(match word {
             0 => NameToken::Undefined,
             1 => NameToken::Position,
             2 => NameToken::ClipDistance,
@@ -194,18 +209,18 @@ impl NameToken {
             20 => NameToken::FinalTriinsidetessfactor,
             21 => NameToken::FinalLineDetailTessfactor,
             22 => NameToken::FinalLineDensityTessfactor,
-            _ => unimplemented!(),
-        }
+            _ => return None,
+        })
     }
 }
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum OperandModifier {
-    None,
-    Neg,
-    Abs,
-    AbsNeg,
+    None = 0,
+    Neg = 1,
+    Abs = 2,
+    AbsNeg = 3,
 }
 
 impl OperandModifier {
@@ -221,26 +236,21 @@ impl OperandModifier {
 }
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum SamplerMode {
-    Default,
-    Comparison,
-    Mono,
+    Default = 0,
+    Comparison = 1,
+    Mono = 2,
 }
 
 impl SamplerMode {
-    pub fn from_word(word: u32) -> Self {
-        match word {
-            0 => SamplerMode::Default,
-            1 => SamplerMode::Comparison,
-            2 => SamplerMode::Mono,
-            _ => unreachable!(),
-        }
+    pub fn from_word(word: u32) -> Option<Self> {
+        SamplerMode::from_int(word).ok()
     }
 }
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum TestBoolean {
     Zero,
     NonZero,
@@ -256,76 +266,124 @@ impl TestBoolean {
     }
 }
 
-#[repr(C)]
+/// The opcode token of an instruction, followed by the rest of the
+/// instruction.
 #[derive(Copy, Clone)]
 pub struct OpcodeToken0<'a> {
-    // TODO: these should probably be slices
-    pub word: *const u32,
-    _phantom: PhantomData<&'a ()>,
+    pub word: u32,
+    tokens: &'a [u8],
 }
 
 impl<'a> OpcodeToken0<'a> {
-    pub fn from_word(word: *const u32) -> Self {
+    /// `tokens` has to start at the opcode token and hold at least every
+    /// extended opcode token that follows it. `SparseInstruction::parse`
+    /// hands out tokens that span the whole instruction.
+    pub fn from_tokens(tokens: &'a [u8]) -> Self {
         OpcodeToken0 {
-            word,
-            _phantom: PhantomData,
+            word: read_token(tokens, 0),
+            tokens,
         }
     }
 
     pub fn is_extended(&self) -> bool {
-        DECODE_IS_D3D10_SB_OPCODE_EXTENDED(unsafe { *self.word }) != 0
+        DECODE_IS_D3D10_SB_OPCODE_EXTENDED(self.word) != 0
     }
 
     pub fn is_saturated(&self) -> bool {
-        DECODE_IS_D3D10_SB_INSTRUCTION_SATURATE_ENABLED(unsafe { *self.word }) != 0
+        DECODE_IS_D3D10_SB_INSTRUCTION_SATURATE_ENABLED(self.word) != 0
     }
 
     pub fn get_test_type(&self) -> TestBoolean {
-        TestBoolean::from_word(unsafe { *self.word })
+        TestBoolean::from_word(self.word)
     }
 
     pub fn get_extended_opcode(&self) -> Option<OpcodeToken1<'a>> {
         if self.is_extended() {
-            Some(OpcodeToken1 {
-                word: unsafe { self.word.offset(1) },
-                _phantom: self._phantom,
-            })
+            Some(OpcodeToken1::from_tokens(&self.tokens[4..]))
         } else {
             None
         }
     }
 
+    /// Walks the extended opcode tokens and returns the first one of type
+    /// `ty`.
+    pub fn find_extended_opcode(&self, ty: ExtendedOpcodeType) -> Option<OpcodeToken1<'a>> {
+        let ty = ty as u32;
+
+        let mut ex = self.get_extended_opcode();
+        while let Some(opcode) = ex {
+            if DECODE_D3D10_SB_EXTENDED_OPCODE_TYPE(opcode.word) == ty {
+                return Some(opcode);
+            }
+
+            ex = opcode.get_extended_opcode();
+        }
+
+        None
+    }
+
+    /// Returns the immediate u, v and w texel offsets (`_aoffimmi`).
+    pub fn get_texel_offsets(&self) -> Option<[i8; 3]> {
+        self.find_extended_opcode(ExtendedOpcodeType::SampleControls)
+            .map(|opcode| opcode.get_texel_offsets())
+    }
+
+    /// Returns the resource dimension and structure stride of instructions
+    /// that access an indexable resource (`_indexable`).
+    pub fn get_extended_resource_dimension(&self) -> Option<(ResourceDimension, u32)> {
+        let opcode = self.find_extended_opcode(ExtendedOpcodeType::ResourceDim)?;
+
+        Some((
+            opcode.get_resource_dimension()?,
+            opcode.get_structure_stride(),
+        ))
+    }
+
+    /// Returns the resource return type that accompanies
+    /// `get_extended_resource_dimension`.
+    pub fn get_extended_resource_return_type(&self) -> Option<OpcodeToken1<'a>> {
+        self.find_extended_opcode(ExtendedOpcodeType::ResourceReturnType)
+    }
+
     pub fn get_opcode_type(&self) -> u32 {
-        DECODE_D3D10_SB_OPCODE_TYPE(unsafe { *self.word })
+        DECODE_D3D10_SB_OPCODE_TYPE(self.word)
     }
 
     pub fn get_instruction_length(&self) -> u32 {
-        DECODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(unsafe { *self.word })
+        DECODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(self.word)
+    }
+
+    pub fn get_resource_dimension(&self) -> Option<ResourceDimension> {
+        ResourceDimension::from_word(DECODE_D3D10_SB_RESOURCE_DIMENSION(self.word))
+    }
+
+    pub fn get_sampler_mode(&self) -> Option<SamplerMode> {
+        SamplerMode::from_word(DECODE_D3D10_SB_SAMPLER_MODE(self.word))
+    }
+
+    pub fn get_interpolation_mode(&self) -> Option<InterpolationMode> {
+        InterpolationMode::from_word(DECODE_D3D10_SB_INPUT_INTERPOLATION_MODE(self.word))
     }
 
-    pub fn get_resource_dimension(&self) -> ResourceDimension {
-        ResourceDimension::from_word(DECODE_D3D10_SB_RESOURCE_DIMENSION(unsafe {
-            *(self.word.offset(0))
-        }))
+    pub fn get_resinfo_return_type(&self) -> Option<ResInfoReturnType> {
+        ResInfoReturnType::from_word(DECODE_D3D10_SB_RESINFO_INSTRUCTION_RETURN_TYPE(self.word))
     }
 
-    pub fn get_sampler_mode(&self) -> SamplerMode {
-        SamplerMode::from_word(DECODE_D3D10_SB_SAMPLER_MODE(unsafe {
-            *(self.word.offset(0))
-        }))
+    /// Set on `dcl_uav_*` declarations marked `globallycoherent`.
+    pub fn is_globally_coherent(&self) -> bool {
+        (self.word & D3D11_SB_GLOBALLY_COHERENT_ACCESS) != 0
     }
 
-    pub fn get_interpolation_mode(&self) -> InterpolationMode {
-        InterpolationMode::from_word(DECODE_D3D10_SB_INPUT_INTERPOLATION_MODE(unsafe {
-            *self.word
-        }))
+    /// Set on `dcl_uav_structured` declarations with a hidden counter.
+    pub fn has_order_preserving_counter(&self) -> bool {
+        (self.word & D3D11_SB_UAV_HAS_ORDER_PRESERVING_COUNTER) != 0
     }
 }
 
 impl<'a> fmt::Debug for OpcodeToken0<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("OpcodeToken0")
-            .field("Raw", &unsafe { *self.word })
+            .field("Raw", &self.word)
             .field("Type", &self.get_opcode_type())
             .field("InstructionLength", &self.get_instruction_length())
             .field("IsSaturated", &self.is_saturated())
@@ -336,7 +394,26 @@ impl<'a> fmt::Debug for OpcodeToken0<'a> {
 }
 
 #[repr(u32)]
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResInfoReturnType {
+    Float = 0,
+    RcpFloat = 1,
+    UInt = 2,
+}
+
+impl ResInfoReturnType {
+    pub fn from_word(word: u32) -> Option<Self> {
+        match word {
+            0 => Some(ResInfoReturnType::Float),
+            1 => Some(ResInfoReturnType::RcpFloat),
+            2 => Some(ResInfoReturnType::UInt),
+            _ => None,
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum InterpolationMode {
     Undefined = 0,
     Constant = 1,
@@ -349,15 +426,13 @@ pub enum InterpolationMode {
 }
 
 impl InterpolationMode {
-    pub fn from_word(word: u32) -> Self {
-        assert!(word <= InterpolationMode::LinearNoPerspectiveSample as u32);
-
-        unsafe { mem::transmute(word) }
+    pub fn from_word(word: u32) -> Option<Self> {
+        InterpolationMode::from_int(word).ok()
     }
 }
 
 #[repr(u32)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum ResourceDimension {
     Unknown = 0,
     Buffer = 1,
@@ -375,26 +450,95 @@ pub enum ResourceDimension {
 }
 
 impl ResourceDimension {
-    pub fn from_word(word: u32) -> Self {
+    pub fn from_word(word: u32) -> Option<Self> {
+        ResourceDimension::from_int(word).ok()
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum TessellatorDomain {
+    Undefined = 0,
+    Isoline = 1,
+    Tri = 2,
+    Quad = 3,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum TessellatorPartitioning {
+    Undefined = 0,
+    Integer = 1,
+    Pow2 = 2,
+    FractionalOdd = 3,
+    FractionalEven = 4,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum TessellatorOutputPrimitive {
+    Undefined = 0,
+    Point = 1,
+    Line = 2,
+    TriangleCw = 3,
+    TriangleCcw = 4,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Primitive {
+    Undefined,
+    Point,
+    Line,
+    Triangle,
+    LineAdj,
+    TriangleAdj,
+    /// A patch with the given number of control points, from 1 to 32.
+    ControlPointPatch(u32),
+}
+
+impl TryFrom<u32> for Primitive {
+    type Error = ();
+
+    fn try_from(word: u32) -> Result<Self, ()> {
         match word {
-            0 => ResourceDimension::Unknown,
-            1 => ResourceDimension::Buffer,
-            2 => ResourceDimension::Texture1D,
-            3 => ResourceDimension::Texture2D,
-            4 => ResourceDimension::Texture2DMS,
-            5 => ResourceDimension::Texture3D,
-            6 => ResourceDimension::TextureCube,
-            7 => ResourceDimension::Texture1DArray,
-            8 => ResourceDimension::Texture2DArray,
-            9 => ResourceDimension::Texture2DMSArray,
-            10 => ResourceDimension::TextureCubeArray,
-            11 => ResourceDimension::RawBuffer,
-            12 => ResourceDimension::StructuredBuffer,
-            _ => unreachable!(),
+            0 => Ok(Primitive::Undefined),
+            1 => Ok(Primitive::Point),
+            2 => Ok(Primitive::Line),
+            3 => Ok(Primitive::Triangle),
+            6 => Ok(Primitive::LineAdj),
+            7 => Ok(Primitive::TriangleAdj),
+            8..=39 => Ok(Primitive::ControlPointPatch(word - 7)),
+            _ => Err(()),
         }
     }
 }
 
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum PrimitiveTopology {
+    Undefined = 0,
+    PointList = 1,
+    LineList = 2,
+    LineStrip = 3,
+    TriangleList = 4,
+    TriangleStrip = 5,
+    LineListAdj = 10,
+    LineStripAdj = 11,
+    TriangleListAdj = 12,
+    TriangleStripAdj = 13,
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum CustomDataClass {
+    Comment = 0,
+    DebugInfo = 1,
+    Opaque = 2,
+    ImmediateConstantBuffer = 3,
+    ShaderMessage = 4,
+    ClipPlaneConstantMappingsForDx9 = 5,
+}
+
 #[repr(u32)]
 #[derive(Debug)]
 pub enum ExtendedOpcodeType {
@@ -405,19 +549,19 @@ pub enum ExtendedOpcodeType {
 }
 
 impl ExtendedOpcodeType {
-    pub fn from_word(word: u32) -> Self {
+    pub fn from_word(word: u32) -> Option<Self> {
         match DECODE_D3D10_SB_EXTENDED_OPCODE_TYPE(word) {
-            0 => ExtendedOpcodeType::Empty,
-            1 => ExtendedOpcodeType::SampleControls,
-            2 => ExtendedOpcodeType::ResourceDim,
-            3 => ExtendedOpcodeType::ResourceReturnType,
-            _ => unreachable!(),
+            0 => Some(ExtendedOpcodeType::Empty),
+            1 => Some(ExtendedOpcodeType::SampleControls),
+            2 => Some(ExtendedOpcodeType::ResourceDim),
+            3 => Some(ExtendedOpcodeType::ResourceReturnType),
+            _ => None,
         }
     }
 }
 
 #[repr(u32)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
 pub enum ResourceReturnType {
     Unorm = 1,
     Snorm = 2,
@@ -431,84 +575,98 @@ pub enum ResourceReturnType {
 }
 
 impl ResourceReturnType {
-    pub fn from_word(word: u32) -> Self {
-        match word {
-            1 => ResourceReturnType::Unorm,
-            2 => ResourceReturnType::Snorm,
-            3 => ResourceReturnType::Sint,
-            4 => ResourceReturnType::Uint,
-            5 => ResourceReturnType::Float,
-            6 => ResourceReturnType::Mixed,
-            7 => ResourceReturnType::Double,
-            8 => ResourceReturnType::Continued,
-            9 => ResourceReturnType::Unused,
-            _ => unreachable!(),
-        }
+    pub fn from_word(word: u32) -> Option<Self> {
+        ResourceReturnType::from_int(word).ok()
     }
 }
 
-#[repr(C)]
 #[derive(Debug, Copy, Clone)]
-pub struct ResourceReturnTypeToken0<'a> {
-    pub word: *const u32,
-    _phantom: PhantomData<&'a ()>,
+pub struct ResourceReturnTypeToken0 {
+    pub word: u32,
 }
 
-impl<'a> ResourceReturnTypeToken0<'a> {
-    pub fn from_word(word: *const u32) -> Self {
-        ResourceReturnTypeToken0 {
-            word,
-            _phantom: PhantomData,
-        }
+impl ResourceReturnTypeToken0 {
+    pub fn from_word(word: u32) -> Self {
+        ResourceReturnTypeToken0 { word }
     }
 
-    pub fn get_return_type(&self, name: ComponentName) -> ResourceReturnType {
-        ResourceReturnType::from_word(DECODE_D3D10_SB_RESOURCE_RETURN_TYPE(
-            unsafe { *(self.word.offset(0)) },
-            name as u32,
-        ))
+    pub fn get_return_type(&self, name: ComponentName) -> Option<ResourceReturnType> {
+        ResourceReturnType::from_word(DECODE_D3D10_SB_RESOURCE_RETURN_TYPE(self.word, name as u32))
     }
 }
 
-#[repr(C)]
+/// Decodes the u, v and w offsets of a sample controls extended opcode token.
+fn decode_texel_offsets(word: u32) -> [i8; 3] {
+    let offset = |coord| {
+        // sign extend the 4 bit offset
+        ((DECODE_IMMEDIATE_D3D10_SB_ADDRESS_OFFSET(coord, word) as i8) << 4) >> 4
+    };
+
+    [
+        offset(D3D10_SB_IMMEDIATE_ADDRESS_OFFSET_U),
+        offset(D3D10_SB_IMMEDIATE_ADDRESS_OFFSET_V),
+        offset(D3D10_SB_IMMEDIATE_ADDRESS_OFFSET_W),
+    ]
+}
+
+/// An extended opcode token, followed by the remaining extended opcode
+/// tokens of the instruction.
 #[derive(Copy, Clone)]
 pub struct OpcodeToken1<'a> {
-    pub word: *const u32,
-    _phantom: PhantomData<&'a ()>,
+    pub word: u32,
+    tokens: &'a [u8],
 }
 
 impl<'a> OpcodeToken1<'a> {
-    pub fn from_word(word: *const u32) -> Self {
+    pub fn from_tokens(tokens: &'a [u8]) -> Self {
         OpcodeToken1 {
-            word,
-            _phantom: PhantomData,
+            word: read_token(tokens, 0),
+            tokens,
         }
     }
 
-    pub fn get_extended_opcode_type(&self) -> ExtendedOpcodeType {
-        ExtendedOpcodeType::from_word(DECODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(unsafe {
-            *self.word
-        }))
+    pub fn get_extended_opcode_type(&self) -> Option<ExtendedOpcodeType> {
+        ExtendedOpcodeType::from_word(self.word)
     }
 
     pub fn get_opcode_modifier(&self) -> u32 {
-        DECODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(unsafe { *self.word })
+        DECODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(self.word)
     }
 
     pub fn is_extended(&self) -> bool {
-        DECODE_IS_D3D10_SB_OPCODE_EXTENDED(unsafe { *self.word }) != 0
+        DECODE_IS_D3D10_SB_OPCODE_EXTENDED(self.word) != 0
     }
 
     pub fn get_extended_opcode(&self) -> Option<OpcodeToken1<'a>> {
         if self.is_extended() {
-            Some(OpcodeToken1 {
-                word: unsafe { self.word.offset(1) },
-                _phantom: self._phantom,
-            })
+            Some(OpcodeToken1::from_tokens(&self.tokens[4..]))
         } else {
             None
         }
     }
+
+    /// Only meaningful for `ExtendedOpcodeType::SampleControls`.
+    pub fn get_texel_offsets(&self) -> [i8; 3] {
+        decode_texel_offsets(self.word)
+    }
+
+    /// Only meaningful for `ExtendedOpcodeType::ResourceDim`.
+    pub fn get_resource_dimension(&self) -> Option<ResourceDimension> {
+        ResourceDimension::from_word(DECODE_D3D11_SB_EXTENDED_RESOURCE_DIMENSION(self.word))
+    }
+
+    /// Only meaningful for `ExtendedOpcodeType::ResourceDim`.
+    pub fn get_structure_stride(&self) -> u32 {
+        DECODE_D3D11_SB_EXTENDED_RESOURCE_DIMENSION_STRUCTURE_STRIDE(self.word)
+    }
+
+    /// Only meaningful for `ExtendedOpcodeType::ResourceReturnType`.
+    pub fn get_return_type(&self, name: ComponentName) -> Option<ResourceReturnType> {
+        ResourceReturnType::from_word(DECODE_D3D11_SB_EXTENDED_RESOURCE_RETURN_TYPE(
+            self.word,
+            name as u32,
+        ))
+    }
 }
 
 impl<'a> fmt::Debug for OpcodeToken1<'a> {
@@ -521,114 +679,159 @@ impl<'a> fmt::Debug for OpcodeToken1<'a> {
     }
 }
 
-#[repr(C)]
 #[derive(Copy, Clone)]
-pub struct OperandToken1<'a> {
-    pub word: *const u32,
-    _phantom: PhantomData<&'a ()>,
+pub struct OperandToken1 {
+    pub word: u32,
 }
 
-impl<'a> OperandToken1<'a> {
-    pub fn from_word(word: *const u32) -> Self {
-        OperandToken1 {
-            word,
-            _phantom: PhantomData,
-        }
+impl OperandToken1 {
+    pub fn from_word(word: u32) -> Self {
+        OperandToken1 { word }
     }
 
     pub fn get_operand_modifier(&self) -> OperandModifier {
-        OperandModifier::from_word(unsafe { *self.word })
+        OperandModifier::from_word(self.word)
     }
 }
 
-#[repr(C)]
+/// An operand token, followed by its extended operand token, indices and
+/// immediates.
 #[derive(Copy, Clone)]
 pub struct OperandToken0<'a> {
-    pub word: *const u32,
-    _phantom: PhantomData<&'a ()>,
+    pub word: u32,
+    tokens: &'a [u8],
 }
 
 impl<'a> OperandToken0<'a> {
-    pub fn from_word(word: *const u32) -> Self {
+    /// `tokens` has to start at the operand token and hold every token that
+    /// belongs to the operand.
+    pub fn from_tokens(tokens: &'a [u8]) -> Self {
         OperandToken0 {
-            word,
-            _phantom: PhantomData,
+            word: read_token(tokens, 0),
+            tokens,
         }
     }
 
-    pub fn parse(decoder: &mut decoder::Decoder) -> OperandToken0<'a> {
-        let operand = OperandToken0::from_word(decoder.read_u32_address());
+    pub fn parse(decoder: &mut decoder::Decoder<'a>) -> Result<OperandToken0<'a>, State> {
+        let start = decoder.get_offset();
+        let operand = OperandToken0 {
+            word: decoder.read_u32()?,
+            tokens: &[],
+        };
 
-        if operand.is_extended() {
-            decoder.skip(4);
+        if !operand.is_valid() {
+            return Err(State::DecoderError(Error::DecodeEnumFailed(start)));
         }
 
-        let len = match operand.get_index_dimension() {
-            IndexDimension::D0 => {
-                let ty = operand.get_operand_type();
+        if operand.is_extended() {
+            decoder.skip(4)?;
+        }
 
-                match ty {
-                    OperandType::Immediate32 | OperandType::Immediate64 => {
-                        operand.get_num_components_u32()
-                    }
-                    _ => 0,
-                }
-            }
-            IndexDimension::D1 => 1,
-            IndexDimension::D2 => 2,
-            IndexDimension::D3 => 3,
-        };
+        decoder.skip(4 * operand.get_literal_len() as usize)?;
 
-        for i in 0..len {
+        for i in 0..operand.get_index_count() {
             let repr = operand.get_index_representation(i);
 
             match repr {
                 IndexRepresentation::Immediate32 => {
-                    decoder.skip(4);
+                    decoder.skip(4)?;
                 }
                 IndexRepresentation::Immediate64 => {
-                    decoder.skip(8);
+                    decoder.skip(8)?;
                 }
                 IndexRepresentation::Relative => {
-                    let _ = OperandToken0::parse(decoder);
+                    OperandToken0::parse(decoder)?;
                 }
                 IndexRepresentation::Immediate32PlusRelative => {
-                    decoder.skip(4);
-                    let _ = OperandToken0::parse(decoder);
+                    decoder.skip(4)?;
+                    OperandToken0::parse(decoder)?;
                 }
                 IndexRepresentation::Immediate64PlusRelative => {
-                    decoder.skip(8);
-                    let _ = OperandToken0::parse(decoder);
+                    decoder.skip(8)?;
+                    OperandToken0::parse(decoder)?;
                 }
             }
         }
 
-        operand
+        let len = decoder.get_offset() - start;
+
+        Ok(OperandToken0::from_tokens(decoder.seek(start)?.bytes(len)?))
     }
 
-    pub fn len(&self) -> u32 {
-        let mut len = 0;
-        if self.is_extended() {
-            len += 1;
+    /// Checks that every field the accessors decode holds a known value.
+    fn is_valid(&self) -> bool {
+        let word = self.word;
+
+        if DECODE_D3D10_SB_OPERAND_TYPE(word) > OperandType::CycleCounter as u32 {
+            return false;
         }
 
-        let dim_len = match self.get_index_dimension() {
-            IndexDimension::D0 => {
-                let ty = self.get_operand_type();
+        let num_components = DECODE_D3D10_SB_OPERAND_NUM_COMPONENTS(word);
+        if num_components == D3D10_SB_OPERAND_4_COMPONENT
+            && DECODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(word)
+                > D3D10_SB_OPERAND_4_COMPONENT_SELECT_1_MODE
+        {
+            return false;
+        }
 
-                match ty {
-                    OperandType::Immediate32 | OperandType::Immediate64 => {
-                        self.get_num_components_u32()
-                    }
-                    _ => 0,
-                }
-            }
+        if self.is_literal() && num_components == D3D10_SB_OPERAND_N_COMPONENT {
+            return false;
+        }
+
+        (0..self.get_index_count()).all(|i| {
+            DECODE_D3D10_SB_OPERAND_INDEX_REPRESENTATION(i, word)
+                <= D3D10_SB_OPERAND_INDEX_IMMEDIATE64_PLUS_RELATIVE
+        })
+    }
+
+    fn get_index_count(&self) -> u32 {
+        match self.get_index_dimension() {
+            IndexDimension::D0 => 0,
             IndexDimension::D1 => 1,
             IndexDimension::D2 => 2,
             IndexDimension::D3 => 3,
-        };
+        }
+    }
+
+    /// Immediate32 and Immediate64 operands carry their values inline
+    /// instead of indices.
+    fn is_literal(&self) -> bool {
+        matches!(
+            self.get_operand_type(),
+            OperandType::Immediate32 | OperandType::Immediate64
+        )
+    }
+
+    /// Returns the number of dwords taken by the literal values. A
+    /// 4-component Immediate64 holds two doubles, one per component pair.
+    fn get_literal_len(&self) -> u32 {
+        match self.get_operand_type() {
+            OperandType::Immediate32 => self.get_num_components_u32(),
+            OperandType::Immediate64 => match self.get_num_components_u32() {
+                1 => 2,
+                n => n,
+            },
+            _ => 0,
+        }
+    }
+
+    /// Returns the relative operand starting at dword `offset`.
+    fn get_relative(&self, offset: u32) -> OperandToken0<'a> {
+        let tokens = &self.tokens[4 * offset as usize..];
+        let len = OperandToken0::from_tokens(tokens).len();
+
+        OperandToken0::from_tokens(&tokens[..4 * len as usize])
+    }
+
+    pub fn len(&self) -> u32 {
+        let mut len = 1;
+        if self.is_extended() {
+            len += 1;
+        }
 
-        for i in 0..dim_len {
+        len += self.get_literal_len();
+
+        for i in 0..self.get_index_count() {
             let repr = self.get_index_representation(i);
 
             match repr {
@@ -639,18 +842,15 @@ impl<'a> OperandToken0<'a> {
                     len += 2;
                 }
                 IndexRepresentation::Relative => {
-                    len +=
-                        OperandToken0::from_word(unsafe { self.word.offset(len as isize) }).len();
+                    len += self.get_relative(len).len();
                 }
                 IndexRepresentation::Immediate32PlusRelative => {
                     len += 1;
-                    len +=
-                        OperandToken0::from_word(unsafe { self.word.offset(len as isize) }).len();
+                    len += self.get_relative(len).len();
                 }
                 IndexRepresentation::Immediate64PlusRelative => {
                     len += 2;
-                    len +=
-                        OperandToken0::from_word(unsafe { self.word.offset(len as isize) }).len();
+                    len += self.get_relative(len).len();
                 }
             }
         }
@@ -662,104 +862,81 @@ impl<'a> OperandToken0<'a> {
         self.len() == 0
     }
 
-    pub fn get_extended_operand(&self) -> Option<OperandToken1<'a>> {
+    pub fn get_extended_operand(&self) -> Option<OperandToken1> {
         if self.is_extended() {
-            Some(OperandToken1 {
-                word: unsafe { self.word.offset(1) },
-                _phantom: self._phantom,
-            })
+            Some(OperandToken1::from_word(read_token(self.tokens, 1)))
         } else {
             None
         }
     }
 
-    fn get_immediate_offset(&self) -> *const u32 {
+    fn get_immediate_offset(&self) -> u32 {
         if self.is_extended() {
-            unsafe { self.word.offset(2) }
+            2
         } else {
-            unsafe { self.word.offset(1) }
+            1
         }
     }
 
-    pub fn get_immediates(&self) -> Vec<Immediate<'a>> {
-        let mut immediates = Vec::new();
-
-        let len = match self.get_index_dimension() {
-            IndexDimension::D0 => {
-                let ty = self.get_operand_type();
+    fn get_literal_count(&self) -> u32 {
+        match self.get_operand_type() {
+            OperandType::Immediate64 => self.get_literal_len() / 2,
+            _ => self.get_literal_len(),
+        }
+    }
 
-                match ty {
-                    OperandType::Immediate32 | OperandType::Immediate64 => {
-                        self.get_num_components_u32()
-                    }
-                    _ => 0,
-                }
-            }
-            IndexDimension::D1 => 1,
-            IndexDimension::D2 => 2,
-            IndexDimension::D3 => 3,
+    /// Returns the literal values of an immediate operand, or the indices of
+    /// any other operand.
+    pub fn get_immediates(&self) -> Vec<Immediate<'a>> {
+        let count = if self.is_literal() {
+            self.get_literal_count()
+        } else {
+            self.get_index_count()
         };
 
-        for i in 0..len {
-            immediates.push(self.get_immediate(i));
-        }
-
-        immediates
+        (0..count).filter_map(|i| self.get_immediate(i)).collect()
     }
 
-    pub fn get_immediate(&self, index: u32) -> Immediate<'a> {
-        let len = match self.get_index_dimension() {
-            IndexDimension::D0 => {
-                let ty = self.get_operand_type();
+    /// Returns the `index`th literal value or index, or `None` if the operand
+    /// has fewer.
+    pub fn get_immediate(&self, index: u32) -> Option<Immediate<'a>> {
+        let mut offset = self.get_immediate_offset();
 
-                match ty {
-                    OperandType::Immediate32 | OperandType::Immediate64 => {
-                        self.get_num_components_u32()
-                    }
-                    _ => 0,
-                }
+        if self.is_literal() {
+            if index >= self.get_literal_count() {
+                return None;
             }
-            IndexDimension::D1 => 1,
-            IndexDimension::D2 => 2,
-            IndexDimension::D3 => 3,
-        };
 
-        let imm = self.get_immediate_offset();
-        let mut offset = 0;
+            return Some(match self.get_operand_type() {
+                OperandType::Immediate64 => {
+                    let at = 4 * (offset + 2 * index) as usize;
+                    Immediate::U64(LittleEndian::read_u64(&self.tokens[at..]))
+                }
+                _ => Immediate::U32(read_token(self.tokens, (offset + index) as usize)),
+            });
+        }
 
         use self::IndexRepresentation::*;
 
-        for i in 0..len {
+        for i in 0..self.get_index_count() {
             let repr = self.get_index_representation(i);
 
             if i == index {
-                match repr {
-                    Immediate32 => {
-                        return Immediate::U32(unsafe { *imm.offset(offset as isize) });
-                    }
-                    Immediate64 => {
-                        return Immediate::U64(unsafe {
-                            *(imm.offset(offset as isize) as *const u64)
-                        });
-                    }
-                    Relative => {
-                        return Immediate::Relative(OperandToken0::from_word(unsafe {
-                            imm.offset(offset as isize)
-                        }));
-                    }
-                    Immediate32PlusRelative => {
-                        return Immediate::U32Relative(
-                            unsafe { *imm.offset(offset as isize) },
-                            OperandToken0::from_word(unsafe { imm.offset(1 + offset as isize) }),
-                        );
-                    }
-                    Immediate64PlusRelative => {
-                        return Immediate::U64Relative(
-                            unsafe { *(imm.offset(offset as isize) as *const u64) },
-                            OperandToken0::from_word(unsafe { imm.offset(2 + offset as isize) }),
-                        );
-                    }
-                }
+                let at = offset as usize;
+
+                return Some(match repr {
+                    Immediate32 => Immediate::U32(read_token(self.tokens, at)),
+                    Immediate64 => Immediate::U64(LittleEndian::read_u64(&self.tokens[4 * at..])),
+                    Relative => Immediate::Relative(self.get_relative(offset)),
+                    Immediate32PlusRelative => Immediate::U32Relative(
+                        read_token(self.tokens, at),
+                        self.get_relative(offset + 1),
+                    ),
+                    Immediate64PlusRelative => Immediate::U64Relative(
+                        LittleEndian::read_u64(&self.tokens[4 * at..]),
+                        self.get_relative(offset + 2),
+                    ),
+                });
             } else {
                 match repr {
                     Immediate32 => {
@@ -769,32 +946,29 @@ impl<'a> OperandToken0<'a> {
                         offset += 2;
                     }
                     Relative => {
-                        offset +=
-                            OperandToken0::from_word(unsafe { imm.offset(offset as isize) }).len();
+                        offset += self.get_relative(offset).len();
                     }
                     Immediate32PlusRelative => {
                         offset += 1;
-                        offset +=
-                            OperandToken0::from_word(unsafe { imm.offset(offset as isize) }).len();
+                        offset += self.get_relative(offset).len();
                     }
                     Immediate64PlusRelative => {
                         offset += 2;
-                        offset +=
-                            OperandToken0::from_word(unsafe { imm.offset(offset as isize) }).len();
+                        offset += self.get_relative(offset).len();
                     }
                 }
             }
         }
 
-        unreachable!()
+        None
     }
 
     pub fn is_extended(&self) -> bool {
-        DECODE_IS_D3D10_SB_OPERAND_EXTENDED(unsafe { *self.word }) != 0
+        DECODE_IS_D3D10_SB_OPERAND_EXTENDED(self.word) != 0
     }
 
     pub fn get_num_components(&self) -> NumComponents {
-        match DECODE_D3D10_SB_OPERAND_NUM_COMPONENTS(unsafe { *self.word }) {
+        match DECODE_D3D10_SB_OPERAND_NUM_COMPONENTS(self.word) {
             0 => NumComponents::Zero,
             1 => NumComponents::One,
             2 => NumComponents::Four,
@@ -804,7 +978,7 @@ impl<'a> OperandToken0<'a> {
     }
 
     pub fn get_num_components_u32(&self) -> u32 {
-        match DECODE_D3D10_SB_OPERAND_NUM_COMPONENTS(unsafe { *self.word }) {
+        match DECODE_D3D10_SB_OPERAND_NUM_COMPONENTS(self.word) {
             0 => 0,
             1 => 1,
             2 => 4,
@@ -812,38 +986,32 @@ impl<'a> OperandToken0<'a> {
         }
     }
 
-    pub fn get_component_select_mode(&self) -> ComponentSelectMode {
-        match DECODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(unsafe { *self.word }) {
-            0 => ComponentSelectMode::Mask,
-            1 => ComponentSelectMode::Swizzle,
-            2 => ComponentSelectMode::Select1,
-            _ => unreachable!(),
+    pub fn get_component_select_mode(&self) -> Option<ComponentSelectMode> {
+        match DECODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(self.word) {
+            0 => Some(ComponentSelectMode::Mask),
+            1 => Some(ComponentSelectMode::Swizzle),
+            2 => Some(ComponentSelectMode::Select1),
+            _ => None,
         }
     }
 
     pub fn get_component_mask(&self) -> ComponentMask {
-        ComponentMask::from_bits_truncate(DECODE_D3D10_SB_OPERAND_4_COMPONENT_MASK(unsafe {
-            *self.word
-        }))
+        ComponentMask::from_bits_truncate(DECODE_D3D10_SB_OPERAND_4_COMPONENT_MASK(self.word))
     }
 
     pub fn get_component_swizzle(&self) -> ComponentSwizzle {
-        let x = DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(
-            unsafe { *self.word },
-            D3D10_SB_4_COMPONENT_X,
-        ) as u8;
-        let y = DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(
-            unsafe { *self.word },
-            D3D10_SB_4_COMPONENT_Y,
-        ) as u8;
-        let z = DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(
-            unsafe { *self.word },
-            D3D10_SB_4_COMPONENT_Z,
-        ) as u8;
-        let w = DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(
-            unsafe { *self.word },
-            D3D10_SB_4_COMPONENT_W,
-        ) as u8;
+        let x =
+            DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(self.word, D3D10_SB_4_COMPONENT_X)
+                as u8;
+        let y =
+            DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(self.word, D3D10_SB_4_COMPONENT_Y)
+                as u8;
+        let z =
+            DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(self.word, D3D10_SB_4_COMPONENT_Z)
+                as u8;
+        let w =
+            DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(self.word, D3D10_SB_4_COMPONENT_W)
+                as u8;
 
         ComponentSwizzle(
             ComponentName::from_word(x),
@@ -854,7 +1022,7 @@ impl<'a> OperandToken0<'a> {
     }
 
     pub fn get_operand_type(&self) -> OperandType {
-        match DECODE_D3D10_SB_OPERAND_TYPE(unsafe { *self.word }) {
+        match DECODE_D3D10_SB_OPERAND_TYPE(self.word) {
             0 => OperandType::Temp,
             1 => OperandType::Input,
             2 => OperandType::Output,
@@ -901,7 +1069,7 @@ impl<'a> OperandToken0<'a> {
     }
 
     pub fn get_index_dimension(&self) -> IndexDimension {
-        match DECODE_D3D10_SB_OPERAND_INDEX_DIMENSION(unsafe { *self.word }) {
+        match DECODE_D3D10_SB_OPERAND_INDEX_DIMENSION(self.word) {
             0 => IndexDimension::D0,
             1 => IndexDimension::D1,
             2 => IndexDimension::D2,
@@ -911,7 +1079,7 @@ impl<'a> OperandToken0<'a> {
     }
 
     pub fn get_index_representation(&self, index: u32) -> IndexRepresentation {
-        match DECODE_D3D10_SB_OPERAND_INDEX_REPRESENTATION(index, unsafe { *self.word }) {
+        match DECODE_D3D10_SB_OPERAND_INDEX_REPRESENTATION(index, self.word) {
             0 => IndexRepresentation::Immediate32,
             1 => IndexRepresentation::Immediate64,
             2 => IndexRepresentation::Relative,
@@ -927,7 +1095,7 @@ use std::fmt;
 impl<'a> fmt::Debug for OperandToken0<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("OperandToken0")
-            .field("Raw", &unsafe { *self.word })
+            .field("Raw", &self.word)
             .field("NumComponents", &self.get_num_components())
             .field("ComponentSelect", &self.get_component_select_mode())
             .field("ComponentMask", &self.get_component_mask())
@@ -989,7 +1157,7 @@ pub struct DclInput<'a> {
 impl<'a> DclInput<'a> {
     pub fn get_input_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
@@ -1003,7 +1171,7 @@ pub struct DclInputPs<'a> {
 impl<'a> DclInputPs<'a> {
     pub fn get_input_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
@@ -1017,7 +1185,7 @@ pub struct DclOutput<'a> {
 impl<'a> DclOutput<'a> {
     pub fn get_output_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
@@ -1040,14 +1208,14 @@ impl<'a> DclConstantBuffer<'a> {
 
     pub fn get_binding(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
 
     pub fn get_size(&self) -> u32 {
         match self.operand.get_immediate(1) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
@@ -1056,13 +1224,13 @@ impl<'a> DclConstantBuffer<'a> {
 #[derive(Debug)]
 pub struct DclResource<'a> {
     pub register: OperandToken0<'a>,
-    pub return_type: ResourceReturnTypeToken0<'a>,
+    pub return_type: ResourceReturnTypeToken0,
 }
 
 impl<'a> DclResource<'a> {
     pub fn get_register(&self) -> u32 {
         match self.register.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
@@ -1076,7 +1244,7 @@ pub struct DclSampler<'a> {
 impl<'a> DclSampler<'a> {
     pub fn get_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
@@ -1091,13 +1259,13 @@ pub struct DclOutputSiv<'a> {
 impl<'a> DclOutputSiv<'a> {
     pub fn get_output_register(&self) -> u32 {
         match self.register.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
 
-    pub fn get_system_name(&self) -> NameToken {
-        NameToken::from_word(DECODE_D3D10_SB_NAME(unsafe { *self.semantic.word }))
+    pub fn get_system_name(&self) -> Option<NameToken> {
+        NameToken::from_word(DECODE_D3D10_SB_NAME(self.semantic.word))
     }
 }
 
@@ -1110,13 +1278,13 @@ pub struct DclOutputSgv<'a> {
 impl<'a> DclOutputSgv<'a> {
     pub fn get_output_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
 
-    pub fn get_system_name(&self) -> NameToken {
-        NameToken::from_word(DECODE_D3D10_SB_NAME(unsafe { *self.operand_2.word }))
+    pub fn get_system_name(&self) -> Option<NameToken> {
+        NameToken::from_word(DECODE_D3D10_SB_NAME(self.operand_2.word))
     }
 }
 
@@ -1129,13 +1297,13 @@ pub struct DclInputPsSiv<'a> {
 impl<'a> DclInputPsSiv<'a> {
     pub fn get_input_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
 
-    pub fn get_system_name(&self) -> NameToken {
-        NameToken::from_word(DECODE_D3D10_SB_NAME(unsafe { *self.operand_2.word }))
+    pub fn get_system_name(&self) -> Option<NameToken> {
+        NameToken::from_word(DECODE_D3D10_SB_NAME(self.operand_2.word))
     }
 }
 
@@ -1148,13 +1316,13 @@ pub struct DclInputPsSgv<'a> {
 impl<'a> DclInputPsSgv<'a> {
     pub fn get_input_register(&self) -> u32 {
         match self.operand.get_immediate(0) {
-            Immediate::U32(reg) => reg,
+            Some(Immediate::U32(reg)) => reg,
             _ => !0,
         }
     }
 
-    pub fn get_system_name(&self) -> NameToken {
-        NameToken::from_word(DECODE_D3D10_SB_NAME(unsafe { *self.operand_2.word }))
+    pub fn get_system_name(&self) -> Option<NameToken> {
+        NameToken::from_word(DECODE_D3D10_SB_NAME(self.operand_2.word))
     }
 }
 
@@ -1170,63 +1338,368 @@ pub struct DclIndexableTemp {
     pub num_components: u32,
 }
 
-// Boolean
-
 #[derive(Debug)]
-pub struct And<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+pub struct DclInputSgv<'a> {
+    pub operand: OperandToken0<'a>,
+    pub operand_2: OperandToken0<'a>,
 }
 
-#[derive(Debug)]
-pub struct Eq<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+impl<'a> DclInputSgv<'a> {
+    pub fn get_input_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+
+    pub fn get_system_name(&self) -> Option<NameToken> {
+        NameToken::from_word(DECODE_D3D10_SB_NAME(self.operand_2.word))
+    }
 }
 
 #[derive(Debug)]
-pub struct Ge<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+pub struct DclInputSiv<'a> {
+    pub operand: OperandToken0<'a>,
+    pub operand_2: OperandToken0<'a>,
 }
 
-#[derive(Debug)]
-pub struct Ige<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+impl<'a> DclInputSiv<'a> {
+    pub fn get_input_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+
+    pub fn get_system_name(&self) -> Option<NameToken> {
+        NameToken::from_word(DECODE_D3D10_SB_NAME(self.operand_2.word))
+    }
 }
 
 #[derive(Debug)]
-pub struct Lt<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+pub struct DclIndexRange<'a> {
+    pub operand: OperandToken0<'a>,
+    pub register_count: u32,
 }
 
 #[derive(Debug)]
-pub struct Ne<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+pub struct DclThreadGroup {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
 }
 
 #[derive(Debug)]
-pub struct Or<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
-    pub b: OperandToken0<'a>,
+pub struct DclTgsmRaw<'a> {
+    pub operand: OperandToken0<'a>,
+    pub byte_count: u32,
 }
 
-// Math
+impl<'a> DclTgsmRaw<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
 
 #[derive(Debug)]
-pub struct Add<'a> {
-    pub dst: OperandToken0<'a>,
-    pub a: OperandToken0<'a>,
+pub struct DclTgsmStructured<'a> {
+    pub operand: OperandToken0<'a>,
+    pub stride: u32,
+    pub count: u32,
+}
+
+impl<'a> DclTgsmStructured<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DclUavTyped<'a> {
+    pub operand: OperandToken0<'a>,
+    pub return_type: ResourceReturnTypeToken0,
+}
+
+impl<'a> DclUavTyped<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DclUavRaw<'a> {
+    pub operand: OperandToken0<'a>,
+}
+
+impl<'a> DclUavRaw<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DclUavStructured<'a> {
+    pub operand: OperandToken0<'a>,
+    pub stride: u32,
+}
+
+impl<'a> DclUavStructured<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DclResourceRaw<'a> {
+    pub operand: OperandToken0<'a>,
+}
+
+impl<'a> DclResourceRaw<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DclResourceStructured<'a> {
+    pub operand: OperandToken0<'a>,
+    pub stride: u32,
+}
+
+impl<'a> DclResourceStructured<'a> {
+    pub fn get_register(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DclInputControlPointCount {
+    pub count: u32,
+}
+
+#[derive(Debug)]
+pub struct DclOutputControlPointCount {
+    pub count: u32,
+}
+
+#[derive(Debug)]
+pub struct DclTessellatorDomain {
+    pub domain: TessellatorDomain,
+}
+
+#[derive(Debug)]
+pub struct DclTessellatorPartitioning {
+    pub partitioning: TessellatorPartitioning,
+}
+
+#[derive(Debug)]
+pub struct DclTessellatorOutputPrimitive {
+    pub output_primitive: TessellatorOutputPrimitive,
+}
+
+#[derive(Debug)]
+pub struct DclHsMaxTessFactor {
+    pub max_tess_factor: f32,
+}
+
+#[derive(Debug)]
+pub struct DclHsForkPhaseInstanceCount {
+    pub instance_count: u32,
+}
+
+#[derive(Debug)]
+pub struct DclHsJoinPhaseInstanceCount {
+    pub instance_count: u32,
+}
+
+#[derive(Debug)]
+pub struct DclGsInputPrimitive {
+    pub primitive: Primitive,
+}
+
+#[derive(Debug)]
+pub struct DclGsOutputPrimitiveTopology {
+    pub topology: PrimitiveTopology,
+}
+
+#[derive(Debug)]
+pub struct DclMaxOutputVertexCount {
+    pub count: u32,
+}
+
+#[derive(Debug)]
+pub struct DclGsInstanceCount {
+    pub instance_count: u32,
+}
+
+#[derive(Debug)]
+pub struct DclStream<'a> {
+    pub operand: OperandToken0<'a>,
+}
+
+impl<'a> DclStream<'a> {
+    pub fn get_stream(&self) -> u32 {
+        match self.operand.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+// Boolean
+
+#[derive(Debug)]
+pub struct And<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Eq<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ge<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ige<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Lt<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ne<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Or<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ieq<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ilt<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ine<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Uge<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ult<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Not<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Xor<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DEq<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DGe<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DLt<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DNe<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+// Math
+
+#[derive(Debug)]
+pub struct Add<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
     pub b: OperandToken0<'a>,
 }
 
@@ -1355,393 +1828,1903 @@ pub struct Sqrt<'a> {
     pub src: OperandToken0<'a>,
 }
 
-// Memory
+#[derive(Debug)]
+pub struct DerivRtx<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
 
 #[derive(Debug)]
-pub struct Mov<'a> {
+pub struct DerivRty<'a> {
     pub dst: OperandToken0<'a>,
     pub src: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub struct MovC<'a> {
+pub struct Imad<'a> {
     pub dst: OperandToken0<'a>,
     pub a: OperandToken0<'a>,
     pub b: OperandToken0<'a>,
     pub c: OperandToken0<'a>,
 }
 
-// Conversions
-
 #[derive(Debug)]
-pub struct Itof<'a> {
+pub struct Imax<'a> {
     pub dst: OperandToken0<'a>,
-    pub src: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub struct Utof<'a> {
+pub struct Imin<'a> {
     pub dst: OperandToken0<'a>,
-    pub src: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub struct Ftou<'a> {
+pub struct Imul<'a> {
+    pub dst_hi: OperandToken0<'a>,
+    pub dst_lo: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ineg<'a> {
     pub dst: OperandToken0<'a>,
     pub src: OperandToken0<'a>,
 }
 
-// Control flow
+#[derive(Debug)]
+pub struct Ishl<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
 
 #[derive(Debug)]
-pub struct If<'a> {
+pub struct Ishr<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Udiv<'a> {
+    pub dst_quot: OperandToken0<'a>,
+    pub dst_rem: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Umad<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Umax<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Umin<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Umul<'a> {
+    pub dst_hi: OperandToken0<'a>,
+    pub dst_lo: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ushr<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DAdd<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DMax<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DMin<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DMul<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DDiv<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DFma<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DRcp<'a> {
+    pub dst: OperandToken0<'a>,
     pub src: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub struct BreakC<'a> {
+pub struct Rcp<'a> {
+    pub dst: OperandToken0<'a>,
     pub src: OperandToken0<'a>,
 }
 
-// Textures
+#[derive(Debug)]
+pub struct Bfi<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
+    pub d: OperandToken0<'a>,
+}
 
 #[derive(Debug)]
-pub struct SampleL<'a> {
+pub struct UBfe<'a> {
     pub dst: OperandToken0<'a>,
-    pub src_address: OperandToken0<'a>,
-    pub src_resource: OperandToken0<'a>,
-    pub src_sampler: OperandToken0<'a>,
-    pub src_lod: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub struct Sample<'a> {
+pub struct IBfe<'a> {
     pub dst: OperandToken0<'a>,
-    pub src_address: OperandToken0<'a>,
-    pub src_resource: OperandToken0<'a>,
-    pub src_sampler: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
 }
 
-#[repr(C)]
 #[derive(Debug)]
-pub struct ShexHeader {
-    pub minor: u8,
-    pub major: u8,
-    pub program_type: u16,
-    pub instruction_length: u32,
+pub struct BfRev<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
 }
 
-impl ShexHeader {
-    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Self, State> {
-        let version = decoder.read_u8();
-        let minor = version & 0x0f;
-        let major = version >> 0x4;
-        decoder.skip(1);
-        let program_type = decoder.read_u16();
-        let instruction_length = decoder.read_u32() - 2;
+#[derive(Debug)]
+pub struct CountBits<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
 
-        Ok(ShexHeader {
-            minor,
-            major,
-            program_type,
-            instruction_length,
-        })
-    }
+#[derive(Debug)]
+pub struct FirstBitHi<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub struct SparseInstruction<'a> {
-    pub opcode: OpcodeToken0<'a>,
-    pub operands: Operands<'a>,
+pub struct FirstBitLo<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
 }
 
 #[derive(Debug)]
-pub enum Operands<'a> {
-    // Declarations
-    DclGlobalFlags(DclGlobalFlags),
-    DclInput(DclInput<'a>),
-    DclInputPs(DclInputPs<'a>),
-    DclOutput(DclOutput<'a>),
-    DclConstantBuffer(DclConstantBuffer<'a>),
-    DclResource(DclResource<'a>),
-    DclSampler(DclSampler<'a>),
-    DclOutputSiv(DclOutputSiv<'a>),
-    DclOutputSgv(DclOutputSgv<'a>),
-    DclInputPsSiv(DclInputPsSiv<'a>),
-    DclInputPsSgv(DclInputPsSgv<'a>),
-    DclTemps(DclTemps),
-    DclIndexableTemp(DclIndexableTemp),
-    // Boolean
-    And(And<'a>),
-    Eq(Eq<'a>),
-    Ge(Ge<'a>),
-    Ige(Ige<'a>),
-    Lt(Lt<'a>),
-    Ne(Ne<'a>),
-    Or(Or<'a>),
-    // Math
-    Add(Add<'a>),
-    Div(Div<'a>),
-    Dp2(Dp2<'a>),
-    Dp3(Dp3<'a>),
-    Dp4(Dp4<'a>),
-    Exp(Exp<'a>),
-    Frc(Frc<'a>),
-    IAdd(IAdd<'a>),
-    Log(Log<'a>),
-    Mad(Mad<'a>),
-    Max(Max<'a>),
-    Min(Min<'a>),
-    Mul(Mul<'a>),
-    RoundNe(RoundNe<'a>),
-    RoundNi(RoundNi<'a>),
-    RoundPi(RoundPi<'a>),
-    RoundZ(RoundZ<'a>),
-    Rsq(Rsq<'a>),
-    SinCos(SinCos<'a>),
-    Sqrt(Sqrt<'a>),
-    // Memory
-    Mov(Mov<'a>),
-    MovC(MovC<'a>),
-    // Conversions
-    Itof(Itof<'a>),
-    Utof(Utof<'a>),
-    Ftou(Ftou<'a>),
-    // Control flow
-    If(If<'a>),
-    Else,
-    EndIf,
-    Loop,
-    EndLoop,
-    Break,
-    BreakC(BreakC<'a>),
-    Ret,
-    // Textures
-    Sample(Sample<'a>),
-    SampleL(SampleL<'a>),
-    // All others
-    Unknown(u32),
+pub struct FirstBitShi<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
 }
 
-impl<'a> SparseInstruction<'a> {
-    pub fn parse<'b>(decoder: &'b mut decoder::Decoder) -> SparseInstruction<'b> {
-        let opcode = OpcodeToken0::from_word(decoder.read_u32_address());
-        let ty = opcode.get_opcode_type();
-        let len = opcode.get_instruction_length();
+#[derive(Debug)]
+pub struct UAddC<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_carry: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
 
-        let mut ex = opcode.get_extended_opcode();
-        while let Some(opc) = ex {
-            // println!("{:?}", opcode);
-            // println!("{:?}", opc);
-            ex = opc.get_extended_opcode();
-            decoder.skip(4);
-        }
+#[derive(Debug)]
+pub struct USubB<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_borrow: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+}
 
-        let operands = match ty {
-            // Declarations
-            D3D10_SB_OPCODE_DCL_GLOBAL_FLAGS => Operands::DclGlobalFlags(DclGlobalFlags {
-                global_flags: DECODE_D3D10_SB_GLOBAL_FLAGS(unsafe { *opcode.word }),
-            }),
-            D3D10_SB_OPCODE_DCL_INPUT => Operands::DclInput(DclInput {
-                operand: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_INPUT_PS => Operands::DclInputPs(DclInputPs {
-                operand: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_INPUT_PS_SIV => Operands::DclInputPsSiv(DclInputPsSiv {
-                operand: OperandToken0::parse(decoder),
-                operand_2: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_INPUT_PS_SGV => Operands::DclInputPsSgv(DclInputPsSgv {
-                operand: OperandToken0::parse(decoder),
-                operand_2: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_OUTPUT => Operands::DclOutput(DclOutput {
-                operand: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_CONSTANT_BUFFER => Operands::DclConstantBuffer(DclConstantBuffer {
-                operand: OperandToken0::parse(decoder),
-                access: DECODE_D3D10_SB_CONSTANT_BUFFER_ACCESS_PATTERN(unsafe { *opcode.word }),
-            }),
-            D3D10_SB_OPCODE_DCL_RESOURCE => Operands::DclResource(DclResource {
-                register: OperandToken0::parse(decoder),
-                return_type: ResourceReturnTypeToken0::from_word(decoder.read_u32_address()),
-            }),
-            D3D10_SB_OPCODE_DCL_SAMPLER => Operands::DclSampler(DclSampler {
-                operand: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_TEMPS => Operands::DclTemps(DclTemps {
-                register_count: decoder.read_u32(),
-            }),
-            D3D10_SB_OPCODE_DCL_INDEXABLE_TEMP => Operands::DclIndexableTemp(DclIndexableTemp {
-                register_index: decoder.read_u32(),
-                register_count: decoder.read_u32(),
-                num_components: decoder.read_u32(),
-            }),
-            D3D10_SB_OPCODE_DCL_OUTPUT_SIV => Operands::DclOutputSiv(DclOutputSiv {
-                register: OperandToken0::parse(decoder),
-                semantic: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_DCL_OUTPUT_SGV => Operands::DclOutputSgv(DclOutputSgv {
-                operand: OperandToken0::parse(decoder),
-                operand_2: OperandToken0::parse(decoder),
-            }),
-            // Boolean
-            D3D10_SB_OPCODE_AND => Operands::And(And {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_EQ => Operands::Eq(Eq {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
-            }),
-            D3D10_SB_OPCODE_GE => Operands::Ge(Ge {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+#[derive(Debug)]
+pub struct Msad<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DerivRtxCoarse<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DerivRtxFine<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DerivRtyCoarse<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DerivRtyFine<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+// Memory
+
+#[derive(Debug)]
+pub struct Mov<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct MovC<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct LdRaw<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_byte_offset: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct LdStructured<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_byte_offset: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct LdUavTyped<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_uav: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct StoreRaw<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_byte_offset: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct StoreStructured<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_byte_offset: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct StoreUavTyped<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DMov<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct DMovC<'a> {
+    pub dst: OperandToken0<'a>,
+    pub a: OperandToken0<'a>,
+    pub b: OperandToken0<'a>,
+    pub c: OperandToken0<'a>,
+}
+
+// Conversions
+
+#[derive(Debug)]
+pub struct Itof<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Utof<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ftou<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ftoi<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Dtof<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ftod<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Dtoi<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Dtou<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Itod<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Utod<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct F32ToF16<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct F16ToF32<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+// Control flow
+
+#[derive(Debug)]
+pub struct If<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct BreakC<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Switch<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Case<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ContinueC<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct RetC<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Discard<'a> {
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Call<'a> {
+    pub label: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct CallC<'a> {
+    pub src: OperandToken0<'a>,
+    pub label: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Label<'a> {
+    pub label: OperandToken0<'a>,
+}
+
+// Textures
+
+#[derive(Debug)]
+pub struct SampleL<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_lod: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Sample<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct SampleB<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_bias: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct SampleC<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_reference: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct SampleCLz<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_reference: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct SampleD<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_ddx: OperandToken0<'a>,
+    pub src_ddy: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Ld<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct LdMs<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sample_index: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ResInfo<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_mip_level: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Lod<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Gather4<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct SamplePos<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sample_index: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct SampleInfo<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Gather4C<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_reference: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Gather4Po<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_offset: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct Gather4PoC<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_address: OperandToken0<'a>,
+    pub src_offset: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+    pub src_sampler: OperandToken0<'a>,
+    pub src_reference: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct BufInfo<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src_resource: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct EvalSnapped<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+    pub src_offset: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct EvalSampleIndex<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+    pub src_sample_index: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct EvalCentroid<'a> {
+    pub dst: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+// Geometry
+
+#[derive(Debug)]
+pub struct EmitStream<'a> {
+    pub stream: OperandToken0<'a>,
+}
+
+impl<'a> EmitStream<'a> {
+    pub fn get_stream(&self) -> u32 {
+        match self.stream.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CutStream<'a> {
+    pub stream: OperandToken0<'a>,
+}
+
+impl<'a> CutStream<'a> {
+    pub fn get_stream(&self) -> u32 {
+        match self.stream.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmitThenCutStream<'a> {
+    pub stream: OperandToken0<'a>,
+}
+
+impl<'a> EmitThenCutStream<'a> {
+    pub fn get_stream(&self) -> u32 {
+        match self.stream.get_immediate(0) {
+            Some(Immediate::U32(reg)) => reg,
+            _ => !0,
+        }
+    }
+}
+
+// Compute
+
+#[derive(Debug)]
+pub struct AtomicAnd<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicOr<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicXor<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicIAdd<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicIMax<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicIMin<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicUMax<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicUMin<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct AtomicCmpStore<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src_compare: OperandToken0<'a>,
+    pub src_value: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicAlloc<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicConsume<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicIAdd<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicAnd<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicOr<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicXor<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicExch<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicIMax<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicIMin<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicUMax<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicUMin<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src: OperandToken0<'a>,
+}
+
+#[derive(Debug)]
+pub struct ImmAtomicCmpExch<'a> {
+    pub dst: OperandToken0<'a>,
+    pub dst_uav: OperandToken0<'a>,
+    pub dst_address: OperandToken0<'a>,
+    pub src_compare: OperandToken0<'a>,
+    pub src_value: OperandToken0<'a>,
+}
+
+// Class linkage
+
+#[derive(Debug)]
+pub struct DclFunctionBody {
+    pub index: u32,
+}
+
+#[derive(Debug)]
+pub struct DclFunctionTable {
+    pub index: u32,
+    /// The function bodies in the table, one per call site.
+    pub bodies: Vec<u32>,
+}
+
+#[derive(Debug)]
+pub struct DclInterface {
+    pub index: u32,
+    pub dynamically_indexed: bool,
+    /// The number of call sites, which every function table bound to the
+    /// interface has a body for.
+    pub body_count: u32,
+    pub array_length: u32,
+    /// The function tables of the class types that implement the interface.
+    pub tables: Vec<u32>,
+}
+
+/// `fcall`
+#[derive(Debug)]
+pub struct InterfaceCall<'a> {
+    /// The call site, which indexes into the bodies of the function table.
+    pub function_index: u32,
+    pub interface: OperandToken0<'a>,
+}
+
+// Custom data
+
+/// `dcl_immediateConstantBuffer`
+#[derive(Debug)]
+pub struct DclImmediateConstantBuffer {
+    pub constants: Vec<[u32; 4]>,
+}
+
+impl DclImmediateConstantBuffer {
+    /// Returns the `icb[]` element that `operand` reads from, or `None` if
+    /// the operand doesn't reference the immediate constant buffer with a
+    /// constant index.
+    pub fn resolve(&self, operand: &OperandToken0) -> Option<[u32; 4]> {
+        if operand.get_operand_type() != OperandType::ImmediateConstantBuffer
+            || matches!(operand.get_index_dimension(), IndexDimension::D0)
+        {
+            return None;
+        }
+
+        match operand.get_immediate(0) {
+            Some(Immediate::U32(index)) => self.constants.get(index as usize).copied(),
+            _ => None,
+        }
+    }
+}
+
+/// A custom data block of any class other than the immediate constant
+/// buffer.
+#[derive(Debug)]
+pub struct CustomData {
+    pub class: CustomDataClass,
+    pub data: Vec<u32>,
+}
+
+/// A message from `printf` or `errorf`, which is stored as custom data of the
+/// `ShaderMessage` class.
+#[derive(Debug)]
+pub struct ShaderMessage {
+    /// `D3D11_SB_SHADER_MESSAGE_ID_MESSAGE` or `D3D11_SB_SHADER_MESSAGE_ID_ERROR`.
+    pub id: u32,
+    /// Whether `text` is plain text or a format string, like `%0 is fine`.
+    pub format: u32,
+    pub text: String,
+    pub operands: Vec<Operand>,
+}
+
+impl ShaderMessage {
+    /// Parses the data of the custom data block, which starts after its
+    /// length token.
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Self, State> {
+        let id = decoder.read_u32()?;
+        let format = decoder.read_u32()?;
+        let text_len = decoder.read_u32()? as usize;
+        let operand_count = decoder.read_u32()?;
+        let operands_len = decoder.read_u32()? as usize;
+
+        let mut operand_decoder = decoder.scoped_decoder(operands_len * 4)?;
+        let operands = (0..operand_count)
+            .map(|_| Operand::parse(&mut operand_decoder))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !operand_decoder.eof() {
+            return Err(State::ChunkIncorrect);
+        }
+        decoder.skip(operands_len * 4)?;
+
+        // the text is null terminated and padded to a whole dword
+        let text = String::from_utf8_lossy(decoder.bytes(text_len)?).into_owned();
+
+        Ok(ShaderMessage {
+            id,
+            format,
+            text,
+            operands,
+        })
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct ShexHeader {
+    pub minor: u8,
+    pub major: u8,
+    pub program_type: u16,
+    pub instruction_length: u32,
+}
+
+impl ShexHeader {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Self, State> {
+        let version = decoder.read_u8()?;
+        let minor = version & 0x0f;
+        let major = version >> 0x4;
+        decoder.skip(1)?;
+        let program_type = decoder.read_u16()?;
+        let instruction_length = decoder
+            .read_u32()?
+            .checked_sub(2)
+            .ok_or(State::ChunkIncorrect)?;
+
+        Ok(ShexHeader {
+            minor,
+            major,
+            program_type,
+            instruction_length,
+        })
+    }
+}
+
+/// The hull shader phase an instruction belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    /// Outside of any hull shader phase, which covers every instruction of
+    /// the other program types.
+    Global,
+    Declarations,
+    ControlPoint,
+    Fork,
+    Join,
+}
+
+/// An instruction as a view into the program's tokens, which is what
+/// `Consumer::consume_instruction` gets. Its operands are decoded lazily, so
+/// walking a program doesn't allocate; `to_instruction` decodes the same
+/// tokens into an owned `Instruction` when it needs to outlive them.
+#[derive(Debug)]
+pub struct SparseInstruction<'a> {
+    pub opcode: OpcodeToken0<'a>,
+    pub operands: Operands<'a>,
+    pub phase: Phase,
+}
+
+#[derive(Debug)]
+pub enum Operands<'a> {
+    // Declarations
+    DclGlobalFlags(DclGlobalFlags),
+    DclInput(DclInput<'a>),
+    DclInputPs(DclInputPs<'a>),
+    DclOutput(DclOutput<'a>),
+    DclConstantBuffer(DclConstantBuffer<'a>),
+    DclResource(DclResource<'a>),
+    DclSampler(DclSampler<'a>),
+    DclOutputSiv(DclOutputSiv<'a>),
+    DclOutputSgv(DclOutputSgv<'a>),
+    DclInputPsSiv(DclInputPsSiv<'a>),
+    DclInputPsSgv(DclInputPsSgv<'a>),
+    DclTemps(DclTemps),
+    DclIndexableTemp(DclIndexableTemp),
+    DclInputSgv(DclInputSgv<'a>),
+    DclInputSiv(DclInputSiv<'a>),
+    DclIndexRange(DclIndexRange<'a>),
+    DclThreadGroup(DclThreadGroup),
+    DclTgsmRaw(DclTgsmRaw<'a>),
+    DclTgsmStructured(DclTgsmStructured<'a>),
+    DclUavTyped(DclUavTyped<'a>),
+    DclUavRaw(DclUavRaw<'a>),
+    DclUavStructured(DclUavStructured<'a>),
+    DclResourceRaw(DclResourceRaw<'a>),
+    DclResourceStructured(DclResourceStructured<'a>),
+    DclInputControlPointCount(DclInputControlPointCount),
+    DclOutputControlPointCount(DclOutputControlPointCount),
+    DclTessellatorDomain(DclTessellatorDomain),
+    DclTessellatorPartitioning(DclTessellatorPartitioning),
+    DclTessellatorOutputPrimitive(DclTessellatorOutputPrimitive),
+    DclHsMaxTessFactor(DclHsMaxTessFactor),
+    DclHsForkPhaseInstanceCount(DclHsForkPhaseInstanceCount),
+    DclHsJoinPhaseInstanceCount(DclHsJoinPhaseInstanceCount),
+    DclGsInputPrimitive(DclGsInputPrimitive),
+    DclGsOutputPrimitiveTopology(DclGsOutputPrimitiveTopology),
+    DclMaxOutputVertexCount(DclMaxOutputVertexCount),
+    DclGsInstanceCount(DclGsInstanceCount),
+    DclStream(DclStream<'a>),
+    // Boolean
+    And(And<'a>),
+    Eq(Eq<'a>),
+    Ge(Ge<'a>),
+    Ige(Ige<'a>),
+    Lt(Lt<'a>),
+    Ne(Ne<'a>),
+    Or(Or<'a>),
+    Ieq(Ieq<'a>),
+    Ilt(Ilt<'a>),
+    Ine(Ine<'a>),
+    Uge(Uge<'a>),
+    Ult(Ult<'a>),
+    Not(Not<'a>),
+    Xor(Xor<'a>),
+    DEq(DEq<'a>),
+    DGe(DGe<'a>),
+    DLt(DLt<'a>),
+    DNe(DNe<'a>),
+    // Math
+    Add(Add<'a>),
+    Div(Div<'a>),
+    Dp2(Dp2<'a>),
+    Dp3(Dp3<'a>),
+    Dp4(Dp4<'a>),
+    Exp(Exp<'a>),
+    Frc(Frc<'a>),
+    IAdd(IAdd<'a>),
+    Log(Log<'a>),
+    Mad(Mad<'a>),
+    Max(Max<'a>),
+    Min(Min<'a>),
+    Mul(Mul<'a>),
+    RoundNe(RoundNe<'a>),
+    RoundNi(RoundNi<'a>),
+    RoundPi(RoundPi<'a>),
+    RoundZ(RoundZ<'a>),
+    Rsq(Rsq<'a>),
+    SinCos(SinCos<'a>),
+    Sqrt(Sqrt<'a>),
+    DerivRtx(DerivRtx<'a>),
+    DerivRty(DerivRty<'a>),
+    Imad(Imad<'a>),
+    Imax(Imax<'a>),
+    Imin(Imin<'a>),
+    Imul(Imul<'a>),
+    Ineg(Ineg<'a>),
+    Ishl(Ishl<'a>),
+    Ishr(Ishr<'a>),
+    Udiv(Udiv<'a>),
+    Umad(Umad<'a>),
+    Umax(Umax<'a>),
+    Umin(Umin<'a>),
+    Umul(Umul<'a>),
+    Ushr(Ushr<'a>),
+    DAdd(DAdd<'a>),
+    DMax(DMax<'a>),
+    DMin(DMin<'a>),
+    DMul(DMul<'a>),
+    DDiv(DDiv<'a>),
+    DFma(DFma<'a>),
+    DRcp(DRcp<'a>),
+    Rcp(Rcp<'a>),
+    Bfi(Bfi<'a>),
+    UBfe(UBfe<'a>),
+    IBfe(IBfe<'a>),
+    BfRev(BfRev<'a>),
+    CountBits(CountBits<'a>),
+    FirstBitHi(FirstBitHi<'a>),
+    FirstBitLo(FirstBitLo<'a>),
+    FirstBitShi(FirstBitShi<'a>),
+    UAddC(UAddC<'a>),
+    USubB(USubB<'a>),
+    Msad(Msad<'a>),
+    DerivRtxCoarse(DerivRtxCoarse<'a>),
+    DerivRtxFine(DerivRtxFine<'a>),
+    DerivRtyCoarse(DerivRtyCoarse<'a>),
+    DerivRtyFine(DerivRtyFine<'a>),
+    // Memory
+    Mov(Mov<'a>),
+    MovC(MovC<'a>),
+    LdRaw(LdRaw<'a>),
+    LdStructured(LdStructured<'a>),
+    LdUavTyped(LdUavTyped<'a>),
+    StoreRaw(StoreRaw<'a>),
+    StoreStructured(StoreStructured<'a>),
+    StoreUavTyped(StoreUavTyped<'a>),
+    DMov(DMov<'a>),
+    DMovC(DMovC<'a>),
+    // Conversions
+    Itof(Itof<'a>),
+    Utof(Utof<'a>),
+    Ftou(Ftou<'a>),
+    Ftoi(Ftoi<'a>),
+    Dtof(Dtof<'a>),
+    Ftod(Ftod<'a>),
+    Dtoi(Dtoi<'a>),
+    Dtou(Dtou<'a>),
+    Itod(Itod<'a>),
+    Utod(Utod<'a>),
+    F32ToF16(F32ToF16<'a>),
+    F16ToF32(F16ToF32<'a>),
+    // Control flow
+    If(If<'a>),
+    Else,
+    EndIf,
+    Loop,
+    EndLoop,
+    Break,
+    BreakC(BreakC<'a>),
+    Ret,
+    Switch(Switch<'a>),
+    Case(Case<'a>),
+    Default,
+    EndSwitch,
+    Continue,
+    ContinueC(ContinueC<'a>),
+    RetC(RetC<'a>),
+    Discard(Discard<'a>),
+    Call(Call<'a>),
+    CallC(CallC<'a>),
+    Label(Label<'a>),
+    Nop,
+    // Textures
+    Sample(Sample<'a>),
+    SampleL(SampleL<'a>),
+    SampleB(SampleB<'a>),
+    SampleC(SampleC<'a>),
+    SampleCLz(SampleCLz<'a>),
+    SampleD(SampleD<'a>),
+    Ld(Ld<'a>),
+    LdMs(LdMs<'a>),
+    ResInfo(ResInfo<'a>),
+    Lod(Lod<'a>),
+    Gather4(Gather4<'a>),
+    SamplePos(SamplePos<'a>),
+    SampleInfo(SampleInfo<'a>),
+    Gather4C(Gather4C<'a>),
+    Gather4Po(Gather4Po<'a>),
+    Gather4PoC(Gather4PoC<'a>),
+    BufInfo(BufInfo<'a>),
+    EvalSnapped(EvalSnapped<'a>),
+    EvalSampleIndex(EvalSampleIndex<'a>),
+    EvalCentroid(EvalCentroid<'a>),
+    // Geometry
+    Emit,
+    Cut,
+    EmitThenCut,
+    EmitStream(EmitStream<'a>),
+    CutStream(CutStream<'a>),
+    EmitThenCutStream(EmitThenCutStream<'a>),
+    // Compute
+    Sync(SyncFlags),
+    AtomicAnd(AtomicAnd<'a>),
+    AtomicOr(AtomicOr<'a>),
+    AtomicXor(AtomicXor<'a>),
+    AtomicIAdd(AtomicIAdd<'a>),
+    AtomicIMax(AtomicIMax<'a>),
+    AtomicIMin(AtomicIMin<'a>),
+    AtomicUMax(AtomicUMax<'a>),
+    AtomicUMin(AtomicUMin<'a>),
+    AtomicCmpStore(AtomicCmpStore<'a>),
+    ImmAtomicAlloc(ImmAtomicAlloc<'a>),
+    ImmAtomicConsume(ImmAtomicConsume<'a>),
+    ImmAtomicIAdd(ImmAtomicIAdd<'a>),
+    ImmAtomicAnd(ImmAtomicAnd<'a>),
+    ImmAtomicOr(ImmAtomicOr<'a>),
+    ImmAtomicXor(ImmAtomicXor<'a>),
+    ImmAtomicExch(ImmAtomicExch<'a>),
+    ImmAtomicIMax(ImmAtomicIMax<'a>),
+    ImmAtomicIMin(ImmAtomicIMin<'a>),
+    ImmAtomicUMax(ImmAtomicUMax<'a>),
+    ImmAtomicUMin(ImmAtomicUMin<'a>),
+    ImmAtomicCmpExch(ImmAtomicCmpExch<'a>),
+    // Hull shader phases
+    HsDecls,
+    HsControlPointPhase,
+    HsForkPhase,
+    HsJoinPhase,
+    // Class linkage
+    DclFunctionBody(DclFunctionBody),
+    DclFunctionTable(DclFunctionTable),
+    DclInterface(DclInterface),
+    InterfaceCall(InterfaceCall<'a>),
+    // Custom data
+    DclImmediateConstantBuffer(DclImmediateConstantBuffer),
+    CustomData(CustomData),
+    // All others
+    Unknown(u32),
+}
+
+impl<'a> SparseInstruction<'a> {
+    /// Parses the instruction at the decoder's offset. `phase` is the phase
+    /// of the preceding instruction, which carries over unless this
+    /// instruction starts a new hull shader phase.
+    pub fn parse(
+        decoder: &mut decoder::Decoder<'a>,
+        phase: Phase,
+    ) -> Result<SparseInstruction<'a>, State> {
+        let start = decoder.get_offset();
+
+        let mut extended = DECODE_IS_D3D10_SB_OPCODE_EXTENDED(decoder.read_u32()?) != 0;
+        while extended {
+            extended = DECODE_IS_D3D10_SB_OPCODE_EXTENDED(decoder.read_u32()?) != 0;
+        }
+
+        let opcode_len = decoder.get_offset() - start;
+        let opcode = OpcodeToken0::from_tokens(decoder.seek(start)?.bytes(opcode_len)?);
+        let ty = opcode.get_opcode_type();
+        let len = opcode.get_instruction_length();
+
+        let operands = match ty {
+            // Declarations
+            D3D10_SB_OPCODE_DCL_GLOBAL_FLAGS => Operands::DclGlobalFlags(DclGlobalFlags {
+                global_flags: DECODE_D3D10_SB_GLOBAL_FLAGS(opcode.word),
+            }),
+            D3D10_SB_OPCODE_DCL_INPUT => Operands::DclInput(DclInput {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_INPUT_PS => Operands::DclInputPs(DclInputPs {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_INPUT_PS_SIV => Operands::DclInputPsSiv(DclInputPsSiv {
+                operand: OperandToken0::parse(decoder)?,
+                operand_2: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_INPUT_PS_SGV => Operands::DclInputPsSgv(DclInputPsSgv {
+                operand: OperandToken0::parse(decoder)?,
+                operand_2: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_OUTPUT => Operands::DclOutput(DclOutput {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_CONSTANT_BUFFER => Operands::DclConstantBuffer(DclConstantBuffer {
+                operand: OperandToken0::parse(decoder)?,
+                access: DECODE_D3D10_SB_CONSTANT_BUFFER_ACCESS_PATTERN(opcode.word),
+            }),
+            D3D10_SB_OPCODE_DCL_RESOURCE => Operands::DclResource(DclResource {
+                register: OperandToken0::parse(decoder)?,
+                return_type: ResourceReturnTypeToken0::from_word(decoder.read_u32()?),
+            }),
+            D3D10_SB_OPCODE_DCL_SAMPLER => Operands::DclSampler(DclSampler {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_TEMPS => Operands::DclTemps(DclTemps {
+                register_count: decoder.read_u32()?,
+            }),
+            D3D10_SB_OPCODE_DCL_INDEXABLE_TEMP => Operands::DclIndexableTemp(DclIndexableTemp {
+                register_index: decoder.read_u32()?,
+                register_count: decoder.read_u32()?,
+                num_components: decoder.read_u32()?,
+            }),
+            D3D10_SB_OPCODE_DCL_OUTPUT_SIV => Operands::DclOutputSiv(DclOutputSiv {
+                register: OperandToken0::parse(decoder)?,
+                semantic: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_OUTPUT_SGV => Operands::DclOutputSgv(DclOutputSgv {
+                operand: OperandToken0::parse(decoder)?,
+                operand_2: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_INPUT_SGV => Operands::DclInputSgv(DclInputSgv {
+                operand: OperandToken0::parse(decoder)?,
+                operand_2: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_INPUT_SIV => Operands::DclInputSiv(DclInputSiv {
+                operand: OperandToken0::parse(decoder)?,
+                operand_2: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DCL_INDEX_RANGE => Operands::DclIndexRange(DclIndexRange {
+                operand: OperandToken0::parse(decoder)?,
+                register_count: decoder.read_u32()?,
+            }),
+            D3D11_SB_OPCODE_DCL_THREAD_GROUP => Operands::DclThreadGroup(DclThreadGroup {
+                x: decoder.read_u32()?,
+                y: decoder.read_u32()?,
+                z: decoder.read_u32()?,
+            }),
+            D3D11_SB_OPCODE_DCL_THREAD_GROUP_SHARED_MEMORY_RAW => {
+                Operands::DclTgsmRaw(DclTgsmRaw {
+                    operand: OperandToken0::parse(decoder)?,
+                    byte_count: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_THREAD_GROUP_SHARED_MEMORY_STRUCTURED => {
+                Operands::DclTgsmStructured(DclTgsmStructured {
+                    operand: OperandToken0::parse(decoder)?,
+                    stride: decoder.read_u32()?,
+                    count: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_UNORDERED_ACCESS_VIEW_TYPED => Operands::DclUavTyped(DclUavTyped {
+                operand: OperandToken0::parse(decoder)?,
+                return_type: ResourceReturnTypeToken0::from_word(decoder.read_u32()?),
+            }),
+            D3D11_SB_OPCODE_DCL_UNORDERED_ACCESS_VIEW_RAW => Operands::DclUavRaw(DclUavRaw {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DCL_UNORDERED_ACCESS_VIEW_STRUCTURED => {
+                Operands::DclUavStructured(DclUavStructured {
+                    operand: OperandToken0::parse(decoder)?,
+                    stride: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_RESOURCE_RAW => Operands::DclResourceRaw(DclResourceRaw {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DCL_RESOURCE_STRUCTURED => {
+                Operands::DclResourceStructured(DclResourceStructured {
+                    operand: OperandToken0::parse(decoder)?,
+                    stride: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_INPUT_CONTROL_POINT_COUNT => {
+                Operands::DclInputControlPointCount(DclInputControlPointCount {
+                    count: DECODE_D3D11_SB_INPUT_CONTROL_POINT_COUNT(opcode.word),
+                })
+            }
+            D3D11_SB_OPCODE_DCL_OUTPUT_CONTROL_POINT_COUNT => {
+                Operands::DclOutputControlPointCount(DclOutputControlPointCount {
+                    count: DECODE_D3D11_SB_OUTPUT_CONTROL_POINT_COUNT(opcode.word),
+                })
+            }
+            D3D11_SB_OPCODE_DCL_TESS_DOMAIN => {
+                Operands::DclTessellatorDomain(DclTessellatorDomain {
+                    domain: TessellatorDomain::from_int(DECODE_D3D11_SB_TESS_DOMAIN(opcode.word))
+                        .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_TESS_PARTITIONING => {
+                Operands::DclTessellatorPartitioning(DclTessellatorPartitioning {
+                    partitioning: TessellatorPartitioning::from_int(
+                        DECODE_D3D11_SB_TESS_PARTITIONING(opcode.word),
+                    )
+                    .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_TESS_OUTPUT_PRIMITIVE => {
+                Operands::DclTessellatorOutputPrimitive(DclTessellatorOutputPrimitive {
+                    output_primitive: TessellatorOutputPrimitive::from_int(
+                        DECODE_D3D11_SB_TESS_OUTPUT_PRIMITIVE(opcode.word),
+                    )
+                    .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_HS_MAX_TESSFACTOR => {
+                Operands::DclHsMaxTessFactor(DclHsMaxTessFactor {
+                    max_tess_factor: f32::from_bits(decoder.read_u32()?),
+                })
+            }
+            D3D11_SB_OPCODE_DCL_HS_FORK_PHASE_INSTANCE_COUNT => {
+                Operands::DclHsForkPhaseInstanceCount(DclHsForkPhaseInstanceCount {
+                    instance_count: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_HS_JOIN_PHASE_INSTANCE_COUNT => {
+                Operands::DclHsJoinPhaseInstanceCount(DclHsJoinPhaseInstanceCount {
+                    instance_count: decoder.read_u32()?,
+                })
+            }
+            D3D10_SB_OPCODE_DCL_GS_INPUT_PRIMITIVE => {
+                Operands::DclGsInputPrimitive(DclGsInputPrimitive {
+                    primitive: Primitive::try_from(DECODE_D3D10_SB_GS_INPUT_PRIMITIVE(opcode.word))
+                        .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?,
+                })
+            }
+            D3D10_SB_OPCODE_DCL_GS_OUTPUT_PRIMITIVE_TOPOLOGY => {
+                Operands::DclGsOutputPrimitiveTopology(DclGsOutputPrimitiveTopology {
+                    topology: PrimitiveTopology::from_int(
+                        DECODE_D3D10_SB_GS_OUTPUT_PRIMITIVE_TOPOLOGY(opcode.word),
+                    )
+                    .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?,
+                })
+            }
+            D3D10_SB_OPCODE_DCL_MAX_OUTPUT_VERTEX_COUNT => {
+                Operands::DclMaxOutputVertexCount(DclMaxOutputVertexCount {
+                    count: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_GS_INSTANCE_COUNT => {
+                Operands::DclGsInstanceCount(DclGsInstanceCount {
+                    instance_count: decoder.read_u32()?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_STREAM => Operands::DclStream(DclStream {
+                operand: OperandToken0::parse(decoder)?,
+            }),
+            // Boolean
+            D3D10_SB_OPCODE_AND => Operands::And(And {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_EQ => Operands::Eq(Eq {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_GE => Operands::Ge(Ge {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_IGE => Operands::Ige(Ige {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_LT => Operands::Lt(Lt {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_NE => Operands::Ne(Ne {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_OR => Operands::Or(Or {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_IEQ => Operands::Ieq(Ieq {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_ILT => Operands::Ilt(Ilt {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_INE => Operands::Ine(Ine {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_UGE => Operands::Uge(Uge {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_ULT => Operands::Ult(Ult {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_NOT => Operands::Not(Not {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_XOR => Operands::Xor(Xor {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DEQ => Operands::DEq(DEq {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DGE => Operands::DGe(DGe {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DLT => Operands::DLt(DLt {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DNE => Operands::DNe(DNe {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             // Math
             D3D10_SB_OPCODE_ADD => Operands::Add(Add {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_DIV => Operands::Div(Div {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_DP2 => Operands::Dp2(Dp2 {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_DP3 => Operands::Dp3(Dp3 {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_DP4 => Operands::Dp4(Dp4 {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_EXP => Operands::Exp(Exp {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_FRC => Operands::Frc(Frc {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_IADD => Operands::IAdd(IAdd {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_LOG => Operands::Log(Log {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_MAD => Operands::Mad(Mad {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
-                c: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_MAX => Operands::Max(Max {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_MIN => Operands::Min(Min {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_MUL => Operands::Mul(Mul {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_ROUND_NE => Operands::RoundNe(RoundNe {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_ROUND_NI => Operands::RoundNi(RoundNi {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_ROUND_PI => Operands::RoundPi(RoundPi {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_ROUND_Z => Operands::RoundZ(RoundZ {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_RSQ => Operands::Rsq(Rsq {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_SINCOS => Operands::SinCos(SinCos {
-                dst_sin: OperandToken0::parse(decoder),
-                dst_cos: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst_sin: OperandToken0::parse(decoder)?,
+                dst_cos: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_SQRT => Operands::Sqrt(Sqrt {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DERIV_RTX => Operands::DerivRtx(DerivRtx {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DERIV_RTY => Operands::DerivRty(DerivRty {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_IMAD => Operands::Imad(Imad {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_IMAX => Operands::Imax(Imax {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_IMIN => Operands::Imin(Imin {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_IMUL => Operands::Imul(Imul {
+                dst_hi: OperandToken0::parse(decoder)?,
+                dst_lo: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_INEG => Operands::Ineg(Ineg {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_ISHL => Operands::Ishl(Ishl {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_ISHR => Operands::Ishr(Ishr {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_UDIV => Operands::Udiv(Udiv {
+                dst_quot: OperandToken0::parse(decoder)?,
+                dst_rem: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_UMAD => Operands::Umad(Umad {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_UMAX => Operands::Umax(Umax {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_UMIN => Operands::Umin(Umin {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_UMUL => Operands::Umul(Umul {
+                dst_hi: OperandToken0::parse(decoder)?,
+                dst_lo: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_USHR => Operands::Ushr(Ushr {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DADD => Operands::DAdd(DAdd {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DMAX => Operands::DMax(DMax {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DMIN => Operands::DMin(DMin {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DMUL => Operands::DMul(DMul {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_DDIV => Operands::DDiv(DDiv {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_DFMA => Operands::DFma(DFma {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_DRCP => Operands::DRcp(DRcp {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_RCP => Operands::Rcp(Rcp {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_BFI => Operands::Bfi(Bfi {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+                d: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_UBFE => Operands::UBfe(UBfe {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IBFE => Operands::IBfe(IBfe {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_BFREV => Operands::BfRev(BfRev {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_COUNTBITS => Operands::CountBits(CountBits {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_FIRSTBIT_HI => Operands::FirstBitHi(FirstBitHi {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_FIRSTBIT_LO => Operands::FirstBitLo(FirstBitLo {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_FIRSTBIT_SHI => Operands::FirstBitShi(FirstBitShi {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_UADDC => Operands::UAddC(UAddC {
+                dst: OperandToken0::parse(decoder)?,
+                dst_carry: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_USUBB => Operands::USubB(USubB {
+                dst: OperandToken0::parse(decoder)?,
+                dst_borrow: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_MSAD => Operands::Msad(Msad {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DERIV_RTX_COARSE => Operands::DerivRtxCoarse(DerivRtxCoarse {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DERIV_RTX_FINE => Operands::DerivRtxFine(DerivRtxFine {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DERIV_RTY_COARSE => Operands::DerivRtyCoarse(DerivRtyCoarse {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DERIV_RTY_FINE => Operands::DerivRtyFine(DerivRtyFine {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             // Memory
             D3D10_SB_OPCODE_MOV => Operands::Mov(Mov {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_MOVC => Operands::MovC(MovC {
-                dst: OperandToken0::parse(decoder),
-                a: OperandToken0::parse(decoder),
-                b: OperandToken0::parse(decoder),
-                c: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_LD_RAW => Operands::LdRaw(LdRaw {
+                dst: OperandToken0::parse(decoder)?,
+                src_byte_offset: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_LD_STRUCTURED => Operands::LdStructured(LdStructured {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_byte_offset: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_LD_UAV_TYPED => Operands::LdUavTyped(LdUavTyped {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_uav: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_STORE_RAW => Operands::StoreRaw(StoreRaw {
+                dst: OperandToken0::parse(decoder)?,
+                src_byte_offset: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_STORE_STRUCTURED => Operands::StoreStructured(StoreStructured {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_byte_offset: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_STORE_UAV_TYPED => Operands::StoreUavTyped(StoreUavTyped {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DMOV => Operands::DMov(DMov {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DMOVC => Operands::DMovC(DMovC {
+                dst: OperandToken0::parse(decoder)?,
+                a: OperandToken0::parse(decoder)?,
+                b: OperandToken0::parse(decoder)?,
+                c: OperandToken0::parse(decoder)?,
             }),
             // Conversions
             D3D10_SB_OPCODE_ITOF => Operands::Itof(Itof {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_UTOF => Operands::Utof(Utof {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_FTOU => Operands::Ftou(Ftou {
-                dst: OperandToken0::parse(decoder),
-                src: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_FTOI => Operands::Ftoi(Ftoi {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_DTOF => Operands::Dtof(Dtof {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_FTOD => Operands::Ftod(Ftod {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_DTOI => Operands::Dtoi(Dtoi {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_DTOU => Operands::Dtou(Dtou {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_ITOD => Operands::Itod(Itod {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_1_SB_OPCODE_UTOD => Operands::Utod(Utod {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_F32TOF16 => Operands::F32ToF16(F32ToF16 {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_F16TOF32 => Operands::F16ToF32(F16ToF32 {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
             // Control flow
             D3D10_SB_OPCODE_IF => Operands::If(If {
-                src: OperandToken0::parse(decoder),
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_ELSE => Operands::Else,
             D3D10_SB_OPCODE_ENDIF => Operands::EndIf,
@@ -1749,33 +3732,2478 @@ impl<'a> SparseInstruction<'a> {
             D3D10_SB_OPCODE_ENDLOOP => Operands::EndLoop,
             D3D10_SB_OPCODE_BREAK => Operands::Break,
             D3D10_SB_OPCODE_BREAKC => Operands::BreakC(BreakC {
-                src: OperandToken0::parse(decoder),
+                src: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_RET => Operands::Ret,
+            D3D10_SB_OPCODE_SWITCH => Operands::Switch(Switch {
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_CASE => Operands::Case(Case {
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DEFAULT => Operands::Default,
+            D3D10_SB_OPCODE_ENDSWITCH => Operands::EndSwitch,
+            D3D10_SB_OPCODE_CONTINUE => Operands::Continue,
+            D3D10_SB_OPCODE_CONTINUEC => Operands::ContinueC(ContinueC {
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_RETC => Operands::RetC(RetC {
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_DISCARD => Operands::Discard(Discard {
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_CALL => Operands::Call(Call {
+                label: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_CALLC => Operands::CallC(CallC {
+                src: OperandToken0::parse(decoder)?,
+                label: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_LABEL => Operands::Label(Label {
+                label: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_NOP => Operands::Nop,
             // Textures
             D3D10_SB_OPCODE_SAMPLE => Operands::Sample(Sample {
-                dst: OperandToken0::parse(decoder),
-                src_address: OperandToken0::parse(decoder),
-                src_resource: OperandToken0::parse(decoder),
-                src_sampler: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
             }),
             D3D10_SB_OPCODE_SAMPLE_L => Operands::SampleL(SampleL {
-                dst: OperandToken0::parse(decoder),
-                src_address: OperandToken0::parse(decoder),
-                src_resource: OperandToken0::parse(decoder),
-                src_sampler: OperandToken0::parse(decoder),
-                src_lod: OperandToken0::parse(decoder),
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_lod: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_SAMPLE_B => Operands::SampleB(SampleB {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_bias: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_SAMPLE_C => Operands::SampleC(SampleC {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_reference: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_SAMPLE_C_LZ => Operands::SampleCLz(SampleCLz {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_reference: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_SAMPLE_D => Operands::SampleD(SampleD {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_ddx: OperandToken0::parse(decoder)?,
+                src_ddy: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_LD => Operands::Ld(Ld {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_LD_MS => Operands::LdMs(LdMs {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sample_index: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_SB_OPCODE_RESINFO => Operands::ResInfo(ResInfo {
+                dst: OperandToken0::parse(decoder)?,
+                src_mip_level: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_1_SB_OPCODE_LOD => Operands::Lod(Lod {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_1_SB_OPCODE_GATHER4 => Operands::Gather4(Gather4 {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_1_SB_OPCODE_SAMPLE_POS => Operands::SamplePos(SamplePos {
+                dst: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sample_index: OperandToken0::parse(decoder)?,
+            }),
+            D3D10_1_SB_OPCODE_SAMPLE_INFO => Operands::SampleInfo(SampleInfo {
+                dst: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_GATHER4_C => Operands::Gather4C(Gather4C {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_reference: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_GATHER4_PO => Operands::Gather4Po(Gather4Po {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_offset: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_GATHER4_PO_C => Operands::Gather4PoC(Gather4PoC {
+                dst: OperandToken0::parse(decoder)?,
+                src_address: OperandToken0::parse(decoder)?,
+                src_offset: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+                src_sampler: OperandToken0::parse(decoder)?,
+                src_reference: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_BUFINFO => Operands::BufInfo(BufInfo {
+                dst: OperandToken0::parse(decoder)?,
+                src_resource: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_EVAL_SNAPPED => Operands::EvalSnapped(EvalSnapped {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+                src_offset: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_EVAL_SAMPLE_INDEX => Operands::EvalSampleIndex(EvalSampleIndex {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+                src_sample_index: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_EVAL_CENTROID => Operands::EvalCentroid(EvalCentroid {
+                dst: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
             }),
+            // Geometry
+            D3D10_SB_OPCODE_EMIT => Operands::Emit,
+            D3D10_SB_OPCODE_CUT => Operands::Cut,
+            D3D10_SB_OPCODE_EMITTHENCUT => Operands::EmitThenCut,
+            D3D11_SB_OPCODE_EMIT_STREAM => Operands::EmitStream(EmitStream {
+                stream: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_CUT_STREAM => Operands::CutStream(CutStream {
+                stream: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_EMITTHENCUT_STREAM => Operands::EmitThenCutStream(EmitThenCutStream {
+                stream: OperandToken0::parse(decoder)?,
+            }),
+            // Compute
+            D3D11_SB_OPCODE_SYNC => Operands::Sync(SyncFlags::from_bits_truncate(
+                DECODE_D3D11_SB_SYNC_FLAGS(opcode.word),
+            )),
+            D3D11_SB_OPCODE_ATOMIC_AND => Operands::AtomicAnd(AtomicAnd {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_OR => Operands::AtomicOr(AtomicOr {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_XOR => Operands::AtomicXor(AtomicXor {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_IADD => Operands::AtomicIAdd(AtomicIAdd {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_IMAX => Operands::AtomicIMax(AtomicIMax {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_IMIN => Operands::AtomicIMin(AtomicIMin {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_UMAX => Operands::AtomicUMax(AtomicUMax {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_UMIN => Operands::AtomicUMin(AtomicUMin {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_ATOMIC_CMP_STORE => Operands::AtomicCmpStore(AtomicCmpStore {
+                dst: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src_compare: OperandToken0::parse(decoder)?,
+                src_value: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_ALLOC => Operands::ImmAtomicAlloc(ImmAtomicAlloc {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_CONSUME => Operands::ImmAtomicConsume(ImmAtomicConsume {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_IADD => Operands::ImmAtomicIAdd(ImmAtomicIAdd {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_AND => Operands::ImmAtomicAnd(ImmAtomicAnd {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_OR => Operands::ImmAtomicOr(ImmAtomicOr {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_XOR => Operands::ImmAtomicXor(ImmAtomicXor {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_EXCH => Operands::ImmAtomicExch(ImmAtomicExch {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_IMAX => Operands::ImmAtomicIMax(ImmAtomicIMax {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_IMIN => Operands::ImmAtomicIMin(ImmAtomicIMin {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_UMAX => Operands::ImmAtomicUMax(ImmAtomicUMax {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_UMIN => Operands::ImmAtomicUMin(ImmAtomicUMin {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src: OperandToken0::parse(decoder)?,
+            }),
+            D3D11_SB_OPCODE_IMM_ATOMIC_CMP_EXCH => Operands::ImmAtomicCmpExch(ImmAtomicCmpExch {
+                dst: OperandToken0::parse(decoder)?,
+                dst_uav: OperandToken0::parse(decoder)?,
+                dst_address: OperandToken0::parse(decoder)?,
+                src_compare: OperandToken0::parse(decoder)?,
+                src_value: OperandToken0::parse(decoder)?,
+            }),
+            // Hull shader phases
+            D3D11_SB_OPCODE_HS_DECLS => Operands::HsDecls,
+            D3D11_SB_OPCODE_HS_CONTROL_POINT_PHASE => Operands::HsControlPointPhase,
+            D3D11_SB_OPCODE_HS_FORK_PHASE => Operands::HsForkPhase,
+            D3D11_SB_OPCODE_HS_JOIN_PHASE => Operands::HsJoinPhase,
+            // Class linkage
+            D3D11_SB_OPCODE_DCL_FUNCTION_BODY => Operands::DclFunctionBody(DclFunctionBody {
+                index: decoder.read_u32()?,
+            }),
+            D3D11_SB_OPCODE_DCL_FUNCTION_TABLE => {
+                let index = decoder.read_u32()?;
+                let body_count = decoder.read_u32()?;
+
+                Operands::DclFunctionTable(DclFunctionTable {
+                    index,
+                    bodies: decoder.words(body_count as usize)?,
+                })
+            }
+            D3D11_SB_OPCODE_DCL_INTERFACE => {
+                let index = decoder.read_u32()?;
+                let body_count = decoder.read_u32()?;
+                let lengths = decoder.read_u32()?;
+
+                Operands::DclInterface(DclInterface {
+                    index,
+                    dynamically_indexed: DECODE_D3D11_SB_INTERFACE_INDEXED_BIT(opcode.word) != 0,
+                    body_count,
+                    array_length: DECODE_D3D11_SB_INTERFACE_ARRAY_LENGTH(lengths),
+                    tables: decoder
+                        .words(DECODE_D3D11_SB_INTERFACE_TABLE_LENGTH(lengths) as usize)?,
+                })
+            }
+            D3D11_SB_OPCODE_INTERFACE_CALL => Operands::InterfaceCall(InterfaceCall {
+                function_index: decoder.read_u32()?,
+                interface: OperandToken0::parse(decoder)?,
+            }),
+            // Custom data
+            D3D10_SB_OPCODE_CUSTOMDATA => {
+                // the opcode token holds the class, and the next token the length
+                let len = decoder.read_u32()? as usize;
+                let data = decoder.words(len.checked_sub(2).ok_or(State::ChunkIncorrect)?)?;
+
+                let class =
+                    CustomDataClass::from_int(DECODE_D3D10_SB_CUSTOMDATA_CLASS(opcode.word))
+                        .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?;
+
+                match class {
+                    CustomDataClass::ImmediateConstantBuffer => {
+                        if data.len() % 4 != 0 {
+                            return Err(State::ChunkIncorrect);
+                        }
+
+                        Operands::DclImmediateConstantBuffer(DclImmediateConstantBuffer {
+                            constants: data
+                                .chunks_exact(4)
+                                .map(|c| [c[0], c[1], c[2], c[3]])
+                                .collect(),
+                        })
+                    }
+                    _ => Operands::CustomData(CustomData { class, data }),
+                }
+            }
             // All others
             _ => {
-                if len > 1 {
-                    decoder.skip(4 * (len as usize - 1));
+                let consumed = decoder.get_offset() - start;
+                let len = 4 * len as usize;
+                if len > consumed {
+                    decoder.skip(len - consumed)?;
+                }
+
+                Operands::Unknown(ty)
+            }
+        };
+
+        let len = decoder.get_offset() - start;
+
+        // only customdata has a length that doesn't fit in the opcode token
+        if ty != D3D10_SB_OPCODE_CUSTOMDATA && len != 4 * opcode.get_instruction_length() as usize {
+            return Err(State::DecoderError(Error::InstructionLengthMismatch(start)));
+        }
+
+        let opcode = OpcodeToken0::from_tokens(decoder.seek(start)?.bytes(len)?);
+
+        let phase = match operands {
+            Operands::HsDecls => Phase::Declarations,
+            Operands::HsControlPointPhase => Phase::ControlPoint,
+            Operands::HsForkPhase => Phase::Fork,
+            Operands::HsJoinPhase => Phase::Join,
+            _ => phase,
+        };
+
+        Ok(SparseInstruction {
+            opcode,
+            operands,
+            phase,
+        })
+    }
+
+    /// Decodes the instruction into an owned `Instruction`.
+    pub fn to_instruction(&self) -> Result<Instruction, State> {
+        Instruction::parse(&mut decoder::Decoder::new(self.opcode.tokens))
+    }
+}
+
+// Owned IR
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum Opcode {
+    Add = 0,
+    And = 1,
+    Break = 2,
+    BreakC = 3,
+    Call = 4,
+    CallC = 5,
+    Case = 6,
+    Continue = 7,
+    ContinueC = 8,
+    Cut = 9,
+    Default = 10,
+    DerivRtx = 11,
+    DerivRty = 12,
+    Discard = 13,
+    Div = 14,
+    Dp2 = 15,
+    Dp3 = 16,
+    Dp4 = 17,
+    Else = 18,
+    Emit = 19,
+    EmitThenCut = 20,
+    EndIf = 21,
+    EndLoop = 22,
+    EndSwitch = 23,
+    Eq = 24,
+    Exp = 25,
+    Frc = 26,
+    Ftoi = 27,
+    Ftou = 28,
+    Ge = 29,
+    IAdd = 30,
+    If = 31,
+    IEq = 32,
+    IGe = 33,
+    ILt = 34,
+    IMad = 35,
+    IMax = 36,
+    IMin = 37,
+    IMul = 38,
+    INe = 39,
+    INeg = 40,
+    IShl = 41,
+    IShr = 42,
+    Itof = 43,
+    Label = 44,
+    Ld = 45,
+    LdMs = 46,
+    Log = 47,
+    Loop = 48,
+    Lt = 49,
+    Mad = 50,
+    Min = 51,
+    Max = 52,
+    CustomData = 53,
+    Mov = 54,
+    MovC = 55,
+    Mul = 56,
+    Ne = 57,
+    Nop = 58,
+    Not = 59,
+    Or = 60,
+    ResInfo = 61,
+    Ret = 62,
+    RetC = 63,
+    RoundNe = 64,
+    RoundNi = 65,
+    RoundPi = 66,
+    RoundZ = 67,
+    Rsq = 68,
+    Sample = 69,
+    SampleC = 70,
+    SampleCLz = 71,
+    SampleL = 72,
+    SampleD = 73,
+    SampleB = 74,
+    Sqrt = 75,
+    Switch = 76,
+    SinCos = 77,
+    UDiv = 78,
+    ULt = 79,
+    UGe = 80,
+    UMul = 81,
+    UMad = 82,
+    UMax = 83,
+    UMin = 84,
+    UShr = 85,
+    Utof = 86,
+    Xor = 87,
+    DclResource = 88,
+    DclConstantBuffer = 89,
+    DclSampler = 90,
+    DclIndexRange = 91,
+    DclGsOutputPrimitiveTopology = 92,
+    DclGsInputPrimitive = 93,
+    DclMaxOutputVertexCount = 94,
+    DclInput = 95,
+    DclInputSgv = 96,
+    DclInputSiv = 97,
+    DclInputPs = 98,
+    DclInputPsSgv = 99,
+    DclInputPsSiv = 100,
+    DclOutput = 101,
+    DclOutputSgv = 102,
+    DclOutputSiv = 103,
+    DclTemps = 104,
+    DclIndexableTemp = 105,
+    DclGlobalFlags = 106,
+    Lod = 108,
+    Gather4 = 109,
+    SamplePos = 110,
+    SampleInfo = 111,
+    HsDecls = 113,
+    HsControlPointPhase = 114,
+    HsForkPhase = 115,
+    HsJoinPhase = 116,
+    EmitStream = 117,
+    CutStream = 118,
+    EmitThenCutStream = 119,
+    InterfaceCall = 120,
+    BufInfo = 121,
+    DerivRtxCoarse = 122,
+    DerivRtxFine = 123,
+    DerivRtyCoarse = 124,
+    DerivRtyFine = 125,
+    Gather4C = 126,
+    Gather4Po = 127,
+    Gather4PoC = 128,
+    Rcp = 129,
+    F32ToF16 = 130,
+    F16ToF32 = 131,
+    UAddC = 132,
+    USubB = 133,
+    CountBits = 134,
+    FirstBitHi = 135,
+    FirstBitLo = 136,
+    FirstBitShi = 137,
+    UBfe = 138,
+    IBfe = 139,
+    Bfi = 140,
+    BfRev = 141,
+    SwapC = 142,
+    DclStream = 143,
+    DclFunctionBody = 144,
+    DclFunctionTable = 145,
+    DclInterface = 146,
+    DclInputControlPointCount = 147,
+    DclOutputControlPointCount = 148,
+    DclTessDomain = 149,
+    DclTessPartitioning = 150,
+    DclTessOutputPrimitive = 151,
+    DclHsMaxTessFactor = 152,
+    DclHsForkPhaseInstanceCount = 153,
+    DclHsJoinPhaseInstanceCount = 154,
+    DclThreadGroup = 155,
+    DclUavTyped = 156,
+    DclUavRaw = 157,
+    DclUavStructured = 158,
+    DclTgsmRaw = 159,
+    DclTgsmStructured = 160,
+    DclResourceRaw = 161,
+    DclResourceStructured = 162,
+    LdUavTyped = 163,
+    StoreUavTyped = 164,
+    LdRaw = 165,
+    StoreRaw = 166,
+    LdStructured = 167,
+    StoreStructured = 168,
+    AtomicAnd = 169,
+    AtomicOr = 170,
+    AtomicXor = 171,
+    AtomicCmpStore = 172,
+    AtomicIAdd = 173,
+    AtomicIMax = 174,
+    AtomicIMin = 175,
+    AtomicUMax = 176,
+    AtomicUMin = 177,
+    ImmAtomicAlloc = 178,
+    ImmAtomicConsume = 179,
+    ImmAtomicIAdd = 180,
+    ImmAtomicAnd = 181,
+    ImmAtomicOr = 182,
+    ImmAtomicXor = 183,
+    ImmAtomicExch = 184,
+    ImmAtomicCmpExch = 185,
+    ImmAtomicIMax = 186,
+    ImmAtomicIMin = 187,
+    ImmAtomicUMax = 188,
+    ImmAtomicUMin = 189,
+    Sync = 190,
+    DAdd = 191,
+    DMax = 192,
+    DMin = 193,
+    DMul = 194,
+    DEq = 195,
+    DGe = 196,
+    DLt = 197,
+    DNe = 198,
+    DMov = 199,
+    DMovC = 200,
+    Dtof = 201,
+    Ftod = 202,
+    EvalSnapped = 203,
+    EvalSampleIndex = 204,
+    EvalCentroid = 205,
+    DclGsInstanceCount = 206,
+    Abort = 207,
+    DebugBreak = 208,
+    DDiv = 210,
+    DFma = 211,
+    DRcp = 212,
+    Msad = 213,
+    Dtoi = 214,
+    Dtou = 215,
+    Itod = 216,
+    Utod = 217,
+}
+
+impl Opcode {
+    pub fn is_declaration(self) -> bool {
+        use self::Opcode::*;
+
+        matches!(
+            self,
+            DclResource
+                | DclConstantBuffer
+                | DclSampler
+                | DclIndexRange
+                | DclGsOutputPrimitiveTopology
+                | DclGsInputPrimitive
+                | DclMaxOutputVertexCount
+                | DclInput
+                | DclInputSgv
+                | DclInputSiv
+                | DclInputPs
+                | DclInputPsSgv
+                | DclInputPsSiv
+                | DclOutput
+                | DclOutputSgv
+                | DclOutputSiv
+                | DclTemps
+                | DclIndexableTemp
+                | DclGlobalFlags
+                | DclStream
+                | DclFunctionBody
+                | DclFunctionTable
+                | DclInterface
+                | DclInputControlPointCount
+                | DclOutputControlPointCount
+                | DclTessDomain
+                | DclTessPartitioning
+                | DclTessOutputPrimitive
+                | DclHsMaxTessFactor
+                | DclHsForkPhaseInstanceCount
+                | DclHsJoinPhaseInstanceCount
+                | DclThreadGroup
+                | DclUavTyped
+                | DclUavRaw
+                | DclUavStructured
+                | DclTgsmRaw
+                | DclTgsmStructured
+                | DclResourceRaw
+                | DclResourceStructured
+                | DclGsInstanceCount
+        )
+    }
+}
+
+/// A decoded extended opcode token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpcodeExtension {
+    /// Immediate texel offsets for the u, v and w coordinates.
+    SampleControls([i8; 3]),
+    ResourceDim {
+        dimension: ResourceDimension,
+        stride: u32,
+    },
+    ResourceReturnType([ResourceReturnType; 4]),
+}
+
+impl OpcodeExtension {
+    fn from_word(word: u32) -> Option<Self> {
+        match DECODE_D3D10_SB_EXTENDED_OPCODE_TYPE(word) {
+            D3D10_SB_EXTENDED_OPCODE_SAMPLE_CONTROLS => {
+                Some(OpcodeExtension::SampleControls(decode_texel_offsets(word)))
+            }
+            D3D11_SB_EXTENDED_OPCODE_RESOURCE_DIM => Some(OpcodeExtension::ResourceDim {
+                dimension: ResourceDimension::from_int(
+                    DECODE_D3D11_SB_EXTENDED_RESOURCE_DIMENSION(word),
+                )
+                .ok()?,
+                stride: DECODE_D3D11_SB_EXTENDED_RESOURCE_DIMENSION_STRUCTURE_STRIDE(word),
+            }),
+            D3D11_SB_EXTENDED_OPCODE_RESOURCE_RETURN_TYPE => {
+                let return_type = |component| {
+                    ResourceReturnType::from_int(DECODE_D3D11_SB_EXTENDED_RESOURCE_RETURN_TYPE(
+                        word, component,
+                    ))
+                    .ok()
+                };
+
+                Some(OpcodeExtension::ResourceReturnType([
+                    return_type(0)?,
+                    return_type(1)?,
+                    return_type(2)?,
+                    return_type(3)?,
+                ]))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntEnum)]
+pub enum MinPrecision {
+    Default = 0,
+    Float16 = 1,
+    Float2_8 = 2,
+    SInt16 = 4,
+    UInt16 = 5,
+}
+
+/// Marks an operand index as non-uniform across the invocations of a wave
+/// (SM 5.1).
+pub(crate) const OPERAND_NON_UNIFORM_MASK: u32 = 0x0002_0000;
+
+/// The deepest a relative address may nest inside another one.
+const MAX_RELATIVE_DEPTH: u32 = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OperandComponents {
+    Zero,
+    One,
+    Mask(ComponentMask),
+    Swizzle(ComponentSwizzle),
+    Select1(ComponentName),
+    /// The component count stored in bits 4 to 11 of the operand token.
+    N(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OperandIndex {
+    Immediate32(u32),
+    Immediate64(u64),
+    Relative(Box<Operand>),
+    Immediate32PlusRelative(u32, Box<Operand>),
+    Immediate64PlusRelative(u64, Box<Operand>),
+}
+
+/// A fully decoded operand, the owned counterpart of `OperandToken0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operand {
+    pub ty: OperandType,
+    pub components: OperandComponents,
+    pub modifier: OperandModifier,
+    pub min_precision: MinPrecision,
+    pub non_uniform: bool,
+    /// One entry per index dimension, e.g. `cb0[1]` has two.
+    pub indices: Vec<OperandIndex>,
+    /// The raw dwords of an `Immediate32` or `Immediate64` operand. 64-bit
+    /// values take two dwords, low dword first.
+    pub immediates: Vec<u32>,
+}
+
+impl Operand {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Operand, State> {
+        Operand::parse_nested(decoder, 0)
+    }
+
+    fn parse_nested(decoder: &mut decoder::Decoder, depth: u32) -> Result<Operand, State> {
+        if depth > MAX_RELATIVE_DEPTH {
+            return Err(State::ChunkIncorrect);
+        }
+
+        let offset = decoder.get_offset();
+        let token = decoder.read_u32()?;
+        let invalid = || State::DecoderError(Error::DecodeEnumFailed(offset));
+
+        let ty =
+            OperandType::from_int(DECODE_D3D10_SB_OPERAND_TYPE(token)).map_err(|_| invalid())?;
+
+        let components = match DECODE_D3D10_SB_OPERAND_NUM_COMPONENTS(token) {
+            D3D10_SB_OPERAND_0_COMPONENT => OperandComponents::Zero,
+            D3D10_SB_OPERAND_1_COMPONENT => OperandComponents::One,
+            D3D10_SB_OPERAND_4_COMPONENT => {
+                match DECODE_D3D10_SB_OPERAND_4_COMPONENT_SELECTION_MODE(token) {
+                    D3D10_SB_OPERAND_4_COMPONENT_MASK_MODE => {
+                        OperandComponents::Mask(ComponentMask::from_bits_truncate(
+                            DECODE_D3D10_SB_OPERAND_4_COMPONENT_MASK(token),
+                        ))
+                    }
+                    D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_MODE => {
+                        let source = |component| {
+                            ComponentName::from_word(
+                                DECODE_D3D10_SB_OPERAND_4_COMPONENT_SWIZZLE_SOURCE(token, component)
+                                    as u8,
+                            )
+                        };
+
+                        OperandComponents::Swizzle(ComponentSwizzle(
+                            source(D3D10_SB_4_COMPONENT_X),
+                            source(D3D10_SB_4_COMPONENT_Y),
+                            source(D3D10_SB_4_COMPONENT_Z),
+                            source(D3D10_SB_4_COMPONENT_W),
+                        ))
+                    }
+                    D3D10_SB_OPERAND_4_COMPONENT_SELECT_1_MODE => {
+                        OperandComponents::Select1(ComponentName::from_word(
+                            DECODE_D3D10_SB_OPERAND_4_COMPONENT_SELECT_1(token) as u8,
+                        ))
+                    }
+                    _ => return Err(invalid()),
                 }
+            }
+            _ => OperandComponents::N((token >> 4) & 0xff),
+        };
 
-                Operands::Unknown(ty)
+        let mut operand = Operand {
+            ty,
+            components,
+            modifier: OperandModifier::None,
+            min_precision: MinPrecision::Default,
+            non_uniform: false,
+            indices: Vec::new(),
+            immediates: Vec::new(),
+        };
+
+        let mut extended = DECODE_IS_D3D10_SB_OPERAND_EXTENDED(token) != 0;
+        while extended {
+            let offset = decoder.get_offset();
+            let word = decoder.read_u32()?;
+
+            if DECODE_D3D10_SB_EXTENDED_OPERAND_TYPE(word) == D3D10_SB_EXTENDED_OPERAND_MODIFIER {
+                let invalid = || State::DecoderError(Error::DecodeEnumFailed(offset));
+
+                operand.modifier =
+                    OperandModifier::from_int(DECODE_D3D10_SB_OPERAND_MODIFIER(word))
+                        .map_err(|_| invalid())?;
+                operand.min_precision =
+                    MinPrecision::from_int(DECODE_D3D11_SB_OPERAND_MIN_PRECISION(word))
+                        .map_err(|_| invalid())?;
+                operand.non_uniform = word & OPERAND_NON_UNIFORM_MASK != 0;
+            }
+
+            extended = DECODE_IS_D3D10_SB_OPERAND_DOUBLE_EXTENDED(word) != 0;
+        }
+
+        let dimension = DECODE_D3D10_SB_OPERAND_INDEX_DIMENSION(token);
+        if dimension == D3D10_SB_OPERAND_INDEX_0D {
+            let count = match (ty, components) {
+                (OperandType::Immediate32, OperandComponents::One) => 1,
+                (OperandType::Immediate32, OperandComponents::Mask(_))
+                | (OperandType::Immediate32, OperandComponents::Swizzle(_))
+                | (OperandType::Immediate32, OperandComponents::Select1(_)) => 4,
+                // a double vector only has room for two values
+                (OperandType::Immediate64, OperandComponents::One) => 2,
+                (OperandType::Immediate64, OperandComponents::Mask(_))
+                | (OperandType::Immediate64, OperandComponents::Swizzle(_))
+                | (OperandType::Immediate64, OperandComponents::Select1(_)) => 4,
+                (OperandType::Immediate32, _) | (OperandType::Immediate64, _) => {
+                    return Err(invalid())
+                }
+                _ => 0,
+            };
+
+            operand.immediates = decoder.words(count)?;
+        }
+
+        for i in 0..dimension {
+            let index = match DECODE_D3D10_SB_OPERAND_INDEX_REPRESENTATION(i, token) {
+                D3D10_SB_OPERAND_INDEX_IMMEDIATE32 => {
+                    OperandIndex::Immediate32(decoder.read_u32()?)
+                }
+                D3D10_SB_OPERAND_INDEX_IMMEDIATE64 => {
+                    OperandIndex::Immediate64(decoder.read_u64()?)
+                }
+                D3D10_SB_OPERAND_INDEX_RELATIVE => {
+                    OperandIndex::Relative(Box::new(Operand::parse_nested(decoder, depth + 1)?))
+                }
+                D3D10_SB_OPERAND_INDEX_IMMEDIATE32_PLUS_RELATIVE => {
+                    OperandIndex::Immediate32PlusRelative(
+                        decoder.read_u32()?,
+                        Box::new(Operand::parse_nested(decoder, depth + 1)?),
+                    )
+                }
+                D3D10_SB_OPERAND_INDEX_IMMEDIATE64_PLUS_RELATIVE => {
+                    OperandIndex::Immediate64PlusRelative(
+                        decoder.read_u64()?,
+                        Box::new(Operand::parse_nested(decoder, depth + 1)?),
+                    )
+                }
+                _ => return Err(invalid()),
+            };
+
+            operand.indices.push(index);
+        }
+
+        Ok(operand)
+    }
+}
+
+/// A decoded `dcl_*` instruction, with the same variants and fields as the
+/// builder's `Dcl*` instructions.
+///
+/// Shader model 5.1 gives resource registers a lower and upper bound as
+/// well, like `T0[0:3]`, and follows them with the register space and, for
+/// constant buffers, the size. Those are `None` for earlier models.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Declaration {
+    GlobalFlags {
+        flags: GlobalFlags,
+    },
+    Temps {
+        count: u32,
+    },
+    IndexableTemp {
+        register: u32,
+        count: u32,
+        components: u32,
+    },
+    Input {
+        register: Operand,
+    },
+    InputSgv {
+        register: Operand,
+        semantic: Semantic,
+    },
+    InputSiv {
+        register: Operand,
+        semantic: Semantic,
+    },
+    InputPs {
+        register: Operand,
+        interpolation: InterpolationMode,
+    },
+    InputPsSgv {
+        register: Operand,
+        interpolation: InterpolationMode,
+        semantic: Semantic,
+    },
+    InputPsSiv {
+        register: Operand,
+        interpolation: InterpolationMode,
+        semantic: Semantic,
+    },
+    Output {
+        register: Operand,
+    },
+    OutputSgv {
+        register: Operand,
+        semantic: Semantic,
+    },
+    OutputSiv {
+        register: Operand,
+        semantic: Semantic,
+    },
+    IndexRange {
+        register: Operand,
+        count: u32,
+    },
+    ConstantBuffer {
+        register: Operand,
+        access: ConstantBufferIndexPattern,
+        /// The size in vectors.
+        size: Option<u32>,
+        space: Option<u32>,
+    },
+    Sampler {
+        register: Operand,
+        mode: SamplerMode,
+        space: Option<u32>,
+    },
+    Resource {
+        register: Operand,
+        dimension: ResourceDimension,
+        return_type: [ResourceReturnType; 4],
+        /// Only used by multisampled textures.
+        sample_count: u32,
+        space: Option<u32>,
+    },
+    ResourceRaw {
+        register: Operand,
+        space: Option<u32>,
+    },
+    ResourceStructured {
+        register: Operand,
+        stride: u32,
+        space: Option<u32>,
+    },
+    UavTyped {
+        register: Operand,
+        dimension: ResourceDimension,
+        return_type: [ResourceReturnType; 4],
+        globally_coherent: bool,
+        space: Option<u32>,
+    },
+    UavRaw {
+        register: Operand,
+        globally_coherent: bool,
+        space: Option<u32>,
+    },
+    UavStructured {
+        register: Operand,
+        stride: u32,
+        globally_coherent: bool,
+        has_counter: bool,
+        space: Option<u32>,
+    },
+    TgsmRaw {
+        register: Operand,
+        /// The size in bytes.
+        count: u32,
+    },
+    TgsmStructured {
+        register: Operand,
+        stride: u32,
+        count: u32,
+    },
+    ThreadGroup {
+        x: u32,
+        y: u32,
+        z: u32,
+    },
+    GsInputPrimitive {
+        primitive: Primitive,
+    },
+    GsOutputPrimitiveTopology {
+        topology: PrimitiveTopology,
+    },
+    MaxOutputVertexCount {
+        count: u32,
+    },
+    GsInstanceCount {
+        count: u32,
+    },
+    Stream {
+        register: Operand,
+    },
+    InputControlPointCount {
+        count: u32,
+    },
+    OutputControlPointCount {
+        count: u32,
+    },
+    TessellatorDomain {
+        domain: TessellatorDomain,
+    },
+    TessellatorPartitioning {
+        partitioning: TessellatorPartitioning,
+    },
+    TessellatorOutputPrimitive {
+        primitive: TessellatorOutputPrimitive,
+    },
+    HsMaxTessFactor {
+        factor: f32,
+    },
+    HsForkPhaseInstanceCount {
+        count: u32,
+    },
+    HsJoinPhaseInstanceCount {
+        count: u32,
+    },
+    FunctionBody {
+        index: u32,
+    },
+    FunctionTable {
+        index: u32,
+        bodies: Vec<u32>,
+    },
+    Interface {
+        index: u32,
+        dynamically_indexed: bool,
+        body_count: u32,
+        array_length: u32,
+        tables: Vec<u32>,
+    },
+}
+
+impl Declaration {
+    /// Decodes the declaration that `token`, the opcode token at `offset`,
+    /// starts. `decoder` is positioned after the opcode token.
+    fn parse(
+        opcode: Opcode,
+        token: u32,
+        offset: usize,
+        decoder: &mut decoder::Decoder,
+    ) -> Result<Declaration, State> {
+        let invalid = || State::DecoderError(Error::DecodeEnumFailed(offset));
+        let globally_coherent =
+            DECODE_D3D11_SB_ACCESS_COHERENCY_FLAGS(token) & D3D11_SB_GLOBALLY_COHERENT_ACCESS != 0;
+        let interpolation = || {
+            InterpolationMode::from_int(DECODE_D3D10_SB_INPUT_INTERPOLATION_MODE(token))
+                .map_err(|_| invalid())
+        };
+        let dimension = || {
+            ResourceDimension::from_int(DECODE_D3D10_SB_RESOURCE_DIMENSION(token))
+                .map_err(|_| invalid())
+        };
+
+        let declaration = match opcode {
+            Opcode::DclGlobalFlags => Declaration::GlobalFlags {
+                flags: GlobalFlags::from_bits_truncate(DECODE_D3D10_SB_GLOBAL_FLAGS(token)),
+            },
+            Opcode::DclTemps => Declaration::Temps {
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclIndexableTemp => Declaration::IndexableTemp {
+                register: decoder.read_u32()?,
+                count: decoder.read_u32()?,
+                components: decoder.read_u32()?,
+            },
+            Opcode::DclInput => Declaration::Input {
+                register: Operand::parse(decoder)?,
+            },
+            Opcode::DclInputSgv => Declaration::InputSgv {
+                register: Operand::parse(decoder)?,
+                semantic: read_semantic(decoder)?,
+            },
+            Opcode::DclInputSiv => Declaration::InputSiv {
+                register: Operand::parse(decoder)?,
+                semantic: read_semantic(decoder)?,
+            },
+            Opcode::DclInputPs => Declaration::InputPs {
+                register: Operand::parse(decoder)?,
+                interpolation: interpolation()?,
+            },
+            Opcode::DclInputPsSgv => Declaration::InputPsSgv {
+                register: Operand::parse(decoder)?,
+                interpolation: interpolation()?,
+                semantic: read_semantic(decoder)?,
+            },
+            Opcode::DclInputPsSiv => Declaration::InputPsSiv {
+                register: Operand::parse(decoder)?,
+                interpolation: interpolation()?,
+                semantic: read_semantic(decoder)?,
+            },
+            Opcode::DclOutput => Declaration::Output {
+                register: Operand::parse(decoder)?,
+            },
+            Opcode::DclOutputSgv => Declaration::OutputSgv {
+                register: Operand::parse(decoder)?,
+                semantic: read_semantic(decoder)?,
+            },
+            Opcode::DclOutputSiv => Declaration::OutputSiv {
+                register: Operand::parse(decoder)?,
+                semantic: read_semantic(decoder)?,
+            },
+            Opcode::DclIndexRange => Declaration::IndexRange {
+                register: Operand::parse(decoder)?,
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclConstantBuffer => {
+                let register = Operand::parse(decoder)?;
+                let size = read_range_dword(decoder, &register)?;
+
+                Declaration::ConstantBuffer {
+                    access: ConstantBufferIndexPattern::from_int(
+                        DECODE_D3D10_SB_CONSTANT_BUFFER_ACCESS_PATTERN(token),
+                    )
+                    .map_err(|_| invalid())?,
+                    size,
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclSampler => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::Sampler {
+                    mode: SamplerMode::from_int(DECODE_D3D10_SB_SAMPLER_MODE(token))
+                        .map_err(|_| invalid())?,
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclResource => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::Resource {
+                    dimension: dimension()?,
+                    return_type: read_return_type(decoder)?,
+                    sample_count: DECODE_D3D10_SB_RESOURCE_SAMPLE_COUNT(token),
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclResourceRaw => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::ResourceRaw {
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclResourceStructured => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::ResourceStructured {
+                    stride: decoder.read_u32()?,
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclUavTyped => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::UavTyped {
+                    dimension: dimension()?,
+                    return_type: read_return_type(decoder)?,
+                    globally_coherent,
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclUavRaw => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::UavRaw {
+                    globally_coherent,
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclUavStructured => {
+                let register = Operand::parse(decoder)?;
+
+                Declaration::UavStructured {
+                    stride: decoder.read_u32()?,
+                    globally_coherent,
+                    has_counter: DECODE_D3D11_SB_UAV_FLAGS(token)
+                        & D3D11_SB_UAV_HAS_ORDER_PRESERVING_COUNTER
+                        != 0,
+                    space: read_range_dword(decoder, &register)?,
+                    register,
+                }
+            }
+            Opcode::DclTgsmRaw => Declaration::TgsmRaw {
+                register: Operand::parse(decoder)?,
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclTgsmStructured => Declaration::TgsmStructured {
+                register: Operand::parse(decoder)?,
+                stride: decoder.read_u32()?,
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclThreadGroup => Declaration::ThreadGroup {
+                x: decoder.read_u32()?,
+                y: decoder.read_u32()?,
+                z: decoder.read_u32()?,
+            },
+            Opcode::DclGsInputPrimitive => Declaration::GsInputPrimitive {
+                primitive: Primitive::try_from(DECODE_D3D10_SB_GS_INPUT_PRIMITIVE(token))
+                    .map_err(|_| invalid())?,
+            },
+            Opcode::DclGsOutputPrimitiveTopology => Declaration::GsOutputPrimitiveTopology {
+                topology: PrimitiveTopology::from_int(
+                    DECODE_D3D10_SB_GS_OUTPUT_PRIMITIVE_TOPOLOGY(token),
+                )
+                .map_err(|_| invalid())?,
+            },
+            Opcode::DclMaxOutputVertexCount => Declaration::MaxOutputVertexCount {
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclGsInstanceCount => Declaration::GsInstanceCount {
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclStream => Declaration::Stream {
+                register: Operand::parse(decoder)?,
+            },
+            Opcode::DclInputControlPointCount => Declaration::InputControlPointCount {
+                count: DECODE_D3D11_SB_INPUT_CONTROL_POINT_COUNT(token),
+            },
+            Opcode::DclOutputControlPointCount => Declaration::OutputControlPointCount {
+                count: DECODE_D3D11_SB_OUTPUT_CONTROL_POINT_COUNT(token),
+            },
+            Opcode::DclTessDomain => Declaration::TessellatorDomain {
+                domain: TessellatorDomain::from_int(DECODE_D3D11_SB_TESS_DOMAIN(token))
+                    .map_err(|_| invalid())?,
+            },
+            Opcode::DclTessPartitioning => Declaration::TessellatorPartitioning {
+                partitioning: TessellatorPartitioning::from_int(DECODE_D3D11_SB_TESS_PARTITIONING(
+                    token,
+                ))
+                .map_err(|_| invalid())?,
+            },
+            Opcode::DclTessOutputPrimitive => Declaration::TessellatorOutputPrimitive {
+                primitive: TessellatorOutputPrimitive::from_int(
+                    DECODE_D3D11_SB_TESS_OUTPUT_PRIMITIVE(token),
+                )
+                .map_err(|_| invalid())?,
+            },
+            Opcode::DclHsMaxTessFactor => Declaration::HsMaxTessFactor {
+                factor: f32::from_bits(decoder.read_u32()?),
+            },
+            Opcode::DclHsForkPhaseInstanceCount => Declaration::HsForkPhaseInstanceCount {
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclHsJoinPhaseInstanceCount => Declaration::HsJoinPhaseInstanceCount {
+                count: decoder.read_u32()?,
+            },
+            Opcode::DclFunctionBody => Declaration::FunctionBody {
+                index: decoder.read_u32()?,
+            },
+            Opcode::DclFunctionTable => {
+                let index = decoder.read_u32()?;
+                let len = decoder.read_u32()? as usize;
+
+                Declaration::FunctionTable {
+                    index,
+                    bodies: decoder.words(len)?,
+                }
+            }
+            Opcode::DclInterface => {
+                let index = decoder.read_u32()?;
+                let body_count = decoder.read_u32()?;
+                let lengths = decoder.read_u32()?;
+
+                Declaration::Interface {
+                    index,
+                    dynamically_indexed: DECODE_D3D11_SB_INTERFACE_INDEXED_BIT(token) != 0,
+                    body_count,
+                    array_length: DECODE_D3D11_SB_INTERFACE_ARRAY_LENGTH(lengths),
+                    tables: decoder
+                        .words(DECODE_D3D11_SB_INTERFACE_TABLE_LENGTH(lengths) as usize)?,
+                }
+            }
+            _ => return Err(invalid()),
+        };
+
+        // flags the fields above don't hold couldn't be written back
+        if declaration.controls() != token & D3D10_SB_OPCODE_CONTROLS_MASK {
+            return Err(invalid());
+        }
+
+        Ok(declaration)
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Declaration::GlobalFlags { .. } => Opcode::DclGlobalFlags,
+            Declaration::Temps { .. } => Opcode::DclTemps,
+            Declaration::IndexableTemp { .. } => Opcode::DclIndexableTemp,
+            Declaration::Input { .. } => Opcode::DclInput,
+            Declaration::InputSgv { .. } => Opcode::DclInputSgv,
+            Declaration::InputSiv { .. } => Opcode::DclInputSiv,
+            Declaration::InputPs { .. } => Opcode::DclInputPs,
+            Declaration::InputPsSgv { .. } => Opcode::DclInputPsSgv,
+            Declaration::InputPsSiv { .. } => Opcode::DclInputPsSiv,
+            Declaration::Output { .. } => Opcode::DclOutput,
+            Declaration::OutputSgv { .. } => Opcode::DclOutputSgv,
+            Declaration::OutputSiv { .. } => Opcode::DclOutputSiv,
+            Declaration::IndexRange { .. } => Opcode::DclIndexRange,
+            Declaration::ConstantBuffer { .. } => Opcode::DclConstantBuffer,
+            Declaration::Sampler { .. } => Opcode::DclSampler,
+            Declaration::Resource { .. } => Opcode::DclResource,
+            Declaration::ResourceRaw { .. } => Opcode::DclResourceRaw,
+            Declaration::ResourceStructured { .. } => Opcode::DclResourceStructured,
+            Declaration::UavTyped { .. } => Opcode::DclUavTyped,
+            Declaration::UavRaw { .. } => Opcode::DclUavRaw,
+            Declaration::UavStructured { .. } => Opcode::DclUavStructured,
+            Declaration::TgsmRaw { .. } => Opcode::DclTgsmRaw,
+            Declaration::TgsmStructured { .. } => Opcode::DclTgsmStructured,
+            Declaration::ThreadGroup { .. } => Opcode::DclThreadGroup,
+            Declaration::GsInputPrimitive { .. } => Opcode::DclGsInputPrimitive,
+            Declaration::GsOutputPrimitiveTopology { .. } => Opcode::DclGsOutputPrimitiveTopology,
+            Declaration::MaxOutputVertexCount { .. } => Opcode::DclMaxOutputVertexCount,
+            Declaration::GsInstanceCount { .. } => Opcode::DclGsInstanceCount,
+            Declaration::Stream { .. } => Opcode::DclStream,
+            Declaration::InputControlPointCount { .. } => Opcode::DclInputControlPointCount,
+            Declaration::OutputControlPointCount { .. } => Opcode::DclOutputControlPointCount,
+            Declaration::TessellatorDomain { .. } => Opcode::DclTessDomain,
+            Declaration::TessellatorPartitioning { .. } => Opcode::DclTessPartitioning,
+            Declaration::TessellatorOutputPrimitive { .. } => Opcode::DclTessOutputPrimitive,
+            Declaration::HsMaxTessFactor { .. } => Opcode::DclHsMaxTessFactor,
+            Declaration::HsForkPhaseInstanceCount { .. } => Opcode::DclHsForkPhaseInstanceCount,
+            Declaration::HsJoinPhaseInstanceCount { .. } => Opcode::DclHsJoinPhaseInstanceCount,
+            Declaration::FunctionBody { .. } => Opcode::DclFunctionBody,
+            Declaration::FunctionTable { .. } => Opcode::DclFunctionTable,
+            Declaration::Interface { .. } => Opcode::DclInterface,
+        }
+    }
+
+    /// Returns the opcode specific bits of the opcode token, in place.
+    pub fn controls(&self) -> u32 {
+        let coherency = |globally_coherent: bool| {
+            if globally_coherent {
+                ENCODE_D3D11_SB_ACCESS_COHERENCY_FLAGS(D3D11_SB_GLOBALLY_COHERENT_ACCESS)
+            } else {
+                0
+            }
+        };
+
+        match *self {
+            Declaration::GlobalFlags { flags } => ENCODE_D3D10_SB_GLOBAL_FLAGS(flags.bits()),
+            Declaration::InputPs { interpolation, .. }
+            | Declaration::InputPsSgv { interpolation, .. }
+            | Declaration::InputPsSiv { interpolation, .. } => {
+                ENCODE_D3D10_SB_INPUT_INTERPOLATION_MODE(interpolation as u32)
+            }
+            Declaration::ConstantBuffer { access, .. } => {
+                ENCODE_D3D10_SB_D3D10_SB_CONSTANT_BUFFER_ACCESS_PATTERN(access as u32)
+            }
+            Declaration::Sampler { mode, .. } => ENCODE_D3D10_SB_SAMPLER_MODE(mode as u32),
+            Declaration::Resource {
+                dimension,
+                sample_count,
+                ..
+            } => {
+                ENCODE_D3D10_SB_RESOURCE_DIMENSION(dimension as u32)
+                    | ENCODE_D3D10_SB_RESOURCE_SAMPLE_COUNT(sample_count)
+            }
+            Declaration::UavTyped {
+                dimension,
+                globally_coherent,
+                ..
+            } => {
+                ENCODE_D3D10_SB_RESOURCE_DIMENSION(dimension as u32) | coherency(globally_coherent)
+            }
+            Declaration::UavRaw {
+                globally_coherent, ..
+            } => coherency(globally_coherent),
+            Declaration::UavStructured {
+                globally_coherent,
+                has_counter,
+                ..
+            } => {
+                let counter = if has_counter {
+                    ENCODE_D3D11_SB_UAV_FLAGS(D3D11_SB_UAV_HAS_ORDER_PRESERVING_COUNTER)
+                } else {
+                    0
+                };
+
+                coherency(globally_coherent) | counter
+            }
+            Declaration::GsInputPrimitive { primitive } => {
+                let primitive = match primitive {
+                    Primitive::Undefined => D3D10_SB_PRIMITIVE_UNDEFINED,
+                    Primitive::Point => D3D10_SB_PRIMITIVE_POINT,
+                    Primitive::Line => D3D10_SB_PRIMITIVE_LINE,
+                    Primitive::Triangle => D3D10_SB_PRIMITIVE_TRIANGLE,
+                    Primitive::LineAdj => D3D10_SB_PRIMITIVE_LINE_ADJ,
+                    Primitive::TriangleAdj => D3D10_SB_PRIMITIVE_TRIANGLE_ADJ,
+                    Primitive::ControlPointPatch(count) => {
+                        D3D11_SB_PRIMITIVE_1_CONTROL_POINT_PATCH + count - 1
+                    }
+                };
+
+                ENCODE_D3D10_SB_GS_INPUT_PRIMITIVE(primitive)
+            }
+            Declaration::GsOutputPrimitiveTopology { topology } => {
+                ENCODE_D3D10_SB_GS_OUTPUT_PRIMITIVE_TOPOLOGY(topology as u32)
+            }
+            Declaration::InputControlPointCount { count } => {
+                ENCODE_D3D11_SB_INPUT_CONTROL_POINT_COUNT(count)
+            }
+            Declaration::OutputControlPointCount { count } => {
+                ENCODE_D3D11_SB_OUTPUT_CONTROL_POINT_COUNT(count)
+            }
+            Declaration::TessellatorDomain { domain } => ENCODE_D3D11_SB_TESS_DOMAIN(domain as u32),
+            Declaration::TessellatorPartitioning { partitioning } => {
+                ENCODE_D3D11_SB_TESS_PARTITIONING(partitioning as u32)
+            }
+            Declaration::TessellatorOutputPrimitive { primitive } => {
+                ENCODE_D3D11_SB_TESS_OUTPUT_PRIMITIVE(primitive as u32)
+            }
+            Declaration::Interface {
+                dynamically_indexed,
+                ..
+            } => ENCODE_D3D11_SB_INTERFACE_INDEXED_BIT(dynamically_indexed as u32),
+            _ => 0,
+        }
+    }
+}
+
+/// The bits of an opcode token between the opcode and the instruction
+/// length.
+const D3D10_SB_OPCODE_CONTROLS_MASK: u32 = 0x00ff_f800;
+
+fn read_semantic(decoder: &mut decoder::Decoder) -> Result<Semantic, State> {
+    let offset = decoder.get_offset();
+
+    Semantic::from_int(DECODE_D3D10_SB_NAME(decoder.read_u32()?))
+        .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(offset)))
+}
+
+fn read_return_type(decoder: &mut decoder::Decoder) -> Result<[ResourceReturnType; 4], State> {
+    let offset = decoder.get_offset();
+    let token = decoder.read_u32()?;
+    let component = |component| {
+        ResourceReturnType::from_int(DECODE_D3D10_SB_RESOURCE_RETURN_TYPE(token, component))
+            .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(offset)))
+    };
+
+    Ok([component(0)?, component(1)?, component(2)?, component(3)?])
+}
+
+/// Reads one of the dwords shader model 5.1 adds after a register range, like
+/// the register space.
+fn read_range_dword(
+    decoder: &mut decoder::Decoder,
+    register: &Operand,
+) -> Result<Option<u32>, State> {
+    if register.indices.len() == 3 {
+        Ok(Some(decoder.read_u32()?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// A fully decoded instruction that doesn't borrow from the shader it was
+/// parsed from, so it can be stored, compared and handed to `dr::Builder`.
+/// `SparseInstruction::to_instruction` gives the same thing for an
+/// instruction a consumer was passed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction {
+    /// A `dcl_*` instruction other than `dcl_immediateConstantBuffer`, which
+    /// is `customdata`.
+    Declaration(Declaration),
+    Operation {
+        opcode: Opcode,
+        /// Bits 11 to 23 of the opcode token: saturate, test boolean,
+        /// resinfo return type and so on.
+        controls: u32,
+        extended: Vec<OpcodeExtension>,
+        operands: Vec<Operand>,
+    },
+    /// `fcall`: a call through an interface's function table.
+    InterfaceCall {
+        controls: u32,
+        function_index: u32,
+        interface: Operand,
+    },
+    CustomData {
+        class: u32,
+        data: Vec<u32>,
+    },
+}
+
+impl Instruction {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<Instruction, State> {
+        let start = decoder.get_offset();
+        let token = decoder.read_u32()?;
+
+        let opcode = Opcode::from_int(DECODE_D3D10_SB_OPCODE_TYPE(token))
+            .map_err(|_| State::DecoderError(Error::DecodeEnumFailed(start)))?;
+
+        if opcode == Opcode::CustomData {
+            // customdata stores its length in the next token instead
+            let len = decoder.read_u32()? as usize;
+            let data_len = len.checked_sub(2).ok_or(State::ChunkIncorrect)?;
+
+            return Ok(Instruction::CustomData {
+                class: DECODE_D3D10_SB_CUSTOMDATA_CLASS(token),
+                data: decoder.words(data_len)?,
+            });
+        }
+
+        let len = DECODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH(token) as usize;
+        let end = start + 4 * len;
+        if end < decoder.get_offset() {
+            return Err(State::DecoderError(Error::InstructionLengthMismatch(start)));
+        }
+
+        // keep the operands from running into the next instruction
+        let body = decoder.get_offset();
+        decoder.seek_mut(end)?;
+        let mut tokens = decoder.seek(body)?;
+        tokens.set_limit(end);
+
+        let mut extended = Vec::new();
+        let mut is_extended = DECODE_IS_D3D10_SB_OPCODE_EXTENDED(token) != 0;
+        while is_extended {
+            let offset = tokens.get_offset();
+            let word = tokens.read_u32()?;
+
+            extended.push(
+                OpcodeExtension::from_word(word)
+                    .ok_or(State::DecoderError(Error::DecodeEnumFailed(offset)))?,
+            );
+            is_extended = DECODE_IS_D3D10_SB_OPCODE_EXTENDED(word) != 0;
+        }
+
+        let controls = (token >> 11) & 0x1fff;
+
+        if opcode == Opcode::InterfaceCall {
+            let function_index = tokens.read_u32()?;
+            let interface = Operand::parse(&mut tokens)?;
+            if !tokens.eof() {
+                return Err(State::DecoderError(Error::InstructionLengthMismatch(start)));
+            }
+
+            return Ok(Instruction::InterfaceCall {
+                controls,
+                function_index,
+                interface,
+            });
+        }
+
+        if opcode.is_declaration() {
+            // declarations don't take extended opcode tokens, so there'd be
+            // nowhere to keep them
+            if !extended.is_empty() {
+                return Err(State::DecoderError(Error::DecodeEnumFailed(start)));
+            }
+
+            let declaration = Declaration::parse(opcode, token, start, &mut tokens)?;
+            if !tokens.eof() {
+                return Err(State::DecoderError(Error::InstructionLengthMismatch(start)));
+            }
+
+            Ok(Instruction::Declaration(declaration))
+        } else {
+            let mut operands = Vec::new();
+            while !tokens.eof() {
+                operands.push(Operand::parse(&mut tokens)?);
+            }
+
+            Ok(Instruction::Operation {
+                opcode,
+                controls,
+                extended,
+                operands,
+            })
+        }
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        match *self {
+            Instruction::Declaration(ref declaration) => declaration.opcode(),
+            Instruction::Operation { opcode, .. } => opcode,
+            Instruction::InterfaceCall { .. } => Opcode::InterfaceCall,
+            Instruction::CustomData { .. } => Opcode::CustomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_bytes(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn immediate64_literals() {
+        let one = 1.5f64.to_bits();
+        let (x, y) = (1.0f64.to_bits(), 2.0f64.to_bits());
+        let bytes = to_bytes(&[
+            // d(1.5)
+            0x0000_5001,
+            one as u32,
+            (one >> 32) as u32,
+            // d(1.0, 2.0)
+            0x0000_5002,
+            x as u32,
+            (x >> 32) as u32,
+            y as u32,
+            (y >> 32) as u32,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+
+        let scalar = OperandToken0::parse(&mut decoder).unwrap();
+        assert_eq!(scalar.len(), 3);
+        assert!(matches!(scalar.get_immediates()[..], [Immediate::U64(v)] if v == one));
+
+        let vector = OperandToken0::parse(&mut decoder).unwrap();
+        assert_eq!(vector.len(), 5);
+        assert!(matches!(
+            vector.get_immediates()[..],
+            [Immediate::U64(a), Immediate::U64(b)] if a == x && b == y
+        ));
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn immediate_constant_buffer() {
+        let bytes = to_bytes(&[
+            // dcl_immediateConstantBuffer { { 0, 1, 2, 3 }, { 4, 5, 6, 7 } }
+            0x0000_1835,
+            10,
+            0,
+            1,
+            2,
+            3,
+            4,
+            5,
+            6,
+            7,
+            // icb[1].xyzw
+            0x0010_9e46,
+            1,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+
+        let instruction = SparseInstruction::parse(&mut decoder, Phase::Global).unwrap();
+        let icb = match instruction.operands {
+            Operands::DclImmediateConstantBuffer(icb) => icb,
+            operands => panic!("unexpected operands {:?}", operands),
+        };
+        assert_eq!(icb.constants, [[0, 1, 2, 3], [4, 5, 6, 7]]);
+
+        let operand = OperandToken0::parse(&mut decoder).unwrap();
+        assert_eq!(icb.resolve(&operand), Some([4, 5, 6, 7]));
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn invalid_tessellator_controls() {
+        // dcl_tessellator_partitioning and dcl_tessellator_output_primitive
+        // with values 5 to 7, which their three bits have room for
+        for opcode in [
+            D3D11_SB_OPCODE_DCL_TESS_PARTITIONING,
+            D3D11_SB_OPCODE_DCL_TESS_OUTPUT_PRIMITIVE,
+        ] {
+            for value in 5..=7 {
+                let bytes = to_bytes(&[opcode | 0x0100_0000 | (value << 11)]);
+
+                let mut decoder = decoder::Decoder::new(&bytes);
+                assert!(matches!(
+                    SparseInstruction::parse(&mut decoder, Phase::Global),
+                    Err(State::DecoderError(Error::DecodeEnumFailed(0)))
+                ));
+
+                let mut decoder = decoder::Decoder::new(&bytes);
+                assert!(matches!(
+                    Instruction::parse(&mut decoder),
+                    Err(State::DecoderError(Error::DecodeEnumFailed(0)))
+                ));
+            }
+
+            let bytes = to_bytes(&[opcode | 0x0100_0000 | (4 << 11)]);
+            let mut decoder = decoder::Decoder::new(&bytes);
+            assert!(SparseInstruction::parse(&mut decoder, Phase::Global).is_ok());
+        }
+    }
+
+    #[test]
+    fn invalid_primitives() {
+        // the six bits of dcl_inputprimitive and dcl_outputtopology leave
+        // gaps and room past the last value
+        let invalid = [
+            (D3D10_SB_OPCODE_DCL_GS_INPUT_PRIMITIVE, [4, 5, 40, 63]),
+            (
+                D3D10_SB_OPCODE_DCL_GS_OUTPUT_PRIMITIVE_TOPOLOGY,
+                [6, 9, 14, 63],
+            ),
+        ];
+
+        for (opcode, values) in invalid {
+            for value in values {
+                let bytes = to_bytes(&[opcode | 0x0100_0000 | (value << 11)]);
+
+                let mut decoder = decoder::Decoder::new(&bytes);
+                assert!(matches!(
+                    SparseInstruction::parse(&mut decoder, Phase::Global),
+                    Err(State::DecoderError(Error::DecodeEnumFailed(0)))
+                ));
+
+                let mut decoder = decoder::Decoder::new(&bytes);
+                assert!(matches!(
+                    Instruction::parse(&mut decoder),
+                    Err(State::DecoderError(Error::DecodeEnumFailed(0)))
+                ));
+            }
+        }
+
+        // dcl_inputprimitive patch32
+        let bytes = to_bytes(&[D3D10_SB_OPCODE_DCL_GS_INPUT_PRIMITIVE | 0x0100_0000 | (39 << 11)]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        match SparseInstruction::parse(&mut decoder, Phase::Global)
+            .unwrap()
+            .operands
+        {
+            Operands::DclGsInputPrimitive(dcl) => {
+                assert_eq!(dcl.primitive, Primitive::ControlPointPatch(32))
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+    }
+
+    #[test]
+    fn invalid_operand_modifier() {
+        // r0.x with modifier 4 in its extended operand token
+        let bytes = to_bytes(&[0x8010_0012, 0x0000_0101, 0]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+
+        assert!(matches!(
+            Operand::parse(&mut decoder),
+            Err(State::DecoderError(Error::DecodeEnumFailed(4)))
+        ));
+    }
+
+    #[test]
+    fn reserved_values() {
+        assert!(NameToken::from_word(22).is_some());
+        assert!(NameToken::from_word(23).is_none());
+        assert!(SamplerMode::from_word(3).is_none());
+        assert!(ResInfoReturnType::from_word(3).is_none());
+        assert!(InterpolationMode::from_word(8).is_none());
+        assert!(ResourceDimension::from_word(13).is_none());
+        assert!(ResourceReturnType::from_word(0).is_none());
+        assert!(ResourceReturnType::from_word(10).is_none());
+        assert!(ExtendedOpcodeType::from_word(4).is_none());
+
+        // every value each field can hold decodes to something or nothing
+        for value in 0..=0xffff {
+            NameToken::from_word(value);
+            SamplerMode::from_word(value & 0xf);
+            ResInfoReturnType::from_word(value & 0x3);
+            InterpolationMode::from_word(value & 0xf);
+            ResourceDimension::from_word(value & 0x1f);
+            ResourceReturnType::from_word(value & 0xf);
+            ExtendedOpcodeType::from_word(value);
+
+            let bytes = to_bytes(&[value << 11 | 0x0100_0000, value << 16 | value]);
+            let opcode = OpcodeToken0::from_tokens(&bytes);
+            opcode.get_resource_dimension();
+            opcode.get_sampler_mode();
+            opcode.get_interpolation_mode();
+            opcode.get_resinfo_return_type();
+            let extended = OpcodeToken1::from_tokens(&bytes[4..]);
+            extended.get_extended_opcode_type();
+            extended.get_resource_dimension();
+            extended.get_return_type(ComponentName::W);
+            ResourceReturnTypeToken0::from_word(value << 16 | value)
+                .get_return_type(ComponentName::W);
+        }
+
+        // r0 with selection mode 3, which is only decoded for 4 components
+        let bytes = to_bytes(&[0x0010_000d, 0]);
+        let operand = OperandToken0::from_tokens(&bytes);
+        assert!(operand.get_component_select_mode().is_none());
+        assert!(operand.get_immediate(1).is_none());
+        assert!(!format!("{:?}", operand).is_empty());
+
+        // dcl_input_ps with interpolation mode 15 and a 0D register
+        let bytes = to_bytes(&[
+            D3D10_SB_OPCODE_DCL_INPUT_PS | 0x0200_0000 | (15 << 11),
+            0x0000_1012,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        let instruction = SparseInstruction::parse(&mut decoder, Phase::Global).unwrap();
+        assert!(instruction.opcode.get_interpolation_mode().is_none());
+        match instruction.operands {
+            Operands::DclInputPs(ref input) => assert_eq!(input.get_input_register(), !0),
+            ref operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(!format!("{:?}", instruction).is_empty());
+    }
+
+    #[test]
+    fn sm4_instructions() {
+        let bytes = to_bytes(&[
+            // imul null, r0.x, r1.x, l(3)
+            0x0800_0026,
+            0x0000_d000,
+            0x0010_0012,
+            0,
+            0x0010_000a,
+            1,
+            0x0000_4001,
+            3,
+            // switch r0.x
+            0x0300_004c,
+            0x0010_000a,
+            0,
+            // ld r0.xyzw, r1.xyzw, t0.xyzw
+            0x0700_002d,
+            0x0010_00f2,
+            0,
+            0x0010_0e46,
+            1,
+            0x0010_7e46,
+            0,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+
+        match SparseInstruction::parse(&mut decoder, Phase::Global)
+            .unwrap()
+            .operands
+        {
+            Operands::Imul(imul) => {
+                assert_eq!(imul.dst_hi.get_operand_type(), OperandType::Null);
+                assert_eq!(imul.dst_lo.get_operand_type(), OperandType::Temp);
+                assert!(matches!(imul.a.get_immediate(0), Some(Immediate::U32(1))));
+                assert!(matches!(imul.b.get_immediates()[..], [Immediate::U32(3)]));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match SparseInstruction::parse(&mut decoder, Phase::Global)
+            .unwrap()
+            .operands
+        {
+            Operands::Switch(switch) => {
+                assert_eq!(switch.src.get_operand_type(), OperandType::Temp)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match SparseInstruction::parse(&mut decoder, Phase::Global)
+            .unwrap()
+            .operands
+        {
+            Operands::Ld(ld) => {
+                assert!(matches!(
+                    ld.src_address.get_immediate(0),
+                    Some(Immediate::U32(1))
+                ));
+                assert_eq!(ld.src_resource.get_operand_type(), OperandType::Resource);
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn instruction_length_mismatch() {
+        // imul null, r0.x, r1.x, l(3) with its length off by one either way,
+        // and with a length of 0
+        for len in [7, 9, 0] {
+            let bytes = to_bytes(&[
+                (len << 24) | 0x26,
+                0x0000_d000,
+                0x0010_0012,
+                0,
+                0x0010_000a,
+                1,
+                0x0000_4001,
+                3,
+                // ret, so a length of 9 doesn't run out of tokens
+                0x0100_003e,
+            ]);
+
+            let mut decoder = decoder::Decoder::new(&bytes);
+            assert!(matches!(
+                SparseInstruction::parse(&mut decoder, Phase::Global),
+                Err(State::DecoderError(Error::InstructionLengthMismatch(0)))
+            ));
+        }
+
+        // dcl_temps 1 with a length that leaves a token over
+        let bytes = to_bytes(&[0x0300_0068, 1, 0]);
+
+        let mut decoder = decoder::Decoder::new(&bytes);
+        assert!(matches!(
+            SparseInstruction::parse(&mut decoder, Phase::Global),
+            Err(State::DecoderError(Error::InstructionLengthMismatch(0)))
+        ));
+
+        let mut decoder = decoder::Decoder::new(&bytes);
+        assert!(matches!(
+            Instruction::parse(&mut decoder),
+            Err(State::DecoderError(Error::InstructionLengthMismatch(0)))
+        ));
+
+        // unknown opcodes of length 0 can't be skipped
+        let bytes = to_bytes(&[0x0000_00cf]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        assert!(matches!(
+            SparseInstruction::parse(&mut decoder, Phase::Global),
+            Err(State::DecoderError(Error::InstructionLengthMismatch(0)))
+        ));
+    }
+
+    #[test]
+    fn compute_instructions() {
+        let bytes = to_bytes(&[
+            // dcl_thread_group 8, 8, 1
+            0x0400_009b,
+            8,
+            8,
+            1,
+            // dcl_tgsm_structured g0, 4, 64
+            0x0500_00a0,
+            0x0011_f000,
+            0,
+            4,
+            64,
+            // ld_structured r0.x, vThreadID.x, l(0), t0.xxxx
+            0x0800_00a7,
+            0x0010_0012,
+            0,
+            0x0002_000a,
+            0x0000_4001,
+            0,
+            0x0010_7006,
+            0,
+            // atomic_iadd u0, r0.x, l(1)
+            0x0700_00ad,
+            0x0011_e000,
+            0,
+            0x0010_000a,
+            0,
+            0x0000_4001,
+            1,
+            // sync_g_t
+            0x0100_18be,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        let mut next = || {
+            SparseInstruction::parse(&mut decoder, Phase::Global)
+                .unwrap()
+                .operands
+        };
+
+        match next() {
+            Operands::DclThreadGroup(dcl) => assert_eq!((dcl.x, dcl.y, dcl.z), (8, 8, 1)),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::DclTgsmStructured(dcl) => {
+                assert_eq!(dcl.get_register(), 0);
+                assert_eq!((dcl.stride, dcl.count), (4, 64));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::LdStructured(ld) => {
+                assert_eq!(
+                    ld.src_address.get_operand_type(),
+                    OperandType::InputThreadId
+                );
+                assert_eq!(ld.src_resource.get_operand_type(), OperandType::Resource);
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::AtomicIAdd(atomic) => {
+                assert_eq!(
+                    atomic.dst.get_operand_type(),
+                    OperandType::UnorderedAccessView
+                );
+                assert!(matches!(
+                    atomic.src.get_immediates()[..],
+                    [Immediate::U32(1)]
+                ));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::Sync(flags) => assert_eq!(
+                flags,
+                SyncFlags::THREADS_IN_GROUP | SyncFlags::THREAD_GROUP_SHARED_MEMORY
+            ),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn gather_and_sample_info() {
+        let bytes = to_bytes(&[
+            // gather4_aoffimmi_indexable(-1,1,0)(texture2d)(float,float,float,float)
+            //     r0.xyzw, r1.xyxx, t0.xyzw, s0.x
+            0x8c00_006d,
+            0x8000_3e01,
+            0x8000_00c2,
+            0x0015_5543,
+            0x0010_00f2,
+            0,
+            0x0010_0046,
+            1,
+            0x0010_7e46,
+            0,
+            0x0010_600a,
+            0,
+            // sample_info r0.x, t0.xyzw
+            0x0500_006f,
+            0x0010_0012,
+            0,
+            0x0010_7e46,
+            0,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+
+        let gather = SparseInstruction::parse(&mut decoder, Phase::Global).unwrap();
+        assert_eq!(gather.opcode.get_texel_offsets(), Some([-1, 1, 0]));
+        assert_eq!(
+            gather.opcode.get_extended_resource_dimension(),
+            Some((ResourceDimension::Texture2D, 0))
+        );
+        let return_type = gather.opcode.get_extended_resource_return_type().unwrap();
+        assert_eq!(
+            return_type.get_return_type(ComponentName::W),
+            Some(ResourceReturnType::Float)
+        );
+        match &gather.operands {
+            Operands::Gather4(gather) => {
+                assert_eq!(gather.src_sampler.get_operand_type(), OperandType::Sampler)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+
+        match gather.to_instruction().unwrap() {
+            Instruction::Operation {
+                extended, operands, ..
+            } => {
+                assert_eq!(
+                    extended,
+                    [
+                        OpcodeExtension::SampleControls([-1, 1, 0]),
+                        OpcodeExtension::ResourceDim {
+                            dimension: ResourceDimension::Texture2D,
+                            stride: 0,
+                        },
+                        OpcodeExtension::ResourceReturnType([ResourceReturnType::Float; 4]),
+                    ]
+                );
+                assert_eq!(operands.len(), 4);
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+
+        match SparseInstruction::parse(&mut decoder, Phase::Global)
+            .unwrap()
+            .operands
+        {
+            Operands::SampleInfo(info) => {
+                assert_eq!(info.src_resource.get_operand_type(), OperandType::Resource)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn class_linkage() {
+        let bytes = to_bytes(&[
+            // dcl_function_body fb0
+            0x0200_0090,
+            0,
+            // dcl_function_table ft0 = {fb0, fb1}
+            0x0500_0091,
+            0,
+            2,
+            0,
+            1,
+            // dcl_interface_dynamicindexed fp0[2][1] = {ft0}
+            0x0500_0892,
+            0,
+            1,
+            0x0002_0001,
+            0,
+            // fcall fp0[0][0], 0
+            0x0500_0078,
+            0,
+            0x0021_3000,
+            0,
+            0,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        let mut next = || SparseInstruction::parse(&mut decoder, Phase::Global).unwrap();
+
+        match next().operands {
+            Operands::DclFunctionBody(dcl) => assert_eq!(dcl.index, 0),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next().operands {
+            Operands::DclFunctionTable(dcl) => {
+                assert_eq!(dcl.index, 0);
+                assert_eq!(dcl.bodies, [0, 1]);
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next().operands {
+            Operands::DclInterface(dcl) => {
+                assert!(dcl.dynamically_indexed);
+                assert_eq!((dcl.body_count, dcl.array_length), (1, 2));
+                assert_eq!(dcl.tables, [0]);
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+
+        let fcall = next();
+        match &fcall.operands {
+            Operands::InterfaceCall(call) => {
+                assert_eq!(call.function_index, 0);
+                assert_eq!(call.interface.get_operand_type(), OperandType::Interface);
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match fcall.to_instruction().unwrap() {
+            Instruction::InterfaceCall {
+                function_index,
+                interface,
+                ..
+            } => {
+                assert_eq!(function_index, 0);
+                assert_eq!(
+                    interface.indices,
+                    [OperandIndex::Immediate32(0), OperandIndex::Immediate32(0)]
+                );
+            }
+            instruction => panic!("unexpected instruction {:?}", instruction),
+        }
+    }
+
+    #[test]
+    fn hull_shader_phases() {
+        let len = ENCODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH;
+        let bytes = to_bytes(&[
+            // hs_decls
+            D3D11_SB_OPCODE_HS_DECLS | len(1),
+            // dcl_input_control_point_count 3
+            D3D11_SB_OPCODE_DCL_INPUT_CONTROL_POINT_COUNT
+                | ENCODE_D3D11_SB_INPUT_CONTROL_POINT_COUNT(3)
+                | len(1),
+            // dcl_output_control_point_count 4
+            D3D11_SB_OPCODE_DCL_OUTPUT_CONTROL_POINT_COUNT
+                | ENCODE_D3D11_SB_OUTPUT_CONTROL_POINT_COUNT(4)
+                | len(1),
+            // dcl_tessellator_domain domain_quad
+            D3D11_SB_OPCODE_DCL_TESS_DOMAIN
+                | ENCODE_D3D11_SB_TESS_DOMAIN(D3D11_SB_TESSELLATOR_DOMAIN_QUAD)
+                | len(1),
+            // dcl_hs_max_tessfactor l(64.000000)
+            D3D11_SB_OPCODE_DCL_HS_MAX_TESSFACTOR | len(2),
+            64.0f32.to_bits(),
+            // hs_control_point_phase
+            D3D11_SB_OPCODE_HS_CONTROL_POINT_PHASE | len(1),
+            // ret
+            D3D10_SB_OPCODE_RET | len(1),
+            // hs_fork_phase
+            D3D11_SB_OPCODE_HS_FORK_PHASE | len(1),
+            // dcl_hs_fork_phase_instance_count 2
+            D3D11_SB_OPCODE_DCL_HS_FORK_PHASE_INSTANCE_COUNT | len(2),
+            2,
+            // ret
+            D3D10_SB_OPCODE_RET | len(1),
+            // hs_join_phase
+            D3D11_SB_OPCODE_HS_JOIN_PHASE | len(1),
+            // ret
+            D3D10_SB_OPCODE_RET | len(1),
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        let mut phase = Phase::Global;
+        let mut next = || {
+            let instruction = SparseInstruction::parse(&mut decoder, phase).unwrap();
+            phase = instruction.phase;
+            (instruction.phase, instruction.operands)
+        };
+
+        assert!(matches!(next(), (Phase::Declarations, Operands::HsDecls)));
+        match next() {
+            (Phase::Declarations, Operands::DclInputControlPointCount(dcl)) => {
+                assert_eq!(dcl.count, 3)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            (Phase::Declarations, Operands::DclOutputControlPointCount(dcl)) => {
+                assert_eq!(dcl.count, 4)
             }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            (Phase::Declarations, Operands::DclTessellatorDomain(dcl)) => {
+                assert_eq!(dcl.domain, TessellatorDomain::Quad)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            (Phase::Declarations, Operands::DclHsMaxTessFactor(dcl)) => {
+                assert_eq!(dcl.max_tess_factor, 64.0)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(matches!(
+            next(),
+            (Phase::ControlPoint, Operands::HsControlPointPhase)
+        ));
+        assert!(matches!(next(), (Phase::ControlPoint, Operands::Ret)));
+        assert!(matches!(next(), (Phase::Fork, Operands::HsForkPhase)));
+        match next() {
+            (Phase::Fork, Operands::DclHsForkPhaseInstanceCount(dcl)) => {
+                assert_eq!(dcl.instance_count, 2)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(matches!(next(), (Phase::Fork, Operands::Ret)));
+        assert!(matches!(next(), (Phase::Join, Operands::HsJoinPhase)));
+        assert!(matches!(next(), (Phase::Join, Operands::Ret)));
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn geometry_shader_streams() {
+        let len = ENCODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH;
+        // m1, a stream operand
+        let stream = |index| [0x0011_0000, index];
+        let bytes = to_bytes(
+            &[
+                // dcl_inputprimitive triangle
+                &[D3D10_SB_OPCODE_DCL_GS_INPUT_PRIMITIVE
+                    | ENCODE_D3D10_SB_GS_INPUT_PRIMITIVE(D3D10_SB_PRIMITIVE_TRIANGLE)
+                    | len(1)][..],
+                // dcl_outputtopology trianglestrip
+                &[D3D10_SB_OPCODE_DCL_GS_OUTPUT_PRIMITIVE_TOPOLOGY
+                    | ENCODE_D3D10_SB_GS_OUTPUT_PRIMITIVE_TOPOLOGY(
+                        D3D10_SB_PRIMITIVE_TOPOLOGY_TRIANGLESTRIP,
+                    )
+                    | len(1)],
+                // dcl_maxout 3
+                &[D3D10_SB_OPCODE_DCL_MAX_OUTPUT_VERTEX_COUNT | len(2), 3],
+                // dcl_gsinstances 2
+                &[D3D11_SB_OPCODE_DCL_GS_INSTANCE_COUNT | len(2), 2],
+                // dcl_stream m1
+                &[D3D11_SB_OPCODE_DCL_STREAM | len(3)],
+                &stream(1),
+                // emit_stream m1
+                &[D3D11_SB_OPCODE_EMIT_STREAM | len(3)],
+                &stream(1),
+                // cut_stream m1
+                &[D3D11_SB_OPCODE_CUT_STREAM | len(3)],
+                &stream(1),
+                // emitThenCut_stream m0
+                &[D3D11_SB_OPCODE_EMITTHENCUT_STREAM | len(3)],
+                &stream(0),
+            ]
+            .concat(),
+        );
+        let mut decoder = decoder::Decoder::new(&bytes);
+        let mut next = || {
+            SparseInstruction::parse(&mut decoder, Phase::Global)
+                .unwrap()
+                .operands
+        };
+
+        match next() {
+            Operands::DclGsInputPrimitive(dcl) => assert_eq!(dcl.primitive, Primitive::Triangle),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::DclGsOutputPrimitiveTopology(dcl) => {
+                assert_eq!(dcl.topology, PrimitiveTopology::TriangleStrip)
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::DclMaxOutputVertexCount(dcl) => assert_eq!(dcl.count, 3),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::DclGsInstanceCount(dcl) => assert_eq!(dcl.instance_count, 2),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::DclStream(dcl) => assert_eq!(dcl.get_stream(), 1),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::EmitStream(emit) => {
+                assert_eq!(emit.stream.get_operand_type(), OperandType::Stream);
+                assert_eq!(emit.get_stream(), 1);
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::CutStream(cut) => assert_eq!(cut.get_stream(), 1),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::EmitThenCutStream(emit) => assert_eq!(emit.get_stream(), 0),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(decoder.eof());
+    }
+
+    #[test]
+    fn double_and_bit_instructions() {
+        let len = ENCODE_D3D10_SB_TOKENIZED_INSTRUCTION_LENGTH;
+        let one = 1.0f64.to_bits();
+        let two = 2.0f64.to_bits();
+        let bytes = to_bytes(&[
+            // dadd r0.xy, r1.xyxy, d(1.000000, 2.000000)
+            D3D11_SB_OPCODE_DADD | len(10),
+            0x0010_0032,
+            0,
+            0x0010_0446,
+            1,
+            0x0000_5002,
+            one as u32,
+            (one >> 32) as u32,
+            two as u32,
+            (two >> 32) as u32,
+            // ftod r0.xy, r1.x
+            D3D11_SB_OPCODE_FTOD | len(5),
+            0x0010_0032,
+            0,
+            0x0010_000a,
+            1,
+            // ubfe r0.x, l(4), l(8), r1.x
+            D3D11_SB_OPCODE_UBFE | len(9),
+            0x0010_0012,
+            0,
+            0x0000_4001,
+            4,
+            0x0000_4001,
+            8,
+            0x0010_000a,
+            1,
+            // umul r0.x, r1.x, r2.x, r3.x
+            D3D10_SB_OPCODE_UMUL | len(9),
+            0x0010_0012,
+            0,
+            0x0010_0012,
+            1,
+            0x0010_000a,
+            2,
+            0x0010_000a,
+            3,
+            // uaddc r0.x, r1.x, r2.x, r3.x
+            D3D11_SB_OPCODE_UADDC | len(9),
+            0x0010_0012,
+            0,
+            0x0010_0012,
+            1,
+            0x0010_000a,
+            2,
+            0x0010_000a,
+            3,
+        ]);
+        let mut decoder = decoder::Decoder::new(&bytes);
+        let mut next = || {
+            SparseInstruction::parse(&mut decoder, Phase::Global)
+                .unwrap()
+                .operands
         };
 
-        SparseInstruction { opcode, operands }
+        match next() {
+            Operands::DAdd(dadd) => {
+                assert_eq!(dadd.dst.get_component_mask().bits(), 0x30);
+                assert_eq!(dadd.b.get_operand_type(), OperandType::Immediate64);
+                assert!(matches!(
+                    dadd.b.get_immediates()[..],
+                    [Immediate::U64(a), Immediate::U64(b)] if (a, b) == (one, two)
+                ));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::Ftod(ftod) => assert_eq!(ftod.src.get_operand_type(), OperandType::Temp),
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::UBfe(ubfe) => {
+                assert!(matches!(ubfe.a.get_immediate(0), Some(Immediate::U32(4))));
+                assert!(matches!(ubfe.b.get_immediate(0), Some(Immediate::U32(8))));
+                assert!(matches!(ubfe.c.get_immediate(0), Some(Immediate::U32(1))));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::Umul(umul) => {
+                assert!(matches!(
+                    umul.dst_hi.get_immediate(0),
+                    Some(Immediate::U32(0))
+                ));
+                assert!(matches!(
+                    umul.dst_lo.get_immediate(0),
+                    Some(Immediate::U32(1))
+                ));
+                assert!(matches!(umul.b.get_immediate(0), Some(Immediate::U32(3))));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        match next() {
+            Operands::UAddC(uaddc) => {
+                assert!(matches!(
+                    uaddc.dst.get_immediate(0),
+                    Some(Immediate::U32(0))
+                ));
+                assert!(matches!(
+                    uaddc.dst_carry.get_immediate(0),
+                    Some(Immediate::U32(1))
+                ));
+            }
+            operands => panic!("unexpected operands {:?}", operands),
+        }
+        assert!(decoder.eof());
     }
 }
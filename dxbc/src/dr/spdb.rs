@@ -0,0 +1,486 @@
+use crate::binary::*;
+
+const MSF_MAGIC: &[u8; 32] = b"Microsoft C/C++ MSF 7.00\r\n\x1aDS\0\0\0";
+
+/// Streams with this size are unused.
+const NIL_STREAM_SIZE: u32 = 0xffff_ffff;
+
+const PDB_INFO_STREAM: usize = 1;
+const DBI_STREAM: usize = 3;
+
+const DBI_HEADER_SIZE: usize = 64;
+const MODULE_INFO_SIZE: usize = 64;
+const NAMES_SIGNATURE: u32 = 0xeffe_effe;
+
+const DEBUG_S_LINES: u32 = 0xf2;
+const DEBUG_S_FILECHKSMS: u32 = 0xf4;
+const CV_LINES_HAVE_COLUMNS: u16 = 0x1;
+
+const SOURCE_FILE_PREFIX: &str = "/src/files/";
+
+/// An HLSL file embedded in the PDB, including any headers it included.
+#[derive(Debug)]
+pub struct SourceFile {
+    pub name: String,
+    pub contents: String,
+}
+
+/// Maps the instruction at `offset` to the source line it was compiled from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LineInfo {
+    /// The code offset recorded by the compiler.
+    pub offset: u32,
+    /// Index into `SpdbChunk::line_files`.
+    pub file: usize,
+    pub line: u32,
+}
+
+/// The MSF (multi-stream file) container of a PDB, embedded in the `SPDB`
+/// chunk of shaders compiled with `/Zi`.
+#[derive(Debug)]
+pub struct SpdbChunk {
+    pub block_size: u32,
+    pub streams: Vec<Vec<u8>>,
+    /// The streams listed by name in the PDB info stream.
+    pub named_streams: Vec<(String, u32)>,
+    pub source_files: Vec<SourceFile>,
+    /// The files referenced by `lines`, which are usually the names of
+    /// `source_files`.
+    pub line_files: Vec<String>,
+    pub lines: Vec<LineInfo>,
+}
+
+impl SpdbChunk {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<SpdbChunk, State> {
+        if decoder.bytes(MSF_MAGIC.len())? != MSF_MAGIC {
+            return Err(State::ChunkIncorrect);
+        }
+
+        let block_size = decoder.read_u32()?;
+        let _free_block_map = decoder.read_u32()?;
+        let _block_count = decoder.read_u32()?;
+        let directory_size = decoder.read_u32()?;
+        let _unknown = decoder.read_u32()?;
+        let block_map = decoder.read_u32()?;
+
+        if block_size == 0 {
+            return Err(State::ChunkIncorrect);
+        }
+
+        // the block map lists the blocks the stream directory is spread over
+        let block_map = (block_map as usize)
+            .checked_mul(block_size as usize)
+            .ok_or(State::ChunkIncorrect)?;
+        let directory_blocks = decoder
+            .seek(block_map)?
+            .words(directory_size.div_ceil(block_size) as usize)?;
+        let directory = read_blocks(
+            decoder,
+            block_size,
+            &directory_blocks,
+            directory_size as usize,
+        )?;
+        let mut directory = decoder::Decoder::new(&directory);
+
+        let stream_count = directory.read_u32()? as usize;
+        let stream_sizes = directory.words(stream_count)?;
+
+        let mut streams = Vec::new();
+        for size in stream_sizes {
+            let size = if size == NIL_STREAM_SIZE { 0 } else { size };
+            let blocks = directory.words(size.div_ceil(block_size) as usize)?;
+
+            streams.push(read_blocks(decoder, block_size, &blocks, size as usize)?);
+        }
+
+        let named_streams = match streams.get(PDB_INFO_STREAM) {
+            Some(info) if !info.is_empty() => parse_named_streams(info)?,
+            _ => Vec::new(),
+        };
+
+        let mut source_files = Vec::new();
+        for (name, stream) in &named_streams {
+            if let Some(name) = name.strip_prefix(SOURCE_FILE_PREFIX) {
+                let contents = streams.get(*stream as usize).ok_or(State::ChunkIncorrect)?;
+
+                source_files.push(SourceFile {
+                    name: name.to_owned(),
+                    contents: String::from_utf8_lossy(contents).into_owned(),
+                });
+            }
+        }
+
+        let mut chunk = SpdbChunk {
+            block_size,
+            streams,
+            named_streams,
+            source_files,
+            line_files: Vec::new(),
+            lines: Vec::new(),
+        };
+        (chunk.line_files, chunk.lines) = chunk.parse_lines()?;
+
+        Ok(chunk)
+    }
+
+    pub fn get_named_stream(&self, name: &str) -> Option<&[u8]> {
+        self.named_streams
+            .iter()
+            .find(|(stream_name, _)| stream_name == name)
+            .and_then(|&(_, stream)| self.streams.get(stream as usize))
+            .map(|stream| &stream[..])
+    }
+
+    /// Returns the embedded source of the file `line` belongs to. fxc
+    /// lowercases the names of the embedded files, so they're compared
+    /// case-insensitively.
+    pub fn get_source_file(&self, line: &LineInfo) -> Option<&SourceFile> {
+        let name = self.line_files.get(line.file)?;
+
+        self.source_files
+            .iter()
+            .find(|file| file.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Reads the C13 line tables of every module in the DBI stream.
+    fn parse_lines(&self) -> Result<(Vec<String>, Vec<LineInfo>), State> {
+        let mut line_files = Vec::new();
+        let mut lines = Vec::new();
+
+        let dbi = match self.streams.get(DBI_STREAM) {
+            Some(dbi) if !dbi.is_empty() => dbi,
+            _ => return Ok((line_files, lines)),
+        };
+        let names = self.get_named_stream("/names");
+
+        let mut decoder = decoder::Decoder::new(dbi);
+        decoder.skip(24)?;
+        let module_info_size = decoder.read_u32()? as usize;
+        decoder.seek_mut(DBI_HEADER_SIZE)?;
+
+        let mut modules = decoder.scoped_decoder(module_info_size)?;
+        while !modules.eof() {
+            let start = modules.get_offset();
+            modules.skip(34)?;
+            let stream = modules.read_u16()?;
+            let symbols_size = modules.read_u32()? as usize;
+            let c11_size = modules.read_u32()? as usize;
+            let c13_size = modules.read_u32()? as usize;
+            modules.seek_mut(start + MODULE_INFO_SIZE)?;
+            let _module_name = modules.str()?;
+            let _object_name = modules.str()?;
+            skip_padding(&mut modules);
+
+            let module = match self.streams.get(stream as usize) {
+                Some(module) if stream != 0xffff => module,
+                _ => continue,
+            };
+
+            let mut c13 = decoder::Decoder::new(module)
+                .seek(symbols_size + c11_size)?
+                .scoped_decoder(c13_size)?;
+            let (module_files, module_lines) = parse_c13_lines(&mut c13, names, line_files.len())?;
+
+            line_files.extend(module_files);
+            lines.extend(module_lines);
+        }
+
+        lines.sort_by_key(|line| line.offset);
+
+        Ok((line_files, lines))
+    }
+}
+
+/// Skips to the next 4-byte boundary, or to the end if the padding is cut
+/// short.
+fn skip_padding(decoder: &mut decoder::Decoder) {
+    let aligned = (decoder.get_offset() + 3) & !3;
+    while decoder.get_offset() < aligned && decoder.read_u8().is_ok() {}
+}
+
+fn read_blocks(
+    decoder: &decoder::Decoder,
+    block_size: u32,
+    blocks: &[u32],
+    size: usize,
+) -> Result<Vec<u8>, State> {
+    let mut bytes = Vec::new();
+
+    for &block in blocks {
+        let offset = (block as usize)
+            .checked_mul(block_size as usize)
+            .ok_or(State::ChunkIncorrect)?;
+        let len = (size - bytes.len()).min(block_size as usize);
+
+        bytes.extend_from_slice(decoder.seek(offset)?.bytes(len)?);
+    }
+
+    Ok(bytes)
+}
+
+/// Reads the named stream map that follows the PDB info stream header.
+fn parse_named_streams(info: &[u8]) -> Result<Vec<(String, u32)>, State> {
+    let mut decoder = decoder::Decoder::new(info);
+    let _version = decoder.read_u32()?;
+    let _signature = decoder.read_u32()?;
+    let _age = decoder.read_u32()?;
+    let _guid = decoder.bytes(16)?;
+
+    let names_size = decoder.read_u32()? as usize;
+    let names = decoder.scoped_decoder(names_size)?;
+    decoder.skip(names_size)?;
+
+    let _size = decoder.read_u32()?;
+    let capacity = decoder.read_u32()?;
+    let present_count = decoder.read_u32()? as usize;
+    let present = decoder.words(present_count)?;
+    let deleted_count = decoder.read_u32()? as usize;
+    decoder.skip(deleted_count.checked_mul(4).ok_or(State::ChunkIncorrect)?)?;
+
+    // entries past the end of the present bit vector can't be present, so
+    // don't trust the capacity beyond that
+    let capacity = (capacity as usize).min(32 * present.len());
+
+    let mut named_streams = Vec::new();
+    for i in 0..capacity {
+        let is_present = present
+            .get(i / 32)
+            .is_some_and(|word| word & (1 << (i % 32)) != 0);
+        if !is_present {
+            continue;
+        }
+
+        let name_offset = decoder.read_u32()? as usize;
+        let stream = decoder.read_u32()?;
+
+        named_streams.push((names.seek(name_offset)?.string()?, stream));
+    }
+
+    Ok(named_streams)
+}
+
+/// Reads the files and lines of a module's C13 debug subsections. The
+/// returned lines index into the returned files, offset by `first_file`.
+fn parse_c13_lines(
+    decoder: &mut decoder::Decoder,
+    names: Option<&[u8]>,
+    first_file: usize,
+) -> Result<(Vec<String>, Vec<LineInfo>), State> {
+    let mut checksums = None;
+    let mut blocks = Vec::new();
+
+    while !decoder.eof() {
+        let kind = decoder.read_u32()?;
+        let len = decoder.read_u32()? as usize;
+        let subsection = decoder.scoped_decoder(len)?;
+        decoder.skip(len)?;
+        skip_padding(decoder);
+
+        match kind {
+            DEBUG_S_FILECHKSMS => checksums = Some(subsection),
+            DEBUG_S_LINES => blocks.push(subsection),
+            _ => {}
+        }
+    }
+
+    let checksums = match checksums {
+        Some(checksums) => checksums,
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+
+    let names = names.ok_or(State::ChunkIncorrect)?;
+    let mut names = decoder::Decoder::new(names);
+    if names.read_u32()? != NAMES_SIGNATURE {
+        return Err(State::ChunkIncorrect);
+    }
+    let _version = names.read_u32()?;
+    let names_size = names.read_u32()? as usize;
+    let names = names.scoped_decoder(names_size)?;
+
+    // lines refer to files by the offset of their checksum entry
+    let mut files = Vec::new();
+    let mut file_ids = Vec::new();
+    let mut entries = checksums;
+    while !entries.eof() {
+        let id = entries.get_offset() as u32;
+        let name_offset = entries.read_u32()? as usize;
+        let checksum_size = entries.read_u8()? as usize;
+        let _checksum_kind = entries.read_u8()?;
+        entries.skip(checksum_size)?;
+        skip_padding(&mut entries);
+
+        files.push(names.seek(name_offset)?.string()?);
+        file_ids.push(id);
+    }
+
+    let mut lines = Vec::new();
+    for mut block in blocks {
+        let base = block.read_u32()?;
+        let _segment = block.read_u16()?;
+        let flags = block.read_u16()?;
+        let _code_size = block.read_u32()?;
+
+        while !block.eof() {
+            let start = block.get_offset();
+            let file_id = block.read_u32()?;
+            let line_count = block.read_u32()? as usize;
+            let block_size = block.read_u32()? as usize;
+
+            let file = file_ids
+                .iter()
+                .position(|&id| id == file_id)
+                .ok_or(State::ChunkIncorrect)?;
+
+            for _ in 0..line_count {
+                let offset = block.read_u32()?;
+                let line = block.read_u32()? & 0x00ff_ffff;
+
+                lines.push(LineInfo {
+                    offset: base.wrapping_add(offset),
+                    file: first_file + file,
+                    line,
+                });
+            }
+
+            if flags & CV_LINES_HAVE_COLUMNS != 0 {
+                block.skip(4 * line_count)?;
+            }
+
+            block.seek_mut(start.checked_add(block_size).ok_or(State::ChunkIncorrect)?)?;
+        }
+    }
+
+    Ok((files, lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK_SIZE: usize = 256;
+
+    fn push_u32(bytes: &mut Vec<u8>, words: &[u32]) {
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    fn build_msf(streams: &[Vec<u8>]) -> Vec<u8> {
+        let mut directory = Vec::new();
+        push_u32(&mut directory, &[streams.len() as u32]);
+        for stream in streams {
+            push_u32(&mut directory, &[stream.len() as u32]);
+        }
+
+        // the superblock, block map and directory take the first three blocks
+        let mut blocks = Vec::new();
+        for stream in streams.iter().filter(|stream| !stream.is_empty()) {
+            push_u32(&mut directory, &[3 + blocks.len() as u32]);
+            blocks.push(stream);
+        }
+
+        let mut msf = MSF_MAGIC.to_vec();
+        push_u32(
+            &mut msf,
+            &[
+                BLOCK_SIZE as u32,
+                0,
+                3 + blocks.len() as u32,
+                directory.len() as u32,
+                0,
+                1,
+            ],
+        );
+        msf.resize(BLOCK_SIZE, 0);
+        push_u32(&mut msf, &[2]);
+        msf.resize(2 * BLOCK_SIZE, 0);
+        msf.extend_from_slice(&directory);
+        for block in blocks {
+            msf.resize(msf.len().next_multiple_of(BLOCK_SIZE), 0);
+            msf.extend_from_slice(block);
+        }
+
+        msf
+    }
+
+    #[test]
+    fn source_files_and_lines() {
+        let source = "float4 main() : SV_Target\n{\n    return 0;\n}\n";
+
+        let mut info = vec![0; 28];
+        let names = b"/src/files/a.hlsl\0/names\0";
+        push_u32(&mut info, &[names.len() as u32]);
+        info.extend_from_slice(names);
+        push_u32(&mut info, &[2, 2, 1, 0b11, 0, 0, 4, 18, 5]);
+
+        let mut dbi = vec![0; DBI_HEADER_SIZE];
+        dbi[24..28].copy_from_slice(&72u32.to_le_bytes());
+        let mut module_info = vec![0; 34];
+        module_info.extend_from_slice(&6u16.to_le_bytes());
+        push_u32(&mut module_info, &[4, 0, 64]);
+        module_info.resize(MODULE_INFO_SIZE, 0);
+        module_info.extend_from_slice(b"mod\0obj\0");
+        dbi.extend_from_slice(&module_info);
+
+        let mut string_table = Vec::new();
+        push_u32(&mut string_table, &[NAMES_SIGNATURE, 1, 8]);
+        string_table.extend_from_slice(b"\0a.hlsl\0");
+
+        let mut module = Vec::new();
+        push_u32(&mut module, &[4]);
+        push_u32(&mut module, &[DEBUG_S_FILECHKSMS, 8, 1, 0]);
+        push_u32(&mut module, &[DEBUG_S_LINES, 40, 0x10, 0, 16]);
+        push_u32(&mut module, &[0, 2, 28, 0, 0x8000_0003, 8, 2]);
+
+        let msf = build_msf(&[
+            Vec::new(),
+            info,
+            Vec::new(),
+            dbi,
+            source.as_bytes().to_vec(),
+            string_table,
+            module,
+        ]);
+        let spdb = SpdbChunk::parse(&mut decoder::Decoder::new(&msf)).unwrap();
+
+        assert_eq!(spdb.source_files.len(), 1);
+        assert_eq!(spdb.source_files[0].name, "a.hlsl");
+        assert_eq!(spdb.source_files[0].contents, source);
+
+        assert_eq!(spdb.line_files, ["a.hlsl"]);
+        assert_eq!(
+            spdb.lines,
+            [
+                LineInfo {
+                    offset: 0x10,
+                    file: 0,
+                    line: 3,
+                },
+                LineInfo {
+                    offset: 0x18,
+                    file: 0,
+                    line: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            spdb.get_source_file(&spdb.lines[0]).unwrap().contents,
+            source
+        );
+    }
+
+    #[test]
+    fn named_streams_capacity() {
+        // a capacity far past the one word of present bits
+        let mut info = vec![0; 28];
+        let names = b"/names\0";
+        push_u32(&mut info, &[names.len() as u32]);
+        info.extend_from_slice(names);
+        push_u32(&mut info, &[1, u32::MAX, 1, 0b1, 0, 0, 5]);
+
+        assert_eq!(
+            parse_named_streams(&info).unwrap(),
+            [("/names".to_owned(), 5)]
+        );
+    }
+}
@@ -22,30 +22,84 @@ pub struct IStatChunk {
     pub texture_comp_instructions: u32,
     pub texture_bias_instructions: u32,
     pub texture_gradient_instructions: u32,
+    pub mov_instruction_count: u32,
+    pub movc_instruction_count: u32,
+    pub conversion_instruction_count: u32,
+    pub bitwise_instruction_count: u32,
+    pub gs_input_primitive: u32,
+    pub gs_output_topology: u32,
+    pub gs_max_output_vertex_count: u32,
+    /// Always 0 in fxc output.
+    pub unknown: [u32; 2],
+    pub is_sample_frequency: u32,
+    /// The statistics only written for shader model 5.0 and later.
+    pub sm5: Option<IStat5>,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct IStat5 {
+    pub gs_instance_count: u32,
+    pub control_points: u32,
+    pub hs_output_primitive: u32,
+    pub hs_partitioning: u32,
+    pub tessellator_domain: u32,
+    pub barrier_instructions: u32,
+    pub interlocked_instructions: u32,
+    pub texture_store_instructions: u32,
+}
+
+impl IStat5 {
+    pub fn parse(decoder: &mut decoder::Decoder) -> Result<IStat5, State> {
+        Ok(IStat5 {
+            gs_instance_count: decoder.read_u32()?,
+            control_points: decoder.read_u32()?,
+            hs_output_primitive: decoder.read_u32()?,
+            hs_partitioning: decoder.read_u32()?,
+            tessellator_domain: decoder.read_u32()?,
+            barrier_instructions: decoder.read_u32()?,
+            interlocked_instructions: decoder.read_u32()?,
+            texture_store_instructions: decoder.read_u32()?,
+        })
+    }
 }
 
 impl IStatChunk {
     pub fn parse(decoder: &mut decoder::Decoder) -> Result<IStatChunk, State> {
         Ok(IStatChunk {
-            instruction_count: decoder.read_u32(),
-            temp_register_count: decoder.read_u32(),
-            def_count: decoder.read_u32(),
-            dcl_count: decoder.read_u32(),
-            float_instruction_count: decoder.read_u32(),
-            int_instruction_count: decoder.read_u32(),
-            uint_instruction_count: decoder.read_u32(),
-            static_flow_control_count: decoder.read_u32(),
-            dynamic_flow_control_count: decoder.read_u32(),
-            macro_instruction_count: decoder.read_u32(),
-            temp_array_count: decoder.read_u32(),
-            array_instruction_count: decoder.read_u32(),
-            cut_instruction_count: decoder.read_u32(),
-            emit_instruction_count: decoder.read_u32(),
-            texture_normal_instructions: decoder.read_u32(),
-            texture_load_instructions: decoder.read_u32(),
-            texture_comp_instructions: decoder.read_u32(),
-            texture_bias_instructions: decoder.read_u32(),
-            texture_gradient_instructions: decoder.read_u32(),
+            instruction_count: decoder.read_u32()?,
+            temp_register_count: decoder.read_u32()?,
+            def_count: decoder.read_u32()?,
+            dcl_count: decoder.read_u32()?,
+            float_instruction_count: decoder.read_u32()?,
+            int_instruction_count: decoder.read_u32()?,
+            uint_instruction_count: decoder.read_u32()?,
+            static_flow_control_count: decoder.read_u32()?,
+            dynamic_flow_control_count: decoder.read_u32()?,
+            macro_instruction_count: decoder.read_u32()?,
+            temp_array_count: decoder.read_u32()?,
+            array_instruction_count: decoder.read_u32()?,
+            cut_instruction_count: decoder.read_u32()?,
+            emit_instruction_count: decoder.read_u32()?,
+            texture_normal_instructions: decoder.read_u32()?,
+            texture_load_instructions: decoder.read_u32()?,
+            texture_comp_instructions: decoder.read_u32()?,
+            texture_bias_instructions: decoder.read_u32()?,
+            texture_gradient_instructions: decoder.read_u32()?,
+            mov_instruction_count: decoder.read_u32()?,
+            movc_instruction_count: decoder.read_u32()?,
+            conversion_instruction_count: decoder.read_u32()?,
+            bitwise_instruction_count: decoder.read_u32()?,
+            gs_input_primitive: decoder.read_u32()?,
+            gs_output_topology: decoder.read_u32()?,
+            gs_max_output_vertex_count: decoder.read_u32()?,
+            unknown: [decoder.read_u32()?, decoder.read_u32()?],
+            is_sample_frequency: decoder.read_u32()?,
+            sm5: if decoder.eof() {
+                None
+            } else {
+                Some(IStat5::parse(decoder)?)
+            },
         })
     }
 }
@@ -3,8 +3,11 @@ extern crate bitflags;
 #[macro_use]
 extern crate paste;
 
+pub mod asm;
 pub mod binary;
 pub mod checksum;
+pub mod disasm;
 pub mod dr;
 mod md5;
+pub mod reflect;
 pub use checksum::*;
@@ -0,0 +1,512 @@
+//! Shader reflection in the spirit of `ID3D11ShaderReflection`, built from
+//! the `RDEF`, signature, `STAT`, `SFI0` and `SHEX` chunks of a container.
+
+use crate::binary::*;
+use crate::dr::*;
+
+use winapi::um::d3d11tokenizedprogramformat::{D3D11_SB_DOMAIN_SHADER, D3D11_SB_HULL_SHADER};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FeatureLevel {
+    Level9_1,
+    Level9_2,
+    Level9_3,
+    Level10_0,
+    Level10_1,
+    Level11_0,
+    Level11_1,
+}
+
+/// The equivalent of `D3D11_SHADER_DESC`.
+#[derive(Debug)]
+pub struct ShaderDesc<'a> {
+    /// The `D3D11_SHADER_VERSION_TYPE` in the high word, the major and minor
+    /// version in the low byte.
+    pub version: u32,
+    pub creator: &'a str,
+    pub flags: u32,
+    pub constant_buffers: u32,
+    pub bound_resources: u32,
+    pub input_parameters: u32,
+    pub output_parameters: u32,
+    pub patch_constant_parameters: u32,
+    pub instruction_count: u32,
+    pub temp_register_count: u32,
+    pub temp_array_count: u32,
+    pub def_count: u32,
+    pub dcl_count: u32,
+    pub texture_normal_instructions: u32,
+    pub texture_load_instructions: u32,
+    pub texture_comp_instructions: u32,
+    pub texture_bias_instructions: u32,
+    pub texture_gradient_instructions: u32,
+    pub float_instruction_count: u32,
+    pub int_instruction_count: u32,
+    pub uint_instruction_count: u32,
+    pub static_flow_control_count: u32,
+    pub dynamic_flow_control_count: u32,
+    pub macro_instruction_count: u32,
+    pub array_instruction_count: u32,
+    pub cut_instruction_count: u32,
+    pub emit_instruction_count: u32,
+    pub gs_output_topology: Option<PrimitiveTopology>,
+    pub gs_max_output_vertex_count: u32,
+    pub input_primitive: Option<Primitive>,
+    pub gs_instance_count: u32,
+    pub control_points: u32,
+    pub hs_output_primitive: Option<TessellatorOutputPrimitive>,
+    pub hs_partitioning: Option<TessellatorPartitioning>,
+    pub tessellator_domain: Option<TessellatorDomain>,
+}
+
+/// The declarations of the `SHEX` chunk that reflection reports.
+#[derive(Debug, Default)]
+struct Declarations {
+    global_flags: u32,
+    thread_group: Option<[u32; 3]>,
+    gs_input_primitive: Option<Primitive>,
+    gs_output_topology: Option<PrimitiveTopology>,
+    gs_max_output_vertex_count: u32,
+    gs_instance_count: u32,
+    input_control_points: u32,
+    output_control_points: u32,
+    tessellator_domain: Option<TessellatorDomain>,
+    tessellator_partitioning: Option<TessellatorPartitioning>,
+    tessellator_output_primitive: Option<TessellatorOutputPrimitive>,
+    max_tess_factor: Option<f32>,
+}
+
+impl Declarations {
+    fn parse(decoder: &mut Decoder) -> Result<(ShexHeader, Self), State> {
+        let header = ShexHeader::parse(decoder)?;
+        let mut decoder = decoder.scoped_decoder(header.instruction_length as usize * 4)?;
+
+        let mut declarations = Declarations::default();
+        let mut phase = Phase::Global;
+        while !decoder.eof() {
+            let instruction = SparseInstruction::parse(&mut decoder, phase)?;
+            phase = instruction.phase;
+
+            match instruction.operands {
+                Operands::DclGlobalFlags(dcl) => declarations.global_flags = dcl.global_flags,
+                Operands::DclThreadGroup(dcl) => {
+                    declarations.thread_group = Some([dcl.x, dcl.y, dcl.z])
+                }
+                Operands::DclGsInputPrimitive(dcl) => {
+                    declarations.gs_input_primitive = Some(dcl.primitive)
+                }
+                Operands::DclGsOutputPrimitiveTopology(dcl) => {
+                    declarations.gs_output_topology = Some(dcl.topology)
+                }
+                Operands::DclMaxOutputVertexCount(dcl) => {
+                    declarations.gs_max_output_vertex_count = dcl.count
+                }
+                Operands::DclGsInstanceCount(dcl) => {
+                    declarations.gs_instance_count = dcl.instance_count
+                }
+                Operands::DclInputControlPointCount(dcl) => {
+                    declarations.input_control_points = dcl.count
+                }
+                Operands::DclOutputControlPointCount(dcl) => {
+                    declarations.output_control_points = dcl.count
+                }
+                Operands::DclTessellatorDomain(dcl) => {
+                    declarations.tessellator_domain = Some(dcl.domain)
+                }
+                Operands::DclTessellatorPartitioning(dcl) => {
+                    declarations.tessellator_partitioning = Some(dcl.partitioning)
+                }
+                Operands::DclTessellatorOutputPrimitive(dcl) => {
+                    declarations.tessellator_output_primitive = Some(dcl.output_primitive)
+                }
+                Operands::DclHsMaxTessFactor(dcl) => {
+                    declarations.max_tess_factor = Some(dcl.max_tess_factor)
+                }
+                _ => {}
+            }
+        }
+
+        Ok((header, declarations))
+    }
+}
+
+#[derive(Debug)]
+pub struct ShaderReflection<'a> {
+    pub rdef: Option<RdefChunk<'a>>,
+    pub inputs: Vec<InputOutputElement>,
+    pub outputs: Vec<InputOutputElement>,
+    pub patch_constants: Vec<InputOutputElement>,
+    pub stat: Option<IStatChunk>,
+    pub sfi0: Option<Sfi0Chunk>,
+    /// The shader model 2 version of a `level_9_x` shader.
+    pub level9_version: Option<Sm2Version>,
+    shex: Option<ShexHeader>,
+    declarations: Declarations,
+}
+
+impl<'a> ShaderReflection<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, State> {
+        let container = Container::parse(bytes)?;
+
+        let mut reflection = ShaderReflection {
+            rdef: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            patch_constants: Vec::new(),
+            stat: None,
+            sfi0: None,
+            level9_version: None,
+            shex: None,
+            declarations: Declarations::default(),
+        };
+
+        for chunk in &container.chunks {
+            let mut decoder = Decoder::new(container.get_chunk_data(chunk));
+
+            let layout = match &chunk.fourcc {
+                b"ISG1" | b"OSG1" => SignatureLayout::StreamMinPrecision,
+                b"OSG5" => SignatureLayout::Stream,
+                _ => SignatureLayout::Basic,
+            };
+
+            match &chunk.fourcc {
+                b"RDEF" => reflection.rdef = Some(RdefChunk::parse(&mut decoder)?),
+                b"ISGN" | b"ISG1" => {
+                    reflection.inputs = IOsgnChunk::parse(&mut decoder, layout)?.elements
+                }
+                b"OSGN" | b"OSG1" | b"OSG5" => {
+                    reflection.outputs = IOsgnChunk::parse(&mut decoder, layout)?.elements
+                }
+                b"PCSG" => {
+                    reflection.patch_constants = IOsgnChunk::parse(&mut decoder, layout)?.elements
+                }
+                b"STAT" => reflection.stat = Some(IStatChunk::parse(&mut decoder)?),
+                b"SFI0" => reflection.sfi0 = Some(Sfi0Chunk::parse(&mut decoder)?),
+                b"Aon9" => {
+                    reflection.level9_version = Some(Aon9Chunk::parse(&mut decoder)?.version)
+                }
+                b"SHEX" | b"SHDR" => {
+                    let (header, declarations) = Declarations::parse(&mut decoder)?;
+                    reflection.shex = Some(header);
+                    reflection.declarations = declarations;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(reflection)
+    }
+
+    /// Returns the `D3D11_SHADER_VERSION_TYPE` in the high word and the
+    /// major and minor version in the low byte.
+    fn get_version(&self) -> u32 {
+        match (&self.shex, &self.rdef) {
+            (Some(shex), _) => {
+                ((shex.program_type as u32) << 16) | ((shex.major as u32) << 4) | shex.minor as u32
+            }
+            (None, Some(rdef)) => {
+                // the `D3D11_SHADER_VERSION_TYPE` numbering used by SHEX
+                let program_type = match rdef.program_ty {
+                    ProgramType::Pixel => 0,
+                    ProgramType::Vertex => 1,
+                    ProgramType::Geometry => 2,
+                    ProgramType::Hull => 3,
+                    ProgramType::Domain => 4,
+                    ProgramType::Compute => 5,
+                };
+                (program_type << 16) | ((rdef.major as u32) << 4) | rdef.minor as u32
+            }
+            (None, None) => 0,
+        }
+    }
+
+    pub fn get_desc(&self) -> ShaderDesc<'a> {
+        let declarations = &self.declarations;
+        let version = self.get_version();
+
+        // hull and domain shaders report the patch they read as the input
+        // primitive, and hull shaders the patch they write as their control
+        // points
+        let input_points = declarations.input_control_points;
+        let input_primitive = declarations
+            .gs_input_primitive
+            .or((input_points > 0).then_some(Primitive::ControlPointPatch(input_points)));
+        let control_points = match declarations.output_control_points {
+            0 => input_points,
+            output_points => output_points,
+        };
+
+        let mut desc = ShaderDesc {
+            version,
+            creator: self.rdef.as_ref().map_or("", |rdef| rdef.author),
+            flags: self.rdef.as_ref().map_or(0, |rdef| rdef.flags),
+            constant_buffers: self.get_constant_buffer_count() as u32,
+            bound_resources: self
+                .rdef
+                .as_ref()
+                .map_or(0, |rdef| rdef.resource_bindings.len() as u32),
+            input_parameters: self.inputs.len() as u32,
+            output_parameters: self.outputs.len() as u32,
+            patch_constant_parameters: self.patch_constants.len() as u32,
+            instruction_count: 0,
+            temp_register_count: 0,
+            temp_array_count: 0,
+            def_count: 0,
+            dcl_count: 0,
+            texture_normal_instructions: 0,
+            texture_load_instructions: 0,
+            texture_comp_instructions: 0,
+            texture_bias_instructions: 0,
+            texture_gradient_instructions: 0,
+            float_instruction_count: 0,
+            int_instruction_count: 0,
+            uint_instruction_count: 0,
+            static_flow_control_count: 0,
+            dynamic_flow_control_count: 0,
+            macro_instruction_count: 0,
+            array_instruction_count: 0,
+            cut_instruction_count: 0,
+            emit_instruction_count: 0,
+            gs_output_topology: declarations.gs_output_topology,
+            gs_max_output_vertex_count: declarations.gs_max_output_vertex_count,
+            input_primitive,
+            gs_instance_count: declarations.gs_instance_count,
+            control_points,
+            hs_output_primitive: declarations.tessellator_output_primitive,
+            hs_partitioning: declarations.tessellator_partitioning,
+            tessellator_domain: declarations.tessellator_domain,
+        };
+
+        if let Some(ref stat) = self.stat {
+            desc.instruction_count = stat.instruction_count;
+            desc.temp_register_count = stat.temp_register_count;
+            desc.temp_array_count = stat.temp_array_count;
+            desc.def_count = stat.def_count;
+            desc.dcl_count = stat.dcl_count;
+            desc.texture_normal_instructions = stat.texture_normal_instructions;
+            desc.texture_load_instructions = stat.texture_load_instructions;
+            desc.texture_comp_instructions = stat.texture_comp_instructions;
+            desc.texture_bias_instructions = stat.texture_bias_instructions;
+            desc.texture_gradient_instructions = stat.texture_gradient_instructions;
+            desc.float_instruction_count = stat.float_instruction_count;
+            desc.int_instruction_count = stat.int_instruction_count;
+            desc.uint_instruction_count = stat.uint_instruction_count;
+            desc.static_flow_control_count = stat.static_flow_control_count;
+            desc.dynamic_flow_control_count = stat.dynamic_flow_control_count;
+            desc.macro_instruction_count = stat.macro_instruction_count;
+            desc.array_instruction_count = stat.array_instruction_count;
+            desc.cut_instruction_count = stat.cut_instruction_count;
+            desc.emit_instruction_count = stat.emit_instruction_count;
+        }
+
+        desc
+    }
+
+    pub fn get_constant_buffer_count(&self) -> usize {
+        self.rdef
+            .as_ref()
+            .map_or(0, |rdef| rdef.constant_buffers.len())
+    }
+
+    pub fn get_constant_buffer_by_index(&self, index: usize) -> Option<&ConstantBuffer<'a>> {
+        self.rdef.as_ref()?.constant_buffers.get(index)
+    }
+
+    pub fn get_constant_buffer_by_name(&self, name: &str) -> Option<&ConstantBuffer<'a>> {
+        self.rdef.as_ref()?.get_constant_buffer(name)
+    }
+
+    /// Returns the variable called `name` in any of the constant buffers.
+    pub fn get_variable_by_name(&self, name: &str) -> Option<&ShaderVariable<'a>> {
+        self.rdef
+            .as_ref()?
+            .constant_buffers
+            .iter()
+            .find_map(|cb| cb.get_variable(name))
+    }
+
+    pub fn get_resource_binding_desc(&self, index: usize) -> Option<&ResourceBinding<'a>> {
+        self.rdef.as_ref()?.resource_bindings.get(index)
+    }
+
+    pub fn get_resource_binding_desc_by_name(&self, name: &str) -> Option<&ResourceBinding<'a>> {
+        self.rdef.as_ref()?.get_resource_binding(name)
+    }
+
+    pub fn get_input_parameter_desc(&self, index: usize) -> Option<&InputOutputElement> {
+        self.inputs.get(index)
+    }
+
+    pub fn get_output_parameter_desc(&self, index: usize) -> Option<&InputOutputElement> {
+        self.outputs.get(index)
+    }
+
+    pub fn get_patch_constant_parameter_desc(&self, index: usize) -> Option<&InputOutputElement> {
+        self.patch_constants.get(index)
+    }
+
+    /// Returns the `[x, y, z]` thread group size of compute shaders.
+    pub fn get_thread_group_size(&self) -> Option<[u32; 3]> {
+        self.declarations.thread_group
+    }
+
+    pub fn get_gs_input_primitive(&self) -> Option<Primitive> {
+        self.declarations.gs_input_primitive
+    }
+
+    pub fn get_max_tess_factor(&self) -> Option<f32> {
+        self.declarations.max_tess_factor
+    }
+
+    pub fn get_num_interface_slots(&self) -> u32 {
+        self.rdef
+            .as_ref()
+            .map_or(0, |rdef| rdef.get_interface_slot_count())
+    }
+
+    pub fn get_global_flags(&self) -> u32 {
+        self.declarations.global_flags
+    }
+
+    pub fn get_requires_flags(&self) -> ShaderFeatureFlags {
+        self.sfi0
+            .as_ref()
+            .map_or(ShaderFeatureFlags::empty(), |sfi0| sfi0.flags)
+    }
+
+    pub fn get_min_feature_level(&self) -> FeatureLevel {
+        if let Some(version) = self.level9_version {
+            // 9_3 is the only level with the 2_x profiles
+            return if version.minor > 0 {
+                FeatureLevel::Level9_3
+            } else {
+                FeatureLevel::Level9_1
+            };
+        }
+
+        // the 11.1 feature flags are optional caps, which don't raise the
+        // level the shader model needs
+        let version = self.get_version();
+        let (program_type, major, minor) = (version >> 16, (version >> 4) & 0xf, version & 0xf);
+        match (program_type, major, minor) {
+            // tessellation only exists since shader model 5.0
+            (D3D11_SB_HULL_SHADER | D3D11_SB_DOMAIN_SHADER, _, _) => FeatureLevel::Level11_0,
+            (_, 5, _) => FeatureLevel::Level11_0,
+            (_, 4, 1) => FeatureLevel::Level10_1,
+            _ => FeatureLevel::Level10_0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complex_shader() {
+        let shader = include_bytes!("../../dxbcd/complex_shader.dxbc");
+        let reflection = ShaderReflection::new(shader).unwrap();
+
+        let desc = reflection.get_desc();
+        // a pixel shader
+        assert_eq!(desc.version >> 16, 0);
+        assert_eq!(desc.version & 0xff, 0x50);
+        assert_eq!(desc.constant_buffers, 1);
+        assert_eq!(desc.bound_resources, 9);
+        assert_eq!(desc.input_parameters, 10);
+        // the counts in the STAT chunk
+        assert_eq!(desc.instruction_count, 210);
+        assert_eq!(desc.temp_register_count, 13);
+        assert_eq!(desc.dcl_count, 12);
+        assert_eq!(
+            (
+                desc.float_instruction_count,
+                desc.int_instruction_count,
+                desc.uint_instruction_count
+            ),
+            (60, 18, 10)
+        );
+        assert_eq!(
+            (
+                desc.static_flow_control_count,
+                desc.dynamic_flow_control_count
+            ),
+            (5, 11)
+        );
+        assert_eq!(
+            (desc.temp_array_count, desc.array_instruction_count),
+            (16, 19)
+        );
+
+        let cb = reflection.get_constant_buffer_by_index(0).unwrap();
+        assert_eq!(cb.name, "cbuf0");
+        assert!(reflection.get_constant_buffer_by_name("cbuf0").is_some());
+
+        let zeek = reflection.get_variable_by_name("zeek").unwrap();
+        assert_eq!(zeek.offset, 16);
+        assert_eq!(zeek.ty.class, ShaderVariableClass::Vector);
+
+        let tex1 = reflection
+            .get_resource_binding_desc_by_name("tex1")
+            .unwrap();
+        assert_eq!(tex1.bind_point, 1);
+
+        assert_eq!(reflection.get_thread_group_size(), None);
+        assert_eq!(reflection.get_min_feature_level(), FeatureLevel::Level11_0);
+    }
+
+    #[test]
+    fn min_feature_level() {
+        let levels = [
+            ("ps_4_0", FeatureLevel::Level10_0),
+            ("vs_4_1", FeatureLevel::Level10_1),
+            ("cs_4_0", FeatureLevel::Level10_0),
+            ("ps_5_0", FeatureLevel::Level11_0),
+        ];
+        for (profile, level) in levels {
+            let mut listing = format!("{}\n", profile);
+            if profile.starts_with("cs") {
+                listing.push_str("dcl_thread_group 1, 1, 1\n");
+            }
+            listing.push_str("ret\n");
+
+            let module = crate::asm::assemble(&listing).unwrap();
+            let reflection = ShaderReflection::new(module.as_bytes()).unwrap();
+            assert_eq!(reflection.get_min_feature_level(), level, "{}", profile);
+        }
+
+        // optional caps don't raise the level
+        let module = crate::asm::assemble("ps_4_0\nret\n").unwrap();
+        let mut builder = Builder::parse(module.as_bytes()).unwrap();
+        builder.set_sfi0(Sfi0Chunk {
+            flags: ShaderFeatureFlags::UAVS_AT_EVERY_STAGE
+                | ShaderFeatureFlags::SHADER_EXTENSIONS_11_1,
+        });
+        let module = builder.module().unwrap();
+        let reflection = ShaderReflection::new(module.as_bytes()).unwrap();
+        assert_eq!(reflection.get_min_feature_level(), FeatureLevel::Level10_0);
+    }
+
+    #[test]
+    fn hull_shader_patches() {
+        let module = crate::asm::assemble(
+            "hs_5_0\n\
+             hs_decls\n\
+             dcl_input_control_point_count 3\n\
+             dcl_output_control_point_count 4\n\
+             dcl_tessellator_domain domain_quad\n\
+             dcl_tessellator_partitioning partitioning_integer\n\
+             dcl_tessellator_output_primitive output_triangle_cw\n\
+             hs_control_point_phase\n\
+             ret\n",
+        )
+        .unwrap();
+        let reflection = ShaderReflection::new(module.as_bytes()).unwrap();
+
+        let desc = reflection.get_desc();
+        assert_eq!(desc.input_primitive, Some(Primitive::ControlPointPatch(3)));
+        assert_eq!(desc.control_points, 4);
+        assert_eq!(desc.tessellator_domain, Some(TessellatorDomain::Quad));
+        assert_eq!(reflection.get_min_feature_level(), FeatureLevel::Level11_0);
+    }
+}
@@ -0,0 +1,32 @@
+extern crate dxbc;
+
+use std::{env, fs, process};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <input.asm> <output.dxbc>", args[0]);
+        process::exit(1);
+    }
+
+    let source = match fs::read_to_string(&args[1]) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{}: {}", args[1], err);
+            process::exit(1);
+        }
+    };
+
+    let module = match dxbc::asm::assemble(&source) {
+        Ok(module) => module,
+        Err(err) => {
+            eprintln!("{}:{}", args[1], err);
+            process::exit(1);
+        }
+    };
+
+    if let Err(err) = fs::write(&args[2], module.as_bytes()) {
+        eprintln!("{}: {}", args[2], err);
+        process::exit(1);
+    }
+}
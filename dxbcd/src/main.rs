@@ -3,11 +3,69 @@ extern crate term;
 
 use dxbc::binary::*;
 use dxbc::dr::shex::*;
+use dxbc::dr::shex::{Immediate, OperandType};
 use dxbc::dr::*;
 
+use std::{env, fs, process};
+
 struct DisasmConsumer {
     out: Box<term::StdoutTerminal>,
     indent: u32,
+    /// The source lines of a `/Zi` shader, by the code offset they start at.
+    source_lines: Vec<(u32, Vec<String>)>,
+    next_source_line: usize,
+}
+
+/// Collects the source lines of the `SPDB` chunk, which comes after the
+/// program and so has to be read before the listing is written.
+#[derive(Default)]
+struct SourceLineConsumer {
+    source_lines: Vec<(u32, Vec<String>)>,
+}
+
+impl Consumer for SourceLineConsumer {
+    fn initialize(&mut self) -> Action {
+        Action::Continue
+    }
+
+    fn finalize(&mut self) -> Action {
+        Action::Continue
+    }
+
+    fn consume_spdb(&mut self, spdb: &dxbc::dr::SpdbChunk) -> Action {
+        let mut previous = None;
+        for line in &spdb.lines {
+            // like fxc, only note where the source line changes
+            if previous == Some((line.file, line.line)) {
+                continue;
+            }
+            previous = Some((line.file, line.line));
+
+            let name = spdb.line_files.get(line.file).map_or("", |name| &name[..]);
+            let mut text = vec![format!("#line {} \"{}\"", line.line, name)];
+
+            let source = spdb.get_source_file(line).and_then(|file| {
+                let index = line.line.checked_sub(1)? as usize;
+                file.contents.lines().nth(index)
+            });
+            if let Some(source) = source {
+                text.push(format!("// {}", source.trim()));
+            }
+
+            self.source_lines.push((line.offset, text));
+        }
+
+        Action::Continue
+    }
+}
+
+/// Prints a field that decoded to a known value with `{:?}`, and the rest as
+/// `unknown`.
+fn debug_name<T: std::fmt::Debug>(value: Option<T>) -> String {
+    match value {
+        Some(value) => format!("{:?}", value),
+        None => String::from("unknown"),
+    }
 }
 
 const COMMENT_COLOR: term::color::Color = term::color::BRIGHT_BLACK;
@@ -44,6 +102,80 @@ fn get_interpolation_mode_name(mode: InterpolationMode) -> &'static str {
     }
 }
 
+fn get_resource_dimension_name(dimension: ResourceDimension) -> &'static str {
+    match dimension {
+        ResourceDimension::Unknown => "",
+        ResourceDimension::Buffer => "_buffer",
+        ResourceDimension::Texture1D => "_texture1d",
+        ResourceDimension::Texture2D => "_texture2d",
+        ResourceDimension::Texture2DMS => "_texture2dms",
+        ResourceDimension::Texture3D => "_texture3d",
+        ResourceDimension::TextureCube => "_texturecube",
+        ResourceDimension::Texture1DArray => "_texture1darray",
+        ResourceDimension::Texture2DArray => "_texture2darray",
+        ResourceDimension::Texture2DMSArray => "_texture2dmsarray",
+        ResourceDimension::TextureCubeArray => "_texturecubearray",
+        ResourceDimension::RawBuffer => "_raw",
+        ResourceDimension::StructuredBuffer => "_structured",
+    }
+}
+
+fn get_tessellator_domain_name(domain: TessellatorDomain) -> &'static str {
+    match domain {
+        TessellatorDomain::Undefined => "domain_undefined",
+        TessellatorDomain::Isoline => "domain_isoline",
+        TessellatorDomain::Tri => "domain_tri",
+        TessellatorDomain::Quad => "domain_quad",
+    }
+}
+
+fn get_tessellator_partitioning_name(partitioning: TessellatorPartitioning) -> &'static str {
+    match partitioning {
+        TessellatorPartitioning::Undefined => "partitioning_undefined",
+        TessellatorPartitioning::Integer => "partitioning_integer",
+        TessellatorPartitioning::Pow2 => "partitioning_pow2",
+        TessellatorPartitioning::FractionalOdd => "partitioning_fractional_odd",
+        TessellatorPartitioning::FractionalEven => "partitioning_fractional_even",
+    }
+}
+
+fn get_tessellator_output_primitive_name(primitive: TessellatorOutputPrimitive) -> &'static str {
+    match primitive {
+        TessellatorOutputPrimitive::Undefined => "output_undefined",
+        TessellatorOutputPrimitive::Point => "output_point",
+        TessellatorOutputPrimitive::Line => "output_line",
+        TessellatorOutputPrimitive::TriangleCw => "output_triangle_cw",
+        TessellatorOutputPrimitive::TriangleCcw => "output_triangle_ccw",
+    }
+}
+
+fn get_primitive_name(primitive: Primitive) -> String {
+    match primitive {
+        Primitive::Undefined => "undefined".to_owned(),
+        Primitive::Point => "point".to_owned(),
+        Primitive::Line => "line".to_owned(),
+        Primitive::Triangle => "triangle".to_owned(),
+        Primitive::LineAdj => "lineadj".to_owned(),
+        Primitive::TriangleAdj => "triangleadj".to_owned(),
+        Primitive::ControlPointPatch(count) => format!("patch{}", count),
+    }
+}
+
+fn get_primitive_topology_name(topology: PrimitiveTopology) -> &'static str {
+    match topology {
+        PrimitiveTopology::Undefined => "undefined",
+        PrimitiveTopology::PointList => "pointlist",
+        PrimitiveTopology::LineList => "linelist",
+        PrimitiveTopology::LineStrip => "linestrip",
+        PrimitiveTopology::TriangleList => "trianglelist",
+        PrimitiveTopology::TriangleStrip => "trianglestrip",
+        PrimitiveTopology::LineListAdj => "linelistadj",
+        PrimitiveTopology::LineStripAdj => "linestripadj",
+        PrimitiveTopology::TriangleListAdj => "trianglelistadj",
+        PrimitiveTopology::TriangleStripAdj => "trianglestripadj",
+    }
+}
+
 fn get_test_boolean_name(test: TestBoolean) -> &'static str {
     match test {
         TestBoolean::Zero => "z",
@@ -51,14 +183,458 @@ fn get_test_boolean_name(test: TestBoolean) -> &'static str {
     }
 }
 
+fn get_shader_visibility_name(visibility: ShaderVisibility) -> &'static str {
+    match visibility {
+        ShaderVisibility::All => "SHADER_VISIBILITY_ALL",
+        ShaderVisibility::Vertex => "SHADER_VISIBILITY_VERTEX",
+        ShaderVisibility::Hull => "SHADER_VISIBILITY_HULL",
+        ShaderVisibility::Domain => "SHADER_VISIBILITY_DOMAIN",
+        ShaderVisibility::Geometry => "SHADER_VISIBILITY_GEOMETRY",
+        ShaderVisibility::Pixel => "SHADER_VISIBILITY_PIXEL",
+        ShaderVisibility::Amplification => "SHADER_VISIBILITY_AMPLIFICATION",
+        ShaderVisibility::Mesh => "SHADER_VISIBILITY_MESH",
+    }
+}
+
+fn get_texture_address_mode_name(mode: TextureAddressMode) -> &'static str {
+    match mode {
+        TextureAddressMode::Wrap => "TEXTURE_ADDRESS_WRAP",
+        TextureAddressMode::Mirror => "TEXTURE_ADDRESS_MIRROR",
+        TextureAddressMode::Clamp => "TEXTURE_ADDRESS_CLAMP",
+        TextureAddressMode::Border => "TEXTURE_ADDRESS_BORDER",
+        TextureAddressMode::MirrorOnce => "TEXTURE_ADDRESS_MIRROR_ONCE",
+    }
+}
+
+fn get_comparison_func_name(func: ComparisonFunc) -> &'static str {
+    match func {
+        ComparisonFunc::None => "COMPARISON_NONE",
+        ComparisonFunc::Never => "COMPARISON_NEVER",
+        ComparisonFunc::Less => "COMPARISON_LESS",
+        ComparisonFunc::Equal => "COMPARISON_EQUAL",
+        ComparisonFunc::LessEqual => "COMPARISON_LESS_EQUAL",
+        ComparisonFunc::Greater => "COMPARISON_GREATER",
+        ComparisonFunc::NotEqual => "COMPARISON_NOT_EQUAL",
+        ComparisonFunc::GreaterEqual => "COMPARISON_GREATER_EQUAL",
+        ComparisonFunc::Always => "COMPARISON_ALWAYS",
+    }
+}
+
+fn get_static_border_color_name(color: StaticBorderColor) -> &'static str {
+    match color {
+        StaticBorderColor::TransparentBlack => "STATIC_BORDER_COLOR_TRANSPARENT_BLACK",
+        StaticBorderColor::OpaqueBlack => "STATIC_BORDER_COLOR_OPAQUE_BLACK",
+        StaticBorderColor::OpaqueWhite => "STATIC_BORDER_COLOR_OPAQUE_WHITE",
+        StaticBorderColor::OpaqueBlackUint => "STATIC_BORDER_COLOR_OPAQUE_BLACK_UINT",
+        StaticBorderColor::OpaqueWhiteUint => "STATIC_BORDER_COLOR_OPAQUE_WHITE_UINT",
+    }
+}
+
+/// Builds the name of a `D3D12_FILTER` from its min, mag and mip filter
+/// types and its reduction type.
+fn get_filter_name(filter: u32) -> String {
+    let reduction = match (filter >> 7) & 0x3 {
+        0 => "",
+        1 => "COMPARISON_",
+        2 => "MINIMUM_",
+        _ => "MAXIMUM_",
+    };
+
+    let ty = |shift: u32| {
+        if (filter >> shift) & 0x3 == 0 {
+            "POINT"
+        } else {
+            "LINEAR"
+        }
+    };
+    let (min, mag, mip) = (ty(4), ty(2), ty(0));
+
+    let name = if filter & 0x40 != 0 {
+        if mip == "POINT" {
+            String::from("MIN_MAG_ANISOTROPIC_MIP_POINT")
+        } else {
+            String::from("ANISOTROPIC")
+        }
+    } else if min == mag && mag == mip {
+        format!("MIN_MAG_MIP_{}", min)
+    } else if min == mag {
+        format!("MIN_MAG_{}_MIP_{}", min, mip)
+    } else if mag == mip {
+        format!("MIN_{}_MAG_MIP_{}", min, mag)
+    } else {
+        format!("MIN_{}_MAG_{}_MIP_{}", min, mag, mip)
+    };
+
+    format!("FILTER_{}{}", reduction, name)
+}
+
+/// Formats `flags` the way the `RootSignature` attribute takes them.
+fn format_root_flags(flags: impl std::fmt::Debug, bits: u32) -> String {
+    if bits == 0 {
+        String::from("0")
+    } else {
+        format!("{:?}", flags)
+    }
+}
+
+fn format_root_parameter(parameter: &RootParameter, version: RootSignatureVersion) -> String {
+    let visibility = get_shader_visibility_name(parameter.visibility);
+
+    let descriptor = |name: &str, prefix: &str, descriptor: &RootDescriptor| {
+        let mut text = format!(
+            "{}({}{}, space = {}, visibility = {}",
+            name, prefix, descriptor.register, descriptor.space, visibility
+        );
+        if version == RootSignatureVersion::V1_1 {
+            text += &format!(
+                ", flags = {}",
+                format_root_flags(descriptor.flags, descriptor.flags.bits())
+            );
+        }
+        text + ")"
+    };
+
+    match parameter.data {
+        RootParameterData::DescriptorTable(ref ranges) => {
+            let mut elements: Vec<String> = ranges
+                .iter()
+                .map(|range| {
+                    let (name, prefix) = match range.ty {
+                        DescriptorRangeType::Srv => ("SRV", "t"),
+                        DescriptorRangeType::Uav => ("UAV", "u"),
+                        DescriptorRangeType::Cbv => ("CBV", "b"),
+                        DescriptorRangeType::Sampler => ("Sampler", "s"),
+                    };
+                    let count = if range.count == DESCRIPTOR_COUNT_UNBOUNDED {
+                        String::from("unbounded")
+                    } else {
+                        range.count.to_string()
+                    };
+                    let offset = if range.offset == DESCRIPTOR_RANGE_OFFSET_APPEND {
+                        String::from("DESCRIPTOR_RANGE_OFFSET_APPEND")
+                    } else {
+                        range.offset.to_string()
+                    };
+
+                    let mut text = format!(
+                        "{}({}{}, numDescriptors = {}, space = {}, offset = {}",
+                        name, prefix, range.base_register, count, range.space, offset
+                    );
+                    if version == RootSignatureVersion::V1_1 {
+                        text += &format!(
+                            ", flags = {}",
+                            format_root_flags(range.flags, range.flags.bits())
+                        );
+                    }
+                    text + ")"
+                })
+                .collect();
+            elements.push(format!("visibility = {}", visibility));
+
+            format!("DescriptorTable({})", elements.join(", "))
+        }
+        RootParameterData::Constants32Bit(ref constants) => format!(
+            "RootConstants(num32BitConstants = {}, b{}, space = {}, visibility = {})",
+            constants.count, constants.register, constants.space, visibility
+        ),
+        RootParameterData::Cbv(ref x) => descriptor("CBV", "b", x),
+        RootParameterData::Srv(ref x) => descriptor("SRV", "t", x),
+        RootParameterData::Uav(ref x) => descriptor("UAV", "u", x),
+    }
+}
+
+fn format_static_sampler(sampler: &StaticSampler) -> String {
+    let mut text = format!(
+        "StaticSampler(s{}, filter = {}, addressU = {}, addressV = {}, addressW = {}, \
+         mipLODBias = {:?}f, maxAnisotropy = {}",
+        sampler.register,
+        get_filter_name(sampler.filter),
+        get_texture_address_mode_name(sampler.address_u),
+        get_texture_address_mode_name(sampler.address_v),
+        get_texture_address_mode_name(sampler.address_w),
+        sampler.mip_lod_bias,
+        sampler.max_anisotropy,
+    );
+    if sampler.comparison_func != ComparisonFunc::None {
+        text += &format!(
+            ", comparisonFunc = {}",
+            get_comparison_func_name(sampler.comparison_func)
+        );
+    }
+    text += &format!(
+        ", borderColor = {}, minLOD = {:?}f, maxLOD = {:?}f, space = {}, visibility = {})",
+        get_static_border_color_name(sampler.border_color),
+        sampler.min_lod,
+        sampler.max_lod,
+        sampler.space,
+        get_shader_visibility_name(sampler.visibility),
+    );
+
+    text
+}
+
+fn get_sm2_register_name(ty: Sm2RegisterType, register: u16, version: Sm2Version) -> String {
+    let prefix = match ty {
+        Sm2RegisterType::Temp => "r",
+        Sm2RegisterType::Input => "v",
+        Sm2RegisterType::Const => "c",
+        Sm2RegisterType::AddrOrTexture => match version.ty {
+            Sm2ShaderType::Vertex => "a",
+            Sm2ShaderType::Pixel => "t",
+        },
+        Sm2RegisterType::RastOut => {
+            return match register {
+                0 => "oPos",
+                1 => "oFog",
+                _ => "oPts",
+            }
+            .to_owned();
+        }
+        Sm2RegisterType::AttrOut => "oD",
+        Sm2RegisterType::TexCrdOutOrOutput if version.major >= 3 => "o",
+        Sm2RegisterType::TexCrdOutOrOutput => "oT",
+        Sm2RegisterType::ConstInt => "i",
+        Sm2RegisterType::ColorOut => "oC",
+        Sm2RegisterType::DepthOut => return "oDepth".to_owned(),
+        Sm2RegisterType::Sampler => "s",
+        Sm2RegisterType::Const2 => return format!("c{}", register as u32 + 2048),
+        Sm2RegisterType::Const3 => return format!("c{}", register as u32 + 4096),
+        Sm2RegisterType::Const4 => return format!("c{}", register as u32 + 6144),
+        Sm2RegisterType::ConstBool => "b",
+        Sm2RegisterType::Loop => return "aL".to_owned(),
+        Sm2RegisterType::TempFloat16 => "half",
+        Sm2RegisterType::MiscType => {
+            return match register {
+                0 => "vPos",
+                _ => "vFace",
+            }
+            .to_owned();
+        }
+        Sm2RegisterType::Label => "l",
+        Sm2RegisterType::Predicate => "p",
+    };
+
+    format!("{}{}", prefix, register)
+}
+
+fn get_sm2_comparison_name(controls: u8) -> &'static str {
+    match controls {
+        1 => "_gt",
+        2 => "_eq",
+        3 => "_ge",
+        4 => "_lt",
+        5 => "_ne",
+        6 => "_le",
+        _ => "",
+    }
+}
+
+fn format_sm2_mask(mask: u8) -> String {
+    if mask == 0xf {
+        return String::new();
+    }
+
+    let mut text = ".".to_owned();
+    for (idx, component) in ['x', 'y', 'z', 'w'].iter().enumerate() {
+        if mask & (1 << idx) != 0 {
+            text.push(*component);
+        }
+    }
+
+    text
+}
+
+fn format_sm2_source(src: &Sm2SourceParameter, version: Sm2Version) -> String {
+    let mut register = get_sm2_register_name(src.ty, src.register, version);
+    if let Some(ref relative) = src.relative {
+        register += &format!("[{}]", format_sm2_source(relative, version));
+    }
+
+    if !src.is_identity_swizzle() {
+        let components: Vec<char> = src
+            .swizzle
+            .iter()
+            .map(|&c| ['x', 'y', 'z', 'w'][c as usize])
+            .collect();
+        register.push('.');
+        if components.iter().all(|&c| c == components[0]) {
+            register.push(components[0]);
+        } else {
+            register.extend(components);
+        }
+    }
+
+    match src.modifier {
+        Sm2SourceModifier::None => register,
+        Sm2SourceModifier::Neg => format!("-{}", register),
+        Sm2SourceModifier::Bias => format!("{}_bias", register),
+        Sm2SourceModifier::BiasNeg => format!("-{}_bias", register),
+        Sm2SourceModifier::Sign => format!("{}_bx2", register),
+        Sm2SourceModifier::SignNeg => format!("-{}_bx2", register),
+        Sm2SourceModifier::Comp => format!("1-{}", register),
+        Sm2SourceModifier::X2 => format!("{}_x2", register),
+        Sm2SourceModifier::X2Neg => format!("-{}_x2", register),
+        Sm2SourceModifier::Dz => format!("{}_dz", register),
+        Sm2SourceModifier::Dw => format!("{}_dw", register),
+        Sm2SourceModifier::Abs => format!("{}_abs", register),
+        Sm2SourceModifier::AbsNeg => format!("-{}_abs", register),
+        Sm2SourceModifier::Not => format!("!{}", register),
+    }
+}
+
+fn format_sm2_destination(dst: &Sm2DestinationParameter, version: Sm2Version) -> String {
+    let mut register = get_sm2_register_name(dst.ty, dst.register, version);
+    if let Some(ref relative) = dst.relative {
+        register += &format!("[{}]", format_sm2_source(relative, version));
+    }
+
+    register + &format_sm2_mask(dst.write_mask)
+}
+
+/// Returns the suffixes the destination's result modifiers add to the opcode.
+fn get_sm2_result_modifier_suffix(dst: &Sm2DestinationParameter) -> String {
+    let mut text = match dst.shift {
+        0 => String::new(),
+        shift if shift > 0 => format!("_x{}", 1 << shift),
+        shift => format!("_d{}", 1 << -shift),
+    };
+    for (modifier, name) in [
+        (Sm2ResultModifier::SATURATE, "_sat"),
+        (Sm2ResultModifier::PARTIAL_PRECISION, "_pp"),
+        (Sm2ResultModifier::CENTROID, "_centroid"),
+    ] {
+        if dst.result_modifier.contains(modifier) {
+            text += name;
+        }
+    }
+
+    text
+}
+
+fn format_sm2_instruction(instruction: &Sm2Instruction, version: Sm2Version) -> Option<String> {
+    let text = match *instruction {
+        Sm2Instruction::Dcl(ref dcl) => {
+            let mut name = "dcl".to_owned();
+            if dcl.dst.ty == Sm2RegisterType::Sampler {
+                name += match dcl.get_texture_type() {
+                    Some(Sm2TextureType::Texture2D) => "_2d",
+                    Some(Sm2TextureType::Cube) => "_cube",
+                    Some(Sm2TextureType::Volume) => "_volume",
+                    _ => "",
+                };
+            } else if version.ty == Sm2ShaderType::Vertex || version.major >= 3 {
+                if let Some(usage) = dcl.get_usage() {
+                    name += &format!("_{:?}", usage).to_lowercase();
+                    if dcl.get_usage_index() != 0 {
+                        name += &dcl.get_usage_index().to_string();
+                    }
+                }
+            }
+
+            format!(
+                "{}{} {}",
+                name,
+                get_sm2_result_modifier_suffix(&dcl.dst),
+                format_sm2_destination(&dcl.dst, version)
+            )
+        }
+        Sm2Instruction::Def { ref dst, values } => format!(
+            "def {}, {}, {}, {}, {}",
+            format_sm2_destination(dst, version),
+            values[0],
+            values[1],
+            values[2],
+            values[3]
+        ),
+        Sm2Instruction::DefI { ref dst, values } => format!(
+            "defi {}, {}, {}, {}, {}",
+            format_sm2_destination(dst, version),
+            values[0],
+            values[1],
+            values[2],
+            values[3]
+        ),
+        Sm2Instruction::DefB { ref dst, value } => {
+            format!("defb {}, {}", format_sm2_destination(dst, version), value)
+        }
+        Sm2Instruction::Texture(ref operation)
+        | Sm2Instruction::Arithmetic(ref operation)
+        | Sm2Instruction::FlowControl(ref operation) => {
+            let mut name = match operation.opcode {
+                Sm2Opcode::Tex if version.major >= 2 => match operation.controls {
+                    1 => "texldp".to_owned(),
+                    2 => "texldb".to_owned(),
+                    _ => "texld".to_owned(),
+                },
+                Sm2Opcode::Ifc | Sm2Opcode::Breakc | Sm2Opcode::Setp => format!(
+                    "{:?}{}",
+                    operation.opcode,
+                    get_sm2_comparison_name(operation.controls)
+                )
+                .to_lowercase(),
+                opcode => format!("{:?}", opcode).to_lowercase(),
+            };
+
+            let mut operands = Vec::new();
+            if let Some(ref dst) = operation.dst {
+                name += &get_sm2_result_modifier_suffix(dst);
+                operands.push(format_sm2_destination(dst, version));
+            }
+            for src in &operation.src {
+                operands.push(format_sm2_source(src, version));
+            }
+
+            let mut text = String::new();
+            if operation.coissue {
+                text.push('+');
+            }
+            if let Some(ref predicate) = operation.predicate {
+                text += &format!("({}) ", format_sm2_source(predicate, version));
+            }
+            text += &name;
+            if !operands.is_empty() {
+                text += &format!(" {}", operands.join(", "));
+            }
+
+            text
+        }
+        Sm2Instruction::Comment(_) => return None,
+        Sm2Instruction::Phase => "phase".to_owned(),
+    };
+
+    Some(text)
+}
+
 impl DisasmConsumer {
-    fn new() -> Self {
+    fn new(source_lines: Vec<(u32, Vec<String>)>) -> Self {
         Self {
             out: term::stdout().unwrap(),
             indent: 0,
+            source_lines,
+            next_source_line: 0,
         }
     }
 
+    /// Writes the source lines that start at or before the instruction at
+    /// `offset`. The line table's offsets count the version and length
+    /// tokens that start the program, while `offset` doesn't.
+    fn write_source_lines(&mut self, offset: u32) {
+        let offset = offset + 8;
+
+        self.out.fg(COMMENT_COLOR).unwrap();
+        while let Some((line_offset, text)) = self.source_lines.get(self.next_source_line) {
+            if *line_offset > offset {
+                break;
+            }
+
+            for line in text {
+                writeln!(self.out, "{}", line).unwrap();
+            }
+            self.next_source_line += 1;
+        }
+        self.out.reset().unwrap();
+    }
+
     fn begin_instruction<'a>(&mut self, _opcode: OpcodeToken0<'a>, offset: u32, instruction: &str) {
         self.out.fg(COMMENT_COLOR).unwrap();
         write!(self.out, "{:#08x}: ", offset).unwrap();
@@ -84,10 +660,29 @@ impl DisasmConsumer {
         //    write!(self.out, "_sat").unwrap();
         //}
 
-        let mut ex = opcode.get_extended_opcode();
-        while let Some(opcode) = ex {
-            // TODO:
-            ex = opcode.get_extended_opcode();
+        if let Some([u, v, w]) = opcode.get_texel_offsets() {
+            write!(self.out, "_aoffimmi({},{},{})", u, v, w).unwrap();
+        }
+
+        if let Some((dimension, stride)) = opcode.get_extended_resource_dimension() {
+            let name = get_resource_dimension_name(dimension).trim_start_matches('_');
+            write!(self.out, "_indexable({}", name).unwrap();
+            if stride != 0 {
+                write!(self.out, ", stride={}", stride).unwrap();
+            }
+            write!(self.out, ")").unwrap();
+        }
+
+        if let Some(return_type) = opcode.get_extended_resource_return_type() {
+            write!(
+                self.out,
+                "({}, {}, {}, {})",
+                debug_name(return_type.get_return_type(ComponentName::X)),
+                debug_name(return_type.get_return_type(ComponentName::Y)),
+                debug_name(return_type.get_return_type(ComponentName::Z)),
+                debug_name(return_type.get_return_type(ComponentName::W))
+            )
+            .unwrap();
         }
 
         self.end_instruction();
@@ -96,31 +691,31 @@ impl DisasmConsumer {
     fn write_resource_return_type<'a>(
         &mut self,
         _opcode: OpcodeToken0<'a>,
-        return_type: ResourceReturnTypeToken0<'a>,
+        return_type: ResourceReturnTypeToken0,
     ) {
         write!(self.out, "(").unwrap();
         write!(
             self.out,
-            "{:?}, ",
-            return_type.get_return_type(ComponentName::X)
+            "{}, ",
+            debug_name(return_type.get_return_type(ComponentName::X))
         )
         .unwrap();
         write!(
             self.out,
-            "{:?}, ",
-            return_type.get_return_type(ComponentName::Y)
+            "{}, ",
+            debug_name(return_type.get_return_type(ComponentName::Y))
         )
         .unwrap();
         write!(
             self.out,
-            "{:?}, ",
-            return_type.get_return_type(ComponentName::Z)
+            "{}, ",
+            debug_name(return_type.get_return_type(ComponentName::Z))
         )
         .unwrap();
         write!(
             self.out,
-            "{:?}",
-            return_type.get_return_type(ComponentName::W)
+            "{}",
+            debug_name(return_type.get_return_type(ComponentName::W))
         )
         .unwrap();
         write!(self.out, ")").unwrap();
@@ -141,7 +736,12 @@ impl DisasmConsumer {
         }
     }
 
-    fn write_immediate(&mut self, imm: Immediate) {
+    fn write_immediate(&mut self, imm: Option<Immediate>) {
+        let imm = match imm {
+            Some(imm) => imm,
+            None => return,
+        };
+
         match imm {
             Immediate::U32(val) => {
                 write!(self.out, "{}", val).unwrap();
@@ -163,6 +763,79 @@ impl DisasmConsumer {
         }
     }
 
+    fn write_signature(&mut self, title: &str, chunk: &dxbc::dr::IOsgnChunk) {
+        self.out.fg(COMMENT_COLOR).unwrap();
+
+        writeln!(self.out, "//").unwrap();
+        writeln!(self.out, "// {}:", title).unwrap();
+        writeln!(self.out, "//").unwrap();
+        writeln!(
+            self.out,
+            "// Name                 Index   Mask Register SysValue  Format   Used"
+        )
+        .unwrap();
+        writeln!(
+            self.out,
+            "// -------------------- ----- ------ -------- -------- ------- ------"
+        )
+        .unwrap();
+
+        for elem in &chunk.elements {
+            writeln!(
+                self.out,
+                "// {:20} {:5} {:6} {:8} {:8?} {:7} {:6}",
+                elem.name,
+                elem.semantic_index,
+                elem.component_mask,
+                elem.register,
+                elem.semantic_type,
+                match elem.component_type {
+                    RegisterComponentType::Unknown => "NONE",
+                    RegisterComponentType::Uint32 => "uint",
+                    RegisterComponentType::Int32 => "int",
+                    RegisterComponentType::Float32 => "float",
+                },
+                elem.rw_mask,
+            )
+            .unwrap();
+        }
+        writeln!(self.out, "//").unwrap();
+        writeln!(self.out, "//").unwrap();
+
+        self.out.reset().unwrap();
+    }
+
+    fn write_level9(&mut self, chunk: &dxbc::dr::Aon9Chunk) {
+        self.out.fg(COMMENT_COLOR).unwrap();
+        writeln!(self.out, "//").unwrap();
+        writeln!(self.out, "// Level9 shader bytecode:").unwrap();
+        writeln!(self.out, "//").unwrap();
+
+        let version = chunk.version;
+        let shader_ty = match version.ty {
+            Sm2ShaderType::Vertex => "vs",
+            Sm2ShaderType::Pixel => "ps",
+        };
+        self.out.fg(OPCODE_COLOR).unwrap();
+        writeln!(
+            self.out,
+            "    {}_{}_{}",
+            shader_ty, version.major, version.minor
+        )
+        .unwrap();
+        self.out.reset().unwrap();
+
+        for instruction in &chunk.instructions {
+            if let Some(text) = format_sm2_instruction(instruction, version) {
+                writeln!(self.out, "    {}", text).unwrap();
+            }
+        }
+
+        self.out.fg(COMMENT_COLOR).unwrap();
+        writeln!(self.out, "//").unwrap();
+        self.out.reset().unwrap();
+    }
+
     fn write_operands<'a>(&mut self, operands: &[OperandToken0<'a>]) {
         let len = operands.len();
 
@@ -231,6 +904,27 @@ impl DisasmConsumer {
             OperandType::Resource => "t",
             OperandType::Sampler => "s",
             OperandType::ConstantBuffer => "cb",
+            OperandType::UnorderedAccessView => "u",
+            OperandType::ImmediateConstantBuffer => "icb",
+            OperandType::FunctionBody => "fb",
+            OperandType::FunctionTable => "ft",
+            OperandType::Interface => "fp",
+            OperandType::ThisPointer => "this",
+            OperandType::Stream => "m",
+            OperandType::InputGsinstanceid => "vGSInstanceID",
+            OperandType::InputPrimitiveId => "vPrim",
+            OperandType::InputControlPoint => "vicp",
+            OperandType::OutputControlPoint => "vocp",
+            OperandType::InputPatchConstant => "vpc",
+            OperandType::InputDomainPoint => "vDomain",
+            OperandType::OutputControlPointId => "vOutputControlPointID",
+            OperandType::InputForkInstanceId => "vForkInstanceID",
+            OperandType::InputJoinInstanceId => "vJoinInstanceID",
+            OperandType::ThreadGroupSharedMemory => "g",
+            OperandType::InputThreadId => "vThreadID",
+            OperandType::InputThreadGroupId => "vThreadGroupID",
+            OperandType::InputThreadIdInGroup => "vThreadIDInGroup",
+            OperandType::InputThreadIdInGroupFlattened => "vThreadIDInGroupFlattened",
 
             OperandType::Immediate32 | OperandType::Immediate64 => {
                 return;
@@ -295,7 +989,7 @@ impl DisasmConsumer {
         }
 
         match operand.get_component_select_mode() {
-            ComponentSelectMode::Mask => {
+            Some(ComponentSelectMode::Mask) => {
                 let mask = operand.get_component_mask();
 
                 if !mask.is_empty() {
@@ -320,7 +1014,7 @@ impl DisasmConsumer {
                 }
                 self.out.reset().unwrap();
             }
-            ComponentSelectMode::Swizzle => {
+            Some(ComponentSelectMode::Swizzle) => {
                 write!(self.out, ".").unwrap();
 
                 let swizzle = operand.get_component_swizzle();
@@ -330,12 +1024,13 @@ impl DisasmConsumer {
                 write_swizzle_component(self, swizzle.2);
                 write_swizzle_component(self, swizzle.3);
             }
-            ComponentSelectMode::Select1 => {
+            Some(ComponentSelectMode::Select1) => {
                 write!(self.out, ".").unwrap();
 
                 let swizzle = operand.get_component_swizzle();
                 write_swizzle_component(self, swizzle.0);
             }
+            None => {}
         }
 
         if let Some(operand) = operand.get_extended_operand() {
@@ -402,86 +1097,88 @@ impl Consumer for DisasmConsumer {
     }
 
     fn consume_isgn(&mut self, isgn: &dxbc::dr::IOsgnChunk) -> Action {
-        self.out.fg(COMMENT_COLOR).unwrap();
+        self.write_signature("Input signature", isgn);
 
-        writeln!(self.out, "//").unwrap();
-        writeln!(self.out, "// Input signature:").unwrap();
-        writeln!(self.out, "//").unwrap();
-        writeln!(
-            self.out,
-            "// Name                 Index   Mask Register SysValue  Format   Used"
-        )
-        .unwrap();
-        writeln!(
-            self.out,
-            "// -------------------- ----- ------ -------- -------- ------- ------"
-        )
-        .unwrap();
+        Action::Continue
+    }
 
-        for elem in &isgn.elements {
-            writeln!(
-                self.out,
-                "// {:20} {:5} {:6} {:8} {:8?} {:7} {:6}",
-                elem.name,
-                elem.semantic_index,
-                elem.component_mask,
-                elem.register,
-                elem.semantic_type,
-                match elem.component_type {
-                    RegisterComponentType::Unknown => "NONE",
-                    RegisterComponentType::Uint32 => "uint",
-                    RegisterComponentType::Int32 => "int",
-                    RegisterComponentType::Float32 => "float",
-                },
-                elem.rw_mask,
-            )
-            .unwrap();
-        }
-        writeln!(self.out, "//").unwrap();
-        writeln!(self.out, "//").unwrap();
+    fn consume_osgn(&mut self, osgn: &dxbc::dr::IOsgnChunk) -> Action {
+        self.write_signature("Output signature", osgn);
 
-        self.out.reset().unwrap();
+        Action::Continue
+    }
+
+    fn consume_isg1(&mut self, isg1: &dxbc::dr::IOsgnChunk) -> Action {
+        self.write_signature("Input signature", isg1);
 
         Action::Continue
     }
 
-    fn consume_osgn(&mut self, osgn: &dxbc::dr::IOsgnChunk) -> Action {
-        self.out.fg(COMMENT_COLOR).unwrap();
+    fn consume_osg1(&mut self, osg1: &dxbc::dr::IOsgnChunk) -> Action {
+        self.write_signature("Output signature", osg1);
 
-        writeln!(self.out, "//").unwrap();
-        writeln!(self.out, "// Output signature:").unwrap();
-        writeln!(self.out, "//").unwrap();
-        writeln!(
-            self.out,
-            "// Name                 Index   Mask Register SysValue  Format   Used"
-        )
-        .unwrap();
-        writeln!(
+        Action::Continue
+    }
+
+    fn consume_osg5(&mut self, osg5: &dxbc::dr::IOsgnChunk) -> Action {
+        self.write_signature("Output signature", osg5);
+
+        Action::Continue
+    }
+
+    fn consume_pcsg(&mut self, pcsg: &dxbc::dr::IOsgnChunk) -> Action {
+        self.write_signature("Patch Constant signature", pcsg);
+
+        Action::Continue
+    }
+
+    fn consume_sfi0(&mut self, sfi0: &dxbc::dr::Sfi0Chunk) -> Action {
+        if sfi0.flags.is_empty() {
+            return Action::Continue;
+        }
+
+        self.out.fg(COMMENT_COLOR).unwrap();
+
+        writeln!(
             self.out,
-            "// -------------------- ----- ------ -------- -------- ------- ------"
+            "// Note: shader requires additional functionality:"
         )
         .unwrap();
+        for &(flag, name) in dxbc::disasm::FEATURES {
+            if sfi0.flags.contains(flag) {
+                writeln!(self.out, "//       {}", name).unwrap();
+            }
+        }
+        writeln!(self.out, "//").unwrap();
+        writeln!(self.out, "//").unwrap();
 
-        for elem in &osgn.elements {
-            writeln!(
-                self.out,
-                "// {:20} {:5} {:6} {:8} {:8?} {:7} {:6}",
-                elem.name,
-                elem.semantic_index,
-                elem.component_mask,
-                elem.register,
-                elem.semantic_type,
-                match elem.component_type {
-                    RegisterComponentType::Unknown => "NONE",
-                    RegisterComponentType::Uint32 => "uint",
-                    RegisterComponentType::Int32 => "int",
-                    RegisterComponentType::Float32 => "float",
-                },
-                elem.rw_mask,
-            )
-            .unwrap();
+        self.out.reset().unwrap();
+
+        Action::Continue
+    }
+
+    fn consume_rts0(&mut self, rts0: &dxbc::dr::Rts0Chunk) -> Action {
+        self.out.fg(COMMENT_COLOR).unwrap();
+
+        let mut elements = vec![format!(
+            "RootFlags({})",
+            format_root_flags(rts0.flags, rts0.flags.bits())
+        )];
+        for parameter in &rts0.parameters {
+            elements.push(format_root_parameter(parameter, rts0.version));
         }
+        for sampler in &rts0.static_samplers {
+            elements.push(format_static_sampler(sampler));
+        }
+
         writeln!(self.out, "//").unwrap();
+        writeln!(self.out, "// Root signature ({:?}):", rts0.version).unwrap();
+        writeln!(self.out, "//").unwrap();
+        let len = elements.len();
+        for (idx, element) in elements.iter().enumerate() {
+            let separator = if idx + 1 != len { ", " } else { "" };
+            writeln!(self.out, "// \"{}{}\"", element, separator).unwrap();
+        }
         writeln!(self.out, "//").unwrap();
 
         self.out.reset().unwrap();
@@ -489,6 +1186,34 @@ impl Consumer for DisasmConsumer {
         Action::Continue
     }
 
+    fn consume_aon9(&mut self, aon9: &dxbc::dr::Aon9Chunk) -> Action {
+        self.write_level9(aon9);
+
+        Action::Continue
+    }
+
+    fn consume_xnas(&mut self, xnas: &dxbc::dr::Aon9Chunk) -> Action {
+        self.write_level9(xnas);
+
+        Action::Continue
+    }
+
+    fn consume_xnap(&mut self, xnap: &dxbc::dr::Aon9Chunk) -> Action {
+        self.write_level9(xnap);
+
+        Action::Continue
+    }
+
+    fn consume_unknown_chunk(&mut self, fourcc: &[u8; 4], data: &[u8]) -> Action {
+        eprintln!(
+            "Unimplemented chunk type '{}' ({} bytes)",
+            String::from_utf8_lossy(fourcc),
+            data.len(),
+        );
+
+        Action::Continue
+    }
+
     fn consume_shex(&mut self, _osgn: &dxbc::dr::ShexHeader) -> Action {
         Action::Continue
     }
@@ -500,6 +1225,8 @@ impl Consumer for DisasmConsumer {
     ) -> Action {
         use dxbc::dr::Operands::*;
 
+        self.write_source_lines(offset);
+
         let opcode = instruction.opcode;
 
         match instruction.operands {
@@ -533,7 +1260,9 @@ impl Consumer for DisasmConsumer {
                 write!(
                     self.out,
                     "{} ",
-                    get_interpolation_mode_name(opcode.get_interpolation_mode())
+                    opcode
+                        .get_interpolation_mode()
+                        .map_or("unknown", get_interpolation_mode_name)
                 )
                 .unwrap();
 
@@ -556,7 +1285,9 @@ impl Consumer for DisasmConsumer {
                 write!(
                     self.out,
                     "{} ",
-                    get_interpolation_mode_name(opcode.get_interpolation_mode())
+                    opcode
+                        .get_interpolation_mode()
+                        .map_or("unknown", get_interpolation_mode_name)
                 )
                 .unwrap();
                 match input.operand.get_operand_type() {
@@ -575,7 +1306,9 @@ impl Consumer for DisasmConsumer {
                 write!(
                     self.out,
                     " {}",
-                    get_name_token_name(input.get_system_name())
+                    input
+                        .get_system_name()
+                        .map_or("unknown", get_name_token_name)
                 )
                 .unwrap();
 
@@ -586,7 +1319,9 @@ impl Consumer for DisasmConsumer {
                 write!(
                     self.out,
                     "{} ",
-                    get_interpolation_mode_name(opcode.get_interpolation_mode())
+                    opcode
+                        .get_interpolation_mode()
+                        .map_or("unknown", get_interpolation_mode_name)
                 )
                 .unwrap();
                 match input.operand.get_operand_type() {
@@ -605,7 +1340,9 @@ impl Consumer for DisasmConsumer {
                 write!(
                     self.out,
                     " {}",
-                    get_name_token_name(input.get_system_name())
+                    input
+                        .get_system_name()
+                        .map_or("unknown", get_name_token_name)
                 )
                 .unwrap();
                 writeln!(self.out).unwrap();
@@ -633,14 +1370,9 @@ impl Consumer for DisasmConsumer {
                 write!(
                     self.out,
                     "{}",
-                    match opcode.get_resource_dimension() {
-                        ResourceDimension::Texture1D => "_texture1d",
-                        ResourceDimension::Texture2D => "_texture2d",
-                        ResourceDimension::Texture3D => "_texture3d",
-                        ResourceDimension::TextureCube => "_texturecube",
-                        ResourceDimension::Texture2DMS => "_texture2dms",
-                        _ => "",
-                    }
+                    opcode
+                        .get_resource_dimension()
+                        .map_or("", get_resource_dimension_name)
                 )
                 .unwrap();
                 self.end_instruction();
@@ -654,7 +1386,7 @@ impl Consumer for DisasmConsumer {
                 self.write_instruction(opcode, offset, "dcl_sampler");
 
                 write!(self.out, "s{}, ", sampler.get_register()).unwrap();
-                writeln!(self.out, "{:?}", opcode.get_sampler_mode()).unwrap();
+                writeln!(self.out, "{}", debug_name(opcode.get_sampler_mode())).unwrap();
 
                 // TODO: mode
             }
@@ -677,7 +1409,7 @@ impl Consumer for DisasmConsumer {
                 self.write_instruction(opcode, offset, "dcl_output_siv");
                 write!(self.out, "o{}.", siv.get_output_register()).unwrap();
                 self.write_mask(siv.register.get_component_mask());
-                writeln!(self.out, ", {:?}", siv.get_system_name()).unwrap();
+                writeln!(self.out, ", {}", debug_name(siv.get_system_name())).unwrap();
             }
             Add(add) => {
                 self.write_instruction(opcode, offset, "add");
@@ -792,6 +1524,839 @@ impl Consumer for DisasmConsumer {
                 self.write_instruction(opcode, offset, "ret");
                 writeln!(self.out).unwrap();
             }
+            Ieq(x) => {
+                self.write_instruction(opcode, offset, "ieq");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Ilt(x) => {
+                self.write_instruction(opcode, offset, "ilt");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Ine(x) => {
+                self.write_instruction(opcode, offset, "ine");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Uge(x) => {
+                self.write_instruction(opcode, offset, "uge");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Ult(x) => {
+                self.write_instruction(opcode, offset, "ult");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Not(x) => {
+                self.write_instruction(opcode, offset, "not");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Xor(x) => {
+                self.write_instruction(opcode, offset, "xor");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DerivRtx(x) => {
+                self.write_instruction(opcode, offset, "deriv_rtx");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DerivRty(x) => {
+                self.write_instruction(opcode, offset, "deriv_rty");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Imad(x) => {
+                self.write_instruction(opcode, offset, "imad");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            Imax(x) => {
+                self.write_instruction(opcode, offset, "imax");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Imin(x) => {
+                self.write_instruction(opcode, offset, "imin");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Imul(x) => {
+                self.write_instruction(opcode, offset, "imul");
+                self.write_operands(&[x.dst_hi, x.dst_lo, x.a, x.b]);
+            }
+            Ineg(x) => {
+                self.write_instruction(opcode, offset, "ineg");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Ishl(x) => {
+                self.write_instruction(opcode, offset, "ishl");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Ishr(x) => {
+                self.write_instruction(opcode, offset, "ishr");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Udiv(x) => {
+                self.write_instruction(opcode, offset, "udiv");
+                self.write_operands(&[x.dst_quot, x.dst_rem, x.a, x.b]);
+            }
+            Umad(x) => {
+                self.write_instruction(opcode, offset, "umad");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            Umax(x) => {
+                self.write_instruction(opcode, offset, "umax");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Umin(x) => {
+                self.write_instruction(opcode, offset, "umin");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Umul(x) => {
+                self.write_instruction(opcode, offset, "umul");
+                self.write_operands(&[x.dst_hi, x.dst_lo, x.a, x.b]);
+            }
+            Ushr(x) => {
+                self.write_instruction(opcode, offset, "ushr");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            Ftoi(x) => {
+                self.write_instruction(opcode, offset, "ftoi");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Call(x) => {
+                self.write_instruction(opcode, offset, "call");
+                self.write_operands(&[x.label]);
+            }
+            Label(x) => {
+                self.write_instruction(opcode, offset, "label");
+                self.write_operands(&[x.label]);
+            }
+            SampleB(x) => {
+                self.write_instruction(opcode, offset, "sample_b");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_resource,
+                    x.src_sampler,
+                    x.src_bias,
+                ]);
+            }
+            SampleC(x) => {
+                self.write_instruction(opcode, offset, "sample_c");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_resource,
+                    x.src_sampler,
+                    x.src_reference,
+                ]);
+            }
+            SampleCLz(x) => {
+                self.write_instruction(opcode, offset, "sample_c_lz");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_resource,
+                    x.src_sampler,
+                    x.src_reference,
+                ]);
+            }
+            SampleD(x) => {
+                self.write_instruction(opcode, offset, "sample_d");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_resource,
+                    x.src_sampler,
+                    x.src_ddx,
+                    x.src_ddy,
+                ]);
+            }
+            Ld(x) => {
+                self.write_instruction(opcode, offset, "ld");
+                self.write_operands(&[x.dst, x.src_address, x.src_resource]);
+            }
+            LdMs(x) => {
+                self.write_instruction(opcode, offset, "ld_ms");
+                self.write_operands(&[x.dst, x.src_address, x.src_resource, x.src_sample_index]);
+            }
+            Lod(x) => {
+                self.write_instruction(opcode, offset, "lod");
+                self.write_operands(&[x.dst, x.src_address, x.src_resource, x.src_sampler]);
+            }
+            Gather4(x) => {
+                self.write_instruction(opcode, offset, "gather4");
+                self.write_operands(&[x.dst, x.src_address, x.src_resource, x.src_sampler]);
+            }
+            SamplePos(x) => {
+                self.write_instruction(opcode, offset, "samplepos");
+                self.write_operands(&[x.dst, x.src_resource, x.src_sample_index]);
+            }
+            SampleInfo(x) => {
+                self.write_instruction(opcode, offset, "sampleinfo");
+                self.write_operands(&[x.dst, x.src_resource]);
+            }
+            ContinueC(x) => {
+                self.begin_instruction(opcode, offset, "continuec");
+                write!(
+                    self.out,
+                    "_{}",
+                    get_test_boolean_name(opcode.get_test_type())
+                )
+                .unwrap();
+                self.end_instruction();
+
+                self.write_operands(&[x.src]);
+            }
+            RetC(x) => {
+                self.begin_instruction(opcode, offset, "retc");
+                write!(
+                    self.out,
+                    "_{}",
+                    get_test_boolean_name(opcode.get_test_type())
+                )
+                .unwrap();
+                self.end_instruction();
+
+                self.write_operands(&[x.src]);
+            }
+            Discard(x) => {
+                self.begin_instruction(opcode, offset, "discard");
+                write!(
+                    self.out,
+                    "_{}",
+                    get_test_boolean_name(opcode.get_test_type())
+                )
+                .unwrap();
+                self.end_instruction();
+
+                self.write_operands(&[x.src]);
+            }
+            CallC(x) => {
+                self.begin_instruction(opcode, offset, "callc");
+                write!(
+                    self.out,
+                    "_{}",
+                    get_test_boolean_name(opcode.get_test_type())
+                )
+                .unwrap();
+                self.end_instruction();
+
+                self.write_operands(&[x.src, x.label]);
+            }
+            ResInfo(x) => {
+                self.begin_instruction(opcode, offset, "resinfo");
+                match opcode.get_resinfo_return_type() {
+                    Some(ResInfoReturnType::Float) => {}
+                    Some(ResInfoReturnType::RcpFloat) => write!(self.out, "_rcpFloat").unwrap(),
+                    Some(ResInfoReturnType::UInt) => write!(self.out, "_uint").unwrap(),
+                    None => write!(self.out, "_unknown").unwrap(),
+                }
+                self.end_instruction();
+
+                self.write_operands(&[x.dst, x.src_mip_level, x.src_resource]);
+            }
+            Switch(x) => {
+                self.write_instruction(opcode, offset, "switch");
+                self.write_operands(&[x.src]);
+
+                self.indent += 1;
+            }
+            Case(x) => {
+                self.indent -= 1;
+                self.write_instruction(opcode, offset, "case");
+                self.write_operands(&[x.src]);
+                self.indent += 1;
+            }
+            Default => {
+                self.indent -= 1;
+                self.write_instruction(opcode, offset, "default");
+                self.indent += 1;
+
+                writeln!(self.out).unwrap();
+            }
+            EndSwitch => {
+                self.indent -= 1;
+                self.write_instruction(opcode, offset, "endswitch");
+
+                writeln!(self.out).unwrap();
+            }
+            Continue => {
+                self.write_instruction(opcode, offset, "continue");
+
+                writeln!(self.out).unwrap();
+            }
+            Nop => {
+                self.write_instruction(opcode, offset, "nop");
+
+                writeln!(self.out).unwrap();
+            }
+            Emit => {
+                self.write_instruction(opcode, offset, "emit");
+
+                writeln!(self.out).unwrap();
+            }
+            Cut => {
+                self.write_instruction(opcode, offset, "cut");
+
+                writeln!(self.out).unwrap();
+            }
+            EmitThenCut => {
+                self.write_instruction(opcode, offset, "emit_then_cut");
+
+                writeln!(self.out).unwrap();
+            }
+            DclThreadGroup(x) => {
+                self.write_instruction(opcode, offset, "dcl_thread_group");
+
+                writeln!(self.out, "{}, {}, {}", x.x, x.y, x.z).unwrap();
+            }
+            DclTgsmRaw(x) => {
+                self.write_instruction(opcode, offset, "dcl_tgsm_raw");
+
+                writeln!(self.out, "g{}, {}", x.get_register(), x.byte_count).unwrap();
+            }
+            DclTgsmStructured(x) => {
+                self.write_instruction(opcode, offset, "dcl_tgsm_structured");
+
+                writeln!(self.out, "g{}, {}, {}", x.get_register(), x.stride, x.count).unwrap();
+            }
+            DclUavTyped(uav) => {
+                self.begin_instruction(opcode, offset, "dcl_uav_typed");
+                write!(
+                    self.out,
+                    "{}",
+                    opcode
+                        .get_resource_dimension()
+                        .map_or("", get_resource_dimension_name)
+                )
+                .unwrap();
+                if opcode.is_globally_coherent() {
+                    write!(self.out, "_glc").unwrap();
+                }
+                self.end_instruction();
+
+                self.write_resource_return_type(opcode, uav.return_type);
+                writeln!(self.out, " u{}", uav.get_register()).unwrap();
+            }
+            DclUavRaw(uav) => {
+                self.begin_instruction(opcode, offset, "dcl_uav_raw");
+                if opcode.is_globally_coherent() {
+                    write!(self.out, "_glc").unwrap();
+                }
+                self.end_instruction();
+
+                writeln!(self.out, "u{}", uav.get_register()).unwrap();
+            }
+            DclUavStructured(uav) => {
+                self.begin_instruction(opcode, offset, "dcl_uav_structured");
+                if opcode.is_globally_coherent() {
+                    write!(self.out, "_glc").unwrap();
+                }
+                if opcode.has_order_preserving_counter() {
+                    write!(self.out, "_opc").unwrap();
+                }
+                self.end_instruction();
+
+                writeln!(self.out, "u{}, {}", uav.get_register(), uav.stride).unwrap();
+            }
+            DclResourceRaw(resource) => {
+                self.write_instruction(opcode, offset, "dcl_resource_raw");
+
+                writeln!(self.out, "t{}", resource.get_register()).unwrap();
+            }
+            DclResourceStructured(resource) => {
+                self.write_instruction(opcode, offset, "dcl_resource_structured");
+
+                writeln!(
+                    self.out,
+                    "t{}, {}",
+                    resource.get_register(),
+                    resource.stride
+                )
+                .unwrap();
+            }
+            LdRaw(x) => {
+                self.write_instruction(opcode, offset, "ld_raw");
+                self.write_operands(&[x.dst, x.src_byte_offset, x.src_resource]);
+            }
+            LdStructured(x) => {
+                self.write_instruction(opcode, offset, "ld_structured");
+                self.write_operands(&[x.dst, x.src_address, x.src_byte_offset, x.src_resource]);
+            }
+            LdUavTyped(x) => {
+                self.write_instruction(opcode, offset, "ld_uav_typed");
+                self.write_operands(&[x.dst, x.src_address, x.src_uav]);
+            }
+            StoreRaw(x) => {
+                self.write_instruction(opcode, offset, "store_raw");
+                self.write_operands(&[x.dst, x.src_byte_offset, x.src]);
+            }
+            StoreStructured(x) => {
+                self.write_instruction(opcode, offset, "store_structured");
+                self.write_operands(&[x.dst, x.src_address, x.src_byte_offset, x.src]);
+            }
+            StoreUavTyped(x) => {
+                self.write_instruction(opcode, offset, "store_uav_typed");
+                self.write_operands(&[x.dst, x.src_address, x.src]);
+            }
+            Sync(flags) => {
+                self.begin_instruction(opcode, offset, "sync");
+                if flags.contains(SyncFlags::UNORDERED_ACCESS_VIEW_MEMORY_GLOBAL) {
+                    write!(self.out, "_uglobal").unwrap();
+                }
+                if flags.contains(SyncFlags::UNORDERED_ACCESS_VIEW_MEMORY_GROUP) {
+                    write!(self.out, "_ugroup").unwrap();
+                }
+                if flags.contains(SyncFlags::THREAD_GROUP_SHARED_MEMORY) {
+                    write!(self.out, "_g").unwrap();
+                }
+                if flags.contains(SyncFlags::THREADS_IN_GROUP) {
+                    write!(self.out, "_t").unwrap();
+                }
+                self.end_instruction();
+
+                writeln!(self.out).unwrap();
+            }
+            AtomicAnd(x) => {
+                self.write_instruction(opcode, offset, "atomic_and");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicOr(x) => {
+                self.write_instruction(opcode, offset, "atomic_or");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicXor(x) => {
+                self.write_instruction(opcode, offset, "atomic_xor");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicIAdd(x) => {
+                self.write_instruction(opcode, offset, "atomic_iadd");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicIMax(x) => {
+                self.write_instruction(opcode, offset, "atomic_imax");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicIMin(x) => {
+                self.write_instruction(opcode, offset, "atomic_imin");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicUMax(x) => {
+                self.write_instruction(opcode, offset, "atomic_umax");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicUMin(x) => {
+                self.write_instruction(opcode, offset, "atomic_umin");
+                self.write_operands(&[x.dst, x.dst_address, x.src]);
+            }
+            AtomicCmpStore(x) => {
+                self.write_instruction(opcode, offset, "atomic_cmp_store");
+                self.write_operands(&[x.dst, x.dst_address, x.src_compare, x.src_value]);
+            }
+            ImmAtomicAlloc(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_alloc");
+                self.write_operands(&[x.dst, x.dst_uav]);
+            }
+            ImmAtomicConsume(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_consume");
+                self.write_operands(&[x.dst, x.dst_uav]);
+            }
+            ImmAtomicIAdd(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_iadd");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicAnd(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_and");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicOr(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_or");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicXor(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_xor");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicExch(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_exch");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicIMax(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_imax");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicIMin(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_imin");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicUMax(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_umax");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicUMin(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_umin");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src]);
+            }
+            ImmAtomicCmpExch(x) => {
+                self.write_instruction(opcode, offset, "imm_atomic_cmp_exch");
+                self.write_operands(&[x.dst, x.dst_uav, x.dst_address, x.src_compare, x.src_value]);
+            }
+            HsDecls => {
+                self.write_instruction(opcode, offset, "hs_decls");
+
+                writeln!(self.out).unwrap();
+            }
+            HsControlPointPhase => {
+                self.write_instruction(opcode, offset, "hs_control_point_phase");
+
+                writeln!(self.out).unwrap();
+            }
+            HsForkPhase => {
+                self.write_instruction(opcode, offset, "hs_fork_phase");
+
+                writeln!(self.out).unwrap();
+            }
+            HsJoinPhase => {
+                self.write_instruction(opcode, offset, "hs_join_phase");
+
+                writeln!(self.out).unwrap();
+            }
+            DclInputControlPointCount(x) => {
+                self.write_instruction(opcode, offset, "dcl_input_control_point_count");
+
+                writeln!(self.out, "{}", x.count).unwrap();
+            }
+            DclOutputControlPointCount(x) => {
+                self.write_instruction(opcode, offset, "dcl_output_control_point_count");
+
+                writeln!(self.out, "{}", x.count).unwrap();
+            }
+            DclTessellatorDomain(x) => {
+                self.write_instruction(opcode, offset, "dcl_tessellator_domain");
+
+                writeln!(self.out, "{}", get_tessellator_domain_name(x.domain)).unwrap();
+            }
+            DclTessellatorPartitioning(x) => {
+                self.write_instruction(opcode, offset, "dcl_tessellator_partitioning");
+
+                writeln!(
+                    self.out,
+                    "{}",
+                    get_tessellator_partitioning_name(x.partitioning)
+                )
+                .unwrap();
+            }
+            DclTessellatorOutputPrimitive(x) => {
+                self.write_instruction(opcode, offset, "dcl_tessellator_output_primitive");
+
+                writeln!(
+                    self.out,
+                    "{}",
+                    get_tessellator_output_primitive_name(x.output_primitive)
+                )
+                .unwrap();
+            }
+            DclHsMaxTessFactor(x) => {
+                self.write_instruction(opcode, offset, "dcl_hs_max_tessfactor");
+
+                writeln!(self.out, "l({:.6})", x.max_tess_factor).unwrap();
+            }
+            DclHsForkPhaseInstanceCount(x) => {
+                self.write_instruction(opcode, offset, "dcl_hs_fork_phase_instance_count");
+
+                writeln!(self.out, "{}", x.instance_count).unwrap();
+            }
+            DclHsJoinPhaseInstanceCount(x) => {
+                self.write_instruction(opcode, offset, "dcl_hs_join_phase_instance_count");
+
+                writeln!(self.out, "{}", x.instance_count).unwrap();
+            }
+            DclGsInputPrimitive(x) => {
+                self.write_instruction(opcode, offset, "dcl_inputprimitive");
+
+                writeln!(self.out, "{}", get_primitive_name(x.primitive)).unwrap();
+            }
+            DclGsOutputPrimitiveTopology(x) => {
+                self.write_instruction(opcode, offset, "dcl_outputtopology");
+
+                writeln!(self.out, "{}", get_primitive_topology_name(x.topology)).unwrap();
+            }
+            DclMaxOutputVertexCount(x) => {
+                self.write_instruction(opcode, offset, "dcl_maxout");
+
+                writeln!(self.out, "{}", x.count).unwrap();
+            }
+            DclGsInstanceCount(x) => {
+                self.write_instruction(opcode, offset, "dcl_gsinstances");
+
+                writeln!(self.out, "{}", x.instance_count).unwrap();
+            }
+            DclStream(x) => {
+                self.write_instruction(opcode, offset, "dcl_stream");
+                self.write_operands(&[x.operand]);
+            }
+            EmitStream(x) => {
+                self.write_instruction(opcode, offset, "emit_stream");
+                self.write_operands(&[x.stream]);
+            }
+            CutStream(x) => {
+                self.write_instruction(opcode, offset, "cut_stream");
+                self.write_operands(&[x.stream]);
+            }
+            EmitThenCutStream(x) => {
+                self.write_instruction(opcode, offset, "emit_then_cut_stream");
+                self.write_operands(&[x.stream]);
+            }
+            DEq(x) => {
+                self.write_instruction(opcode, offset, "deq");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DGe(x) => {
+                self.write_instruction(opcode, offset, "dge");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DLt(x) => {
+                self.write_instruction(opcode, offset, "dlt");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DNe(x) => {
+                self.write_instruction(opcode, offset, "dne");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DAdd(x) => {
+                self.write_instruction(opcode, offset, "dadd");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DMax(x) => {
+                self.write_instruction(opcode, offset, "dmax");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DMin(x) => {
+                self.write_instruction(opcode, offset, "dmin");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DMul(x) => {
+                self.write_instruction(opcode, offset, "dmul");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DDiv(x) => {
+                self.write_instruction(opcode, offset, "ddiv");
+                self.write_operands(&[x.dst, x.a, x.b]);
+            }
+            DFma(x) => {
+                self.write_instruction(opcode, offset, "dfma");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            DRcp(x) => {
+                self.write_instruction(opcode, offset, "drcp");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Rcp(x) => {
+                self.write_instruction(opcode, offset, "rcp");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Bfi(x) => {
+                self.write_instruction(opcode, offset, "bfi");
+                self.write_operands(&[x.dst, x.a, x.b, x.c, x.d]);
+            }
+            UBfe(x) => {
+                self.write_instruction(opcode, offset, "ubfe");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            IBfe(x) => {
+                self.write_instruction(opcode, offset, "ibfe");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            BfRev(x) => {
+                self.write_instruction(opcode, offset, "bfrev");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            CountBits(x) => {
+                self.write_instruction(opcode, offset, "countbits");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            FirstBitHi(x) => {
+                self.write_instruction(opcode, offset, "firstbit_hi");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            FirstBitLo(x) => {
+                self.write_instruction(opcode, offset, "firstbit_lo");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            FirstBitShi(x) => {
+                self.write_instruction(opcode, offset, "firstbit_shi");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            UAddC(x) => {
+                self.write_instruction(opcode, offset, "uaddc");
+                self.write_operands(&[x.dst, x.dst_carry, x.a, x.b]);
+            }
+            USubB(x) => {
+                self.write_instruction(opcode, offset, "usubb");
+                self.write_operands(&[x.dst, x.dst_borrow, x.a, x.b]);
+            }
+            Msad(x) => {
+                self.write_instruction(opcode, offset, "msad");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            DMov(x) => {
+                self.write_instruction(opcode, offset, "dmov");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DMovC(x) => {
+                self.write_instruction(opcode, offset, "dmovc");
+                self.write_operands(&[x.dst, x.a, x.b, x.c]);
+            }
+            Dtof(x) => {
+                self.write_instruction(opcode, offset, "dtof");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Ftod(x) => {
+                self.write_instruction(opcode, offset, "ftod");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Dtoi(x) => {
+                self.write_instruction(opcode, offset, "dtoi");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Dtou(x) => {
+                self.write_instruction(opcode, offset, "dtou");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Itod(x) => {
+                self.write_instruction(opcode, offset, "itod");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Utod(x) => {
+                self.write_instruction(opcode, offset, "utod");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            F32ToF16(x) => {
+                self.write_instruction(opcode, offset, "f32tof16");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            F16ToF32(x) => {
+                self.write_instruction(opcode, offset, "f16tof32");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DerivRtxCoarse(x) => {
+                self.write_instruction(opcode, offset, "deriv_rtx_coarse");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DerivRtxFine(x) => {
+                self.write_instruction(opcode, offset, "deriv_rtx_fine");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DerivRtyCoarse(x) => {
+                self.write_instruction(opcode, offset, "deriv_rty_coarse");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DerivRtyFine(x) => {
+                self.write_instruction(opcode, offset, "deriv_rty_fine");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            Gather4C(x) => {
+                self.write_instruction(opcode, offset, "gather4_c");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_resource,
+                    x.src_sampler,
+                    x.src_reference,
+                ]);
+            }
+            Gather4Po(x) => {
+                self.write_instruction(opcode, offset, "gather4_po");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_offset,
+                    x.src_resource,
+                    x.src_sampler,
+                ]);
+            }
+            Gather4PoC(x) => {
+                self.write_instruction(opcode, offset, "gather4_po_c");
+                self.write_operands(&[
+                    x.dst,
+                    x.src_address,
+                    x.src_offset,
+                    x.src_resource,
+                    x.src_sampler,
+                    x.src_reference,
+                ]);
+            }
+            BufInfo(x) => {
+                self.write_instruction(opcode, offset, "bufinfo");
+                self.write_operands(&[x.dst, x.src_resource]);
+            }
+            EvalSnapped(x) => {
+                self.write_instruction(opcode, offset, "eval_snapped");
+                self.write_operands(&[x.dst, x.src, x.src_offset]);
+            }
+            EvalSampleIndex(x) => {
+                self.write_instruction(opcode, offset, "eval_sample_index");
+                self.write_operands(&[x.dst, x.src, x.src_sample_index]);
+            }
+            EvalCentroid(x) => {
+                self.write_instruction(opcode, offset, "eval_centroid");
+                self.write_operands(&[x.dst, x.src]);
+            }
+            DclFunctionBody(x) => {
+                self.write_instruction(opcode, offset, "dcl_function_body");
+
+                writeln!(self.out, "fb{}", x.index).unwrap();
+            }
+            DclFunctionTable(x) => {
+                self.write_instruction(opcode, offset, "dcl_function_table");
+
+                let bodies: Vec<String> =
+                    x.bodies.iter().map(|body| format!("fb{}", body)).collect();
+                writeln!(self.out, "ft{} = {{{}}}", x.index, bodies.join(", ")).unwrap();
+            }
+            DclInterface(x) => {
+                self.begin_instruction(opcode, offset, "dcl_interface");
+                if x.dynamically_indexed {
+                    write!(self.out, "_dynamicindexed").unwrap();
+                }
+                self.end_instruction();
+
+                let tables: Vec<String> = x
+                    .tables
+                    .iter()
+                    .map(|table| format!("ft{}", table))
+                    .collect();
+                writeln!(
+                    self.out,
+                    "fp{}[{}][{}] = {{{}}}",
+                    x.index,
+                    x.array_length,
+                    x.body_count,
+                    tables.join(", ")
+                )
+                .unwrap();
+            }
+            InterfaceCall(x) => {
+                self.write_instruction(opcode, offset, "fcall");
+
+                self.write_operand(&x.interface);
+                writeln!(self.out, "[{}]", x.function_index).unwrap();
+            }
+            DclImmediateConstantBuffer(x) => {
+                self.write_instruction(opcode, offset, "dcl_immediateConstantBuffer");
+
+                let constants: Vec<String> = x
+                    .constants
+                    .iter()
+                    .map(|constant| {
+                        let values: Vec<String> = constant
+                            .iter()
+                            .map(|&val| format!("{:.6}", f32::from_bits(val)))
+                            .collect();
+                        format!("{{ {} }}", values.join(", "))
+                    })
+                    .collect();
+                writeln!(self.out, "{{ {} }}", constants.join(", ")).unwrap();
+            }
+            CustomData(x) => {
+                self.write_instruction(opcode, offset, "customdata");
+
+                writeln!(self.out, "{:?}, {} dwords", x.class, x.data.len()).unwrap();
+            }
             _ => {
                 println!("  {:?}", instruction);
             }
@@ -804,19 +2369,50 @@ impl Consumer for DisasmConsumer {
 }
 
 fn main() {
-    let shader_bytes = include_bytes!("..\\assembled.dxbc");
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (fxc, path) = match &args[..] {
+        [path] => (false, path),
+        [flag, path] if flag == "--fxc" => (true, path),
+        _ => {
+            eprintln!("usage: dxbcd [--fxc] <shader.dxbc>");
+            process::exit(1);
+        }
+    };
 
-    let start = 0x4;
-    println!("Real Checksum: {:?}", unsafe {
-        ::std::slice::from_raw_parts(
-            &shader_bytes[start..(start + 16)] as *const _ as *const u32,
-            4,
-        )
+    let shader_bytes = fs::read(path).unwrap_or_else(|err| {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
     });
-    println!("???? Checksum: {:?}", dxbc::checksum(shader_bytes));
 
-    let mut consumer = DisasmConsumer::new();
-    let mut parser = Parser::new(shader_bytes, &mut consumer);
+    // print the listing fxc /dumpbin would, so it can be diffed against one
+    if fxc {
+        match dxbc::disasm::disassemble(&shader_bytes) {
+            Ok(listing) => print!("{}", listing),
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
 
-    parser.parse().unwrap();
+    let start = 0x4;
+    let checksum: Vec<u32> = shader_bytes[start..(start + 16)]
+        .chunks_exact(4)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+        .collect();
+    println!("Real Checksum: {:?}", checksum);
+    println!("???? Checksum: {:?}", dxbc::checksum(&shader_bytes));
+
+    // errors are reported by the parse that writes the listing
+    let mut source_lines = SourceLineConsumer::default();
+    let _ = Parser::new(&shader_bytes, &mut source_lines).parse();
+
+    let mut consumer = DisasmConsumer::new(source_lines.source_lines);
+    let mut parser = Parser::new(&shader_bytes, &mut consumer);
+
+    if let Err(err) = parser.parse() {
+        eprintln!("{}: {}", path, err);
+        process::exit(1);
+    }
 }
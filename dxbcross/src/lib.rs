@@ -329,6 +329,7 @@ impl SpirvModule {
         };
 
         let mut elem = dr::InputOutputElement {
+            stream: 0,
             name: String::from("TEXCOORD"),
             semantic_index: 0,
             semantic_type: dr::SemanticName::Undefined,
@@ -336,6 +337,7 @@ impl SpirvModule {
             register: 0,
             component_mask: 0,
             rw_mask: 0,
+            min_precision: dr::MinPrecision::Default,
         };
 
         for decoration in self.meta.get_decorations(type_id) {
@@ -447,9 +449,11 @@ impl SpirvModule {
         decorations: &[spv_dr::Instruction],
     ) -> (dr::IOsgnChunk, dr::IOsgnChunk) {
         let mut isgn = dr::IOsgnChunk {
+            layout: dr::SignatureLayout::Basic,
             elements: Vec::new(),
         };
         let mut osgn = dr::IOsgnChunk {
+            layout: dr::SignatureLayout::Basic,
             elements: Vec::new(),
         };
 
@@ -494,12 +498,12 @@ impl SpirvModule {
         builder.set_rdef(dr::RdefChunk {
             constant_buffers: Vec::new(),
             resource_bindings: Vec::new(),
-            program_ty: 1,
+            program_ty: dr::ProgramType::Vertex,
             minor: 0,
             major: 5,
             flags: 0,
             author: "DXBCross 0",
-            rd11: Some([0u32; 7]),
+            rd11: Some(dr::Rd11Header::new(dr::ShaderModel::V5_0, 0)),
         });
 
         let (isgn, osgn) = self.get_iosgn(